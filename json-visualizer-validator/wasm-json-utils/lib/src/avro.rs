@@ -0,0 +1,857 @@
+use std::convert::TryFrom;
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+use wasm_bindgen::prelude::*;
+
+use crate::extended_json::{civil_from_days, days_from_civil, parse_rfc3339};
+use crate::formats::check_format;
+use crate::lenient_json::{parse_json_text, to_ndjson, JsonSyntax};
+use crate::naming::{avro_name, dedupe_names};
+use crate::protobuf::timestamp_text;
+use crate::schema_types::{StringFormat, TypeDef, TypeKind, TypeModel, TypeRef};
+use crate::validator::{escape, type_of};
+
+// Apache Avro's JSON encoding, and Avro schema generation from a type model.
+//
+// Avro JSON differs from plain JSON where a value alone doesn't say which
+// schema type it has: a union value other than null is wrapped in an object
+// keyed by its branch's type name (`{"string": "a"}`, `{"com.acme.Address":
+// {...}}`), and bytes and fixed values are strings of the code points U+0000
+// to U+00FF. Converting plain JSON picks the union branch that fits the value
+// best, e.g. `5` goes to `int` or `long` before `double`, and a string to
+// `string` before an enum, a logical type or `bytes`.
+//
+// On the plain side bytes are base64, and `timestamp-millis`,
+// `timestamp-micros` and `date` values are ISO 8601 strings (numbers are
+// accepted for them too). A key matches the field whose `jsonKey` attribute
+// it is, which `json_to_avro_schema` writes wherever the two differ;
+// otherwise a key that isn't an Avro name matches the field named by its
+// `_`-escaped spelling.
+
+/// Converts plain JSON to Avro's JSON encoding of `schema` (Avro schema
+/// JSON). NDJSON input is converted record by record.
+#[wasm_bindgen]
+pub fn json_to_avro_json(json_str: &str, schema: &str, syntax: Option<String>) -> Result<String, JsValue> {
+    convert(json_str, schema, syntax.as_deref(), AvroSchema::encode).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Converts Avro's JSON encoding of `schema` back to plain JSON.
+#[wasm_bindgen]
+pub fn avro_json_to_json(json_str: &str, schema: &str, syntax: Option<String>) -> Result<String, JsValue> {
+    convert(json_str, schema, syntax.as_deref(), AvroSchema::decode).map_err(|e| JsValue::from_str(&e))
+}
+
+type Conversion = fn(&AvroSchema, &AvroType, &Value, &str) -> Result<Value, String>;
+
+fn convert(json_str: &str, schema: &str, syntax: Option<&str>, conversion: Conversion) -> Result<String, String> {
+    let schema = AvroSchema::parse(schema)?;
+    let syntax = JsonSyntax::parse(syntax)?;
+    let value = parse_json_text(json_str, syntax).map_err(|e| format!("JSON parsing error: {}", e))?;
+    match value {
+        Value::Array(records) if syntax == JsonSyntax::Ndjson => {
+            let records = records
+                .iter()
+                .enumerate()
+                .map(|(i, record)| {
+                    conversion(&schema, &schema.root, record, "").map_err(|e| format!("Record {}: {}", i + 1, e))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            to_ndjson(&Value::Array(records))
+        }
+        value => {
+            let converted = conversion(&schema, &schema.root, &value, "")?;
+            serde_json::to_string_pretty(&converted).map_err(|e| e.to_string())
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum AvroType {
+    Null,
+    Boolean,
+    Int,
+    Long,
+    Float,
+    Double,
+    Bytes,
+    String,
+    /// A record, enum or fixed type, by index into `AvroSchema::named`.
+    Named(usize),
+    Array(Box<AvroType>),
+    Map(Box<AvroType>),
+    Union(Vec<AvroType>),
+    Logical(Logical, Box<AvroType>),
+}
+
+/// The logical types whose plain JSON form differs from the Avro one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Logical {
+    TimestampMillis,
+    TimestampMicros,
+    Date,
+}
+
+#[derive(Debug)]
+struct NamedType {
+    /// The full name, including the namespace.
+    name: String,
+    kind: NamedKind,
+}
+
+#[derive(Debug)]
+enum NamedKind {
+    Record(Vec<AvroField>),
+    Enum(Vec<String>),
+    Fixed(usize),
+}
+
+#[derive(Debug)]
+struct AvroField {
+    name: String,
+    /// The plain JSON key from a `jsonKey` attribute, when it isn't the name.
+    json_key: Option<String>,
+    ty: AvroType,
+    default: Option<Value>,
+}
+
+#[derive(Debug)]
+struct AvroSchema {
+    named: Vec<NamedType>,
+    root: AvroType,
+}
+
+const PRIMITIVES: &[(&str, AvroType)] = &[
+    ("null", AvroType::Null),
+    ("boolean", AvroType::Boolean),
+    ("int", AvroType::Int),
+    ("long", AvroType::Long),
+    ("float", AvroType::Float),
+    ("double", AvroType::Double),
+    ("bytes", AvroType::Bytes),
+    ("string", AvroType::String),
+];
+
+impl AvroSchema {
+    fn parse(text: &str) -> Result<AvroSchema, String> {
+        let json: Value = serde_json::from_str(text).map_err(|e| format!("Avro schema parsing error: {}", e))?;
+        let mut schema = AvroSchema { named: Vec::new(), root: AvroType::Null };
+        schema.root = schema.read(&json, "", "")?;
+        Ok(schema)
+    }
+
+    /// Reads a schema in which names without a dot are in `namespace`.
+    fn read(&mut self, json: &Value, namespace: &str, pointer: &str) -> Result<AvroType, String> {
+        let obj = match json {
+            Value::String(name) => return self.reference(name, namespace, pointer),
+            Value::Array(branches) => {
+                let branches = branches
+                    .iter()
+                    .enumerate()
+                    .map(|(i, branch)| self.read(branch, namespace, &format!("{}/{}", pointer, i)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                for (i, branch) in branches.iter().enumerate() {
+                    if matches!(branch, AvroType::Union(_)) {
+                        return Err(invalid_schema("union directly inside a union", pointer));
+                    }
+                    if branches[..i].iter().any(|earlier| self.branch_name(earlier) == self.branch_name(branch)) {
+                        return Err(invalid_schema(
+                            &format!("union with two '{}' types", self.branch_name(branch)),
+                            pointer,
+                        ));
+                    }
+                }
+                return Ok(AvroType::Union(branches));
+            }
+            Value::Object(obj) => obj,
+            _ => return Err(invalid_schema("schema (expected a name, an object or a union array)", pointer)),
+        };
+        let base = match obj.get("type") {
+            Some(Value::String(ty)) => match ty.as_str() {
+                "record" | "error" | "enum" | "fixed" => self.named(obj, ty, namespace, pointer)?,
+                "array" => {
+                    let items = obj.get("items").ok_or_else(|| invalid_schema("array without items", pointer))?;
+                    AvroType::Array(Box::new(self.read(items, namespace, &format!("{}/items", pointer))?))
+                }
+                "map" => {
+                    let values = obj.get("values").ok_or_else(|| invalid_schema("map without values", pointer))?;
+                    AvroType::Map(Box::new(self.read(values, namespace, &format!("{}/values", pointer))?))
+                }
+                name => self.reference(name, namespace, pointer)?,
+            },
+            Some(nested) => self.read(nested, namespace, &format!("{}/type", pointer))?,
+            None => return Err(invalid_schema("schema without a type", pointer)),
+        };
+        // Unknown logical types, or ones on the wrong type, are ignored.
+        let logical = match (obj.get("logicalType").and_then(Value::as_str), &base) {
+            (Some("timestamp-millis"), AvroType::Long) => Logical::TimestampMillis,
+            (Some("timestamp-micros"), AvroType::Long) => Logical::TimestampMicros,
+            (Some("date"), AvroType::Int) => Logical::Date,
+            _ => return Ok(base),
+        };
+        Ok(AvroType::Logical(logical, Box::new(base)))
+    }
+
+    fn reference(&self, name: &str, namespace: &str, pointer: &str) -> Result<AvroType, String> {
+        if let Some((_, primitive)) = PRIMITIVES.iter().find(|(primitive, _)| *primitive == name) {
+            return Ok(primitive.clone());
+        }
+        let full = full_name(name, namespace);
+        self.named
+            .iter()
+            .position(|named| named.name == full || named.name == name)
+            .map(AvroType::Named)
+            .ok_or_else(|| format!("Unknown Avro type '{}' at {}", name, location(pointer)))
+    }
+
+    /// Reads a record, enum or fixed definition. The name is registered
+    /// before the fields are read so that a record can contain itself.
+    fn named(
+        &mut self,
+        obj: &Map<String, Value>,
+        ty: &str,
+        namespace: &str,
+        pointer: &str,
+    ) -> Result<AvroType, String> {
+        let name = obj
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| invalid_schema(&format!("{} without a name", ty), pointer))?;
+        let namespace = obj.get("namespace").and_then(Value::as_str).unwrap_or(namespace);
+        let name = full_name(name, namespace);
+        if self.named.iter().any(|named| named.name == name) {
+            return Err(invalid_schema(&format!("second definition of '{}'", name), pointer));
+        }
+        let index = self.named.len();
+        self.named.push(NamedType { name: name.clone(), kind: NamedKind::Fixed(0) });
+        let kind = match ty {
+            "enum" => {
+                let symbols = obj
+                    .get("symbols")
+                    .and_then(Value::as_array)
+                    .map(|symbols| symbols.iter().map(|s| s.as_str().map(str::to_string)).collect::<Option<Vec<_>>>());
+                NamedKind::Enum(symbols.flatten().ok_or_else(|| invalid_schema("enum symbols", pointer))?)
+            }
+            "fixed" => {
+                let size = obj.get("size").and_then(Value::as_u64).and_then(|size| usize::try_from(size).ok());
+                NamedKind::Fixed(size.ok_or_else(|| invalid_schema("fixed size", pointer))?)
+            }
+            _ => {
+                let inner_namespace = name.rsplit_once('.').map_or("", |(namespace, _)| namespace);
+                let fields = obj
+                    .get("fields")
+                    .and_then(Value::as_array)
+                    .ok_or_else(|| invalid_schema("record fields", pointer))?;
+                let mut record = Vec::new();
+                for (i, field) in fields.iter().enumerate() {
+                    let field_pointer = format!("{}/fields/{}", pointer, i);
+                    let field_name = field.get("name").and_then(Value::as_str);
+                    let (field_name, ty) = match (field_name, field.get("type")) {
+                        (Some(name), Some(ty)) => (name, ty),
+                        _ => return Err(invalid_schema("field (expected a name and a type)", &field_pointer)),
+                    };
+                    record.push(AvroField {
+                        name: field_name.to_string(),
+                        json_key: field.get("jsonKey").and_then(Value::as_str).map(str::to_string),
+                        ty: self.read(ty, inner_namespace, &format!("{}/type", field_pointer))?,
+                        default: field.get("default").cloned(),
+                    });
+                }
+                NamedKind::Record(record)
+            }
+        };
+        self.named[index].kind = kind;
+        Ok(AvroType::Named(index))
+    }
+
+    /// The name a union branch is selected by in Avro JSON.
+    fn branch_name<'s>(&'s self, ty: &AvroType) -> &'s str {
+        match ty {
+            AvroType::Named(index) => &self.named[*index].name,
+            AvroType::Array(_) => "array",
+            AvroType::Map(_) => "map",
+            AvroType::Union(_) => "union",
+            AvroType::Logical(_, inner) => self.branch_name(inner),
+            primitive => PRIMITIVES.iter().find(|(_, p)| p == primitive).map_or("", |(name, _)| name),
+        }
+    }
+
+    /// How `ty` is named in error messages.
+    fn describe(&self, ty: &AvroType) -> String {
+        match ty {
+            AvroType::Named(index) => {
+                let kind = match self.named[*index].kind {
+                    NamedKind::Record(_) => "record",
+                    NamedKind::Enum(_) => "enum",
+                    NamedKind::Fixed(_) => "fixed",
+                };
+                format!("{} {}", kind, self.named[*index].name)
+            }
+            AvroType::Union(branches) => {
+                let names: Vec<&str> = branches.iter().map(|b| self.branch_name(b)).collect();
+                format!("one of {}", names.join(", "))
+            }
+            AvroType::Logical(Logical::TimestampMillis, _) => "timestamp-millis".to_string(),
+            AvroType::Logical(Logical::TimestampMicros, _) => "timestamp-micros".to_string(),
+            AvroType::Logical(Logical::Date, _) => "date".to_string(),
+            other => self.branch_name(other).to_string(),
+        }
+    }
+
+    // Plain JSON to Avro JSON
+
+    fn encode(&self, ty: &AvroType, value: &Value, pointer: &str) -> Result<Value, String> {
+        Ok(match (ty, value) {
+            (AvroType::Named(index), _) => self.encode_named(*index, value, pointer)?,
+            (AvroType::Bytes, Value::String(text)) => avro_bytes(&base64(text, pointer)?),
+            (AvroType::Array(items), Value::Array(values)) => Value::Array(
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| self.encode(items, item, &format!("{}/{}", pointer, i)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            (AvroType::Map(values), Value::Object(obj)) => Value::Object(
+                obj.iter()
+                    .map(|(key, item)| Ok((key.clone(), self.encode(values, item, &child(pointer, key))?)))
+                    .collect::<Result<_, String>>()?,
+            ),
+            (AvroType::Union(branches), _) => self.encode_union(branches, value, pointer)?,
+            (AvroType::Logical(logical, _), Value::String(text)) => match parse_logical(*logical, text) {
+                Some(number) => number.into(),
+                None => return Err(mismatch(&self.describe(ty), value, pointer)),
+            },
+            (AvroType::Logical(_, inner), _) => self.encode(inner, value, pointer)?,
+            _ if self.is_primitive_value(ty, value) => value.clone(),
+            _ => return Err(mismatch(&self.describe(ty), value, pointer)),
+        })
+    }
+
+    fn encode_named(&self, index: usize, value: &Value, pointer: &str) -> Result<Value, String> {
+        let named = &self.named[index];
+        match (&named.kind, value) {
+            (NamedKind::Record(fields), Value::Object(obj)) => {
+                let keys = self.record_keys(&named.name, fields, obj, true, pointer)?;
+                let mut record = Map::new();
+                for (field, key) in fields.iter().zip(keys) {
+                    let encoded = match key {
+                        Some(key) => self.encode(&field.ty, &obj[key], &child(pointer, key))?,
+                        None => self.missing(field, pointer)?,
+                    };
+                    record.insert(field.name.clone(), encoded);
+                }
+                Ok(Value::Object(record))
+            }
+            (NamedKind::Enum(symbols), Value::String(symbol)) if symbols.contains(symbol) => Ok(value.clone()),
+            (NamedKind::Fixed(size), Value::String(text)) => {
+                let bytes = base64(text, pointer)?;
+                if bytes.len() != *size {
+                    return Err(format!(
+                        "Expected {} bytes for fixed {} at {}, found {}",
+                        size,
+                        named.name,
+                        location(pointer),
+                        bytes.len()
+                    ));
+                }
+                Ok(avro_bytes(&bytes))
+            }
+            _ => Err(mismatch(&self.describe(&AvroType::Named(index)), value, pointer)),
+        }
+    }
+
+    /// Encodes `value` as the best fitting branch: an exact type match
+    /// before a looser one, and the earlier branch on a tie.
+    fn encode_union(&self, branches: &[AvroType], value: &Value, pointer: &str) -> Result<Value, String> {
+        let mut candidates: Vec<(u8, &AvroType)> =
+            branches.iter().filter_map(|branch| Some((self.rank(branch, value)?, branch))).collect();
+        candidates.sort_by_key(|(rank, _)| *rank);
+        let mut error = None;
+        for (_, branch) in candidates {
+            match self.encode(branch, value, pointer) {
+                Ok(encoded) if *branch == AvroType::Null => return Ok(encoded),
+                Ok(encoded) => return Ok(wrap(self.branch_name(branch), encoded)),
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+        }
+        Err(error.unwrap_or_else(|| mismatch(&self.describe(&AvroType::Union(branches.to_vec())), value, pointer)))
+    }
+
+    /// The order in which a union branch is tried for `value`, or `None`
+    /// if it can't hold it.
+    fn rank(&self, branch: &AvroType, value: &Value) -> Option<u8> {
+        Some(match (branch, value) {
+            (AvroType::Null, Value::Null)
+            | (AvroType::Boolean, Value::Bool(_))
+            | (AvroType::Int, Value::Number(_))
+            | (AvroType::String, Value::String(_))
+            | (AvroType::Array(_), Value::Array(_)) => 0,
+            (AvroType::Long, Value::Number(_)) => 1,
+            (AvroType::Double, Value::Number(_)) => 2,
+            (AvroType::Float, Value::Number(_)) => 3,
+            (AvroType::Map(_), Value::Object(_)) => 1,
+            (AvroType::Logical(..), Value::String(_)) => 2,
+            (AvroType::Logical(_, inner), _) => self.rank(inner, value)?,
+            (AvroType::Bytes, Value::String(_)) => 3,
+            (AvroType::Named(index), _) => match (&self.named[*index].kind, value) {
+                (NamedKind::Record(_), Value::Object(_)) => 0,
+                (NamedKind::Enum(_), Value::String(_)) => 1,
+                (NamedKind::Fixed(_), Value::String(_)) => 3,
+                _ => return None,
+            },
+            _ => return None,
+        })
+    }
+
+    // Avro JSON to plain JSON
+
+    fn decode(&self, ty: &AvroType, value: &Value, pointer: &str) -> Result<Value, String> {
+        Ok(match (ty, value) {
+            (AvroType::Named(index), _) => self.decode_named(*index, value, pointer)?,
+            (AvroType::Bytes, Value::String(text)) => STANDARD.encode(plain_bytes(text, pointer)?).into(),
+            (AvroType::Array(items), Value::Array(values)) => Value::Array(
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| self.decode(items, item, &format!("{}/{}", pointer, i)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            (AvroType::Map(values), Value::Object(obj)) => Value::Object(
+                obj.iter()
+                    .map(|(key, item)| Ok((key.clone(), self.decode(values, item, &child(pointer, key))?)))
+                    .collect::<Result<_, String>>()?,
+            ),
+            (AvroType::Union(branches), Value::Null) if branches.contains(&AvroType::Null) => Value::Null,
+            (AvroType::Union(branches), Value::Object(obj)) if obj.len() == 1 => {
+                let (name, inner) = obj.iter().next().unwrap_or_else(|| unreachable!());
+                match branches.iter().find(|branch| self.branch_name(branch) == name) {
+                    Some(branch) => self.decode(branch, inner, &child(pointer, name))?,
+                    None => {
+                        return Err(format!(
+                            "'{}' is not a type of the union at {} ({})",
+                            name,
+                            location(pointer),
+                            self.describe(ty)
+                        ))
+                    }
+                }
+            }
+            (AvroType::Union(_), _) => {
+                let expected = format!("null or a single-key object naming the type, for {}", self.describe(ty));
+                return Err(mismatch(&expected, value, pointer));
+            }
+            (AvroType::Logical(logical, inner), _) => {
+                let number = self.decode(inner, value, pointer)?;
+                number.as_i64().and_then(|n| format_logical(*logical, n)).map_or(number, Value::from)
+            }
+            _ if self.is_primitive_value(ty, value) => value.clone(),
+            _ => return Err(mismatch(&self.describe(ty), value, pointer)),
+        })
+    }
+
+    fn decode_named(&self, index: usize, value: &Value, pointer: &str) -> Result<Value, String> {
+        let named = &self.named[index];
+        match (&named.kind, value) {
+            (NamedKind::Record(fields), Value::Object(obj)) => {
+                let keys = self.record_keys(&named.name, fields, obj, false, pointer)?;
+                let mut record = Map::new();
+                for (field, key) in fields.iter().zip(keys) {
+                    let decoded = match key {
+                        Some(key) => self.decode(&field.ty, &obj[key], &child(pointer, key))?,
+                        None => self.decode(&field.ty, &self.missing(field, pointer)?, &child(pointer, &field.name))?,
+                    };
+                    record.insert(field.json_key.as_ref().unwrap_or(&field.name).clone(), decoded);
+                }
+                Ok(Value::Object(record))
+            }
+            (NamedKind::Enum(symbols), Value::String(symbol)) if symbols.contains(symbol) => Ok(value.clone()),
+            (NamedKind::Fixed(size), Value::String(text)) => {
+                let bytes = plain_bytes(text, pointer)?;
+                if bytes.len() != *size {
+                    return Err(format!(
+                        "Expected {} bytes for fixed {} at {}, found {}",
+                        size,
+                        named.name,
+                        location(pointer),
+                        bytes.len()
+                    ));
+                }
+                Ok(STANDARD.encode(bytes).into())
+            }
+            _ => Err(mismatch(&self.describe(&AvroType::Named(index)), value, pointer)),
+        }
+    }
+
+    // Shared by both directions
+
+    /// Whether `value` is already valid for a primitive type in both forms.
+    fn is_primitive_value(&self, ty: &AvroType, value: &Value) -> bool {
+        match (ty, value) {
+            (AvroType::Null, Value::Null)
+            | (AvroType::Boolean, Value::Bool(_))
+            | (AvroType::String, Value::String(_)) => true,
+            (AvroType::Int, Value::Number(n)) => n.as_i64().is_some_and(|i| i32::try_from(i).is_ok()),
+            (AvroType::Long, Value::Number(n)) => n.is_i64(),
+            (AvroType::Float | AvroType::Double, Value::Number(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// The key of `obj` holding each field: its `jsonKey` in `plain` JSON or
+    /// its name in Avro JSON, else a key not taken yet that escapes to the
+    /// name. Keys that belong to no field are an error.
+    fn record_keys<'v>(
+        &self,
+        record: &str,
+        fields: &[AvroField],
+        obj: &'v Map<String, Value>,
+        plain: bool,
+        pointer: &str,
+    ) -> Result<Vec<Option<&'v String>>, String> {
+        let mut keys: Vec<Option<&String>> = fields
+            .iter()
+            .map(|field| {
+                let exact = if plain { field.json_key.as_ref().unwrap_or(&field.name) } else { &field.name };
+                obj.get_key_value(exact).map(|(key, _)| key)
+            })
+            .collect();
+        for (i, field) in fields.iter().enumerate() {
+            if keys[i].is_none() {
+                keys[i] = obj.keys().find(|key| avro_name(key) == field.name && !keys.contains(&Some(*key)));
+            }
+        }
+        match obj.keys().find(|key| !keys.contains(&Some(*key))) {
+            Some(unknown) => {
+                Err(format!("Unknown field '{}' at {} (not in record {})", unknown, location(pointer), record))
+            }
+            None => Ok(keys),
+        }
+    }
+
+    /// The Avro JSON value of a field missing from a record: its default,
+    /// or null when it can hold null.
+    fn missing(&self, field: &AvroField, pointer: &str) -> Result<Value, String> {
+        match &field.default {
+            Some(default) => Ok(self.default_value(&field.ty, default)),
+            None if field.ty == AvroType::Null
+                || matches!(&field.ty, AvroType::Union(b) if b.contains(&AvroType::Null)) =>
+            {
+                Ok(Value::Null)
+            }
+            None => Err(format!("Missing field '{}' at {}", field.name, location(pointer))),
+        }
+    }
+
+    /// A schema default in Avro JSON. Defaults are written unwrapped, and a
+    /// union's is of its first type.
+    fn default_value(&self, ty: &AvroType, default: &Value) -> Value {
+        match (ty, default) {
+            (AvroType::Union(branches), _) => match branches.first() {
+                Some(AvroType::Null) | None => default.clone(),
+                Some(first) => wrap(self.branch_name(first), self.default_value(first, default)),
+            },
+            (AvroType::Named(index), Value::Object(obj)) => match &self.named[*index].kind {
+                NamedKind::Record(fields) => Value::Object(
+                    fields
+                        .iter()
+                        .filter_map(|field| {
+                            let value = obj.get(&field.name).or(field.default.as_ref())?;
+                            Some((field.name.clone(), self.default_value(&field.ty, value)))
+                        })
+                        .collect(),
+                ),
+                _ => default.clone(),
+            },
+            (AvroType::Array(items), Value::Array(values)) => {
+                Value::Array(values.iter().map(|item| self.default_value(items, item)).collect())
+            }
+            (AvroType::Map(values), Value::Object(obj)) => {
+                Value::Object(obj.iter().map(|(key, item)| (key.clone(), self.default_value(values, item))).collect())
+            }
+            _ => default.clone(),
+        }
+    }
+}
+
+fn full_name(name: &str, namespace: &str) -> String {
+    if name.contains('.') || namespace.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", namespace, name)
+    }
+}
+
+fn wrap(branch: &str, value: Value) -> Value {
+    let mut wrapper = Map::new();
+    wrapper.insert(branch.to_string(), value);
+    Value::Object(wrapper)
+}
+
+/// Bytes as Avro JSON writes them: one code point per byte.
+fn avro_bytes(bytes: &[u8]) -> Value {
+    bytes.iter().map(|&b| char::from(b)).collect::<String>().into()
+}
+
+fn plain_bytes(text: &str, pointer: &str) -> Result<Vec<u8>, String> {
+    text.chars()
+        .map(|c| u8::try_from(u32::from(c)).ok())
+        .collect::<Option<_>>()
+        .ok_or_else(|| format!("Invalid Avro bytes at {} (code points above U+00FF)", location(pointer)))
+}
+
+fn base64(text: &str, pointer: &str) -> Result<Vec<u8>, String> {
+    STANDARD.decode(text).map_err(|e| format!("Invalid base64 at {}: {}", location(pointer), e))
+}
+
+/// The Avro number for a logical type's ISO 8601 text.
+fn parse_logical(logical: Logical, text: &str) -> Option<i64> {
+    match logical {
+        Logical::TimestampMillis => parse_rfc3339(text).map(|(seconds, nanos)| seconds * 1000 + nanos / 1_000_000),
+        Logical::TimestampMicros => parse_rfc3339(text).map(|(seconds, nanos)| seconds * 1_000_000 + nanos / 1000),
+        Logical::Date if check_format("date", text) == Some(true) => {
+            let part = |range: std::ops::Range<usize>| text[range].parse::<i64>().ok();
+            let days = days_from_civil(part(0..4)?, part(5..7)?, part(8..10)?);
+            i32::try_from(days).ok().map(i64::from)
+        }
+        Logical::Date => None,
+    }
+}
+
+/// The ISO 8601 text of a logical type's number, when in years 1 to 9999.
+fn format_logical(logical: Logical, number: i64) -> Option<String> {
+    match logical {
+        Logical::TimestampMillis => timestamp_text(number.div_euclid(1000), number.rem_euclid(1000) * 1_000_000),
+        Logical::TimestampMicros => timestamp_text(number.div_euclid(1_000_000), number.rem_euclid(1_000_000) * 1000),
+        Logical::Date => {
+            let (year, month, day) = civil_from_days(number);
+            (1..=9999).contains(&year).then(|| format!("{:04}-{:02}-{:02}", year, month, day))
+        }
+    }
+}
+
+fn child(pointer: &str, key: &str) -> String {
+    format!("{}/{}", pointer, escape(key))
+}
+
+fn location(pointer: &str) -> &str {
+    if pointer.is_empty() {
+        "the top level"
+    } else {
+        pointer
+    }
+}
+
+fn mismatch(expected: &str, value: &Value, pointer: &str) -> String {
+    let found = match value {
+        // Out of range for `int`, or a fraction for `long`.
+        Value::Number(n) => format!("{} {}", type_of(value), n),
+        _ => type_of(value).to_string(),
+    };
+    format!("Expected {} at {}, found {}", expected, location(pointer), found)
+}
+
+fn invalid_schema(what: &str, pointer: &str) -> String {
+    format!("Invalid Avro schema: {} at {}", what, location(pointer))
+}
+
+// Schema generation
+
+/// An Avro schema for the model. A record or enum is written out in full
+/// where it is first used and referred to by name after that, as Avro
+/// requires.
+pub(crate) fn avro_schema(model: &TypeModel) -> Value {
+    avro_type(model, &TypeRef::Named(model.root.clone()), &mut HashSet::new())
+}
+
+fn avro_type(model: &TypeModel, ty: &TypeRef, defined: &mut HashSet<String>) -> Value {
+    match ty {
+        // Avro has no dynamic type, so anything else only takes scalars.
+        TypeRef::Any => avro_union(["null", "boolean", "long", "double", "string"].iter().map(|&t| t.into()).collect()),
+        TypeRef::Null => "null".into(),
+        TypeRef::Boolean => "boolean".into(),
+        TypeRef::Integer => "long".into(),
+        TypeRef::Number => "double".into(),
+        TypeRef::Formatted(StringFormat::DateTime) => json!({ "type": "long", "logicalType": "timestamp-millis" }),
+        TypeRef::Formatted(StringFormat::Date) => json!({ "type": "int", "logicalType": "date" }),
+        TypeRef::Formatted(StringFormat::Uuid) => json!({ "type": "string", "logicalType": "uuid" }),
+        TypeRef::String | TypeRef::Formatted(_) => "string".into(),
+        TypeRef::Array(inner) => json!({ "type": "array", "items": avro_type(model, inner, defined) }),
+        TypeRef::Map(inner) => json!({ "type": "map", "values": avro_type(model, inner, defined) }),
+        TypeRef::Nullable(inner) => avro_union(vec!["null".into(), avro_type(model, inner, defined)]),
+        TypeRef::Named(name) => match model.get(name) {
+            Some(def) => avro_def(model, def, defined),
+            None => avro_name(name).into(),
+        },
+    }
+}
+
+fn avro_def(model: &TypeModel, def: &TypeDef, defined: &mut HashSet<String>) -> Value {
+    let name = avro_name(&def.name);
+    // Symbols must be names themselves; other values stay strings.
+    if let TypeKind::Enum(values) = &def.kind {
+        if values.iter().any(|v| avro_name(v) != *v) {
+            return "string".into();
+        }
+    }
+    if !defined.insert(def.name.clone()) {
+        return match def.kind {
+            TypeKind::Struct(_) | TypeKind::Enum(_) => name.into(),
+            // Unions and aliases have no name to refer back to.
+            _ => avro_type(model, &TypeRef::Any, defined),
+        };
+    }
+    let mut schema = Map::new();
+    match &def.kind {
+        TypeKind::Struct(fields) => {
+            let names = dedupe_names(fields.iter().map(|f| avro_name(&f.json_name)).collect());
+            let fields: Vec<Value> = fields
+                .iter()
+                .zip(names)
+                .map(|(field, name)| {
+                    let mut ty = avro_type(model, &field.ty, defined);
+                    if !field.required {
+                        ty = avro_union(vec!["null".into(), ty]);
+                    }
+                    let mut avro_field = Map::new();
+                    // Keys that aren't Avro names, or collide once escaped, keep the original for matching.
+                    if name != field.json_name {
+                        avro_field.insert("jsonKey".to_string(), field.json_name.as_str().into());
+                    }
+                    avro_field.insert("name".to_string(), name.into());
+                    if let Some(doc) = &field.description {
+                        avro_field.insert("doc".to_string(), doc.as_str().into());
+                    }
+                    // A union's default has to be of its first type.
+                    if ty == "null" || ty.get(0).is_some_and(|first| first == "null") {
+                        avro_field.insert("default".to_string(), Value::Null);
+                    }
+                    avro_field.insert("type".to_string(), ty);
+                    Value::Object(avro_field)
+                })
+                .collect();
+            schema.insert("type".to_string(), "record".into());
+            schema.insert("fields".to_string(), fields.into());
+        }
+        TypeKind::Enum(values) => {
+            schema.insert("type".to_string(), "enum".into());
+            schema.insert("symbols".to_string(), values.clone().into());
+        }
+        TypeKind::Union { variants, .. } => {
+            let branches = variants.iter().map(|v| avro_type(model, v, defined)).collect();
+            defined.remove(&def.name);
+            return avro_union(branches);
+        }
+        TypeKind::Alias(target) => {
+            let target = avro_type(model, target, defined);
+            defined.remove(&def.name);
+            return target;
+        }
+    }
+    schema.insert("name".to_string(), name.into());
+    if let Some(doc) = &def.description {
+        schema.insert("doc".to_string(), doc.as_str().into());
+    }
+    Value::Object(schema)
+}
+
+/// A union of `branches` that Avro accepts: nested unions are flattened,
+/// `null` comes first and a second array or map merges into the first.
+fn avro_union(branches: Vec<Value>) -> Value {
+    let mut union: Vec<Value> = Vec::new();
+    for branch in branches.into_iter().flat_map(|b| if let Value::Array(items) = b { items } else { vec![b] }) {
+        let name = avro_branch_name(&branch);
+        match union.iter_mut().find(|b| avro_branch_name(b) == name) {
+            Some(Value::Object(existing)) if name == "array" || name == "map" => {
+                let key = if name == "array" { "items" } else { "values" };
+                let merged = avro_union(vec![existing[key].take(), branch[key].clone()]);
+                existing.insert(key.to_string(), merged);
+            }
+            Some(_) => {}
+            None => union.push(branch),
+        }
+    }
+    if let Some(i) = union.iter().position(|b| b == "null") {
+        let null = union.remove(i);
+        union.insert(0, null);
+    }
+    if union.len() == 1 {
+        union.remove(0)
+    } else {
+        Value::Array(union)
+    }
+}
+
+/// The name a union branch goes by: its full name for named types and its
+/// type otherwise.
+fn avro_branch_name(schema: &Value) -> String {
+    let ty = match schema {
+        Value::Object(obj) => obj.get("type").unwrap_or(&Value::Null),
+        other => other,
+    };
+    match (ty.as_str(), schema.get("name").and_then(Value::as_str)) {
+        (Some("record" | "enum" | "fixed"), Some(name)) => name.to_string(),
+        (Some(ty), _) => ty.to_string(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::json_to_avro_schema;
+    use serde_json::json;
+
+    fn encode(schema: &str, value: Value) -> Result<Value, String> {
+        let schema = AvroSchema::parse(schema)?;
+        schema.encode(&schema.root, &value, "")
+    }
+
+    fn decode(schema: &str, value: Value) -> Result<Value, String> {
+        let schema = AvroSchema::parse(schema)?;
+        schema.decode(&schema.root, &value, "")
+    }
+
+    #[test]
+    fn colliding_keys_keep_their_fields() {
+        let document = json!({"first-name": "Ada", "first_name": "Lovelace", "2fa": true});
+        let schema = json_to_avro_schema(&document.to_string(), None).unwrap();
+        let avro = encode(&schema, document.clone()).unwrap();
+        assert_eq!(avro, json!({"first_name": "Ada", "first_name2": "Lovelace", "_2fa": true}));
+        assert_eq!(decode(&schema, avro).unwrap(), document);
+    }
+
+    #[test]
+    fn keys_match_escaped_field_names_without_json_key() {
+        let schema = r#"{"type": "record", "name": "R", "fields": [{"name": "first_name", "type": "string"}]}"#;
+        assert_eq!(encode(schema, json!({"first-name": "Ada"})).unwrap(), json!({"first_name": "Ada"}));
+        let error = encode(schema, json!({"first_name": "Ada", "last": "L"})).unwrap_err();
+        assert!(error.starts_with("Unknown field 'last'"), "{}", error);
+    }
+
+    #[test]
+    fn unions_are_wrapped_by_branch() {
+        let schema = r#"{"type": "record", "name": "R", "fields": [
+            {"name": "n", "type": ["null", "long", "string"]},
+            {"name": "o", "type": ["null", "string"], "default": null}
+        ]}"#;
+        assert_eq!(encode(schema, json!({"n": 5})).unwrap(), json!({"n": {"long": 5}, "o": null}));
+        let wrapped = json!({"n": {"string": "x"}, "o": {"string": "y"}});
+        assert_eq!(encode(schema, json!({"n": "x", "o": "y"})).unwrap(), wrapped);
+        assert_eq!(decode(schema, json!({"n": {"long": 5}, "o": null})).unwrap(), json!({"n": 5, "o": null}));
+    }
+
+    #[test]
+    fn logical_types_and_bytes() {
+        let schema = r#"{"type": "record", "name": "R", "fields": [
+            {"name": "at", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+            {"name": "b", "type": "bytes"}
+        ]}"#;
+        let plain = json!({"at": "2024-01-02T03:04:05Z", "b": "AAH/"});
+        let avro = encode(schema, plain.clone()).unwrap();
+        assert_eq!(avro, json!({"at": 1_704_164_645_000i64, "b": "\u{0}\u{1}\u{ff}"}));
+        assert_eq!(decode(schema, avro).unwrap()["b"], plain["b"]);
+    }
+}
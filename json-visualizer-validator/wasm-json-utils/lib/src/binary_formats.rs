@@ -0,0 +1,504 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use js_sys::Uint8Array;
+use serde_json::{json, Map, Number, Value};
+use std::convert::TryFrom;
+use wasm_bindgen::prelude::*;
+
+use crate::lenient_json::{parse_json_text, JsonSyntax};
+
+// MessagePack and CBOR.
+//
+// Both decode into `serde_json::Value`, so every JSON converter, type
+// generator and visualizer works on them, and encode back from it. Values
+// JSON has no form for become marker objects:
+//
+// - byte strings: `{"$binary": "<base64>"}`
+// - MessagePack extension types: `{"$ext": <type>, "data": "<base64>"}`
+// - CBOR tags: `{"$tag": <tag number>, "value": <tagged value>}`
+//
+// Map keys that aren't strings become their JSON text (`1`, `true`). NaN,
+// infinities, CBOR `undefined` and simple values become null. Encoding turns
+// `$binary` and the format's own markers back into bytes, extensions and
+// tags; the other format's markers are written as ordinary maps.
+
+/// Decodes a MessagePack value into pretty-printed JSON.
+#[wasm_bindgen]
+pub fn msgpack_to_json(bytes: &Uint8Array) -> Result<String, JsValue> {
+    let value = decode_msgpack(&bytes.to_vec()).map_err(|e| JsValue::from_str(&e))?;
+    serde_json::to_string_pretty(&value).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Encodes a JSON document as MessagePack. `syntax` works as for `json_to_yaml`.
+#[wasm_bindgen]
+pub fn json_to_msgpack(json_str: &str, syntax: Option<String>) -> Result<Uint8Array, JsValue> {
+    let value = parse_input(json_str, syntax.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    let bytes = encode_msgpack(&value).map_err(|e| JsValue::from_str(&e))?;
+    Ok(Uint8Array::from(bytes.as_slice()))
+}
+
+/// Decodes a CBOR data item into pretty-printed JSON.
+#[wasm_bindgen]
+pub fn cbor_to_json(bytes: &Uint8Array) -> Result<String, JsValue> {
+    let value = decode_cbor(&bytes.to_vec()).map_err(|e| JsValue::from_str(&e))?;
+    serde_json::to_string_pretty(&value).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Encodes a JSON document as CBOR. `syntax` works as for `json_to_yaml`.
+#[wasm_bindgen]
+pub fn json_to_cbor(json_str: &str, syntax: Option<String>) -> Result<Uint8Array, JsValue> {
+    let value = parse_input(json_str, syntax.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    let bytes = encode_cbor(&value).map_err(|e| JsValue::from_str(&e))?;
+    Ok(Uint8Array::from(bytes.as_slice()))
+}
+
+fn parse_input(json_str: &str, syntax: Option<&str>) -> Result<Value, String> {
+    let syntax = JsonSyntax::parse(syntax)?;
+    parse_json_text(json_str, syntax).map_err(|e| format!("JSON parsing error: {}", e))
+}
+
+pub(crate) fn decode_msgpack(bytes: &[u8]) -> Result<Value, String> {
+    let mut reader = MsgpackReader { bytes, pos: 0 };
+    let value = reader
+        .value(MAX_DEPTH)
+        .map_err(|(offset, message)| decode_error("MessagePack", bytes, offset, &message))?;
+    check_trailing("MessagePack", bytes, &bytes[reader.pos..])?;
+    Ok(value)
+}
+
+pub(crate) fn encode_msgpack(value: &Value) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    write_msgpack(&mut bytes, value)?;
+    Ok(bytes)
+}
+
+pub(crate) fn decode_cbor(bytes: &[u8]) -> Result<Value, String> {
+    use ciborium::de::Error;
+
+    let mut rest = bytes;
+    let value: ciborium::Value = ciborium::de::from_reader(&mut rest).map_err(|e| {
+        let consumed = bytes.len() - rest.len();
+        let (offset, message) = match e {
+            Error::Io(_) => (consumed, "unexpected end of input".to_string()),
+            Error::Syntax(offset) => (offset, "invalid data item".to_string()),
+            Error::Semantic(offset, message) => (offset.unwrap_or(consumed), message),
+            Error::RecursionLimitExceeded => (consumed, "nesting is too deep".to_string()),
+        };
+        decode_error("CBOR", bytes, offset, &message)
+    })?;
+    check_trailing("CBOR", bytes, rest)?;
+    Ok(from_cbor(value))
+}
+
+pub(crate) fn encode_cbor(value: &Value) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    ciborium::ser::into_writer(&to_cbor(value)?, &mut bytes).map_err(|e| format!("CBOR encoding error: {}", e))?;
+    Ok(bytes)
+}
+
+fn from_cbor(value: ciborium::Value) -> Value {
+    match value {
+        ciborium::Value::Integer(i) => {
+            let i = i128::from(i);
+            match (i64::try_from(i), u64::try_from(i)) {
+                (Ok(i), _) => Value::Number(i.into()),
+                (_, Ok(u)) => Value::Number(u.into()),
+                _ => float(i as f64),
+            }
+        }
+        ciborium::Value::Bytes(bytes) => json!({ "$binary": STANDARD.encode(bytes) }),
+        ciborium::Value::Float(f) => float(f),
+        ciborium::Value::Text(s) => Value::String(s),
+        ciborium::Value::Bool(b) => Value::Bool(b),
+        ciborium::Value::Tag(tag, value) => json!({ "$tag": tag, "value": from_cbor(*value) }),
+        ciborium::Value::Array(items) => Value::Array(items.into_iter().map(from_cbor).collect()),
+        ciborium::Value::Map(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key_text(from_cbor(key)), from_cbor(value)))
+                .collect(),
+        ),
+        _ => Value::Null,
+    }
+}
+
+fn to_cbor(value: &Value) -> Result<ciborium::Value, String> {
+    Ok(match value {
+        Value::Null => ciborium::Value::Null,
+        Value::Bool(b) => ciborium::Value::Bool(*b),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => ciborium::Value::Integer(i.into()),
+            (None, Some(u)) => ciborium::Value::Integer(u.into()),
+            _ => ciborium::Value::Float(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => ciborium::Value::Text(s.clone()),
+        Value::Array(items) => ciborium::Value::Array(items.iter().map(to_cbor).collect::<Result<_, _>>()?),
+        Value::Object(map) => {
+            if let Some(bytes) = binary_marker(map)? {
+                return Ok(ciborium::Value::Bytes(bytes));
+            }
+            if let (2, Some(tag), Some(tagged)) = (map.len(), map.get("$tag"), map.get("value")) {
+                let tag = tag.as_u64().ok_or_else(|| format!("$tag must be a non-negative integer, found {}", tag))?;
+                return Ok(ciborium::Value::Tag(tag, Box::new(to_cbor(tagged)?)));
+            }
+            ciborium::Value::Map(
+                map.iter()
+                    .map(|(key, value)| Ok((ciborium::Value::Text(key.clone()), to_cbor(value)?)))
+                    .collect::<Result<_, String>>()?,
+            )
+        }
+    })
+}
+
+/// How deeply arrays and maps may nest before decoding gives up.
+pub(crate) const MAX_DEPTH: usize = 256;
+
+/// A decoding failure: the byte offset and what went wrong there.
+type DecodeError = (usize, String);
+
+struct MsgpackReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> MsgpackReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let end = self.pos.checked_add(len).filter(|end| *end <= self.bytes.len());
+        let end = end.ok_or_else(|| (self.bytes.len(), "unexpected end of input".to_string()))?;
+        let taken = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(taken)
+    }
+
+    /// A big-endian unsigned integer of `len` bytes.
+    fn uint(&mut self, len: usize) -> Result<u64, DecodeError> {
+        Ok(self.take(len)?.iter().fold(0, |n, byte| n << 8 | u64::from(*byte)))
+    }
+
+    fn len(&mut self, len: usize) -> Result<usize, DecodeError> {
+        let n = self.uint(len)?;
+        usize::try_from(n).map_err(|_| (self.pos, format!("length {} is too large", n)))
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, DecodeError> {
+        let start = self.pos;
+        let marker = self.take(1)?[0];
+        Ok(match marker {
+            0x00..=0x7f => Value::from(marker),
+            0x80..=0x8f => self.map(usize::from(marker & 0x0f), depth)?,
+            0x90..=0x9f => self.array(usize::from(marker & 0x0f), depth)?,
+            0xa0..=0xbf => self.string(start, usize::from(marker & 0x1f))?,
+            0xc0 => Value::Null,
+            0xc1 => return Err((start, "reserved marker 0xc1".to_string())),
+            0xc2 => Value::Bool(false),
+            0xc3 => Value::Bool(true),
+            0xc4..=0xc6 => {
+                let len = self.len(1 << (marker - 0xc4))?;
+                json!({ "$binary": STANDARD.encode(self.take(len)?) })
+            }
+            0xc7..=0xc9 => {
+                let len = self.len(1 << (marker - 0xc7))?;
+                self.ext(len)?
+            }
+            0xca => float(f64::from(f32::from_bits(self.uint(4)? as u32))),
+            0xcb => float(f64::from_bits(self.uint(8)?)),
+            0xcc..=0xcf => Value::from(self.uint(1 << (marker - 0xcc))?),
+            0xd0..=0xd3 => {
+                let len = 1 << (marker - 0xd0);
+                let bits = (8 - len) * 8;
+                // Shift the value to the top and back to sign-extend it.
+                Value::from(((self.uint(len)? << bits) as i64) >> bits)
+            }
+            0xd4..=0xd8 => self.ext(1 << (marker - 0xd4))?,
+            0xd9..=0xdb => {
+                let len = self.len(1 << (marker - 0xd9))?;
+                self.string(start, len)?
+            }
+            0xdc | 0xdd => {
+                let len = self.len(2 << (marker - 0xdc))?;
+                self.array(len, depth)?
+            }
+            0xde | 0xdf => {
+                let len = self.len(2 << (marker - 0xde))?;
+                self.map(len, depth)?
+            }
+            0xe0..=0xff => Value::from(marker as i8),
+        })
+    }
+
+    fn string(&mut self, start: usize, len: usize) -> Result<Value, DecodeError> {
+        let bytes = self.take(len)?;
+        let text = std::str::from_utf8(bytes).map_err(|_| (start, "string is not valid UTF-8".to_string()))?;
+        Ok(Value::String(text.to_string()))
+    }
+
+    fn ext(&mut self, len: usize) -> Result<Value, DecodeError> {
+        let kind = self.take(1)?[0] as i8;
+        Ok(json!({ "$ext": kind, "data": STANDARD.encode(self.take(len)?) }))
+    }
+
+    fn array(&mut self, len: usize, depth: usize) -> Result<Value, DecodeError> {
+        let depth = self.nest(depth)?;
+        let mut items = Vec::new();
+        for _ in 0..len {
+            items.push(self.value(depth)?);
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn map(&mut self, len: usize, depth: usize) -> Result<Value, DecodeError> {
+        let depth = self.nest(depth)?;
+        let mut map = Map::new();
+        for _ in 0..len {
+            let key = key_text(self.value(depth)?);
+            map.insert(key, self.value(depth)?);
+        }
+        Ok(Value::Object(map))
+    }
+
+    fn nest(&self, depth: usize) -> Result<usize, DecodeError> {
+        depth.checked_sub(1).ok_or_else(|| (self.pos, "nesting is too deep".to_string()))
+    }
+}
+
+fn write_msgpack(out: &mut Vec<u8>, value: &Value) -> Result<(), String> {
+    match value {
+        Value::Null => out.push(0xc0),
+        Value::Bool(b) => out.push(if *b { 0xc3 } else { 0xc2 }),
+        Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(u), _) if u < 0x80 => out.push(u as u8),
+            (Some(u), _) if u <= 0xff => out.extend([0xcc, u as u8]),
+            (Some(u), _) if u <= 0xffff => write_marker(out, 0xcd, &(u as u16).to_be_bytes()),
+            (Some(u), _) if u <= 0xffff_ffff => write_marker(out, 0xce, &(u as u32).to_be_bytes()),
+            (Some(u), _) => write_marker(out, 0xcf, &u.to_be_bytes()),
+            (None, Some(i)) if i >= -32 => out.push(i as u8),
+            (None, Some(i)) if i >= i64::from(i8::MIN) => out.extend([0xd0, i as u8]),
+            (None, Some(i)) if i >= i64::from(i16::MIN) => write_marker(out, 0xd1, &(i as i16).to_be_bytes()),
+            (None, Some(i)) if i >= i64::from(i32::MIN) => write_marker(out, 0xd2, &(i as i32).to_be_bytes()),
+            (None, Some(i)) => write_marker(out, 0xd3, &i.to_be_bytes()),
+            (None, None) => write_marker(out, 0xcb, &n.as_f64().unwrap_or_default().to_be_bytes()),
+        },
+        Value::String(s) => {
+            match s.len() {
+                len if len < 32 => out.push(0xa0 | len as u8),
+                len => write_len(out, [0xd9, 0xda, 0xdb], len)?,
+            }
+            out.extend(s.as_bytes());
+        }
+        Value::Array(items) => {
+            match items.len() {
+                len if len < 16 => out.push(0x90 | len as u8),
+                len => write_len(out, [0, 0xdc, 0xdd], len)?,
+            }
+            for item in items {
+                write_msgpack(out, item)?;
+            }
+        }
+        Value::Object(map) => {
+            if let Some(bytes) = binary_marker(map)? {
+                write_len(out, [0xc4, 0xc5, 0xc6], bytes.len())?;
+                out.extend(bytes);
+                return Ok(());
+            }
+            if let (2, Some(kind), Some(Value::String(data))) = (map.len(), map.get("$ext"), map.get("data")) {
+                let kind = kind
+                    .as_i64()
+                    .and_then(|kind| i8::try_from(kind).ok())
+                    .ok_or_else(|| format!("$ext type must be an integer from -128 to 127, found {}", kind))?;
+                let data = decode_base64("data", data)?;
+                match data.len() {
+                    1 => out.push(0xd4),
+                    2 => out.push(0xd5),
+                    4 => out.push(0xd6),
+                    8 => out.push(0xd7),
+                    16 => out.push(0xd8),
+                    len => write_len(out, [0xc7, 0xc8, 0xc9], len)?,
+                }
+                out.push(kind as u8);
+                out.extend(data);
+                return Ok(());
+            }
+            match map.len() {
+                len if len < 16 => out.push(0x80 | len as u8),
+                len => write_len(out, [0, 0xde, 0xdf], len)?,
+            }
+            for (key, value) in map {
+                write_msgpack(out, &Value::String(key.clone()))?;
+                write_msgpack(out, value)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_marker(out: &mut Vec<u8>, marker: u8, bytes: &[u8]) {
+    out.push(marker);
+    out.extend(bytes);
+}
+
+/// Writes the marker for a length with the 8-, 16- or 32-bit form of
+/// `markers`; a zero marker means that form doesn't exist.
+fn write_len(out: &mut Vec<u8>, markers: [u8; 3], len: usize) -> Result<(), String> {
+    match len {
+        len if len <= 0xff && markers[0] != 0 => out.extend([markers[0], len as u8]),
+        len if len <= 0xffff => write_marker(out, markers[1], &(len as u16).to_be_bytes()),
+        len => {
+            let len = u32::try_from(len).map_err(|_| format!("{} items or bytes is too many for MessagePack", len))?;
+            write_marker(out, markers[2], &len.to_be_bytes());
+        }
+    }
+    Ok(())
+}
+
+/// The bytes of a `{"$binary": "<base64>"}` marker.
+fn binary_marker(map: &Map<String, Value>) -> Result<Option<Vec<u8>>, String> {
+    match (map.len(), map.get("$binary")) {
+        (1, Some(Value::String(data))) => decode_base64("$binary", data).map(Some),
+        _ => Ok(None),
+    }
+}
+
+fn decode_base64(field: &str, data: &str) -> Result<Vec<u8>, String> {
+    STANDARD.decode(data).map_err(|e| format!("{} is not valid base64: {}", field, e))
+}
+
+fn float(f: f64) -> Value {
+    Number::from_f64(f).map_or(Value::Null, Value::Number)
+}
+
+fn key_text(key: Value) -> String {
+    match key {
+        Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
+
+fn check_trailing(format: &str, bytes: &[u8], rest: &[u8]) -> Result<(), String> {
+    if rest.is_empty() {
+        return Ok(());
+    }
+    let offset = bytes.len() - rest.len();
+    Err(decode_error(format, bytes, offset, &format!("{} bytes left after the first value", rest.len())))
+}
+
+/// An error message naming the byte offset, followed by a hex dump of the
+/// bytes around it with the offending byte marked.
+pub(crate) fn decode_error(format: &str, bytes: &[u8], offset: usize, message: &str) -> String {
+    format!(
+        "{} decoding error at byte {} (0x{:x}): {}\n{}",
+        format,
+        offset,
+        offset,
+        message,
+        hex_dump(bytes, offset)
+    )
+}
+
+/// Up to two rows of 16 bytes either side of the row holding `offset`, in
+/// the `hexdump -C` layout, with `^^` under the byte at `offset`.
+pub(crate) fn hex_dump(bytes: &[u8], offset: usize) -> String {
+    const ROW: usize = 16;
+    let focus = offset / ROW;
+    let last_row = bytes.len().saturating_sub(1) / ROW;
+    let mut out = String::new();
+    for row in focus.saturating_sub(2)..=(focus + 2).min(last_row).max(focus) {
+        let start = row * ROW;
+        let chunk = bytes.get(start..bytes.len().min(start + ROW)).unwrap_or_default();
+        let mut hex = String::new();
+        for i in 0..ROW {
+            if i == ROW / 2 {
+                hex.push(' ');
+            }
+            match chunk.get(i) {
+                Some(byte) => hex.push_str(&format!("{:02x} ", byte)),
+                None => hex.push_str("   "),
+            }
+        }
+        let ascii: String = chunk
+            .iter()
+            .map(|byte| if byte.is_ascii_graphic() || *byte == b' ' { *byte as char } else { '.' })
+            .collect();
+        out.push_str(&format!("{:08x}  {} |{}|\n", start, hex, ascii));
+        if row == focus {
+            let column = offset % ROW;
+            let indent = 10 + column * 3 + usize::from(column >= ROW / 2);
+            out.push_str(&format!("{}^^\n", " ".repeat(indent)));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document() -> Value {
+        json!({
+            "name": "widget",
+            "long": "x".repeat(40),
+            "counts": [0, 127, 128, 255, 65536, -1, -33, -129, -40000, i64::MIN, u64::MAX],
+            "ratio": 0.5,
+            "flags": {"on": true, "off": false, "none": null},
+            "blob": {"$binary": "AAEC/w=="},
+            "many": (0..20).collect::<Vec<_>>()
+        })
+    }
+
+    #[test]
+    fn msgpack_round_trips() {
+        let bytes = encode_msgpack(&document()).unwrap();
+        assert_eq!(decode_msgpack(&bytes).unwrap(), document());
+    }
+
+    #[test]
+    fn msgpack_uses_compact_forms() {
+        assert_eq!(encode_msgpack(&json!({"a": [1, -1, null]})).unwrap(), [0x81, 0xa1, b'a', 0x93, 0x01, 0xff, 0xc0]);
+        assert_eq!(encode_msgpack(&json!(300)).unwrap(), [0xcd, 0x01, 0x2c]);
+    }
+
+    #[test]
+    fn msgpack_extensions_round_trip() {
+        let timestamp = json!({"$ext": -1, "data": "AAAAAGXv/4A="});
+        let bytes = encode_msgpack(&timestamp).unwrap();
+        assert_eq!(bytes[..2], [0xd7, 0xff]);
+        assert_eq!(decode_msgpack(&bytes).unwrap(), timestamp);
+        assert!(encode_msgpack(&json!({"$ext": 300, "data": ""})).is_err());
+    }
+
+    #[test]
+    fn msgpack_errors_point_at_the_offset() {
+        let error = decode_msgpack(&[0x92, 0x01]).unwrap_err();
+        assert!(error.starts_with("MessagePack decoding error at byte 2"), "{}", error);
+        assert!(decode_msgpack(&[0x01, 0x02]).unwrap_err().contains("1 bytes left after the first value"));
+        assert!(decode_msgpack(&[0xc1]).is_err());
+    }
+
+    #[test]
+    fn msgpack_nesting_is_limited() {
+        let bytes = vec![0x91; MAX_DEPTH + 1];
+        assert!(decode_msgpack(&bytes).unwrap_err().contains("nesting is too deep"));
+    }
+
+    #[test]
+    fn cbor_round_trips() {
+        let bytes = encode_cbor(&document()).unwrap();
+        assert_eq!(decode_cbor(&bytes).unwrap(), document());
+    }
+
+    #[test]
+    fn cbor_tags_and_non_string_keys() {
+        let date = json!({"$tag": 0, "value": "2024-01-01T00:00:00Z"});
+        let bytes = encode_cbor(&date).unwrap();
+        assert_eq!(bytes[0], 0xc0);
+        assert_eq!(decode_cbor(&bytes).unwrap(), date);
+        // {1: "a", true: "b"}
+        assert_eq!(decode_cbor(&[0xa2, 0x01, 0x61, b'a', 0xf5, 0x61, b'b']).unwrap(), json!({"1": "a", "true": "b"}));
+    }
+
+    #[test]
+    fn cbor_errors_point_at_the_offset() {
+        let error = decode_cbor(&[0x82, 0x01]).unwrap_err();
+        assert!(error.starts_with("CBOR decoding error"), "{}", error);
+        assert!(decode_cbor(&[0x01, 0x02]).unwrap_err().contains("1 bytes left after the first value"));
+    }
+}
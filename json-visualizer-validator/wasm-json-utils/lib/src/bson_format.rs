@@ -0,0 +1,362 @@
+use js_sys::Uint8Array;
+use std::convert::TryFrom;
+use wasm_bindgen::prelude::*;
+
+use crate::binary_formats::{decode_error, MAX_DEPTH};
+use crate::extended_json::{Bson, ExtendedJsonMode};
+use crate::lenient_json::{parse_json_text, JsonSyntax};
+
+// BSON, as MongoDB stores documents.
+//
+// Documents decode to Extended JSON (see `extended_json`) and encode from it,
+// so ObjectIds, dates, int64s, decimals and binary data survive the round
+// trip. A `mongodump` file is a run of documents back to back; it decodes to
+// a JSON array, and an array of objects encodes back to one.
+
+/// Decodes BSON into pretty-printed Extended JSON. `mode` is "relaxed"
+/// (the default), "canonical", or "plain" for JSON without type wrappers.
+#[wasm_bindgen]
+pub fn bson_to_json(bytes: &Uint8Array, mode: Option<String>) -> Result<String, JsValue> {
+    let mode = ExtendedJsonMode::parse(mode.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    let bson = decode_bson(&bytes.to_vec()).map_err(|e| JsValue::from_str(&e))?;
+    serde_json::to_string_pretty(&bson.to_extended_json(mode)).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Encodes an Extended JSON object, or an array of them, as BSON.
+/// `syntax` works as for `json_to_yaml`.
+#[wasm_bindgen]
+pub fn json_to_bson(json_str: &str, syntax: Option<String>) -> Result<Uint8Array, JsValue> {
+    let syntax = JsonSyntax::parse(syntax.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    let value = parse_json_text(json_str, syntax).map_err(|e| JsValue::from_str(&format!("JSON parsing error: {}", e)))?;
+    let bson = Bson::from_extended_json(&value).map_err(|e| JsValue::from_str(&e))?;
+    let bytes = encode_bson(&bson).map_err(|e| JsValue::from_str(&e))?;
+    Ok(Uint8Array::from(bytes.as_slice()))
+}
+
+/// Decodes one document, or an array of them when several follow each other.
+pub(crate) fn decode_bson(bytes: &[u8]) -> Result<Bson, String> {
+    let mut reader = BsonReader { bytes, pos: 0 };
+    let mut documents = Vec::new();
+    loop {
+        let document = reader.document(MAX_DEPTH).map_err(|(offset, message)| decode_error("BSON", bytes, offset, &message))?;
+        documents.push(Bson::Document(document));
+        if reader.pos == bytes.len() {
+            break;
+        }
+    }
+    Ok(if documents.len() == 1 { documents.remove(0) } else { Bson::Array(documents) })
+}
+
+/// Encodes a document, or each document of an array back to back.
+pub(crate) fn encode_bson(bson: &Bson) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    match bson {
+        Bson::Document(entries) => write_document(&mut out, entries)?,
+        Bson::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                match item {
+                    Bson::Document(entries) => write_document(&mut out, entries)?,
+                    other => return Err(format!("BSON documents must be objects, but item {} is {}", i, other.type_name())),
+                }
+            }
+        }
+        other => return Err(format!("BSON documents must be objects, found {}", other.type_name())),
+    }
+    Ok(out)
+}
+
+/// A decoding failure: the byte offset and what went wrong there.
+type DecodeError = (usize, String);
+
+struct BsonReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BsonReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let end = self.pos.checked_add(len).filter(|end| *end <= self.bytes.len());
+        let end = end.ok_or_else(|| (self.bytes.len(), "unexpected end of input".to_string()))?;
+        let taken = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn i32(&mut self) -> Result<i32, DecodeError> {
+        Ok(i32::from_le_bytes(self.array()?))
+    }
+
+    /// A length prefix, which must be at least `min`.
+    fn len(&mut self, min: usize, what: &str) -> Result<usize, DecodeError> {
+        let start = self.pos;
+        let len = self.i32()?;
+        usize::try_from(len)
+            .ok()
+            .filter(|len| *len >= min)
+            .ok_or_else(|| (start, format!("{} length {} is less than {}", what, len, min)))
+    }
+
+    fn cstring(&mut self) -> Result<String, DecodeError> {
+        let start = self.pos;
+        let rest = &self.bytes[start..];
+        let len = rest
+            .iter()
+            .position(|byte| *byte == 0)
+            .ok_or_else(|| (self.bytes.len(), "unexpected end of input in a C string".to_string()))?;
+        self.pos += len + 1;
+        utf8(&rest[..len], start)
+    }
+
+    fn string(&mut self) -> Result<String, DecodeError> {
+        let len = self.len(1, "string")?;
+        let start = self.pos;
+        let bytes = self.take(len)?;
+        if bytes[len - 1] != 0 {
+            return Err((start + len - 1, "string is missing its terminating null byte".to_string()));
+        }
+        utf8(&bytes[..len - 1], start)
+    }
+
+    fn document(&mut self, depth: usize) -> Result<Vec<(String, Bson)>, DecodeError> {
+        let start = self.pos;
+        if depth == 0 {
+            return Err((start, "nesting is too deep".to_string()));
+        }
+        let len = self.len(5, "document")?;
+        if start + len > self.bytes.len() {
+            return Err((start, format!("document length {} runs past the end of input", len)));
+        }
+        let mut entries = Vec::new();
+        loop {
+            let element_start = self.pos;
+            let element_type = self.take(1)?[0];
+            if element_type == 0 {
+                break;
+            }
+            let name = self.cstring()?;
+            let value = self.element(element_start, element_type, depth)?;
+            entries.push((name, value));
+        }
+        if self.pos != start + len {
+            let message = format!("document length is {} but its elements end after {} bytes", len, self.pos - start);
+            return Err((start, message));
+        }
+        Ok(entries)
+    }
+
+    fn element(&mut self, start: usize, element_type: u8, depth: usize) -> Result<Bson, DecodeError> {
+        Ok(match element_type {
+            0x01 => Bson::Double(f64::from_le_bytes(self.array()?)),
+            0x02 => Bson::String(self.string()?),
+            0x03 => Bson::Document(self.document(depth - 1)?),
+            0x04 => Bson::Array(self.document(depth - 1)?.into_iter().map(|(_, value)| value).collect()),
+            0x05 => {
+                let len = self.len(0, "binary")?;
+                let subtype = self.take(1)?[0];
+                Bson::Binary(subtype, self.take(len)?.to_vec())
+            }
+            0x06 => Bson::Undefined,
+            0x07 => Bson::ObjectId(self.array()?),
+            0x08 => match self.take(1)?[0] {
+                0 => Bson::Boolean(false),
+                1 => Bson::Boolean(true),
+                other => return Err((self.pos - 1, format!("boolean byte is 0x{:02x}, not 0 or 1", other))),
+            },
+            0x09 => Bson::DateTime(i64::from_le_bytes(self.array()?)),
+            0x0a => Bson::Null,
+            0x0b => Bson::Regex(self.cstring()?, self.cstring()?),
+            0x0c => Bson::DbPointer(self.string()?, self.array()?),
+            0x0d => Bson::Code(self.string()?),
+            0x0e => Bson::Symbol(self.string()?),
+            0x0f => {
+                let scope_start = self.pos;
+                let len = self.len(14, "code with scope")?;
+                let code = self.string()?;
+                let scope = self.document(depth - 1)?;
+                if self.pos != scope_start + len {
+                    return Err((scope_start, format!("code with scope length is {} but it ends after {} bytes", len, self.pos - scope_start)));
+                }
+                Bson::CodeWithScope(code, scope)
+            }
+            0x10 => Bson::Int32(self.i32()?),
+            0x11 => {
+                let increment = u32::from_le_bytes(self.array()?);
+                Bson::Timestamp(u32::from_le_bytes(self.array()?), increment)
+            }
+            0x12 => Bson::Int64(i64::from_le_bytes(self.array()?)),
+            0x13 => Bson::Decimal128(self.array()?),
+            0xff => Bson::MinKey,
+            0x7f => Bson::MaxKey,
+            other => return Err((start, format!("unknown element type 0x{:02x}", other))),
+        })
+    }
+}
+
+fn utf8(bytes: &[u8], start: usize) -> Result<String, DecodeError> {
+    String::from_utf8(bytes.to_vec()).map_err(|e| (start + e.utf8_error().valid_up_to(), "invalid UTF-8".to_string()))
+}
+
+fn write_document(out: &mut Vec<u8>, entries: &[(String, Bson)]) -> Result<(), String> {
+    let start = out.len();
+    out.extend([0; 4]);
+    for (name, value) in entries {
+        write_element(out, name, value)?;
+    }
+    out.push(0);
+    let len = i32::try_from(out.len() - start).map_err(|_| "document is too large for BSON".to_string())?;
+    out[start..start + 4].copy_from_slice(&len.to_le_bytes());
+    Ok(())
+}
+
+fn write_element(out: &mut Vec<u8>, name: &str, value: &Bson) -> Result<(), String> {
+    out.push(element_type(value));
+    write_cstring(out, name, "key")?;
+    match value {
+        Bson::Double(f) => out.extend(f.to_le_bytes()),
+        Bson::String(s) | Bson::Code(s) | Bson::Symbol(s) => write_string(out, s)?,
+        Bson::Document(entries) => write_document(out, entries)?,
+        Bson::Array(items) => {
+            let entries: Vec<(String, Bson)> = items.iter().enumerate().map(|(i, item)| (i.to_string(), item.clone())).collect();
+            write_document(out, &entries)?;
+        }
+        Bson::Binary(subtype, bytes) => {
+            let len = i32::try_from(bytes.len()).map_err(|_| "binary value is too large for BSON".to_string())?;
+            out.extend(len.to_le_bytes());
+            out.push(*subtype);
+            out.extend(bytes);
+        }
+        Bson::ObjectId(id) => out.extend(id),
+        Bson::Boolean(b) => out.push(u8::from(*b)),
+        Bson::DateTime(millis) | Bson::Int64(millis) => out.extend(millis.to_le_bytes()),
+        Bson::Regex(pattern, options) => {
+            write_cstring(out, pattern, "regular expression pattern")?;
+            // Drivers require the options in alphabetical order.
+            let mut options: Vec<char> = options.chars().collect();
+            options.sort_unstable();
+            write_cstring(out, &options.into_iter().collect::<String>(), "regular expression options")?;
+        }
+        Bson::DbPointer(namespace, id) => {
+            write_string(out, namespace)?;
+            out.extend(id);
+        }
+        Bson::CodeWithScope(code, scope) => {
+            let start = out.len();
+            out.extend([0; 4]);
+            write_string(out, code)?;
+            write_document(out, scope)?;
+            let len = (out.len() - start) as i32;
+            out[start..start + 4].copy_from_slice(&len.to_le_bytes());
+        }
+        Bson::Int32(i) => out.extend(i.to_le_bytes()),
+        Bson::Timestamp(t, i) => {
+            out.extend(i.to_le_bytes());
+            out.extend(t.to_le_bytes());
+        }
+        Bson::Decimal128(bytes) => out.extend(bytes),
+        Bson::Undefined | Bson::Null | Bson::MinKey | Bson::MaxKey => {}
+    }
+    Ok(())
+}
+
+fn element_type(value: &Bson) -> u8 {
+    match value {
+        Bson::Double(_) => 0x01,
+        Bson::String(_) => 0x02,
+        Bson::Document(_) => 0x03,
+        Bson::Array(_) => 0x04,
+        Bson::Binary(..) => 0x05,
+        Bson::Undefined => 0x06,
+        Bson::ObjectId(_) => 0x07,
+        Bson::Boolean(_) => 0x08,
+        Bson::DateTime(_) => 0x09,
+        Bson::Null => 0x0a,
+        Bson::Regex(..) => 0x0b,
+        Bson::DbPointer(..) => 0x0c,
+        Bson::Code(_) => 0x0d,
+        Bson::Symbol(_) => 0x0e,
+        Bson::CodeWithScope(..) => 0x0f,
+        Bson::Int32(_) => 0x10,
+        Bson::Timestamp(..) => 0x11,
+        Bson::Int64(_) => 0x12,
+        Bson::Decimal128(_) => 0x13,
+        Bson::MinKey => 0xff,
+        Bson::MaxKey => 0x7f,
+    }
+}
+
+fn write_cstring(out: &mut Vec<u8>, text: &str, what: &str) -> Result<(), String> {
+    if text.contains('\0') {
+        return Err(format!("BSON {} {:?} contains a null character", what, text));
+    }
+    out.extend(text.as_bytes());
+    out.push(0);
+    Ok(())
+}
+
+fn write_string(out: &mut Vec<u8>, text: &str) -> Result<(), String> {
+    let len = i32::try_from(text.len() + 1).map_err(|_| "string is too large for BSON".to_string())?;
+    out.extend(len.to_le_bytes());
+    out.extend(text.as_bytes());
+    out.push(0);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn round_trip(value: Value) {
+        let bson = Bson::from_extended_json(&value).unwrap();
+        let decoded = decode_bson(&encode_bson(&bson).unwrap()).unwrap();
+        let canonical = ExtendedJsonMode::Canonical;
+        assert_eq!(decoded.to_extended_json(canonical), bson.to_extended_json(canonical));
+    }
+
+    #[test]
+    fn documents_round_trip() {
+        round_trip(json!({
+            "_id": {"$oid": "65f1a2b3c4d5e6f708192a3b"},
+            "name": "widget",
+            "count": 3,
+            "big": {"$numberLong": "9007199254740993"},
+            "price": {"$numberDecimal": "19.99"},
+            "ratio": 0.25,
+            "tags": ["a", {"nested": true}],
+            "when": {"$date": "2024-01-02T03:04:05.678Z"},
+            "blob": {"$binary": {"base64": "AAEC", "subType": "00"}},
+            "uuid": {"$uuid": "123e4567-e89b-12d3-a456-426614174000"},
+            "re": {"$regularExpression": {"pattern": "^a", "options": "i"}},
+            "ts": {"$timestamp": {"t": 1, "i": 2}},
+            "nothing": null
+        }));
+    }
+
+    #[test]
+    fn encodes_the_spec_example() {
+        let bson = Bson::from_extended_json(&json!({"hello": "world"})).unwrap();
+        let bytes = encode_bson(&bson).unwrap();
+        assert_eq!(bytes, b"\x16\x00\x00\x00\x02hello\x00\x06\x00\x00\x00world\x00\x00");
+    }
+
+    #[test]
+    fn concatenated_documents_become_an_array() {
+        let bson = Bson::from_extended_json(&json!([{"a": 1}, {"b": 2}])).unwrap();
+        let decoded = decode_bson(&encode_bson(&bson).unwrap()).unwrap();
+        assert_eq!(decoded.to_extended_json(ExtendedJsonMode::Relaxed), json!([{"a": 1}, {"b": 2}]));
+        assert!(encode_bson(&Bson::Int32(1)).is_err());
+    }
+
+    #[test]
+    fn truncated_input_is_an_error() {
+        let error = decode_bson(b"\x16\x00\x00\x00\x02hello\x00").unwrap_err();
+        assert!(error.starts_with("BSON decoding error"), "{}", error);
+        assert!(decode_bson(&[0x05, 0x00, 0x00, 0x00, 0x01]).is_err());
+    }
+}
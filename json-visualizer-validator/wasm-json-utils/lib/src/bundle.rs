@@ -0,0 +1,380 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashSet};
+
+use crate::extended_json::to_plain;
+use crate::lenient_json::{parse_json_text, JsonSyntax};
+use crate::naming::{python_field_name, rust_field_name, snake_case};
+use crate::schema::infer_schema;
+use crate::schema_types::{
+    build_type_model, go_file, golang_def, java_file, java_union_parents, java_unit, python_def,
+    python_header, referenced_names, rust_def, rust_file, typescript_def, JavaOptions, PythonImports,
+    PythonStyle, TypeModel,
+};
+
+// Multi-file code generation.
+//
+// Instead of one string with every type in it, each named type goes into its
+// own file laid out the way the target language expects (a Java package, a
+// Python package with `__init__.py`, a TypeScript barrel, ...). The result is
+// a JSON array of `{path, contents}` objects.
+
+#[derive(Debug, Serialize)]
+struct GeneratedFile {
+    path: String,
+    contents: String,
+}
+
+/// Options for a bundle, e.g. `{"package": "com.example.model", "java": {"style": "record"}}`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct BundleOptions {
+    /// Java/Go package, Python package or TypeScript/Rust module directory.
+    package: Option<String>,
+    /// Python class style: dataclass, pydantic or typeddict.
+    flavor: Option<String>,
+    /// Java style and annotations; types always go to separate files.
+    java: JavaOptions,
+}
+
+/// `language` is one of typescript, java, golang, python or rust.
+#[wasm_bindgen]
+pub fn json_schema_to_bundle(schema_str: &str, language: &str, options: Option<String>) -> Result<String, JsValue> {
+    let schema: Value = serde_json::from_str(schema_str)
+        .map_err(|e| JsValue::from_str(&format!("JSON Schema parsing error: {}", e)))?;
+    generate_bundle(&schema, language, options.as_deref()).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Same as `json_schema_to_bundle`, with the schema inferred from a sample document
+/// in any JSON `syntax`. NDJSON lines are merged into one record type.
+#[wasm_bindgen]
+pub fn json_to_bundle(
+    json_str: &str,
+    language: &str,
+    options: Option<String>,
+    syntax: Option<String>,
+) -> Result<String, JsValue> {
+    let syntax = JsonSyntax::parse(syntax.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    let sample = parse_json_text(json_str, syntax)
+        .map(|value| to_plain(&value))
+        .map_err(|e| JsValue::from_str(&format!("JSON parsing error: {}", e)))?;
+    let schema = match &sample {
+        Value::Array(records) if syntax == JsonSyntax::Ndjson => infer_schema(&records.iter().collect::<Vec<_>>()),
+        sample => infer_schema(&[sample]),
+    };
+    generate_bundle(&schema, language, options.as_deref()).map_err(|e| JsValue::from_str(&e))
+}
+
+fn generate_bundle(schema: &Value, language: &str, options: Option<&str>) -> Result<String, String> {
+    let options: BundleOptions = match options.map(str::trim) {
+        None | Some("") => BundleOptions::default(),
+        Some(options) => serde_json::from_str(options).map_err(|e| format!("Invalid bundle options: {}", e))?,
+    };
+    let model = build_type_model(schema, "RootType")?;
+    let package = options.package.as_deref().map(str::trim).filter(|p| !p.is_empty());
+
+    let files = match language.to_ascii_lowercase().as_str() {
+        "typescript" | "ts" => typescript_files(&model, package.unwrap_or("models")),
+        "java" => java_files(&model, package, &options.java),
+        "golang" | "go" => golang_files(&model, package.unwrap_or("models")),
+        "python" | "py" => {
+            let style = PythonStyle::parse(options.flavor.as_deref().unwrap_or_default())?;
+            python_files(&model, package.unwrap_or("models"), style)
+        }
+        "rust" | "rs" => rust_files(&model, package.unwrap_or("models")),
+        other => return Err(format!("Unsupported language for bundles: {}", other)),
+    };
+
+    serde_json::to_string_pretty(&files).map_err(|e| format!("Bundle serialization error: {}", e))
+}
+
+/// Turns a dotted package name into a directory path.
+fn package_dir(package: &str) -> String {
+    package.split(['.', '/']).filter(|part| !part.is_empty()).collect::<Vec<_>>().join("/")
+}
+
+fn file_path(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+/// A Python module name for a type, clear of keywords.
+fn python_module(type_name: &str) -> String {
+    python_field_name(type_name)
+}
+
+fn typescript_files(model: &TypeModel, package: &str) -> Vec<GeneratedFile> {
+    let dir = package_dir(package);
+    let mut files = Vec::new();
+    let mut index = String::new();
+    for def in &model.defs {
+        let mut contents = String::new();
+        let references = referenced_names(def);
+        for name in &references {
+            contents.push_str(&format!("import type {{ {} }} from \"./{}\";\n", name, name));
+        }
+        if !references.is_empty() {
+            contents.push('\n');
+        }
+        contents.push_str(&typescript_def(def));
+        contents.push('\n');
+        files.push(GeneratedFile {
+            path: file_path(&dir, &format!("{}.ts", def.name)),
+            contents,
+        });
+        index.push_str(&format!("export * from \"./{}\";\n", def.name));
+    }
+    files.push(GeneratedFile {
+        path: file_path(&dir, "index.ts"),
+        contents: index,
+    });
+    files
+}
+
+fn java_files(model: &TypeModel, package: Option<&str>, options: &JavaOptions) -> Vec<GeneratedFile> {
+    let dir = package.map(package_dir).unwrap_or_default();
+    let parents = java_union_parents(model);
+    model
+        .defs
+        .iter()
+        .map(|def| {
+            let unit = java_unit(def, model, options, &parents, false);
+            let mut contents = String::new();
+            if let Some(package) = package {
+                contents.push_str(&format!("package {};\n\n", package));
+            }
+            contents.push_str(&java_file(&unit.imports, &unit.code));
+            GeneratedFile {
+                path: file_path(&dir, &format!("{}.java", unit.name)),
+                contents,
+            }
+        })
+        .collect()
+}
+
+fn golang_files(model: &TypeModel, package: &str) -> Vec<GeneratedFile> {
+    let dir = package_dir(package);
+    // Go names a package after the last element of its import path.
+    let name = dir.rsplit('/').next().map(snake_case).filter(|n| !n.is_empty()).unwrap_or_else(|| "models".to_string());
+    model
+        .defs
+        .iter()
+        .map(|def| {
+            let mut imports = BTreeSet::new();
+            let code = golang_def(model, def, &mut imports);
+            GeneratedFile {
+                path: file_path(&dir, &format!("{}.go", snake_case(&def.name))),
+                contents: go_file(&name, &imports, &code),
+            }
+        })
+        .collect()
+}
+
+fn python_files(model: &TypeModel, package: &str, style: PythonStyle) -> Vec<GeneratedFile> {
+    let dir = package_dir(package);
+    let mut files = Vec::new();
+    let mut earlier = HashSet::new();
+    let mut init = String::new();
+    let mut exported = Vec::new();
+
+    for def in &model.defs {
+        // Definitions come in dependency order, so a reference to a later one
+        // means a cycle. Those modules are imported at the bottom, after this
+        // module's own class exists, which keeps the circular import working.
+        let (before, after): (Vec<String>, Vec<String>) =
+            referenced_names(def).into_iter().partition(|name| earlier.contains(name));
+        let defined: HashSet<String> = before.iter().cloned().collect();
+
+        let mut imports = PythonImports::default();
+        let code = python_def(def, style, &mut imports, &defined);
+        let mut contents = python_header(&imports);
+        if !before.is_empty() {
+            if !contents.ends_with("annotations\n\n") {
+                contents.push('\n');
+            }
+            for name in &before {
+                contents.push_str(&format!("from .{} import {}\n", python_module(name), name));
+            }
+        }
+        contents.push_str("\n\n");
+        contents.push_str(&code);
+        if !after.is_empty() {
+            contents.push_str("\n\n");
+            for name in &after {
+                contents.push_str(&format!("from .{} import {}  # noqa: E402\n", python_module(name), name));
+            }
+        }
+        files.push(GeneratedFile {
+            path: file_path(&dir, &format!("{}.py", python_module(&def.name))),
+            contents,
+        });
+
+        init.push_str(&format!("from .{} import {}\n", python_module(&def.name), def.name));
+        exported.push(format!("    \"{}\",", def.name));
+        earlier.insert(def.name.clone());
+    }
+
+    init.push_str(&format!("\n__all__ = [\n{}\n]\n", exported.join("\n")));
+    files.push(GeneratedFile {
+        path: file_path(&dir, "__init__.py"),
+        contents: init,
+    });
+    files
+}
+
+fn rust_files(model: &TypeModel, package: &str) -> Vec<GeneratedFile> {
+    let dir = package_dir(package);
+    let mut files = Vec::new();
+    let mut modules = Vec::new();
+    for def in &model.defs {
+        let mut uses = BTreeSet::new();
+        let code = rust_def(model, def, &mut uses);
+        let mut contents = rust_file(&uses, &code);
+        let references: Vec<String> = referenced_names(def).into_iter().collect();
+        if !references.is_empty() {
+            let line = match references.as_slice() {
+                [single] => format!("use super::{};\n", single),
+                _ => format!("use super::{{{}}};\n", references.join(", ")),
+            };
+            // Keep the local import with the other `use` lines.
+            match contents.find("\n\n") {
+                Some(i) if contents.starts_with("use ") => contents.insert_str(i + 1, &line),
+                _ => contents.insert_str(0, &format!("{}\n", line)),
+            }
+        }
+        let module = snake_case(&def.name);
+        files.push(GeneratedFile {
+            path: file_path(&dir, &format!("{}.rs", module)),
+            contents,
+        });
+        modules.push((module, def.name.clone()));
+    }
+
+    let mut mod_rs = String::new();
+    for (module, _) in &modules {
+        mod_rs.push_str(&format!("mod {};\n", raw_module(module)));
+    }
+    mod_rs.push('\n');
+    for (module, name) in &modules {
+        mod_rs.push_str(&format!("pub use self::{}::{};\n", raw_module(module), name));
+    }
+    files.push(GeneratedFile {
+        path: file_path(&dir, "mod.rs"),
+        contents: mod_rs,
+    });
+    files
+}
+
+/// Module names that are Rust keywords need the raw identifier syntax.
+fn raw_module(module: &str) -> String {
+    match rust_field_name(module) {
+        name if name.starts_with("r#") => name,
+        _ => module.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// The bundle's `(path, contents)` pairs for an order schema.
+    fn bundle(language: &str, options: Option<&str>) -> Vec<(String, String)> {
+        let schema = json!({
+            "title": "Order",
+            "type": "object",
+            "properties": {
+                "id": {"type": "integer"},
+                "type": {"enum": ["a", "b"]},
+                "customer": {"$ref": "#/definitions/Customer"}
+            },
+            "definitions": {"Customer": {"type": "object", "properties": {"name": {"type": "string"}}}}
+        });
+        let files: Value = serde_json::from_str(&generate_bundle(&schema, language, options).unwrap()).unwrap();
+        files
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|file| (file["path"].as_str().unwrap().to_string(), file["contents"].as_str().unwrap().to_string()))
+            .collect()
+    }
+
+    fn contents<'f>(files: &'f [(String, String)], path: &str) -> &'f str {
+        &files.iter().find(|(p, _)| p == path).unwrap_or_else(|| panic!("no {} in {:?}", path, files)).1
+    }
+
+    fn paths(files: &[(String, String)]) -> Vec<&str> {
+        files.iter().map(|(path, _)| path.as_str()).collect()
+    }
+
+    #[test]
+    fn java_files_go_in_the_package_directory() {
+        let files = bundle("java", Some(r#"{"package": "com.example.model", "java": {"style": "record"}}"#));
+        let expected =
+            ["com/example/model/Customer.java", "com/example/model/OrderType.java", "com/example/model/Order.java"];
+        assert_eq!(paths(&files), expected);
+        let order = contents(&files, "com/example/model/Order.java");
+        assert!(order.starts_with("package com.example.model;\n\n"), "{}", order);
+        assert!(order.contains("public record Order("), "{}", order);
+    }
+
+    #[test]
+    fn typescript_bundles_have_a_barrel() {
+        let files = bundle("typescript", None);
+        assert_eq!(paths(&files), ["models/Customer.ts", "models/OrderType.ts", "models/Order.ts", "models/index.ts"]);
+        let order = contents(&files, "models/Order.ts");
+        assert!(order.starts_with("import type { Customer } from \"./Customer\";\n"), "{}", order);
+        let index = contents(&files, "models/index.ts");
+        assert!(index.contains("export * from \"./Customer\";\n"), "{}", index);
+        assert!(index.contains("export * from \"./Order\";\n"), "{}", index);
+    }
+
+    #[test]
+    fn python_packages_have_an_init_module() {
+        let files = bundle("python", Some(r#"{"package": "shop.models", "flavor": "pydantic"}"#));
+        let expected = [
+            "shop/models/customer.py",
+            "shop/models/order_type.py",
+            "shop/models/order.py",
+            "shop/models/__init__.py",
+        ];
+        assert_eq!(paths(&files), expected);
+        let order = contents(&files, "shop/models/order.py");
+        assert!(order.contains("from .customer import Customer\nfrom .order_type import OrderType\n"), "{}", order);
+        assert!(order.contains("class Order(BaseModel):"), "{}", order);
+        let init = contents(&files, "shop/models/__init__.py");
+        assert!(init.contains("from .order import Order\n"), "{}", init);
+        assert!(init.contains("__all__ = [\n    \"Customer\",\n"), "{}", init);
+    }
+
+    #[test]
+    fn rust_modules_have_a_mod_file() {
+        let files = bundle("rust", None);
+        assert_eq!(paths(&files), ["models/customer.rs", "models/order_type.rs", "models/order.rs", "models/mod.rs"]);
+        let order = contents(&files, "models/order.rs");
+        assert!(order.contains("use super::{Customer, OrderType};\n"), "{}", order);
+        assert!(order.contains("    pub r#type: Option<OrderType>,\n"), "{}", order);
+        let module = contents(&files, "models/mod.rs");
+        assert!(module.contains("mod order_type;\n"), "{}", module);
+        assert!(module.contains("pub use self::order::Order;\n"), "{}", module);
+    }
+
+    #[test]
+    fn go_files_share_the_package() {
+        let files = bundle("go", Some(r#"{"package": "api/model"}"#));
+        assert_eq!(paths(&files), ["api/model/customer.go", "api/model/order_type.go", "api/model/order.go"]);
+        assert!(files.iter().all(|(_, contents)| contents.starts_with("package model\n")), "{:?}", files);
+    }
+
+    #[test]
+    fn bad_languages_and_options_are_reported() {
+        let schema = json!({"type": "object"});
+        assert!(generate_bundle(&schema, "cobol", None).is_err());
+        assert!(generate_bundle(&schema, "java", Some(r#"{"packages": "x"}"#)).is_err());
+        assert!(generate_bundle(&schema, "python", Some(r#"{"flavor": "attrs"}"#)).is_err());
+    }
+}
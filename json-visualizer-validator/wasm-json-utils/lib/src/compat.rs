@@ -0,0 +1,589 @@
+use wasm_bindgen::prelude::*;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeSet, HashSet};
+
+use crate::validator::{escape, resolve_ref};
+
+// Schema compatibility.
+//
+// Walks two versions of a schema side by side and classifies each difference
+// by what it does to the set of valid documents. A change that narrows the set
+// breaks writers still producing documents for the old schema; one that widens
+// it breaks readers still expecting documents of the old shape.
+
+/// Ordered from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum Compat {
+    NonBreaking,
+    Unknown,
+    Breaking,
+}
+
+/// What a change does to the documents the schema accepts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Effect {
+    Narrowed,
+    Widened,
+    /// Accepts some new documents and rejects some old ones.
+    Changed,
+    Unknown,
+}
+
+impl Effect {
+    /// Whether readers of old documents and writers of old documents are affected.
+    fn classify(self) -> (Compat, Compat) {
+        match self {
+            Effect::Narrowed => (Compat::NonBreaking, Compat::Breaking),
+            Effect::Widened => (Compat::Breaking, Compat::NonBreaking),
+            Effect::Changed => (Compat::Breaking, Compat::Breaking),
+            Effect::Unknown => (Compat::Unknown, Compat::Unknown),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Difference {
+    keyword: String,
+    message: String,
+    old_path: String,
+    new_path: String,
+    /// Consumers built against the old schema reading documents valid under the new one.
+    reader: Compat,
+    /// Producers still writing documents for the old schema to consumers of the new one.
+    writer: Compat,
+}
+
+#[derive(Serialize)]
+struct CompatReport {
+    reader: Compat,
+    writer: Compat,
+    differences: Vec<Difference>,
+}
+
+/// Compares two versions of a JSON Schema. Returns the overall reader and
+/// writer compatibility and every difference, with JSON Pointers into both.
+#[wasm_bindgen]
+pub fn schema_compat(old_str: &str, new_str: &str) -> Result<String, JsValue> {
+    let old: Value = serde_json::from_str(old_str)
+        .map_err(|e| JsValue::from_str(&format!("Old schema parsing error: {}", e)))?;
+    let new: Value = serde_json::from_str(new_str)
+        .map_err(|e| JsValue::from_str(&format!("New schema parsing error: {}", e)))?;
+
+    let differences = compare_schemas(&old, &new);
+    let worst = |side: fn(&Difference) -> Compat| {
+        differences.iter().map(side).max().unwrap_or(Compat::NonBreaking)
+    };
+    let report = CompatReport {
+        reader: worst(|d| d.reader),
+        writer: worst(|d| d.writer),
+        differences,
+    };
+    serde_json::to_string_pretty(&report).map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+fn compare_schemas(old: &Value, new: &Value) -> Vec<Difference> {
+    let mut comparison = Comparison {
+        old_root: old,
+        new_root: new,
+        visited: HashSet::new(),
+        differences: Vec::new(),
+    };
+    comparison.compare(old, "#", new, "#");
+    comparison.differences
+}
+
+struct Comparison<'a> {
+    old_root: &'a Value,
+    new_root: &'a Value,
+    /// Pairs of paths already compared, which ends recursion through `$ref`s.
+    visited: HashSet<(String, String)>,
+    differences: Vec<Difference>,
+}
+
+impl<'a> Comparison<'a> {
+    fn report(&mut self, keyword: &str, old_path: &str, new_path: &str, effect: Effect, message: String) {
+        let (reader, writer) = effect.classify();
+        self.differences.push(Difference {
+            keyword: keyword.to_string(),
+            message,
+            old_path: join(old_path, keyword),
+            new_path: join(new_path, keyword),
+            reader,
+            writer,
+        });
+    }
+
+    fn compare(&mut self, old: &'a Value, old_path: &str, new: &'a Value, new_path: &str) {
+        if !self.visited.insert((old_path.to_string(), new_path.to_string())) {
+            return;
+        }
+        let (old, old_path) = match self.follow(self.old_root, old, old_path) {
+            Some(resolved) => resolved,
+            None => return self.report("$ref", old_path, new_path, Effect::Unknown, "Unresolvable reference in the old schema".to_string()),
+        };
+        let (new, new_path) = match self.follow(self.new_root, new, new_path) {
+            Some(resolved) => resolved,
+            None => return self.report("$ref", &old_path, new_path, Effect::Unknown, "Unresolvable reference in the new schema".to_string()),
+        };
+        if old == new {
+            return;
+        }
+
+        let (old_obj, new_obj) = match (old, new) {
+            (Value::Object(old_obj), Value::Object(new_obj)) => (old_obj, new_obj),
+            _ => {
+                let effect = match (old, new) {
+                    (_, Value::Bool(false)) | (Value::Bool(true), _) => Effect::Narrowed,
+                    _ => Effect::Widened,
+                };
+                let message = format!("Schema changed from {} to {}", describe(old), describe(new));
+                return self.report("", &old_path, &new_path, effect, message);
+            }
+        };
+
+        self.compare_types(old_obj, &old_path, new_obj, &new_path);
+        self.compare_values(old_obj, &old_path, new_obj, &new_path);
+        for keyword in ["minimum", "exclusiveMinimum", "minLength", "minItems", "minProperties", "minContains"] {
+            self.compare_bound(keyword, true, old_obj, &old_path, new_obj, &new_path);
+        }
+        for keyword in ["maximum", "exclusiveMaximum", "maxLength", "maxItems", "maxProperties", "maxContains"] {
+            self.compare_bound(keyword, false, old_obj, &old_path, new_obj, &new_path);
+        }
+        self.compare_assertions(old_obj, &old_path, new_obj, &new_path);
+        self.compare_required(old_obj, &old_path, new_obj, &new_path);
+        self.compare_properties(old_obj, &old_path, new_obj, &new_path);
+        self.compare_items(old_obj, &old_path, new_obj, &new_path);
+        self.compare_subschemas(old_obj, &old_path, new_obj, &new_path);
+    }
+
+    /// Follows `$ref`s to the schema they point at.
+    fn follow(&self, root: &'a Value, mut schema: &'a Value, path: &str) -> Option<(&'a Value, String)> {
+        let mut path = path.to_string();
+        let mut seen = HashSet::new();
+        while let Some(Value::String(reference)) = schema.get("$ref") {
+            if !seen.insert(reference.clone()) {
+                return None;
+            }
+            let (target, target_path) = resolve_ref(root, reference)?;
+            schema = target;
+            path = target_path;
+        }
+        Some((schema, path))
+    }
+
+    fn compare_types(&mut self, old: &Map<String, Value>, old_path: &str, new: &Map<String, Value>, new_path: &str) {
+        let (old_types, new_types) = (types(old), types(new));
+        let effect = match (&old_types, &new_types) {
+            (None, None) => return,
+            (None, Some(_)) => Effect::Narrowed,
+            (Some(_), None) => Effect::Widened,
+            (Some(old_types), Some(new_types)) => {
+                let removed: Vec<&str> = uncovered(old_types, new_types);
+                let added: Vec<&str> = uncovered(new_types, old_types);
+                let effect = match (removed.is_empty(), added.is_empty()) {
+                    (true, true) => return,
+                    (false, true) => Effect::Narrowed,
+                    (true, false) => Effect::Widened,
+                    (false, false) => Effect::Changed,
+                };
+                let mut parts = Vec::new();
+                if !removed.is_empty() {
+                    parts.push(format!("no longer allows {}", removed.join(", ")));
+                }
+                if !added.is_empty() {
+                    parts.push(format!("now allows {}", added.join(", ")));
+                }
+                return self.report("type", old_path, new_path, effect, format!("Type {}", parts.join(" and ")));
+            }
+        };
+        let describe = |types: &Option<BTreeSet<String>>| match types {
+            Some(types) => types.iter().cloned().collect::<Vec<_>>().join(", "),
+            None => "any".to_string(),
+        };
+        let message = format!("Type changed from {} to {}", describe(&old_types), describe(&new_types));
+        self.report("type", old_path, new_path, effect, message);
+    }
+
+    /// `enum` and `const`, compared as sets of allowed values.
+    fn compare_values(&mut self, old: &Map<String, Value>, old_path: &str, new: &Map<String, Value>, new_path: &str) {
+        let keyword = if old.contains_key("const") || new.contains_key("const") { "const" } else { "enum" };
+        let (old_values, new_values) = (allowed_values(old), allowed_values(new));
+        let effect = match (&old_values, &new_values) {
+            (None, None) => return,
+            (None, Some(_)) => Effect::Narrowed,
+            (Some(_), None) => Effect::Widened,
+            (Some(old_values), Some(new_values)) => {
+                let removed: Vec<String> = old_values.iter().filter(|v| !new_values.contains(v)).map(|v| v.to_string()).collect();
+                let added: Vec<String> = new_values.iter().filter(|v| !old_values.contains(v)).map(|v| v.to_string()).collect();
+                let effect = match (removed.is_empty(), added.is_empty()) {
+                    (true, true) => return,
+                    (false, true) => Effect::Narrowed,
+                    (true, false) => Effect::Widened,
+                    (false, false) => Effect::Changed,
+                };
+                let mut parts = Vec::new();
+                if !removed.is_empty() {
+                    parts.push(format!("removed {}", removed.join(", ")));
+                }
+                if !added.is_empty() {
+                    parts.push(format!("added {}", added.join(", ")));
+                }
+                return self.report(keyword, old_path, new_path, effect, format!("Allowed values {}", parts.join(" and ")));
+            }
+        };
+        let message = match effect {
+            Effect::Narrowed => "Values are now restricted to a fixed set",
+            _ => "Values are no longer restricted to a fixed set",
+        };
+        self.report(keyword, old_path, new_path, effect, message.to_string());
+    }
+
+    /// A numeric limit; `lower` for minimums, where raising the limit narrows.
+    fn compare_bound(
+        &mut self,
+        keyword: &str,
+        lower: bool,
+        old: &Map<String, Value>,
+        old_path: &str,
+        new: &Map<String, Value>,
+        new_path: &str,
+    ) {
+        let (old_bound, new_bound) = (old.get(keyword).and_then(Value::as_f64), new.get(keyword).and_then(Value::as_f64));
+        let (effect, message) = match (old_bound, new_bound) {
+            (None, None) => return,
+            (None, Some(bound)) => (Effect::Narrowed, format!("{} of {} added", keyword, bound)),
+            (Some(bound), None) => (Effect::Widened, format!("{} of {} removed", keyword, bound)),
+            (Some(old_bound), Some(new_bound)) if old_bound == new_bound => return,
+            (Some(old_bound), Some(new_bound)) => {
+                let effect = if (new_bound > old_bound) == lower { Effect::Narrowed } else { Effect::Widened };
+                let verb = if new_bound > old_bound { "raised" } else { "lowered" };
+                (effect, format!("{} {} from {} to {}", keyword, verb, old_bound, new_bound))
+            }
+        };
+        self.report(keyword, old_path, new_path, effect, message);
+    }
+
+    /// `multipleOf`, `pattern`, `format` and `uniqueItems`.
+    fn compare_assertions(&mut self, old: &Map<String, Value>, old_path: &str, new: &Map<String, Value>, new_path: &str) {
+        for keyword in ["multipleOf", "pattern", "format", "uniqueItems"] {
+            let (old_value, new_value) = (old.get(keyword), new.get(keyword));
+            let effect = match (old_value, new_value) {
+                (a, b) if a == b => continue,
+                (None | Some(Value::Bool(false)), Some(_)) => Effect::Narrowed,
+                (Some(_), None | Some(Value::Bool(false))) => Effect::Widened,
+                (Some(Value::Number(a)), Some(Value::Number(b))) => {
+                    let (a, b) = (a.as_f64().unwrap_or(1.0), b.as_f64().unwrap_or(1.0));
+                    let divides = |x: f64, y: f64| y != 0.0 && ((x / y) - (x / y).round()).abs() < 1e-9;
+                    if divides(b, a) {
+                        Effect::Narrowed
+                    } else if divides(a, b) {
+                        Effect::Widened
+                    } else {
+                        Effect::Changed
+                    }
+                }
+                // Whether one pattern or format contains another can't be told in general.
+                _ => Effect::Unknown,
+            };
+            let show = |value: Option<&Value>| value.map_or("none".to_string(), Value::to_string);
+            let message = format!("{} changed from {} to {}", keyword, show(old_value), show(new_value));
+            self.report(keyword, old_path, new_path, effect, message);
+        }
+    }
+
+    fn compare_required(&mut self, old: &Map<String, Value>, old_path: &str, new: &Map<String, Value>, new_path: &str) {
+        let (old_required, new_required) = (required(old), required(new));
+        for name in new_required.difference(&old_required) {
+            let message = format!("Property \"{}\" is now required", name);
+            self.report("required", old_path, new_path, Effect::Narrowed, message);
+        }
+        for name in old_required.difference(&new_required) {
+            let message = format!("Property \"{}\" is no longer required", name);
+            self.report("required", old_path, new_path, Effect::Widened, message);
+        }
+    }
+
+    fn compare_properties(&mut self, old: &'a Map<String, Value>, old_path: &str, new: &'a Map<String, Value>, new_path: &str) {
+        let old_properties = old.get("properties").and_then(Value::as_object);
+        let new_properties = new.get("properties").and_then(Value::as_object);
+        let closed = |obj: &Map<String, Value>| obj.get("additionalProperties") == Some(&Value::Bool(false));
+
+        for (name, old_schema) in old_properties.into_iter().flatten() {
+            let old_property = format!("{}/properties/{}", old_path, escape(name));
+            let new_property = format!("{}/properties/{}", new_path, escape(name));
+            match new_properties.and_then(|properties| properties.get(name)) {
+                Some(new_schema) => self.compare(old_schema, &old_property, new_schema, &new_property),
+                None => {
+                    // Without the property, a closed object rejects it and an
+                    // open one accepts anything under that name.
+                    let effect = if closed(new) { Effect::Narrowed } else { Effect::Widened };
+                    self.differences.push(difference(
+                        "properties",
+                        format!("Property \"{}\" removed", name),
+                        old_property,
+                        join(&join(new_path, "properties"), name),
+                        effect.classify(),
+                    ));
+                }
+            }
+        }
+        let added = new_properties.into_iter().flatten().map(|(name, _)| name);
+        for name in added.filter(|name| old_properties.is_none_or(|properties| !properties.contains_key(*name))) {
+            // Adding a property to a closed object only widens it. Adding one
+            // to an open object breaks old writers only if they already sent
+            // that name with some other shape.
+            let compat = if closed(old) { Effect::Widened.classify() } else { (Compat::NonBreaking, Compat::Unknown) };
+            self.differences.push(difference(
+                "properties",
+                format!("Property \"{}\" added", name),
+                join(&join(old_path, "properties"), name),
+                format!("{}/properties/{}", new_path, escape(name)),
+                compat,
+            ));
+        }
+
+        self.compare_keyword("additionalProperties", old, old_path, new, new_path);
+    }
+
+    fn compare_items(&mut self, old: &'a Map<String, Value>, old_path: &str, new: &'a Map<String, Value>, new_path: &str) {
+        match (tuple(old), tuple(new)) {
+            (None, None) => {}
+            (Some((old_keyword, old_items)), Some((new_keyword, new_items))) => {
+                for (i, (old_item, new_item)) in old_items.iter().zip(new_items).enumerate() {
+                    let old_item_path = format!("{}/{}/{}", old_path, old_keyword, i);
+                    let new_item_path = format!("{}/{}/{}", new_path, new_keyword, i);
+                    self.compare(old_item, &old_item_path, new_item, &new_item_path);
+                }
+                if old_items.len() != new_items.len() {
+                    let message = format!("Tuple length changed from {} to {}", old_items.len(), new_items.len());
+                    self.report(new_keyword, old_path, new_path, Effect::Unknown, message);
+                }
+                return;
+            }
+            (_, Some((keyword, _))) | (Some((keyword, _)), None) => {
+                let message = "Array changed between a tuple and a list".to_string();
+                return self.report(keyword, old_path, new_path, Effect::Unknown, message);
+            }
+        }
+        self.compare_keyword("items", old, old_path, new, new_path);
+    }
+
+    /// Subschema keywords compared position by position.
+    fn compare_subschemas(&mut self, old: &'a Map<String, Value>, old_path: &str, new: &'a Map<String, Value>, new_path: &str) {
+        for keyword in ["contains", "propertyNames"] {
+            self.compare_keyword(keyword, old, old_path, new, new_path);
+        }
+        for keyword in ["allOf", "anyOf", "oneOf"] {
+            match (old.get(keyword), new.get(keyword)) {
+                (Some(Value::Array(old_options)), Some(Value::Array(new_options))) if old_options.len() == new_options.len() => {
+                    for (i, (old_option, new_option)) in old_options.iter().zip(new_options).enumerate() {
+                        let old_option_path = format!("{}/{}/{}", old_path, keyword, i);
+                        let new_option_path = format!("{}/{}/{}", new_path, keyword, i);
+                        self.compare(old_option, &old_option_path, new_option, &new_option_path);
+                    }
+                }
+                (old_options, new_options) if old_options != new_options => {
+                    let count = |options: Option<&Value>| options.and_then(Value::as_array).map_or(0, Vec::len);
+                    let message = format!("{} changed from {} to {} schemas", keyword, count(old_options), count(new_options));
+                    self.report(keyword, old_path, new_path, Effect::Unknown, message);
+                }
+                _ => {}
+            }
+        }
+        for keyword in ["not", "if", "then", "else", "dependencies", "dependentRequired", "dependentSchemas"] {
+            if old.get(keyword) != new.get(keyword) {
+                self.report(keyword, old_path, new_path, Effect::Unknown, format!("{} changed", keyword));
+            }
+        }
+    }
+
+    /// A keyword holding one subschema, where a missing keyword allows anything.
+    fn compare_keyword(&mut self, keyword: &str, old: &'a Map<String, Value>, old_path: &str, new: &'a Map<String, Value>, new_path: &str) {
+        match (old.get(keyword), new.get(keyword)) {
+            (None, None) => {}
+            (Some(old_schema @ Value::Object(_)), Some(new_schema @ Value::Object(_))) => {
+                self.compare(old_schema, &join(old_path, keyword), new_schema, &join(new_path, keyword));
+            }
+            (old_schema, new_schema) => {
+                let old_schema = old_schema.unwrap_or(&ANYTHING);
+                let new_schema = new_schema.unwrap_or(&ANYTHING);
+                if old_schema == new_schema {
+                    return;
+                }
+                let effect = match (old_schema, new_schema) {
+                    (_, Value::Bool(false)) | (Value::Bool(true), _) => Effect::Narrowed,
+                    _ => Effect::Widened,
+                };
+                let message = format!("{} changed from {} to {}", keyword, describe(old_schema), describe(new_schema));
+                self.report(keyword, old_path, new_path, effect, message);
+            }
+        }
+    }
+}
+
+static ANYTHING: Value = Value::Bool(true);
+
+fn describe(schema: &Value) -> &'static str {
+    match schema {
+        Value::Bool(true) => "any value",
+        Value::Bool(false) => "no value",
+        _ => "a schema",
+    }
+}
+
+fn difference(keyword: &str, message: String, old_path: String, new_path: String, compat: (Compat, Compat)) -> Difference {
+    Difference {
+        keyword: keyword.to_string(),
+        message,
+        old_path,
+        new_path,
+        reader: compat.0,
+        writer: compat.1,
+    }
+}
+
+/// The allowed types, `None` when any type is allowed.
+fn types(obj: &Map<String, Value>) -> Option<BTreeSet<String>> {
+    let mut types: BTreeSet<String> = match obj.get("type")? {
+        Value::String(kind) => std::iter::once(kind.clone()).collect(),
+        Value::Array(kinds) => kinds.iter().filter_map(Value::as_str).map(str::to_string).collect(),
+        _ => return None,
+    };
+    if obj.get("nullable") == Some(&Value::Bool(true)) {
+        types.insert("null".to_string());
+    }
+    Some(types)
+}
+
+/// Types in `from` that `to` does not accept; `number` accepts `integer`.
+fn uncovered<'t>(from: &'t BTreeSet<String>, to: &BTreeSet<String>) -> Vec<&'t str> {
+    from.iter()
+        .filter(|kind| !(to.contains(*kind) || kind.as_str() == "integer" && to.contains("number")))
+        .map(String::as_str)
+        .collect()
+}
+
+fn allowed_values(obj: &Map<String, Value>) -> Option<Vec<&Value>> {
+    match (obj.get("const"), obj.get("enum")) {
+        (Some(value), _) => Some(vec![value]),
+        (None, Some(Value::Array(values))) => Some(values.iter().collect()),
+        _ => None,
+    }
+}
+
+fn required(obj: &Map<String, Value>) -> BTreeSet<&str> {
+    match obj.get("required") {
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+        _ => BTreeSet::new(),
+    }
+}
+
+/// Tuple positions from `prefixItems` or an `items` array, with the keyword used.
+fn tuple(obj: &Map<String, Value>) -> Option<(&'static str, &Vec<Value>)> {
+    match (obj.get("prefixItems"), obj.get("items")) {
+        (Some(Value::Array(items)), _) => Some(("prefixItems", items)),
+        (_, Some(Value::Array(items))) => Some(("items", items)),
+        _ => None,
+    }
+}
+
+fn join(path: &str, keyword: &str) -> String {
+    if keyword.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}/{}", path, escape(keyword))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use Compat::{Breaking, NonBreaking, Unknown};
+
+    /// `(keyword, new path, reader, writer)` for each difference.
+    fn compare(old: Value, new: Value) -> Vec<(String, String, Compat, Compat)> {
+        compare_schemas(&old, &new).into_iter().map(|d| (d.keyword, d.new_path, d.reader, d.writer)).collect()
+    }
+
+    fn entry(keyword: &str, path: &str, reader: Compat, writer: Compat) -> (String, String, Compat, Compat) {
+        (keyword.to_string(), path.to_string(), reader, writer)
+    }
+
+    #[test]
+    fn new_required_fields_break_writers() {
+        let old = json!({"type": "object", "properties": {"a": {"type": "string"}}});
+        let properties = json!({"a": {"type": "string"}, "b": {"type": "integer"}});
+        let new = json!({"type": "object", "properties": properties, "required": ["b"]});
+        assert_eq!(
+            compare(old, new),
+            [
+                entry("required", "#/required", NonBreaking, Breaking),
+                entry("properties", "#/properties/b", NonBreaking, Unknown),
+            ]
+        );
+    }
+
+    #[test]
+    fn removed_properties_depend_on_additional_properties() {
+        let old = json!({"type": "object", "properties": {"a": {"type": "string"}, "b": {"type": "string"}}});
+        let open = json!({"type": "object", "properties": {"a": {"type": "string"}}});
+        assert_eq!(compare(old.clone(), open), [entry("properties", "#/properties/b", Breaking, NonBreaking)]);
+        let closed = json!({"type": "object", "properties": {"a": {"type": "string"}}, "additionalProperties": false});
+        let differences = compare(old, closed);
+        assert_eq!(differences[0], entry("properties", "#/properties/b", NonBreaking, Breaking));
+        assert_eq!(differences[1], entry("additionalProperties", "#/additionalProperties", NonBreaking, Breaking));
+    }
+
+    #[test]
+    fn enum_changes_are_classified_by_direction() {
+        let old = json!({"enum": ["a", "b"]});
+        assert_eq!(compare(old.clone(), json!({"enum": ["a"]})), [entry("enum", "#/enum", NonBreaking, Breaking)]);
+        let widened = compare(old.clone(), json!({"enum": ["a", "b", "c"]}));
+        assert_eq!(widened, [entry("enum", "#/enum", Breaking, NonBreaking)]);
+        assert_eq!(compare(old.clone(), json!({"enum": ["a", "c"]})), [entry("enum", "#/enum", Breaking, Breaking)]);
+        assert_eq!(compare(old, json!({"enum": ["b", "a"]})), []);
+    }
+
+    #[test]
+    fn bounds_narrow_or_widen() {
+        let old = json!({"properties": {"n": {"type": "integer", "minimum": 0, "maximum": 10}}});
+        let new = json!({"properties": {"n": {"type": "integer", "minimum": 1, "maximum": 20}}});
+        assert_eq!(
+            compare(old, new),
+            [
+                entry("minimum", "#/properties/n/minimum", NonBreaking, Breaking),
+                entry("maximum", "#/properties/n/maximum", Breaking, NonBreaking),
+            ]
+        );
+        let differences = compare_schemas(&json!({"maxLength": 5}), &json!({"maxLength": 3}));
+        assert_eq!(differences[0].message, "maxLength lowered from 5 to 3");
+        assert_eq!((differences[0].reader, differences[0].writer), (NonBreaking, Breaking));
+    }
+
+    #[test]
+    fn recursive_refs_are_compared_once() {
+        let tree = |name_type: &str| {
+            json!({
+                "$ref": "#/definitions/Node",
+                "definitions": {"Node": {
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": name_type},
+                        "children": {"type": "array", "items": {"$ref": "#/definitions/Node"}}
+                    }
+                }}
+            })
+        };
+        assert_eq!(compare(tree("string"), tree("string")), []);
+        assert_eq!(
+            compare(tree("integer"), tree("number")),
+            [entry("type", "#/definitions/Node/properties/name/type", Breaking, NonBreaking)]
+        );
+        // A reference to itself resolves to nothing and is reported rather than followed forever.
+        let looping = compare(json!({"$ref": "#"}), json!({"type": "string"}));
+        assert_eq!(looping, [entry("$ref", "#/$ref", Unknown, Unknown)]);
+    }
+}
@@ -0,0 +1,913 @@
+use serde_json::{Map, Number, Value};
+use std::collections::HashSet;
+
+use crate::lenient_json::{Comments, NodeComments};
+use crate::validator::escape;
+
+// INI, Java `.properties` and `.env` reading and writing.
+//
+// All three are flat lists of `key = value` lines, read into nested JSON
+// values so they plug into the converters and visualizers:
+//
+// - INI sections become objects, with `[a.b]` nested inside `a` and
+//   `key[] = value` lines collecting into arrays (the npm `ini` conventions)
+// - `.properties` keys nest at dots, with `list[0]` indices making arrays
+// - `.env` variables nest at double underscores, `A__B__0`, as ASP.NET and
+//   pydantic-settings read them
+//
+// Unquoted values that look like booleans or numbers read as those; quoting
+// them (not possible in `.properties`) keeps them strings. A key that is both
+// a value and a parent of other keys, like log4j's `appender.A1` and
+// `appender.A1.layout`, keeps its children as flat dotted keys beside it.
+// Comments are kept against the JSON Pointer of the entry they precede or
+// share a line with, in the form YAML output and the writers here take.
+
+/// One key and value read from a file, with the comments around it.
+struct Entry {
+    /// The key split into the levels it nests at.
+    path: Vec<String>,
+    /// An object for INI section headers, which may have no keys.
+    value: Value,
+    /// INI's `key[] = value`, which adds to an array.
+    append: bool,
+    comments: NodeComments,
+}
+
+/// Nests `entries` into one object, joining the rest of a path with
+/// `separator` below a key that holds a value itself.
+fn nest(entries: Vec<Entry>, separator: &str, end: Vec<String>) -> (Value, Comments) {
+    let leaves: HashSet<Vec<String>> =
+        entries.iter().filter(|e| !e.value.is_object()).map(|e| e.path.clone()).collect();
+    let mut root = Map::new();
+    let mut comments = Comments::new();
+    for entry in entries {
+        let mut keys = Vec::new();
+        for i in 0..entry.path.len() {
+            if i + 1 < entry.path.len() && leaves.contains(&entry.path[..=i]) {
+                keys.push(entry.path[i + 1..].iter().fold(entry.path[i].clone(), |key, part| key + separator + part));
+                break;
+            }
+            keys.push(entry.path[i].clone());
+        }
+        let (last, parents) = match keys.split_last() {
+            Some(split) => split,
+            None => continue,
+        };
+        let mut pointer = String::new();
+        let mut target = &mut root;
+        for key in parents {
+            pointer = format!("{}/{}", pointer, escape(key));
+            target = object_at(target, key);
+        }
+        pointer = format!("{}/{}", pointer, escape(last));
+        if entry.append {
+            let slot = target.entry(last.clone()).or_insert_with(|| Value::Array(Vec::new()));
+            if !slot.is_array() {
+                *slot = Value::Array(vec![slot.take()]);
+            }
+            if let Value::Array(items) = slot {
+                pointer = format!("{}/{}", pointer, items.len());
+                items.push(entry.value);
+            }
+        } else if entry.value.is_object() {
+            // A section named like a plain key leaves the key's value alone.
+            if !leaves.contains(&entry.path) {
+                object_at(target, last);
+            }
+        } else {
+            target.insert(last.clone(), entry.value);
+        }
+        let NodeComments { before, after, end } = entry.comments;
+        if !before.is_empty() || !after.is_empty() || !end.is_empty() {
+            let notes = comments.entry(pointer).or_default();
+            notes.before.extend(before);
+            notes.after.extend(after);
+            notes.end.extend(end);
+        }
+    }
+    if !end.is_empty() {
+        comments.entry(String::new()).or_default().end.extend(end);
+    }
+    (Value::Object(root), comments)
+}
+
+/// The object under `key`, replacing whatever else is there.
+fn object_at<'m>(map: &'m mut Map<String, Value>, key: &str) -> &'m mut Map<String, Value> {
+    let slot = map.entry(key.to_string()).or_insert_with(|| Value::Object(Map::new()));
+    if !slot.is_object() {
+        *slot = Value::Object(Map::new());
+    }
+    match slot {
+        Value::Object(map) => map,
+        _ => unreachable!(),
+    }
+}
+
+/// Turns objects whose keys are the indices `0..n` (as `index` reads them)
+/// into arrays, moving the comments filed under the old keys.
+fn index_arrays(value: &mut Value, pointer: &str, comments: &mut Comments, index: fn(&str) -> Option<usize>) {
+    let map = match value {
+        Value::Object(map) => map,
+        _ => return,
+    };
+    for (key, child) in map.iter_mut() {
+        index_arrays(child, &format!("{}/{}", pointer, escape(key)), comments, index);
+    }
+    let mut indices: Vec<(usize, String)> = match map.keys().map(|key| Some((index(key)?, key.clone()))).collect() {
+        Some(indices) => indices,
+        None => return,
+    };
+    indices.sort();
+    if indices.is_empty() || indices.iter().enumerate().any(|(i, (n, _))| i != *n) {
+        return;
+    }
+    for (i, key) in &indices {
+        let (old, new) = (format!("{}/{}", pointer, escape(key)), format!("{}/{}", pointer, i));
+        if old == new {
+            continue;
+        }
+        let moved: Vec<String> =
+            comments.keys().filter(|p| **p == old || p.starts_with(&format!("{}/", old))).cloned().collect();
+        for p in moved {
+            if let Some(notes) = comments.remove(&p) {
+                comments.insert(format!("{}{}", new, &p[old.len()..]), notes);
+            }
+        }
+    }
+    *value = Value::Array(indices.iter().filter_map(|(_, key)| map.remove(key)).collect());
+}
+
+/// An unquoted value: a boolean or number when it reads as one exactly.
+fn typed(text: &str) -> Value {
+    match text {
+        "true" => return Value::Bool(true),
+        "false" => return Value::Bool(false),
+        _ => {}
+    }
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    let integer = digits.split(['.', 'e', 'E']).next().unwrap_or_default();
+    // Leading zeros (`007`, zip codes) and `+1` stay strings.
+    let plain = !integer.is_empty() && (integer == "0" || !integer.starts_with('0'));
+    if plain && digits.bytes().all(|b| b.is_ascii_digit()) {
+        if let Ok(i) = text.parse::<i64>() {
+            return Value::Number(i.into());
+        }
+    } else if plain && integer.bytes().all(|b| b.is_ascii_digit()) && !digits.ends_with(['.', 'e', 'E']) {
+        if let Some(n) = text.parse::<f64>().ok().and_then(Number::from_f64) {
+            return Value::Number(n);
+        }
+    }
+    Value::String(text.to_string())
+}
+
+/// The text a scalar is written as; null is empty.
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Whether a string would read back as something else when written bare.
+fn needs_quotes(text: &str, comment_markers: &[char]) -> bool {
+    text.trim() != text
+        || !typed(text).is_string()
+        || text.starts_with(['"', '\'', '`'])
+        || text.starts_with(comment_markers)
+        || text.contains(|c: char| c.is_control())
+        || text.char_indices().any(|(i, c)| comment_markers.contains(&c) && text[..i].ends_with(char::is_whitespace))
+}
+
+fn double_quoted(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Reads a double-quoted string from just after its opening quote, returning
+/// the text and what follows the closing quote.
+fn read_double_quoted(text: &str) -> Option<(String, &str)> {
+    let mut out = String::new();
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((out, &text[i + 1..])),
+            '\\' => match chars.next() {
+                Some((_, 'n')) => out.push('\n'),
+                Some((_, 'r')) => out.push('\r'),
+                Some((_, 't')) => out.push('\t'),
+                Some((_, escaped @ ('"' | '\\' | '$' | '\''))) => out.push(escaped),
+                Some((_, other)) => {
+                    out.push('\\');
+                    out.push(other);
+                }
+                None => out.push('\\'),
+            },
+            c => out.push(c),
+        }
+    }
+    None
+}
+
+/// Splits a comment off an unquoted value at a marker after whitespace.
+fn split_inline_comment<'t>(text: &'t str, markers: &[char]) -> (&'t str, Option<&'t str>) {
+    let start = text
+        .char_indices()
+        .find(|&(i, c)| markers.contains(&c) && (i == 0 || text[..i].ends_with(char::is_whitespace)));
+    match start {
+        Some((i, _)) => (text[..i].trim_end(), Some(text[i + 1..].trim())),
+        None => (text.trim_end(), None),
+    }
+}
+
+/// A comment after a closing quote, which is the only thing allowed there.
+fn trailing_comment<'t>(rest: &'t str, markers: &[char], line: usize) -> Result<Option<&'t str>, String> {
+    let rest = rest.trim();
+    match rest.chars().next() {
+        None => Ok(None),
+        Some(c) if markers.contains(&c) => Ok(Some(rest[c.len_utf8()..].trim())),
+        Some(_) => Err(format!("Unexpected text after a quoted value on line {}: {}", line, rest)),
+    }
+}
+
+fn comment_notes(before: Vec<String>, after: Option<&str>) -> NodeComments {
+    NodeComments {
+        before,
+        after: after.filter(|text| !text.is_empty()).map(str::to_string).into_iter().collect(),
+        end: Vec::new(),
+    }
+}
+
+fn require_object<'v>(value: &'v Value, format: &str) -> Result<&'v Map<String, Value>, String> {
+    value.as_object().ok_or_else(|| {
+        let found = match value {
+            Value::Array(_) => "an array",
+            Value::Null => "null",
+            _ => "a single value",
+        };
+        format!("{} files hold an object of keys, found {} at the top level", format, found)
+    })
+}
+
+/// Writes `lines` as comments starting with `marker`.
+fn push_comments(out: &mut String, marker: &str, lines: &[String]) {
+    for line in lines {
+        out.push_str(&format!("{} {}\n", marker, line));
+    }
+}
+
+/// Writes the document's leading comments.
+fn push_head_comments(out: &mut String, marker: &str, comments: &Comments) {
+    if let Some(root) = comments.get("") {
+        push_comments(out, marker, &root.before);
+        push_comments(out, marker, &root.after);
+    }
+}
+
+fn end_comments<'c>(comments: &'c Comments, pointer: &str) -> &'c [String] {
+    comments.get(pointer).map_or(&[], |notes| &notes.end)
+}
+
+// INI
+
+/// Parses an INI file. `;` and `#` start comments, also after a value when
+/// preceded by whitespace; a key without `=` reads as null.
+pub(crate) fn parse_ini(text: &str) -> Result<(Value, Comments), String> {
+    const MARKERS: &[char] = &[';', '#'];
+    let mut entries = Vec::new();
+    let mut pending = Vec::new();
+    let mut section: Vec<String> = Vec::new();
+    for (i, raw) in text.lines().enumerate() {
+        let (line, number) = (raw.trim(), i + 1);
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with(MARKERS) {
+            let comment = line[1..].trim();
+            if !comment.is_empty() {
+                pending.push(comment.to_string());
+            }
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[') {
+            let close =
+                header.rfind(']').ok_or_else(|| format!("Unclosed section header on line {}: {}", number, line))?;
+            let comment = trailing_comment(&header[close + 1..], MARKERS, number)?;
+            section = split_section(header[..close].trim());
+            entries.push(Entry {
+                path: section.clone(),
+                value: Value::Object(Map::new()),
+                append: false,
+                comments: comment_notes(std::mem::take(&mut pending), comment),
+            });
+            continue;
+        }
+
+        let (key, rest) = ini_key(line, number)?;
+        let (key, append) = match key.strip_suffix("[]") {
+            Some(key) => (key.to_string(), true),
+            None => (key, false),
+        };
+        let (value, comment) = match rest {
+            None => (Value::Null, None),
+            Some(rest) => ini_value(rest.trim_start(), MARKERS, number)?,
+        };
+        let mut path = section.clone();
+        path.push(key);
+        entries.push(Entry { path, value, append, comments: comment_notes(std::mem::take(&mut pending), comment) });
+    }
+    Ok(nest(entries, ".", pending))
+}
+
+/// Splits a section name at dots, except ones escaped as `\.`.
+fn split_section(name: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'.') => {
+                parts.last_mut().unwrap_or_else(|| unreachable!()).push('.');
+                chars.next();
+            }
+            '.' => parts.push(String::new()),
+            c => parts.last_mut().unwrap_or_else(|| unreachable!()).push(c),
+        }
+    }
+    parts.iter().map(|part| part.trim().to_string()).collect()
+}
+
+/// A line's key, which may be double-quoted, and the text after its `=`
+/// (or `:`), if any.
+fn ini_key(line: &str, number: usize) -> Result<(String, Option<&str>), String> {
+    if let Some(quoted) = line.strip_prefix('"') {
+        let (key, rest) =
+            read_double_quoted(quoted).ok_or_else(|| format!("Unclosed quoted key on line {}", number))?;
+        let rest = rest.trim_start();
+        return match rest.chars().next() {
+            None => Ok((key, None)),
+            Some('=' | ':') => Ok((key, Some(&rest[1..]))),
+            Some(_) => Err(format!("Expected '=' after the key on line {}: {}", number, line)),
+        };
+    }
+    Ok(match line.find('=').or_else(|| line.find(':')) {
+        Some(i) => (line[..i].trim().to_string(), Some(&line[i + 1..])),
+        None => (line.to_string(), None),
+    })
+}
+
+/// Reads a value that may be quoted, and the comment after it.
+fn ini_value<'t>(text: &'t str, markers: &[char], number: usize) -> Result<(Value, Option<&'t str>), String> {
+    if let Some(quoted) = text.strip_prefix('"') {
+        let (value, rest) =
+            read_double_quoted(quoted).ok_or_else(|| format!("Unclosed quoted value on line {}", number))?;
+        return Ok((Value::String(value), trailing_comment(rest, markers, number)?));
+    }
+    if let Some((value, rest)) = text.strip_prefix('\'').and_then(|quoted| quoted.split_once('\'')) {
+        return Ok((Value::String(value.to_string()), trailing_comment(rest, markers, number)?));
+    }
+    let (value, comment) = split_inline_comment(text, markers);
+    Ok((typed(value), comment))
+}
+
+/// Writes an INI file. Objects become sections, nested ones as `[a.b]`,
+/// and arrays of plain values become `key[] = value` lines.
+pub(crate) fn to_ini_string(value: &Value, comments: &Comments) -> Result<String, String> {
+    let map = require_object(value, "INI")?;
+    let mut out = String::new();
+    push_head_comments(&mut out, ";", comments);
+    ini_members(&mut out, map, "", comments)?;
+    for (key, child) in map {
+        if let Value::Object(section) = child {
+            ini_section(&mut out, &[key.as_str()], section, &format!("/{}", escape(key)), comments)?;
+        }
+    }
+    push_comments(&mut out, ";", end_comments(comments, ""));
+    Ok(out)
+}
+
+fn ini_section(
+    out: &mut String,
+    names: &[&str],
+    map: &Map<String, Value>,
+    pointer: &str,
+    comments: &Comments,
+) -> Result<(), String> {
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    let notes = comments.get(pointer);
+    push_comments(out, ";", notes.map_or(&[], |notes| &notes.before));
+    let header: Vec<String> = names.iter().map(|name| name.replace('.', "\\.")).collect();
+    out.push_str(&format!("[{}]{}\n", header.join("."), inline_comment(" ;", notes)));
+    ini_members(out, map, pointer, comments)?;
+    push_comments(out, ";", end_comments(comments, pointer));
+    for (key, child) in map {
+        if let Value::Object(section) = child {
+            let mut names = names.to_vec();
+            names.push(key);
+            ini_section(out, &names, section, &format!("{}/{}", pointer, escape(key)), comments)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the keys of a section that aren't sections themselves.
+fn ini_members(out: &mut String, map: &Map<String, Value>, pointer: &str, comments: &Comments) -> Result<(), String> {
+    for (key, child) in map {
+        let child_pointer = format!("{}/{}", pointer, escape(key));
+        let key = if key.is_empty() || key.trim() != key || key.contains(['=', ':', '"', '[', ';', '#']) {
+            double_quoted(key)
+        } else {
+            key.clone()
+        };
+        match child {
+            Value::Object(_) => {}
+            Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if item.is_object() || item.is_array() {
+                        return Err(format!("INI can only hold arrays of plain values (at {}/{})", child_pointer, i));
+                    }
+                    let item_pointer = format!("{}/{}", child_pointer, i);
+                    ini_line(out, &format!("{}[]", key), item, comments.get(&item_pointer));
+                }
+            }
+            value => ini_line(out, &key, value, comments.get(&child_pointer)),
+        }
+    }
+    Ok(())
+}
+
+fn ini_line(out: &mut String, key: &str, value: &Value, notes: Option<&NodeComments>) {
+    push_comments(out, ";", notes.map_or(&[], |notes| &notes.before));
+    let line = match value {
+        Value::Null => key.to_string(),
+        Value::String(s) if needs_quotes(s, &[';', '#']) => format!("{} = {}", key, double_quoted(s)),
+        value => format!("{} = {}", key, scalar_text(value)),
+    };
+    out.push_str(&format!("{}{}\n", line, inline_comment(" ;", notes)));
+}
+
+fn inline_comment(marker: &str, notes: Option<&NodeComments>) -> String {
+    match notes {
+        Some(notes) if !notes.after.is_empty() => format!("{} {}", marker, notes.after.join(" ")),
+        _ => String::new(),
+    }
+}
+
+// Java .properties
+
+/// Parses a `.properties` file as `java.util.Properties` reads it: `#` and
+/// `!` comment lines, `=`, `:` or whitespace between key and value,
+/// backslash line continuations and `\uXXXX` escapes.
+pub(crate) fn parse_properties(text: &str) -> Result<(Value, Comments), String> {
+    let mut entries = Vec::new();
+    let mut pending = Vec::new();
+    let mut lines = text.lines().enumerate();
+    while let Some((i, raw)) = lines.next() {
+        let line = raw.trim_start();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with(['#', '!']) {
+            let comment = line[1..].trim();
+            if !comment.is_empty() {
+                pending.push(comment.to_string());
+            }
+            continue;
+        }
+        let mut logical = line.to_string();
+        while ends_with_continuation(&logical) {
+            logical.pop();
+            match lines.next() {
+                Some((_, next)) => logical.push_str(next.trim_start()),
+                None => break,
+            }
+        }
+        let (key, value) = split_property(&logical);
+        let (key, value) = (unescape_property(key, i + 1)?, unescape_property(value, i + 1)?);
+        entries.push(Entry {
+            path: property_path(&key),
+            value: typed(&value),
+            append: false,
+            comments: comment_notes(std::mem::take(&mut pending), None),
+        });
+    }
+    let (mut value, mut comments) = nest(entries, ".", pending);
+    index_arrays(&mut value, "", &mut comments, |key| key.strip_prefix('[')?.strip_suffix(']')?.parse().ok());
+    Ok((value, comments))
+}
+
+/// Whether a line ends in an odd number of backslashes.
+fn ends_with_continuation(line: &str) -> bool {
+    line.chars().rev().take_while(|&c| c == '\\').count() % 2 == 1
+}
+
+/// Splits at the first unescaped `=`, `:` or whitespace, also dropping the
+/// whitespace and single separator between key and value.
+fn split_property(line: &str) -> (&str, &str) {
+    let mut escaped = false;
+    let end = line
+        .char_indices()
+        .find(|&(_, c)| {
+            let found = !escaped && (c == '=' || c == ':' || c.is_whitespace());
+            escaped = !escaped && c == '\\';
+            found
+        })
+        .map_or(line.len(), |(i, _)| i);
+    let rest = line[end..].trim_start();
+    let rest = rest.strip_prefix(['=', ':']).map_or(rest, str::trim_start);
+    (&line[..end], rest)
+}
+
+fn unescape_property(text: &str, number: usize) -> Result<String, String> {
+    let mut out = String::new();
+    let mut units = Vec::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push_str(&String::from_utf16_lossy(&std::mem::take(&mut units)));
+            out.push(c);
+            continue;
+        }
+        let escaped = match chars.next() {
+            Some('u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                let unit = u16::from_str_radix(&hex, 16)
+                    .ok()
+                    .filter(|_| hex.len() == 4)
+                    .ok_or_else(|| format!("Invalid \\u escape on line {}: \\u{}", number, hex))?;
+                // Surrogate pairs come as two escapes.
+                units.push(unit);
+                continue;
+            }
+            Some('t') => '\t',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('f') => '\u{c}',
+            Some(other) => other,
+            None => continue,
+        };
+        out.push_str(&String::from_utf16_lossy(&std::mem::take(&mut units)));
+        out.push(escaped);
+    }
+    out.push_str(&String::from_utf16_lossy(&units));
+    Ok(out)
+}
+
+/// `a.b[0].c` as `a`, `b`, `[0]`, `c`. Keys with empty parts stay whole.
+fn property_path(key: &str) -> Vec<String> {
+    let mut path = Vec::new();
+    for part in key.split('.') {
+        let (name, indices) = match part.find('[') {
+            Some(i) if part.ends_with(']') => (&part[..i], &part[i..]),
+            _ => (part, ""),
+        };
+        let indices: Option<Vec<&str>> = indices
+            .split_inclusive(']')
+            .map(|index| {
+                let digits = index.strip_prefix('[')?.strip_suffix(']')?;
+                Some(index).filter(|_| !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()))
+            })
+            .collect();
+        match indices {
+            Some(indices) if !name.is_empty() => {
+                path.push(name.to_string());
+                path.extend(indices.into_iter().map(str::to_string));
+            }
+            _ if part.is_empty() => return vec![key.to_string()],
+            _ => path.push(part.to_string()),
+        }
+    }
+    path
+}
+
+/// Writes a `.properties` file with nested keys joined by dots and array
+/// items as `key[0]`. Non-ASCII text is escaped, as `Properties.load` reads
+/// ISO 8859-1. Comments beside a value go on the line before it, since
+/// `.properties` has no inline comments.
+pub(crate) fn to_properties_string(value: &Value, comments: &Comments) -> Result<String, String> {
+    let map = require_object(value, ".properties")?;
+    let mut out = String::new();
+    push_head_comments(&mut out, "#", comments);
+    for (key, child) in map {
+        let pointer = format!("/{}", escape(key));
+        PROPERTIES_STYLE.flatten(&mut out, &escape_property(key, true), child, &pointer, comments);
+    }
+    push_comments(&mut out, "#", end_comments(comments, ""));
+    Ok(out)
+}
+
+fn escape_property(text: &str, key: bool) -> String {
+    let mut out = String::new();
+    for (i, c) in text.chars().enumerate() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\u{c}' => out.push_str("\\f"),
+            ' ' if key || i == 0 => out.push_str("\\ "),
+            '=' | ':' if key => {
+                out.push('\\');
+                out.push(c);
+            }
+            '#' | '!' if i == 0 => {
+                out.push('\\');
+                out.push(c);
+            }
+            c if (' '..='~').contains(&c) => out.push(c),
+            c => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    out.push_str(&format!("\\u{:04X}", unit));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// How a flat format names nested keys and writes a value's line.
+struct FlatStyle {
+    member: fn(&str, &str) -> String,
+    item: fn(&str, usize) -> String,
+    /// The line for a plain value, without its line break.
+    line: fn(&str, &Value) -> String,
+    /// Whether a comment can follow a value on its line.
+    inline_comments: bool,
+}
+
+const PROPERTIES_STYLE: FlatStyle = FlatStyle {
+    member: |parent, key| format!("{}.{}", parent, escape_property(key, true)),
+    item: |parent, i| format!("{}[{}]", parent, i),
+    line: |key, value| format!("{}={}", key, escape_property(&scalar_text(value), false)),
+    inline_comments: false,
+};
+
+const ENV_STYLE: FlatStyle = FlatStyle {
+    member: |parent, key| format!("{}__{}", parent, key),
+    item: |parent, i| format!("{}__{}", parent, i),
+    line: |key, value| format!("{}={}", key, env_value(value)),
+    inline_comments: true,
+};
+
+impl FlatStyle {
+    /// Writes `value` under `key` as one line per plain value. Empty objects
+    /// and arrays have no lines, so they are left out.
+    fn flatten(&self, out: &mut String, key: &str, value: &Value, pointer: &str, comments: &Comments) {
+        let notes = comments.get(pointer);
+        if let Some(notes) = notes {
+            push_comments(out, "#", &notes.before);
+        }
+        let children: Vec<(String, &Value, String)> = match value {
+            Value::Object(map) => map
+                .iter()
+                .map(|(k, child)| ((self.member)(key, k), child, format!("{}/{}", pointer, escape(k))))
+                .collect(),
+            Value::Array(items) => items
+                .iter()
+                .enumerate()
+                .map(|(i, child)| ((self.item)(key, i), child, format!("{}/{}", pointer, i)))
+                .collect(),
+            _ => {
+                let after = notes.map_or(&[][..], |notes| &notes.after);
+                if !self.inline_comments {
+                    push_comments(out, "#", after);
+                }
+                out.push_str(&(self.line)(key, value));
+                if self.inline_comments && !after.is_empty() {
+                    out.push_str(&format!(" # {}", after.join(" ")));
+                }
+                out.push('\n');
+                return;
+            }
+        };
+        if let Some(notes) = notes {
+            push_comments(out, "#", &notes.after);
+        }
+        for (child_key, child, child_pointer) in children {
+            self.flatten(out, &child_key, child, &child_pointer, comments);
+        }
+        push_comments(out, "#", end_comments(comments, pointer));
+    }
+}
+
+// .env
+
+/// Parses a `.env` file: `KEY=value` lines with an optional `export`,
+/// single-quoted literal values and double-quoted ones with escapes, either
+/// of which may span lines. Variables are not expanded.
+pub(crate) fn parse_env(text: &str) -> Result<(Value, Comments), String> {
+    let mut entries = Vec::new();
+    let mut pending = Vec::new();
+    let mut lines = text.lines().enumerate();
+    while let Some((i, raw)) = lines.next() {
+        let (line, number) = (raw.trim(), i + 1);
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            if !comment.trim().is_empty() {
+                pending.push(comment.trim().to_string());
+            }
+            continue;
+        }
+        let line = match line.strip_prefix("export") {
+            Some(rest) if rest.starts_with(char::is_whitespace) => rest.trim_start(),
+            _ => line,
+        };
+        let (key, rest) = line
+            .split_once('=')
+            .map(|(key, rest)| (key.trim(), rest.trim_start()))
+            .filter(|(key, _)| !key.is_empty() && !key.contains(char::is_whitespace))
+            .ok_or_else(|| format!("Expected KEY=value on line {}: {}", number, line))?;
+
+        let (value, comment) = match rest.chars().next() {
+            Some(quote @ ('"' | '\'' | '`')) => {
+                // Quoted values run on over line breaks until the closing quote.
+                let mut text = rest[1..].to_string();
+                loop {
+                    let closed = if quote == '"' {
+                        read_double_quoted(&text)
+                    } else {
+                        text.split_once(quote).map(|(value, rest)| (value.to_string(), rest))
+                    };
+                    if let Some((value, rest)) = closed {
+                        let comment = trailing_comment(rest, &['#'], number)?.map(str::to_string);
+                        break (Value::String(value), comment);
+                    }
+                    match lines.next() {
+                        Some((_, next)) => {
+                            text.push('\n');
+                            text.push_str(next);
+                        }
+                        None => return Err(format!("Unclosed quoted value for {} on line {}", key, number)),
+                    }
+                }
+            }
+            _ => {
+                let (value, comment) = split_inline_comment(rest, &['#']);
+                (typed(value), comment.map(str::to_string))
+            }
+        };
+        entries.push(Entry {
+            path: key.split("__").map(str::to_string).collect(),
+            value,
+            append: false,
+            comments: comment_notes(std::mem::take(&mut pending), comment.as_deref()),
+        });
+    }
+    let (mut value, mut comments) = nest(entries, "__", pending);
+    index_arrays(&mut value, "", &mut comments, |key| {
+        Some(key).filter(|key| *key == "0" || !key.starts_with('0')).and_then(|key| key.parse().ok())
+    });
+    Ok((value, comments))
+}
+
+/// Writes a `.env` file with nested keys joined by `__`. Strings that would
+/// read back differently bare are quoted: single quotes keep `$` and `\`
+/// literal, double quotes are used for control characters and single quotes.
+pub(crate) fn to_env_string(value: &Value, comments: &Comments) -> Result<String, String> {
+    let map = require_object(value, ".env")?;
+    let mut names = Vec::new();
+    collect_env_names(value, "", "", &mut names);
+    if let Some((name, pointer)) = names.iter().find(|(name, _)| !is_env_name(name)) {
+        return Err(format!("'{}' can't be a .env variable name (at {})", name, pointer));
+    }
+    let mut out = String::new();
+    push_head_comments(&mut out, "#", comments);
+    for (key, child) in map {
+        let pointer = format!("/{}", escape(key));
+        ENV_STYLE.flatten(&mut out, key, child, &pointer, comments);
+    }
+    push_comments(&mut out, "#", end_comments(comments, ""));
+    Ok(out)
+}
+
+/// The variable names `value` flattens to, with the pointer of each.
+fn collect_env_names(value: &Value, name: &str, pointer: &str, names: &mut Vec<(String, String)>) {
+    let join = |key: &str| if name.is_empty() { key.to_string() } else { format!("{}__{}", name, key) };
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                collect_env_names(child, &join(key), &format!("{}/{}", pointer, escape(key)), names);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (i, child) in items.iter().enumerate() {
+                collect_env_names(child, &join(&i.to_string()), &format!("{}/{}", pointer, i), names);
+            }
+        }
+        _ => names.push((name.to_string(), pointer.to_string())),
+    }
+}
+
+/// Letters, digits, `_`, `.` and `-`, which dotenv parsers accept.
+fn is_env_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+fn env_value(value: &Value) -> String {
+    match value {
+        Value::String(s) if s.contains(|c: char| c.is_control() || c == '\'') => double_quoted(s),
+        Value::String(s) if needs_quotes(s, &['#']) || s.contains(['$', '\\', '"', '`']) => format!("'{}'", s),
+        value => scalar_text(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    type Parse = fn(&str) -> Result<(Value, Comments), String>;
+    type Write = fn(&Value, &Comments) -> Result<String, String>;
+
+    /// Parses `text`, writes it back and checks that reading the output
+    /// gives the same value and comments.
+    fn round_trip(text: &str, parse: Parse, write: Write) -> (Value, Comments, String) {
+        let (value, comments) = parse(text).unwrap();
+        let written = write(&value, &comments).unwrap();
+        let (again, again_comments) = parse(&written).unwrap();
+        assert_eq!(again, value, "{}", written);
+        assert_eq!(again_comments, comments, "{}", written);
+        (value, comments, written)
+    }
+
+    #[test]
+    fn ini_round_trips() {
+        let text = "; top\n[server]\nhost = example.com ; inline\nport = 8080\nname = \"8080\"\n\n\
+                    [server.tls]\ncert = /etc/cert.pem\n\n[paths]\nlist[] = a\nlist[] = b\nflag\n";
+        let (value, comments, _) = round_trip(text, parse_ini, to_ini_string);
+        assert_eq!(
+            value,
+            json!({
+                "server": {"host": "example.com", "port": 8080, "name": "8080", "tls": {"cert": "/etc/cert.pem"}},
+                "paths": {"list": ["a", "b"], "flag": null}
+            })
+        );
+        assert_eq!(comments["/server"].before, ["top"]);
+        assert_eq!(comments["/server/host"].after, ["inline"]);
+    }
+
+    #[test]
+    fn ini_errors_name_the_line() {
+        assert_eq!(parse_ini("a = 1\n[unclosed\n").unwrap_err(), "Unclosed section header on line 2: [unclosed");
+    }
+
+    #[test]
+    fn properties_round_trip() {
+        let text = "# comment\napp.name = Demo\napp.port=8080\nlist[0]=x\nlist[1]=y\n\
+                    appender.A1=org.Console\nappender.A1.layout=Pattern\nmulti = one \\\n   two\nunicode=caf\\u00e9\n";
+        let (value, comments, written) = round_trip(text, parse_properties, to_properties_string);
+        assert_eq!(
+            value,
+            json!({
+                "app": {"name": "Demo", "port": 8080},
+                "list": ["x", "y"],
+                "appender": {"A1": "org.Console", "A1.layout": "Pattern"},
+                "multi": "one two",
+                "unicode": "café"
+            })
+        );
+        assert_eq!(comments["/app/name"].before, ["comment"]);
+        assert!(written.contains("unicode=caf\\u00E9\n"), "{}", written);
+    }
+
+    #[test]
+    fn env_round_trips() {
+        let text = "# db\nDB__HOST=localhost\nDB__PORT=5432\nexport NAME=\"two words\"\n\
+                    HOSTS__0=a\nHOSTS__1=b\nEMPTY=\nQUOTED='$HOME'\n";
+        let (value, comments, _) = round_trip(text, parse_env, to_env_string);
+        assert_eq!(
+            value,
+            json!({
+                "DB": {"HOST": "localhost", "PORT": 5432},
+                "NAME": "two words",
+                "HOSTS": ["a", "b"],
+                "EMPTY": "",
+                "QUOTED": "$HOME"
+            })
+        );
+        assert_eq!(comments["/DB/HOST"].before, ["db"]);
+    }
+
+    #[test]
+    fn writers_need_an_object() {
+        let comments = Comments::new();
+        assert!(to_env_string(&json!([1]), &comments).is_err());
+        assert!(to_ini_string(&json!("text"), &comments).is_err());
+        assert!(to_properties_string(&json!(null), &comments).is_err());
+    }
+}
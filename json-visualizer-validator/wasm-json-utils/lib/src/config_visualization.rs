@@ -0,0 +1,57 @@
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::config_formats::{parse_env, parse_ini, parse_properties};
+use crate::visualization::{convert_to_d3_format, json_graph};
+
+// Graph and tree views of INI, `.properties` and `.env` files, read into the
+// same nested values the converters use.
+
+fn graph(value: Result<Value, String>) -> Result<String, JsValue> {
+    let value = value.map_err(|e| JsValue::from_str(&e))?;
+    serde_json::to_string(&json_graph(&value)).map_err(|e| JsValue::from_str(&format!("Error building JSON: {}", e)))
+}
+
+fn tree(value: Result<Value, String>) -> Result<String, JsValue> {
+    let value = value.map_err(|e| JsValue::from_str(&e))?;
+    serde_json::to_string(&convert_to_d3_format(&value, "root"))
+        .map_err(|e| JsValue::from_str(&format!("Error building JSON: {}", e)))
+}
+
+/// Processes an INI file into the node/link graph format of `process_json`;
+/// sections are objects under the root.
+#[wasm_bindgen]
+pub fn process_ini_graph(ini_str: &str) -> Result<String, JsValue> {
+    graph(parse_ini(ini_str).map(|(value, _)| value))
+}
+
+/// Processes an INI file into the tree format of `process_json_tree`.
+#[wasm_bindgen]
+pub fn process_ini_tree(ini_str: &str) -> Result<String, JsValue> {
+    tree(parse_ini(ini_str).map(|(value, _)| value))
+}
+
+/// Processes a `.properties` file into the node/link graph format, with
+/// dotted keys nested.
+#[wasm_bindgen]
+pub fn process_properties_graph(properties_str: &str) -> Result<String, JsValue> {
+    graph(parse_properties(properties_str).map(|(value, _)| value))
+}
+
+/// Processes a `.properties` file into the tree format.
+#[wasm_bindgen]
+pub fn process_properties_tree(properties_str: &str) -> Result<String, JsValue> {
+    tree(parse_properties(properties_str).map(|(value, _)| value))
+}
+
+/// Processes a `.env` file into the node/link graph format.
+#[wasm_bindgen]
+pub fn process_env_graph(env_str: &str) -> Result<String, JsValue> {
+    graph(parse_env(env_str).map(|(value, _)| value))
+}
+
+/// Processes a `.env` file into the tree format.
+#[wasm_bindgen]
+pub fn process_env_tree(env_str: &str) -> Result<String, JsValue> {
+    tree(parse_env(env_str).map(|(value, _)| value))
+}
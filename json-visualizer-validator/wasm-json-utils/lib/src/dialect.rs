@@ -0,0 +1,364 @@
+use wasm_bindgen::prelude::*;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+// JSON Schema drafts and OpenAPI schema objects.
+//
+// The generators build draft-07 schemas. This module rewrites a schema into the
+// keywords of another draft, or into OpenAPI component schemas. Converting an
+// authored schema goes through the same draft-07 form: it is normalised first
+// and then written out for the target.
+
+/// A JSON Schema draft or OpenAPI version.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+pub(crate) enum Dialect {
+    #[serde(rename = "draft-04")]
+    Draft04,
+    #[serde(rename = "draft-06")]
+    Draft06,
+    #[default]
+    #[serde(rename = "draft-07")]
+    Draft07,
+    #[serde(rename = "2019-09")]
+    Draft201909,
+    #[serde(rename = "2020-12")]
+    Draft202012,
+    #[serde(rename = "openapi-3.0")]
+    OpenApi30,
+    #[serde(rename = "openapi-3.1")]
+    OpenApi31,
+}
+
+impl Dialect {
+    pub(crate) fn parse(name: &str) -> Result<Dialect, String> {
+        serde_json::from_value(Value::String(name.trim().to_ascii_lowercase())).map_err(|_| {
+            format!(
+                "Unknown schema dialect '{}' (expected draft-04, draft-06, draft-07, 2019-09, 2020-12, openapi-3.0 or openapi-3.1)",
+                name
+            )
+        })
+    }
+
+    /// The dialect a schema declares with `$schema`, or an OpenAPI document
+    /// with its `openapi` version, if it is one we know.
+    pub(crate) fn declared_by(schema: &Value) -> Option<Dialect> {
+        if let Some(Value::String(version)) = schema.get("openapi") {
+            return match version.get(..3) {
+                Some("3.0") => Some(Dialect::OpenApi30),
+                Some("3.1") => Some(Dialect::OpenApi31),
+                _ => None,
+            };
+        }
+        let uri = schema.get("$schema")?.as_str()?;
+        [
+            ("draft-04", Dialect::Draft04),
+            ("draft-06", Dialect::Draft06),
+            ("draft-07", Dialect::Draft07),
+            ("2019-09", Dialect::Draft201909),
+            ("2020-12", Dialect::Draft202012),
+        ]
+        .iter()
+        .find(|(marker, _)| uri.contains(marker))
+        .map(|(_, dialect)| *dialect)
+    }
+
+    fn schema_uri(self) -> Option<&'static str> {
+        match self {
+            Dialect::Draft04 => Some("http://json-schema.org/draft-04/schema#"),
+            Dialect::Draft06 => Some("http://json-schema.org/draft-06/schema#"),
+            Dialect::Draft07 => Some("http://json-schema.org/draft-07/schema#"),
+            Dialect::Draft201909 => Some("https://json-schema.org/draft/2019-09/schema"),
+            Dialect::Draft202012 => Some("https://json-schema.org/draft/2020-12/schema"),
+            Dialect::OpenApi30 | Dialect::OpenApi31 => None,
+        }
+    }
+
+    fn definitions_pointer(self) -> &'static str {
+        match self {
+            Dialect::Draft04 | Dialect::Draft06 | Dialect::Draft07 => "#/definitions/",
+            Dialect::Draft201909 | Dialect::Draft202012 => "#/$defs/",
+            Dialect::OpenApi30 | Dialect::OpenApi31 => "#/components/schemas/",
+        }
+    }
+
+    fn is_openapi(self) -> bool {
+        matches!(self, Dialect::OpenApi30 | Dialect::OpenApi31)
+    }
+}
+
+/// Rewrites a schema from one draft to another. `target` is draft-04,
+/// draft-06, draft-07, 2019-09, 2020-12, openapi-3.0 or openapi-3.1; the
+/// source draft is read from `$schema` (draft-07 when missing).
+#[wasm_bindgen]
+pub fn convert_json_schema(schema_str: &str, target: &str) -> Result<String, JsValue> {
+    let schema: Value = serde_json::from_str(schema_str)
+        .map_err(|e| JsValue::from_str(&format!("JSON Schema parsing error: {}", e)))?;
+    let target = Dialect::parse(target).map_err(|e| JsValue::from_str(&e))?;
+    let converted = to_dialect(normalize(schema), target, "Root");
+    serde_json::to_string_pretty(&converted)
+        .map_err(|e| JsValue::from_str(&format!("Schema serialization error: {}", e)))
+}
+
+/// Brings a schema of any supported draft into draft-07 form.
+pub(crate) fn normalize(mut schema: Value) -> Value {
+    let source = Dialect::declared_by(&schema).unwrap_or_default();
+    normalize_node(&mut schema, source);
+    if let Value::Object(obj) = &mut schema {
+        obj.remove("$schema");
+    }
+    schema
+}
+
+/// Writes a draft-07 schema for `target`. OpenAPI output is a
+/// `components.schemas` object with the root schema stored under `name`.
+pub(crate) fn to_dialect(mut schema: Value, target: Dialect, name: &str) -> Value {
+    let root_pointer = format!("{}{}", target.definitions_pointer(), name);
+    write_node(&mut schema, target, &root_pointer);
+
+    let mut root = match schema {
+        Value::Object(obj) => obj,
+        other => return other,
+    };
+    root.remove("$schema");
+    let definitions = root.remove("definitions");
+
+    if target.is_openapi() {
+        let mut schemas = Map::new();
+        schemas.insert(name.to_string(), Value::Object(root));
+        if let Some(Value::Object(definitions)) = definitions {
+            schemas.extend(definitions);
+        }
+        let mut components = Map::new();
+        components.insert("schemas".into(), Value::Object(schemas));
+        let mut document = Map::new();
+        document.insert("components".into(), Value::Object(components));
+        return Value::Object(document);
+    }
+
+    let mut result = Map::new();
+    if let Some(uri) = target.schema_uri() {
+        result.insert("$schema".into(), Value::String(uri.into()));
+    }
+    result.extend(root);
+    if let Some(definitions) = definitions {
+        let key = match target {
+            Dialect::Draft201909 | Dialect::Draft202012 => "$defs",
+            _ => "definitions",
+        };
+        result.insert(key.into(), definitions);
+    }
+    Value::Object(result)
+}
+
+/// Calls `f` on every direct subschema of `obj`.
+fn for_each_subschema(obj: &mut Map<String, Value>, f: &mut dyn FnMut(&mut Value)) {
+    for (key, value) in obj.iter_mut() {
+        match (key.as_str(), value) {
+            (
+                "items" | "additionalItems" | "additionalProperties" | "not" | "contains" | "propertyNames"
+                | "if" | "then" | "else" | "unevaluatedItems" | "unevaluatedProperties",
+                value @ (Value::Object(_) | Value::Bool(_)),
+            ) => f(value),
+            ("items" | "prefixItems" | "allOf" | "anyOf" | "oneOf", Value::Array(list)) => list.iter_mut().for_each(&mut *f),
+            (
+                "properties" | "patternProperties" | "definitions" | "$defs" | "dependentSchemas" | "dependencies",
+                Value::Object(map),
+            ) => map.values_mut().filter(|v| !v.is_array()).for_each(&mut *f),
+            _ => {}
+        }
+    }
+}
+
+fn normalize_node(schema: &mut Value, source: Dialect) {
+    let obj = match schema {
+        Value::Object(obj) => obj,
+        _ => return,
+    };
+
+    if let Some(Value::Object(defs)) = obj.remove("$defs") {
+        if let Value::Object(definitions) = obj.entry("definitions").or_insert_with(|| Value::Object(Map::new())) {
+            definitions.extend(defs);
+        }
+    }
+    if let Some(Value::String(reference)) = obj.get_mut("$ref") {
+        if let Some(name) = reference.strip_prefix("#/$defs/") {
+            *reference = format!("#/definitions/{}", name);
+        }
+    }
+    if let Some(prefix) = obj.remove("prefixItems") {
+        if let Some(rest) = obj.remove("items") {
+            obj.insert("additionalItems".into(), rest);
+        }
+        obj.insert("items".into(), prefix);
+    }
+    if source == Dialect::Draft04 {
+        if let Some(id) = obj.remove("id") {
+            obj.insert("$id".into(), id);
+        }
+        for (flag, bound) in [("exclusiveMinimum", "minimum"), ("exclusiveMaximum", "maximum")] {
+            match obj.get(flag) {
+                Some(Value::Bool(true)) => {
+                    if let Some(value) = obj.remove(bound) {
+                        obj.insert(flag.into(), value);
+                    }
+                }
+                Some(Value::Bool(false)) => {
+                    obj.remove(flag);
+                }
+                _ => {}
+            }
+        }
+    }
+    // OpenAPI 3.0 marks nullability outside of `type`.
+    if obj.remove("nullable") == Some(Value::Bool(true)) {
+        make_nullable(obj);
+    }
+
+    for_each_subschema(obj, &mut |sub| normalize_node(sub, source));
+}
+
+fn make_nullable(obj: &mut Map<String, Value>) {
+    if let Some(Value::Array(values)) = obj.get_mut("enum") {
+        if !values.contains(&Value::Null) {
+            values.push(Value::Null);
+        }
+    }
+    match obj.get_mut("type") {
+        Some(Value::String(kind)) => {
+            let kind = std::mem::take(kind);
+            obj.insert("type".into(), Value::Array(vec![Value::String(kind), Value::String("null".into())]));
+        }
+        Some(Value::Array(kinds)) => {
+            if !kinds.iter().any(|k| k == "null") {
+                kinds.push(Value::String("null".into()));
+            }
+        }
+        _ => {
+            let rest = std::mem::take(obj);
+            let mut null = Map::new();
+            null.insert("type".into(), Value::String("null".into()));
+            obj.insert("anyOf".into(), Value::Array(vec![Value::Object(rest), Value::Object(null)]));
+        }
+    }
+}
+
+fn write_node(schema: &mut Value, target: Dialect, root_pointer: &str) {
+    // Draft-04 and OpenAPI 3.0 have no boolean schemas.
+    if let (Value::Bool(accept), Dialect::Draft04 | Dialect::OpenApi30) = (&*schema, target) {
+        *schema = if *accept { Value::Object(Map::new()) } else { serde_json::json!({ "not": {} }) };
+    }
+    let obj = match schema {
+        Value::Object(obj) => obj,
+        _ => return,
+    };
+
+    if let Some(Value::String(reference)) = obj.get_mut("$ref") {
+        if reference == "#" && target.is_openapi() {
+            *reference = root_pointer.to_string();
+        } else if let Some(name) = reference.strip_prefix("#/definitions/") {
+            *reference = format!("{}{}", target.definitions_pointer(), name);
+        }
+    }
+
+    match target {
+        Dialect::Draft04 | Dialect::OpenApi30 => {
+            if let Some(value) = obj.remove("const") {
+                obj.insert("enum".into(), Value::Array(vec![value]));
+            }
+            for (flag, bound) in [("exclusiveMinimum", "minimum"), ("exclusiveMaximum", "maximum")] {
+                if let Some(value) = obj.get(flag).filter(|v| v.is_number()).cloned() {
+                    obj.insert(bound.into(), value);
+                    obj.insert(flag.into(), Value::Bool(true));
+                }
+            }
+            if target == Dialect::Draft04 {
+                if let Some(id) = obj.remove("$id") {
+                    obj.insert("id".into(), id);
+                }
+            } else {
+                write_openapi30(obj);
+            }
+        }
+        Dialect::Draft202012 | Dialect::OpenApi31 => {
+            if let Some(Value::Array(prefix)) = obj.get_mut("items") {
+                let prefix = std::mem::take(prefix);
+                obj.remove("items");
+                obj.insert("prefixItems".into(), Value::Array(prefix));
+                if let Some(rest) = obj.remove("additionalItems") {
+                    obj.insert("items".into(), rest);
+                }
+            }
+        }
+        _ => {}
+    }
+
+    for_each_subschema(obj, &mut |sub| write_node(sub, target, root_pointer));
+}
+
+/// OpenAPI 3.0 takes a single `type` plus `nullable`, a single `items` schema
+/// and `example` instead of `examples`.
+fn write_openapi30(obj: &mut Map<String, Value>) {
+    obj.remove("$id");
+    if let Some(Value::Array(examples)) = obj.remove("examples") {
+        if let Some(example) = examples.into_iter().next() {
+            obj.insert("example".into(), example);
+        }
+    }
+    if let Some(Value::Array(items)) = obj.get_mut("items") {
+        let items = std::mem::take(items);
+        obj.remove("additionalItems");
+        obj.insert("items".into(), serde_json::json!({ "anyOf": items }));
+    }
+
+    for combinator in ["anyOf", "oneOf"] {
+        if let Some(Value::Array(options)) = obj.get_mut(combinator) {
+            let before = options.len();
+            options.retain(|option| option.get("type").and_then(Value::as_str) != Some("null"));
+            if options.len() < before {
+                obj.insert("nullable".into(), Value::Bool(true));
+            }
+        }
+    }
+
+    let kinds = match obj.remove("type") {
+        Some(Value::Array(kinds)) => kinds,
+        Some(kind) => {
+            obj.insert("type".into(), kind);
+            return;
+        }
+        None => return,
+    };
+    let (nulls, kinds): (Vec<Value>, Vec<Value>) = kinds.into_iter().partition(|k| k == "null");
+    if !nulls.is_empty() {
+        obj.insert("nullable".into(), Value::Bool(true));
+    }
+    match kinds.len() {
+        0 => {}
+        1 => {
+            obj.insert("type".into(), kinds.into_iter().next().unwrap_or(Value::Null));
+        }
+        _ => {
+            // Split the type-specific keywords out into one option per type.
+            let options = kinds
+                .into_iter()
+                .map(|kind| {
+                    let mut option = Map::new();
+                    let keys: &[&str] = match kind.as_str() {
+                        Some("object") => &["properties", "required", "additionalProperties"],
+                        Some("array") => &["items", "minItems", "maxItems"],
+                        Some("string") => &["format", "pattern", "minLength", "maxLength"],
+                        _ => &[],
+                    };
+                    option.insert("type".into(), kind);
+                    for key in keys {
+                        if let Some(value) = obj.remove(*key) {
+                            option.insert((*key).into(), value);
+                        }
+                    }
+                    Value::Object(option)
+                })
+                .collect();
+            obj.insert("anyOf".into(), Value::Array(options));
+        }
+    }
+}
@@ -0,0 +1,729 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::{json, Map, Number, Value};
+use std::convert::TryFrom;
+use wasm_bindgen::prelude::*;
+
+use crate::lenient_json::{parse_json_text, JsonSyntax};
+
+// MongoDB Extended JSON v2.
+//
+// BSON types JSON lacks are written as wrapper objects such as
+// `{"$oid": "..."}` or `{"$date": "..."}`. Canonical mode wraps every number
+// and date so nothing is lost; relaxed mode writes int32, int64 and finite
+// doubles as plain numbers and dates in the ISO 8601 range as strings. The
+// reader accepts both, along with the `$uuid` shorthand and the legacy
+// `$binary`/`$type` and numeric `$date` forms.
+//
+// Wrappers are recognised only when their keys and values have exactly the
+// shape the specification gives, so ordinary objects are never mistaken for
+// them. The graph shows recognised wrappers as typed leaves, and the type
+// generators see them as plain values: ObjectIds and decimals as strings,
+// dates as `date-time` strings, UUIDs as `uuid` strings, numbers as numbers.
+
+/// A BSON value.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Bson {
+    Double(f64),
+    String(String),
+    Document(Vec<(String, Bson)>),
+    Array(Vec<Bson>),
+    Binary(u8, Vec<u8>),
+    Undefined,
+    ObjectId([u8; 12]),
+    Boolean(bool),
+    /// Milliseconds since the Unix epoch.
+    DateTime(i64),
+    Null,
+    Regex(String, String),
+    DbPointer(String, [u8; 12]),
+    Code(String),
+    Symbol(String),
+    CodeWithScope(String, Vec<(String, Bson)>),
+    Int32(i32),
+    /// Seconds and increment.
+    Timestamp(u32, u32),
+    Int64(i64),
+    /// IEEE 754-2008 decimal128, little-endian as BSON stores it.
+    Decimal128([u8; 16]),
+    MinKey,
+    MaxKey,
+}
+
+/// How BSON is written as JSON.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ExtendedJsonMode {
+    Relaxed,
+    Canonical,
+    /// Plain JSON without wrappers, as the type generators see it. Lossy.
+    Plain,
+}
+
+impl ExtendedJsonMode {
+    pub(crate) fn parse(name: Option<&str>) -> Result<ExtendedJsonMode, String> {
+        match name.unwrap_or_default().trim().to_ascii_lowercase().as_str() {
+            "" | "relaxed" => Ok(ExtendedJsonMode::Relaxed),
+            "canonical" => Ok(ExtendedJsonMode::Canonical),
+            "plain" => Ok(ExtendedJsonMode::Plain),
+            other => Err(format!("Unknown Extended JSON mode '{}' (expected relaxed, canonical or plain)", other)),
+        }
+    }
+}
+
+/// Rewrites Extended JSON in another `mode` ("relaxed" by default,
+/// "canonical" or "plain"). `syntax` works as for `json_to_yaml`.
+#[wasm_bindgen]
+pub fn extended_json_to_json(json_str: &str, mode: Option<String>, syntax: Option<String>) -> Result<String, JsValue> {
+    let mode = ExtendedJsonMode::parse(mode.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    let syntax = JsonSyntax::parse(syntax.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    let value = parse_json_text(json_str, syntax).map_err(|e| JsValue::from_str(&format!("JSON parsing error: {}", e)))?;
+    let bson = Bson::from_extended_json(&value).map_err(|e| JsValue::from_str(&e))?;
+    serde_json::to_string_pretty(&bson.to_extended_json(mode)).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+impl Bson {
+    /// Reads Extended JSON in either mode. Malformed wrappers are errors.
+    pub(crate) fn from_extended_json(value: &Value) -> Result<Bson, String> {
+        read(value, "")
+    }
+
+    pub(crate) fn to_extended_json(&self, mode: ExtendedJsonMode) -> Value {
+        let canonical = mode == ExtendedJsonMode::Canonical;
+        let plain = mode == ExtendedJsonMode::Plain;
+        match self {
+            Bson::Double(f) if plain => float(*f),
+            Bson::Double(f) if !canonical && f.is_finite() => float(*f),
+            Bson::Double(f) => json!({ "$numberDouble": double_text(*f) }),
+            Bson::String(s) => Value::String(s.clone()),
+            Bson::Document(entries) => {
+                Value::Object(entries.iter().map(|(key, value)| (key.clone(), value.to_extended_json(mode))).collect())
+            }
+            Bson::Array(items) => Value::Array(items.iter().map(|item| item.to_extended_json(mode)).collect()),
+            Bson::Binary(4, bytes) if plain && bytes.len() == 16 => Value::String(uuid_text(bytes)),
+            Bson::Binary(_, bytes) if plain => Value::String(STANDARD.encode(bytes)),
+            Bson::Binary(subtype, bytes) => {
+                json!({ "$binary": { "base64": STANDARD.encode(bytes), "subType": format!("{:02x}", subtype) } })
+            }
+            Bson::Undefined if plain => Value::Null,
+            Bson::Undefined => json!({ "$undefined": true }),
+            Bson::ObjectId(id) if plain => Value::String(hex(id)),
+            Bson::ObjectId(id) => json!({ "$oid": hex(id) }),
+            Bson::Boolean(b) => Value::Bool(*b),
+            Bson::DateTime(millis) => match iso_date(*millis) {
+                Some(date) if plain => Value::String(date),
+                None if plain => Value::from(*millis),
+                Some(date) if !canonical => json!({ "$date": date }),
+                _ => json!({ "$date": { "$numberLong": millis.to_string() } }),
+            },
+            Bson::Null => Value::Null,
+            Bson::Regex(pattern, options) if plain => Value::String(format!("/{}/{}", pattern, options)),
+            Bson::Regex(pattern, options) => {
+                json!({ "$regularExpression": { "pattern": pattern, "options": options } })
+            }
+            Bson::DbPointer(namespace, id) if plain => json!({ "$ref": namespace, "$id": hex(id) }),
+            Bson::DbPointer(namespace, id) => json!({ "$dbPointer": { "$ref": namespace, "$id": { "$oid": hex(id) } } }),
+            Bson::Code(code) | Bson::CodeWithScope(code, _) if plain => Value::String(code.clone()),
+            Bson::Code(code) => json!({ "$code": code }),
+            Bson::Symbol(symbol) if plain => Value::String(symbol.clone()),
+            Bson::Symbol(symbol) => json!({ "$symbol": symbol }),
+            Bson::CodeWithScope(code, scope) => {
+                json!({ "$code": code, "$scope": Bson::Document(scope.clone()).to_extended_json(mode) })
+            }
+            Bson::Int32(i) if canonical => json!({ "$numberInt": i.to_string() }),
+            Bson::Int32(i) => Value::from(*i),
+            Bson::Timestamp(t, i) if plain => Value::from(u64::from(*t) << 32 | u64::from(*i)),
+            Bson::Timestamp(t, i) => json!({ "$timestamp": { "t": t, "i": i } }),
+            Bson::Int64(i) if canonical => json!({ "$numberLong": i.to_string() }),
+            Bson::Int64(i) => Value::from(*i),
+            Bson::Decimal128(bytes) if plain => Value::String(decimal_text(bytes)),
+            Bson::Decimal128(bytes) => json!({ "$numberDecimal": decimal_text(bytes) }),
+            Bson::MinKey if plain => Value::Null,
+            Bson::MinKey => json!({ "$minKey": 1 }),
+            Bson::MaxKey if plain => Value::Null,
+            Bson::MaxKey => json!({ "$maxKey": 1 }),
+        }
+    }
+
+    /// The BSON type's alias, as MongoDB's `$type` operator names it.
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            Bson::Double(_) => "double",
+            Bson::String(_) => "string",
+            Bson::Document(_) => "object",
+            Bson::Array(_) => "array",
+            Bson::Binary(..) => "binData",
+            Bson::Undefined => "undefined",
+            Bson::ObjectId(_) => "objectId",
+            Bson::Boolean(_) => "bool",
+            Bson::DateTime(_) => "date",
+            Bson::Null => "null",
+            Bson::Regex(..) => "regex",
+            Bson::DbPointer(..) => "dbPointer",
+            Bson::Code(_) => "javascript",
+            Bson::Symbol(_) => "symbol",
+            Bson::CodeWithScope(..) => "javascriptWithScope",
+            Bson::Int32(_) => "int",
+            Bson::Timestamp(..) => "timestamp",
+            Bson::Int64(_) => "long",
+            Bson::Decimal128(_) => "decimal",
+            Bson::MinKey => "minKey",
+            Bson::MaxKey => "maxKey",
+        }
+    }
+
+    /// The value as the mongo shell prints it, e.g. `ObjectId("...")`.
+    pub(crate) fn display(&self) -> String {
+        match self {
+            Bson::Binary(4, bytes) if bytes.len() == 16 => format!("UUID(\"{}\")", uuid_text(bytes)),
+            Bson::Binary(subtype, bytes) => format!("BinData({}, \"{}\")", subtype, STANDARD.encode(bytes)),
+            Bson::Undefined => "undefined".to_string(),
+            Bson::ObjectId(id) => format!("ObjectId(\"{}\")", hex(id)),
+            Bson::DateTime(millis) => match iso_date(*millis) {
+                Some(date) => format!("ISODate(\"{}\")", date),
+                None => format!("Date({})", millis),
+            },
+            Bson::Regex(pattern, options) => format!("/{}/{}", pattern, options),
+            Bson::DbPointer(namespace, id) => format!("DBPointer(\"{}\", ObjectId(\"{}\"))", namespace, hex(id)),
+            Bson::Code(code) | Bson::CodeWithScope(code, _) => format!("Code({:?})", code),
+            Bson::Symbol(symbol) => format!("Symbol({:?})", symbol),
+            Bson::Int32(i) => format!("NumberInt({})", i),
+            Bson::Timestamp(t, i) => format!("Timestamp({}, {})", t, i),
+            Bson::Int64(i) => format!("NumberLong({})", i),
+            Bson::Decimal128(bytes) => format!("NumberDecimal(\"{}\")", decimal_text(bytes)),
+            Bson::MinKey => "MinKey".to_string(),
+            Bson::MaxKey => "MaxKey".to_string(),
+            Bson::Double(f) => double_text(*f),
+            other => other.to_extended_json(ExtendedJsonMode::Relaxed).to_string(),
+        }
+    }
+}
+
+/// The BSON value of an Extended JSON wrapper object, or `None` for an
+/// ordinary object or a malformed wrapper.
+pub(crate) fn wrapper_value(map: &Map<String, Value>) -> Option<Bson> {
+    wrapper(map, "").ok().flatten()
+}
+
+/// Replaces the Extended JSON wrappers in `value` with plain JSON values.
+pub(crate) fn to_plain(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => match wrapper_value(map) {
+            Some(bson) => bson.to_extended_json(ExtendedJsonMode::Plain),
+            None => Value::Object(map.iter().map(|(key, value)| (key.clone(), to_plain(value))).collect()),
+        },
+        Value::Array(items) => Value::Array(items.iter().map(to_plain).collect()),
+        other => other.clone(),
+    }
+}
+
+fn read(value: &Value, pointer: &str) -> Result<Bson, String> {
+    Ok(match value {
+        Value::Null => Bson::Null,
+        Value::Bool(b) => Bson::Boolean(*b),
+        Value::Number(n) => number(n),
+        Value::String(s) => Bson::String(s.clone()),
+        Value::Array(items) => Bson::Array(
+            items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| read(item, &format!("{}/{}", pointer, i)))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Object(map) => match wrapper(map, pointer)? {
+            Some(bson) => bson,
+            None => Bson::Document(document(map, pointer)?),
+        },
+    })
+}
+
+fn document(map: &Map<String, Value>, pointer: &str) -> Result<Vec<(String, Bson)>, String> {
+    map.iter()
+        .map(|(key, value)| Ok((key.clone(), read(value, &format!("{}/{}", pointer, crate::validator::escape(key)))?)))
+        .collect()
+}
+
+/// A JSON number as the BSON type a driver would pick: int32 when it fits,
+/// then int64, otherwise double.
+fn number(n: &Number) -> Bson {
+    match n.as_i64() {
+        Some(i) => i32::try_from(i).map_or(Bson::Int64(i), Bson::Int32),
+        None => Bson::Double(n.as_f64().unwrap_or_default()),
+    }
+}
+
+/// Reads `map` as a type wrapper. `Ok(None)` means it is an ordinary
+/// document; a wrapper key with the wrong shape is an error.
+fn wrapper(map: &Map<String, Value>, pointer: &str) -> Result<Option<Bson>, String> {
+    let keys: Vec<&str> = map.keys().map(String::as_str).collect();
+    let first = match keys.iter().find(|key| key.starts_with('$')) {
+        Some(key) => *key,
+        None => return Ok(None),
+    };
+    let invalid = |what: &str| {
+        let location = if pointer.is_empty() { "the top level".to_string() } else { pointer.to_string() };
+        Err(format!("Invalid Extended JSON {} at {}", what, location))
+    };
+    let text = |key: &str| map.get(key).and_then(Value::as_str);
+    let bson = match keys.as_slice() {
+        ["$oid"] => match text("$oid").and_then(object_id) {
+            Some(id) => Bson::ObjectId(id),
+            None => return invalid("$oid (expected 24 hexadecimal digits)"),
+        },
+        ["$symbol"] => match text("$symbol") {
+            Some(symbol) => Bson::Symbol(symbol.to_string()),
+            None => return invalid("$symbol"),
+        },
+        ["$numberInt"] => match text("$numberInt").and_then(|s| s.parse().ok()) {
+            Some(i) => Bson::Int32(i),
+            None => return invalid("$numberInt"),
+        },
+        ["$numberLong"] => match text("$numberLong").and_then(|s| s.parse().ok()) {
+            Some(i) => Bson::Int64(i),
+            None => return invalid("$numberLong"),
+        },
+        ["$numberDouble"] => match text("$numberDouble").and_then(parse_double) {
+            Some(f) => Bson::Double(f),
+            None => return invalid("$numberDouble"),
+        },
+        ["$numberDecimal"] => match text("$numberDecimal").map(parse_decimal) {
+            Some(Ok(bytes)) => Bson::Decimal128(bytes),
+            Some(Err(e)) => return invalid(&format!("$numberDecimal ({})", e)),
+            None => return invalid("$numberDecimal"),
+        },
+        ["$binary"] => {
+            let binary = map["$binary"].as_object();
+            let field = |key: &str| binary.and_then(|binary| binary.get(key)).and_then(Value::as_str);
+            match (binary.map(Map::len), field("base64"), field("subType")) {
+                (Some(2), Some(data), Some(subtype)) => match (STANDARD.decode(data), subtype_byte(subtype)) {
+                    (Ok(bytes), Some(subtype)) => Bson::Binary(subtype, bytes),
+                    _ => return invalid("$binary"),
+                },
+                _ => return invalid("$binary (expected base64 and subType)"),
+            }
+        }
+        ["$binary", "$type"] => match (text("$binary").map(|data| STANDARD.decode(data)), text("$type").and_then(subtype_byte)) {
+            (Some(Ok(bytes)), Some(subtype)) => Bson::Binary(subtype, bytes),
+            _ => return invalid("$binary"),
+        },
+        ["$uuid"] => match text("$uuid").and_then(uuid_bytes) {
+            Some(bytes) => Bson::Binary(4, bytes),
+            None => return invalid("$uuid"),
+        },
+        ["$code"] => match text("$code") {
+            Some(code) => Bson::Code(code.to_string()),
+            None => return invalid("$code"),
+        },
+        ["$code", "$scope"] => match (text("$code"), map["$scope"].as_object()) {
+            (Some(code), Some(scope)) => Bson::CodeWithScope(code.to_string(), document(scope, &format!("{}/$scope", pointer))?),
+            _ => return invalid("$code with $scope"),
+        },
+        ["$timestamp"] => {
+            let timestamp = map["$timestamp"].as_object();
+            let field = |key: &str| timestamp.and_then(|t| t.get(key)).and_then(Value::as_u64).and_then(|n| u32::try_from(n).ok());
+            match (timestamp.map(Map::len), field("t"), field("i")) {
+                (Some(2), Some(t), Some(i)) => Bson::Timestamp(t, i),
+                _ => return invalid("$timestamp (expected t and i)"),
+            }
+        }
+        ["$regularExpression"] => {
+            let regex = map["$regularExpression"].as_object();
+            let field = |key: &str| regex.and_then(|regex| regex.get(key)).and_then(Value::as_str);
+            match (regex.map(Map::len), field("pattern"), field("options")) {
+                (Some(2), Some(pattern), Some(options)) => Bson::Regex(pattern.to_string(), options.to_string()),
+                _ => return invalid("$regularExpression (expected pattern and options)"),
+            }
+        }
+        ["$dbPointer"] => {
+            let pointer_value = map["$dbPointer"].as_object();
+            let namespace = pointer_value.and_then(|p| p.get("$ref")).and_then(Value::as_str);
+            let id = pointer_value.and_then(|p| p.get("$id")).and_then(Value::as_object).and_then(wrapper_value);
+            match (pointer_value.map(Map::len), namespace, id) {
+                (Some(2), Some(namespace), Some(Bson::ObjectId(id))) => Bson::DbPointer(namespace.to_string(), id),
+                _ => return invalid("$dbPointer (expected $ref and an $oid $id)"),
+            }
+        }
+        ["$date"] => match &map["$date"] {
+            Value::String(date) => match parse_iso_date(date) {
+                Some(millis) => Bson::DateTime(millis),
+                None => return invalid("$date (expected an ISO 8601 date-time)"),
+            },
+            Value::Object(inner) => match wrapper(inner, pointer) {
+                Ok(Some(Bson::Int64(millis))) => Bson::DateTime(millis),
+                _ => return invalid("$date (expected $numberLong)"),
+            },
+            Value::Number(n) => match n.as_i64() {
+                Some(millis) => Bson::DateTime(millis),
+                None => return invalid("$date"),
+            },
+            _ => return invalid("$date"),
+        },
+        ["$minKey"] if map["$minKey"] == 1 => Bson::MinKey,
+        ["$maxKey"] if map["$maxKey"] == 1 => Bson::MaxKey,
+        ["$undefined"] if map["$undefined"] == true => Bson::Undefined,
+        // Keys like `$ref` and `$id` (DBRefs) belong to ordinary documents.
+        _ if WRAPPER_KEYS.contains(&first) => return invalid(first),
+        _ => return Ok(None),
+    };
+    Ok(Some(bson))
+}
+
+const WRAPPER_KEYS: &[&str] = &[
+    "$oid", "$symbol", "$numberInt", "$numberLong", "$numberDouble", "$numberDecimal", "$binary", "$uuid", "$code",
+    "$timestamp", "$regularExpression", "$dbPointer", "$date", "$minKey", "$maxKey", "$undefined",
+];
+
+fn float(f: f64) -> Value {
+    Number::from_f64(f).map_or(Value::Null, Value::Number)
+}
+
+fn double_text(f: f64) -> String {
+    if f.is_nan() {
+        "NaN".to_string()
+    } else if f.is_infinite() {
+        if f > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+    } else if f.fract() == 0.0 && f.abs() < 1e16 {
+        format!("{:.1}", f)
+    } else {
+        f.to_string()
+    }
+}
+
+fn parse_double(text: &str) -> Option<f64> {
+    match text {
+        "Infinity" => Some(f64::INFINITY),
+        "-Infinity" => Some(f64::NEG_INFINITY),
+        "NaN" => Some(f64::NAN),
+        _ => text.parse().ok().filter(|f: &f64| f.is_finite()),
+    }
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn object_id(text: &str) -> Option<[u8; 12]> {
+    let bytes = hex_bytes(text)?;
+    <[u8; 12]>::try_from(bytes.as_slice()).ok()
+}
+
+fn hex_bytes(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) || !text.is_ascii() {
+        return None;
+    }
+    (0..text.len()).step_by(2).map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok()).collect()
+}
+
+fn subtype_byte(text: &str) -> Option<u8> {
+    if text.is_empty() || text.len() > 2 {
+        return None;
+    }
+    u8::from_str_radix(text, 16).ok()
+}
+
+fn uuid_text(bytes: &[u8]) -> String {
+    let hex = hex(bytes);
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+fn uuid_bytes(text: &str) -> Option<Vec<u8>> {
+    let parts: Vec<&str> = text.split('-').collect();
+    if parts.iter().map(|part| part.len()).collect::<Vec<_>>() != [8, 4, 4, 4, 12] {
+        return None;
+    }
+    hex_bytes(&parts.concat())
+}
+
+// Decimal128 uses the binary integer decimal encoding: a sign bit, a 14-bit
+// exponent biased by 6176 and a coefficient of up to 34 decimal digits.
+
+const DECIMAL_BIAS: i64 = 6176;
+const DECIMAL_MAX_EXPONENT: i64 = 6111;
+const DECIMAL_MAX_DIGITS: usize = 34;
+
+/// The decimal128's string form, as the specification's algorithm gives it.
+pub(crate) fn decimal_text(bytes: &[u8; 16]) -> String {
+    let mut low = [0; 8];
+    let mut high = [0; 8];
+    low.copy_from_slice(&bytes[..8]);
+    high.copy_from_slice(&bytes[8..]);
+    let (low, high) = (u64::from_le_bytes(low), u64::from_le_bytes(high));
+    let sign = if high >> 63 == 1 { "-" } else { "" };
+
+    let combination = (high >> 58) & 0x1f;
+    if combination == 0x1f {
+        return "NaN".to_string();
+    }
+    if combination == 0x1e {
+        return format!("{}Infinity", sign);
+    }
+    let (biased, coefficient) = if (high >> 61) & 0b11 == 0b11 {
+        // This form's coefficient is always too large, so reads as zero.
+        ((high >> 47) & 0x3fff, 0)
+    } else {
+        ((high >> 49) & 0x3fff, u128::from(high & 0x1_ffff_ffff_ffff) << 64 | u128::from(low))
+    };
+    let coefficient = if coefficient >= 10u128.pow(DECIMAL_MAX_DIGITS as u32) { 0 } else { coefficient };
+    let exponent = biased as i64 - DECIMAL_BIAS;
+
+    let digits = coefficient.to_string();
+    let adjusted = exponent + digits.len() as i64 - 1;
+    let text = if exponent <= 0 && adjusted >= -6 {
+        let point = digits.len() as i64 + exponent;
+        if exponent == 0 {
+            digits
+        } else if point > 0 {
+            format!("{}.{}", &digits[..point as usize], &digits[point as usize..])
+        } else {
+            format!("0.{}{}", "0".repeat((-point) as usize), digits)
+        }
+    } else {
+        let fraction = if digits.len() > 1 { format!(".{}", &digits[1..]) } else { String::new() };
+        format!("{}{}E{:+}", &digits[..1], fraction, adjusted)
+    };
+    format!("{}{}", sign, text)
+}
+
+/// Parses a decimal string into decimal128, without rounding: values that
+/// need more than 34 digits are errors.
+pub(crate) fn parse_decimal(text: &str) -> Result<[u8; 16], String> {
+    let (negative, body) = match text.strip_prefix('-') {
+        Some(body) => (true, body),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let sign = if negative { 1u64 << 63 } else { 0 };
+    let special = match body.to_ascii_lowercase().as_str() {
+        "nan" => Some(0x7c00_0000_0000_0000),
+        "inf" | "infinity" => Some(sign | 0x7800_0000_0000_0000),
+        _ => None,
+    };
+    if let Some(high) = special {
+        return Ok(decimal_bytes(0, high));
+    }
+
+    let (mantissa, exponent) = match body.find(['e', 'E']) {
+        Some(at) => (&body[..at], body[at + 1..].parse::<i64>().map_err(|_| format!("'{}' is not a decimal", text))?),
+        None => (body, 0),
+    };
+    let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if whole.is_empty() && fraction.is_empty() || !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+        return Err(format!("'{}' is not a decimal", text));
+    }
+    let out_of_range = || format!("'{}' is out of decimal128's range", text);
+    let digits = format!("{}{}", whole, fraction);
+    let digits = digits.trim_start_matches('0');
+    // Drop trailing zeros past the precision into the exponent.
+    let zeros = digits.len() - digits.trim_end_matches('0').len();
+    let dropped = digits.len().saturating_sub(DECIMAL_MAX_DIGITS).min(zeros);
+    let digits = &digits[..digits.len() - dropped];
+    if digits.len() > DECIMAL_MAX_DIGITS {
+        return Err(format!("'{}' has more than {} significant digits", text, DECIMAL_MAX_DIGITS));
+    }
+    let mut exponent = i64::try_from(dropped)
+        .ok()
+        .and_then(|dropped| exponent.checked_add(dropped))
+        .and_then(|exponent| exponent.checked_sub(i64::try_from(fraction.len()).ok()?))
+        .ok_or_else(out_of_range)?;
+    let mut coefficient: u128 = if digits.is_empty() { 0 } else { digits.parse().map_err(|_| format!("'{}' is not a decimal", text))? };
+
+    if coefficient == 0 {
+        exponent = exponent.clamp(-DECIMAL_BIAS, DECIMAL_MAX_EXPONENT);
+    } else if exponent > DECIMAL_MAX_EXPONENT {
+        // Trade exponent for trailing zeros to bring it into range.
+        let shift = exponent - DECIMAL_MAX_EXPONENT;
+        if shift <= (DECIMAL_MAX_DIGITS - digits.len()) as i64 {
+            coefficient *= 10u128.pow(shift as u32);
+            exponent = DECIMAL_MAX_EXPONENT;
+        }
+    } else if exponent < -DECIMAL_BIAS {
+        let shift = -DECIMAL_BIAS - exponent;
+        let zeros = digits.len() - digits.trim_end_matches('0').len();
+        if shift <= zeros as i64 {
+            coefficient /= 10u128.pow(shift as u32);
+            exponent = -DECIMAL_BIAS;
+        }
+    }
+    if !(-DECIMAL_BIAS..=DECIMAL_MAX_EXPONENT).contains(&exponent) {
+        return Err(out_of_range());
+    }
+    let high = sign | ((exponent + DECIMAL_BIAS) as u64) << 49 | (coefficient >> 64) as u64;
+    Ok(decimal_bytes(coefficient as u64, high))
+}
+
+fn decimal_bytes(low: u64, high: u64) -> [u8; 16] {
+    let mut bytes = [0; 16];
+    bytes[..8].copy_from_slice(&low.to_le_bytes());
+    bytes[8..].copy_from_slice(&high.to_le_bytes());
+    bytes
+}
+
+// Dates are milliseconds since 1970 in the proleptic Gregorian calendar.
+
+/// The ISO 8601 form of a date in years 1970 to 9999, which relaxed mode
+/// writes as a string.
+fn iso_date(millis: i64) -> Option<String> {
+    let days = millis.div_euclid(86_400_000);
+    let in_day = millis.rem_euclid(86_400_000);
+    let (year, month, day) = civil_from_days(days);
+    if !(1970..=9999).contains(&year) {
+        return None;
+    }
+    let (seconds, millis) = (in_day / 1000, in_day % 1000);
+    let fraction = if millis == 0 { String::new() } else { format!(".{:03}", millis) };
+    Some(format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        fraction
+    ))
+}
+
+/// Parses `YYYY-MM-DDTHH:MM:SS[.fff](Z|±HH:MM)` into milliseconds.
+fn parse_iso_date(text: &str) -> Option<i64> {
+    parse_rfc3339(text).map(|(seconds, nanos)| seconds * 1000 + nanos / 1_000_000)
+}
+
+/// Parses an RFC 3339 date-time into seconds and nanoseconds since 1970.
+pub(crate) fn parse_rfc3339(text: &str) -> Option<(i64, i64)> {
+    let bytes = text.as_bytes();
+    let field = |range: std::ops::Range<usize>| -> Option<i64> {
+        let part = text.get(range)?;
+        if part.bytes().all(|b| b.is_ascii_digit()) { part.parse().ok() } else { None }
+    };
+    if bytes.len() < 20 || bytes[4] != b'-' || bytes[7] != b'-' || !matches!(bytes[10], b'T' | b't') || bytes[13] != b':' || bytes[16] != b':' {
+        return None;
+    }
+    let (year, month, day) = (field(0..4)?, field(5..7)?, field(8..10)?);
+    let (hour, minute, second) = (field(11..13)?, field(14..16)?, field(17..19)?);
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let mut rest = &text[19..];
+    let mut nanos = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return None;
+        }
+        let padded = format!("{:0<9}", &fraction[..digits.min(9)]);
+        nanos = padded.parse::<i64>().ok()?;
+        rest = &fraction[digits..];
+    }
+    let offset_minutes = match rest {
+        "Z" | "z" => 0,
+        _ => {
+            let sign = match rest.as_bytes().first() {
+                Some(b'+') => 1,
+                Some(b'-') => -1,
+                _ => return None,
+            };
+            let offset = rest[1..].replace(':', "");
+            if offset.len() != 4 || !offset.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            sign * (offset[..2].parse::<i64>().ok()? * 60 + offset[2..].parse::<i64>().ok()?)
+        }
+    };
+    let days = days_from_civil(year, month, day);
+    Some((((days * 24 + hour) * 60 + minute - offset_minutes) * 60 + second, nanos))
+}
+
+pub(crate) fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a Gregorian date (Howard Hinnant's algorithm).
+pub(crate) fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+pub(crate) fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decimal(text: &str) -> Result<String, String> {
+        parse_decimal(text).map(|bytes| decimal_text(&bytes))
+    }
+
+    #[test]
+    fn decimals_round_trip() {
+        for text in ["1", "123.45", "-0.001", "1E+3", "1.0", "0.000001234", "NaN", "Infinity", "-Infinity"] {
+            assert_eq!(decimal(text).unwrap(), text);
+        }
+        assert_eq!(parse_decimal("1").unwrap(), decimal_bytes(1, 0x3040_0000_0000_0000));
+    }
+
+    #[test]
+    fn decimal_exponents_are_brought_into_range() {
+        assert_eq!(decimal("1E6144").unwrap(), "1.000000000000000000000000000000000E+6144");
+        assert_eq!(decimal("10E-6177").unwrap(), "1E-6176");
+        let trailing_zeros = "12345678901234567890123456789012340000";
+        assert_eq!(decimal(trailing_zeros).unwrap(), "1.234567890123456789012345678901234E+37");
+        assert!(decimal("1E6145").unwrap_err().contains("out of decimal128's range"));
+        let too_precise = "1234567890123456789012345678901234567";
+        assert!(decimal(too_precise).unwrap_err().contains("more than 34 significant digits"));
+    }
+
+    #[test]
+    fn huge_decimal_exponents_are_clamped_or_rejected() {
+        assert_eq!(decimal("0E+100000000000").unwrap(), "0E+6111");
+        assert_eq!(decimal("-0E-100000000000").unwrap(), "-0E-6176");
+        assert!(decimal("1E-100000000000").is_err());
+        assert!(decimal("1.5E-9223372036854775808").is_err());
+        assert!(decimal("1.5E9223372036854775807").is_err());
+    }
+
+    #[test]
+    fn malformed_decimals_are_errors() {
+        for text in ["abc", "1e", ".", "1.2.3", "--1"] {
+            assert_eq!(decimal(text).unwrap_err(), format!("'{}' is not a decimal", text));
+        }
+    }
+
+    #[test]
+    fn extended_json_modes() {
+        let value = json!({"n": {"$numberLong": "5"}, "d": {"$date": "2024-01-02T03:04:05Z"}, "x": 1.5});
+        let bson = Bson::from_extended_json(&value).unwrap();
+        assert_eq!(
+            bson.to_extended_json(ExtendedJsonMode::Relaxed),
+            json!({"n": 5, "d": {"$date": "2024-01-02T03:04:05Z"}, "x": 1.5})
+        );
+        assert_eq!(
+            bson.to_extended_json(ExtendedJsonMode::Canonical),
+            json!({
+                "n": {"$numberLong": "5"},
+                "d": {"$date": {"$numberLong": "1704164645000"}},
+                "x": {"$numberDouble": "1.5"}
+            })
+        );
+        assert_eq!(to_plain(&value), json!({"n": 5, "d": "2024-01-02T03:04:05Z", "x": 1.5}));
+        assert!(Bson::from_extended_json(&json!({"$oid": "nope"})).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+// String format detection.
+//
+// Recognises the JSON Schema `format` values that are common in real payloads.
+// The checks are deliberately strict: a false positive turns a plain string
+// field into a `datetime` or `UUID` in generated code, which then fails to
+// deserialize the next sample that doesn't match.
+
+/// The JSON Schema `format` keyword that `value` satisfies, if any.
+pub(crate) fn detect_format(value: &str) -> Option<&'static str> {
+    if is_date_time(value) {
+        Some("date-time")
+    } else if is_date(value) {
+        Some("date")
+    } else if is_time(value) {
+        Some("time")
+    } else if is_uuid(value) {
+        Some("uuid")
+    } else if value.parse::<Ipv4Addr>().is_ok() {
+        Some("ipv4")
+    } else if value.contains(':') && value.parse::<Ipv6Addr>().is_ok() {
+        Some("ipv6")
+    } else if is_email(value) {
+        Some("email")
+    } else if is_uri(value) {
+        Some("uri")
+    } else if is_likely_hostname(value) {
+        Some("hostname")
+    } else {
+        None
+    }
+}
+
+/// Whether `value` satisfies `format`; `None` for formats this module does
+/// not know, which validators treat as annotations only.
+pub(crate) fn check_format(format: &str, value: &str) -> Option<bool> {
+    Some(match format {
+        "date-time" => is_date_time(value),
+        "date" => is_date(value),
+        "time" => is_time(value),
+        "uuid" => is_uuid(value),
+        "ipv4" => value.parse::<Ipv4Addr>().is_ok(),
+        "ipv6" => value.parse::<Ipv6Addr>().is_ok(),
+        "email" => is_email(value),
+        "uri" => is_absolute_uri(value),
+        "hostname" => is_hostname(value) || is_label(value),
+        _ => return None,
+    })
+}
+
+fn digits(s: &str, len: usize) -> Option<u32> {
+    if s.len() == len && s.bytes().all(|b| b.is_ascii_digit()) {
+        s.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// RFC 3339 `full-date`: `2024-02-29`.
+fn is_date(s: &str) -> bool {
+    let mut parts = s.splitn(3, '-');
+    let (year, month, day) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(y), Some(m), Some(d)) => (digits(y, 4), digits(m, 2), digits(d, 2)),
+        _ => return false,
+    };
+    let (year, month, day) = match (year, month, day) {
+        (Some(y), Some(m), Some(d)) => (y, m, d),
+        _ => return false,
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    day >= 1 && day <= days
+}
+
+/// RFC 3339 `full-time`: `13:45:30.5Z`, `13:45:30+02:00`.
+fn is_time(s: &str) -> bool {
+    let (partial, offset) = match s.find(['Z', 'z', '+', '-']) {
+        Some(i) => s.split_at(i),
+        None => return false,
+    };
+    let offset_ok = match offset {
+        "Z" | "z" => true,
+        _ => {
+            let (hours, minutes) = match offset[1..].split_once(':') {
+                Some(parts) => parts,
+                None => return false,
+            };
+            matches!((digits(hours, 2), digits(minutes, 2)), (Some(h), Some(m)) if h < 24 && m < 60)
+        }
+    };
+    let (clock, fraction) = match partial.split_once('.') {
+        Some((clock, fraction)) => (clock, Some(fraction)),
+        None => (partial, None),
+    };
+    let fraction_ok = fraction.is_none_or(|f| !f.is_empty() && f.bytes().all(|b| b.is_ascii_digit()));
+    let mut parts = clock.split(':');
+    let clock_ok = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(h), Some(m), Some(sec), None) => matches!(
+            (digits(h, 2), digits(m, 2), digits(sec, 2)),
+            // 60 allows for leap seconds.
+            (Some(h), Some(m), Some(sec)) if h < 24 && m < 60 && sec <= 60
+        ),
+        _ => false,
+    };
+    offset_ok && fraction_ok && clock_ok
+}
+
+/// RFC 3339 `date-time`: `2024-02-29T13:45:30Z`.
+fn is_date_time(s: &str) -> bool {
+    match s.find(['T', 't']) {
+        Some(i) => is_date(&s[..i]) && is_time(&s[i + 1..]),
+        None => false,
+    }
+}
+
+/// The hyphenated 8-4-4-4-12 form only.
+fn is_uuid(s: &str) -> bool {
+    let groups: Vec<&str> = s.split('-').collect();
+    groups.len() == 5
+        && groups.iter().zip([8, 4, 4, 4, 12]).all(|(group, len)| {
+            group.len() == len && group.bytes().all(|b| b.is_ascii_hexdigit())
+        })
+}
+
+fn is_email(s: &str) -> bool {
+    match s.rsplit_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !local.contains(|c: char| c.is_whitespace() || c == '@')
+                && is_hostname(domain)
+        }
+        None => false,
+    }
+}
+
+/// Any absolute URI: a scheme, a colon and something after it.
+fn is_absolute_uri(s: &str) -> bool {
+    match s.split_once(':') {
+        Some((scheme, rest)) => {
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+                && !rest.is_empty()
+                && !s.contains(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+/// An absolute URI with an authority (`https://...`) or one of the common
+/// schemes without one. A bare `word:rest` is too likely to be something else.
+fn is_uri(s: &str) -> bool {
+    let (scheme, rest) = match s.split_once(':') {
+        Some(parts) => parts,
+        None => return false,
+    };
+    let rest_ok = match scheme.to_ascii_lowercase().as_str() {
+        "mailto" | "urn" | "tel" | "data" => true,
+        _ => rest.len() > 2 && rest.starts_with("//"),
+    };
+    is_absolute_uri(s) && rest_ok
+}
+
+/// A single DNS label such as `localhost`. Valid, but too common in ordinary
+/// text for detection to rely on.
+fn is_label(s: &str) -> bool {
+    !s.is_empty()
+        && s.len() <= 63
+        && !s.starts_with('-')
+        && !s.ends_with('-')
+        && s.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+}
+
+/// Top-level domains that detection accepts. Ones that are also common file
+/// extensions (`md`, `py`, `rs`, `sh`, `pl`, ...) are left out on purpose.
+const COMMON_TLDS: &[&str] = &[
+    "com", "org", "net", "edu", "gov", "mil", "int", "io", "co", "dev", "app", "ai", "cloud", "info", "biz", "me",
+    "tech", "online", "site", "xyz", "local", "internal", "test", "example", "localhost", "us", "uk", "de", "fr",
+    "jp", "cn", "ru", "br", "au", "ca", "nl", "eu", "es", "it", "ch", "se", "no", "fi", "dk", "at", "be", "ie", "nz",
+    "kr", "mx", "ar", "za", "tw", "hk", "sg",
+];
+
+/// A dotted DNS name whose last label looks like a top-level domain.
+fn is_hostname(s: &str) -> bool {
+    let labels: Vec<&str> = s.split('.').collect();
+    let tld = labels[labels.len() - 1];
+    s.len() <= 253
+        && labels.len() >= 2
+        && tld.len() >= 2
+        && tld.bytes().all(|b| b.is_ascii_alphabetic())
+        && labels.iter().all(|label| is_label(label))
+}
+
+/// A hostname ending in a well-known top-level domain. Detection needs this
+/// much more than `is_hostname`, which also accepts `report.pdf` or `john.doe`.
+fn is_likely_hostname(s: &str) -> bool {
+    is_hostname(s) && s.rsplit('.').next().is_some_and(|tld| COMMON_TLDS.contains(&tld.to_ascii_lowercase().as_str()))
+}
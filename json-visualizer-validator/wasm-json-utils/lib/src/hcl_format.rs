@@ -0,0 +1,1172 @@
+use serde_json::{json, Map, Number, Value};
+use std::collections::HashSet;
+
+use crate::schema::infer_schema;
+use crate::validator::escape;
+
+// HCL (Terraform) reading and writing.
+//
+// Native HCL2 is read into the equivalent of Terraform's JSON syntax: blocks
+// nest by type and then by label (`resource "aws_instance" "web"` becomes
+// `{"resource": {"aws_instance": {"web": {...}}}}`), repeated blocks become
+// arrays, and any expression that is not a literal is kept as a `"${...}"`
+// template string holding its source text. The writer goes the other way
+// for simple cases: objects under Terraform's block keywords are written as
+// blocks, and so are lists of objects and objects holding anything but
+// strings inside resource-like blocks (`ingress`, `root_block_device`, ...).
+// The JSON syntax only tells nested blocks from object attributes through
+// provider schemas, so a nested block of nothing but strings is written as
+// a map attribute like `tags`, and a list of objects meant as an attribute
+// comes out as repeated blocks.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TokenKind {
+    Ident,
+    Number,
+    /// A quoted string or heredoc; the text is the template as JSON holds it.
+    Template,
+    Punct,
+    Newline,
+    Eof,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    text: String,
+    start: usize,
+    end: usize,
+}
+
+const PUNCTUATION: &[&str] = &[
+    "...", "==", "!=", "<=", ">=", "&&", "||", "=>", "::", "+", "-", "*", "/", "%", "<", ">", "!", "?", ":", "=", ".",
+    ",", "(", ")", "[", "]", "{", "}",
+];
+
+const BINARY_OPERATORS: &[&str] = &["==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "/", "%", "<", ">"];
+
+fn error_at(src: &str, offset: usize, message: &str) -> String {
+    let before = &src[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |text| text.chars().count()) + 1;
+    format!("Invalid HCL on line {}, column {}: {}", line, column, message)
+}
+
+fn line_of(src: &str, offset: usize) -> usize {
+    src[..offset].matches('\n').count() + 1
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Whether `name` can be written as a bare HCL identifier.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(is_ident_start) && chars.all(is_ident_char)
+}
+
+struct Lexer<'s> {
+    src: &'s str,
+    pos: usize,
+}
+
+impl<'s> Lexer<'s> {
+    fn rest(&self) -> &'s str {
+        &self.src[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn tokens(mut self) -> Result<Vec<Token>, String> {
+        let mut tokens = Vec::new();
+        while let Some(c) = self.peek() {
+            let start = self.pos;
+            let rest = self.rest();
+            let (kind, text) = if c == '\n' {
+                self.pos += 1;
+                (TokenKind::Newline, "\n".to_string())
+            } else if c.is_whitespace() {
+                self.pos += c.len_utf8();
+                continue;
+            } else if c == '#' || rest.starts_with("//") {
+                self.pos += rest.find('\n').unwrap_or(rest.len());
+                continue;
+            } else if rest.starts_with("/*") {
+                let end = rest.find("*/").ok_or_else(|| error_at(self.src, start, "Unclosed comment"))?;
+                self.pos += end + 2;
+                continue;
+            } else if c == '"' {
+                (TokenKind::Template, self.quoted()?)
+            } else if let Some(text) = self.heredoc()? {
+                (TokenKind::Template, text)
+            } else if c.is_ascii_digit() {
+                (TokenKind::Number, self.number())
+            } else if is_ident_start(c) {
+                let end = rest.find(|c: char| !is_ident_char(c)).unwrap_or(rest.len());
+                self.pos += end;
+                (TokenKind::Ident, rest[..end].to_string())
+            } else if let Some(punct) = PUNCTUATION.iter().find(|p| rest.starts_with(**p)) {
+                self.pos += punct.len();
+                (TokenKind::Punct, punct.to_string())
+            } else {
+                return Err(error_at(self.src, start, &format!("Unexpected character '{}'", c)));
+            };
+            tokens.push(Token { kind, text, start, end: self.pos });
+        }
+        tokens.push(Token { kind: TokenKind::Eof, text: String::new(), start: self.pos, end: self.pos });
+        Ok(tokens)
+    }
+
+    fn number(&mut self) -> String {
+        let bytes = self.src.as_bytes();
+        let start = self.pos;
+        let digits = |pos: &mut usize| {
+            while bytes.get(*pos).is_some_and(u8::is_ascii_digit) {
+                *pos += 1;
+            }
+        };
+        digits(&mut self.pos);
+        if bytes.get(self.pos) == Some(&b'.') && bytes.get(self.pos + 1).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+            digits(&mut self.pos);
+        }
+        if matches!(bytes.get(self.pos), Some(b'e' | b'E')) {
+            let sign = usize::from(matches!(bytes.get(self.pos + 1), Some(b'+' | b'-')));
+            if bytes.get(self.pos + 1 + sign).is_some_and(u8::is_ascii_digit) {
+                self.pos += 1 + sign;
+                digits(&mut self.pos);
+            }
+        }
+        self.src[start..self.pos].to_string()
+    }
+
+    /// Reads a quoted template, decoding escapes but keeping `${...}` and
+    /// `%{...}` sequences as written.
+    fn quoted(&mut self) -> Result<String, String> {
+        let open = self.pos;
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let rest = self.rest();
+            match rest.chars().next() {
+                None | Some('\n') => return Err(error_at(self.src, open, "Unclosed string")),
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some('\\') => {
+                    let (c, length) =
+                        unescape(&rest[1..]).ok_or_else(|| error_at(self.src, self.pos, "Invalid escape"))?;
+                    out.push(c);
+                    self.pos += 1 + length;
+                }
+                Some(_) if rest.starts_with("$${") || rest.starts_with("%%{") => {
+                    out.push_str(&rest[..3]);
+                    self.pos += 3;
+                }
+                Some(_) if rest.starts_with("${") || rest.starts_with("%{") => {
+                    let end = interpolation_end(self.src, self.pos)
+                        .ok_or_else(|| error_at(self.src, self.pos, "Unclosed template sequence"))?;
+                    out.push_str(&self.src[self.pos..end]);
+                    self.pos = end;
+                }
+                Some(c) => {
+                    out.push(c);
+                    self.pos += c.len_utf8();
+                }
+            }
+        }
+    }
+
+    /// Reads a `<<EOT` or `<<-EOT` heredoc, if one starts here.
+    fn heredoc(&mut self) -> Result<Option<String>, String> {
+        let rest = self.rest();
+        let Some(after) = rest.strip_prefix("<<") else { return Ok(None) };
+        let (indented, after) = match after.strip_prefix('-') {
+            Some(after) => (true, after),
+            None => (false, after),
+        };
+        let marker_len = after.find(|c: char| !is_ident_char(c)).unwrap_or(after.len());
+        let marker = &after[..marker_len];
+        if !is_identifier(marker) {
+            return Ok(None);
+        }
+        let header_end = rest.len() - after.len() + marker_len;
+        let line_end = rest.find('\n').unwrap_or(rest.len());
+        if !rest[header_end..line_end].trim().is_empty() || line_end == rest.len() {
+            return Err(error_at(self.src, self.pos, "Expected a newline after the heredoc marker"));
+        }
+        let mut lines = Vec::new();
+        let mut offset = line_end + 1;
+        loop {
+            if offset > rest.len() {
+                return Err(error_at(self.src, self.pos, &format!("Unclosed heredoc, expected {}", marker)));
+            }
+            let end = rest[offset..].find('\n').map_or(rest.len(), |i| offset + i);
+            let line = rest[offset..end].trim_end_matches('\r');
+            if line.trim() == marker {
+                self.pos += offset + line.len();
+                break;
+            }
+            lines.push(line);
+            offset = end + 1;
+        }
+        let strip = if indented {
+            lines
+                .iter()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| line.len() - line.trim_start().len())
+                .min()
+                .unwrap_or(0)
+        } else {
+            0
+        };
+        let mut text = String::new();
+        for line in lines {
+            text.push_str(line.get(strip..).unwrap_or_else(|| line.trim_start()));
+            text.push('\n');
+        }
+        Ok(Some(text))
+    }
+}
+
+/// Decodes the escape after a backslash, returning the character and the
+/// number of bytes it took.
+fn unescape(text: &str) -> Option<(char, usize)> {
+    let c = text.chars().next()?;
+    let simple = match c {
+        'n' => Some('\n'),
+        'r' => Some('\r'),
+        't' => Some('\t'),
+        '"' => Some('"'),
+        '\\' => Some('\\'),
+        _ => None,
+    };
+    if let Some(decoded) = simple {
+        return Some((decoded, 1));
+    }
+    let digits = match c {
+        'u' => 4,
+        'U' => 8,
+        _ => return None,
+    };
+    let hex = text.get(1..1 + digits)?;
+    let code = u32::from_str_radix(hex, 16).ok()?;
+    Some((char::from_u32(code)?, 1 + digits))
+}
+
+/// The end of the `${...}` or `%{...}` sequence starting at `start`,
+/// skipping over braces and strings nested inside it.
+fn interpolation_end(src: &str, start: usize) -> Option<usize> {
+    let bytes = src.as_bytes();
+    let mut pos = start + 2;
+    let mut depth = 1;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(pos + 1);
+                }
+            }
+            b'"' => {
+                pos = string_end(src, pos)?;
+                continue;
+            }
+            _ => {}
+        }
+        pos += 1;
+    }
+    None
+}
+
+/// The end of the quoted string starting at `start`.
+fn string_end(src: &str, start: usize) -> Option<usize> {
+    let bytes = src.as_bytes();
+    let mut pos = start + 1;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'"' => return Some(pos + 1),
+            b'\\' => pos += 2,
+            b'$' | b'%' if bytes.get(pos + 1) == Some(&b'{') => pos = interpolation_end(src, pos)?,
+            b'\n' => return None,
+            _ => pos += 1,
+        }
+    }
+    None
+}
+
+/// One attribute or block of an HCL body.
+pub(crate) enum Item {
+    Attribute { name: String, expr: Expr, line: usize },
+    Block { kind: String, labels: Vec<String>, body: Vec<Item>, line: usize },
+}
+
+/// An expression with its source text. Only the shapes that matter for JSON
+/// output and type constraints are told apart; the rest are `Other`.
+pub(crate) struct Expr {
+    kind: ExprKind,
+    text: String,
+}
+
+enum ExprKind {
+    /// Numbers, booleans, null and string templates.
+    Literal(Value),
+    Tuple(Vec<Expr>),
+    Object(Vec<(String, Expr)>),
+    Call(String, Vec<Expr>),
+    Variable(String),
+    Other,
+}
+
+struct Parser<'s> {
+    src: &'s str,
+    tokens: Vec<Token>,
+    pos: usize,
+    /// How many brackets the parser is inside; newlines don't count there.
+    nesting: usize,
+    last_end: usize,
+}
+
+impl<'s> Parser<'s> {
+    fn index(&self) -> usize {
+        let mut index = self.pos;
+        while self.nesting > 0 && self.tokens[index].kind == TokenKind::Newline {
+            index += 1;
+        }
+        index
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.index()]
+    }
+
+    fn next(&mut self) -> Token {
+        let index = self.index();
+        let token = self.tokens[index].clone();
+        if token.kind != TokenKind::Eof {
+            self.pos = index + 1;
+            self.last_end = token.end;
+        }
+        token
+    }
+
+    fn is_punct(&self, punct: &str) -> bool {
+        let token = self.peek();
+        token.kind == TokenKind::Punct && token.text == punct
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        let found = self.is_punct(punct);
+        if found {
+            self.next();
+        }
+        found
+    }
+
+    fn error(&self, message: &str) -> String {
+        let token = self.peek();
+        let found = match token.kind {
+            TokenKind::Eof => "the end of the file".to_string(),
+            TokenKind::Newline => "a newline".to_string(),
+            TokenKind::Template => "a string".to_string(),
+            _ => format!("'{}'", token.text),
+        };
+        error_at(self.src, token.start, &format!("{}, found {}", message, found))
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), String> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", punct)))
+        }
+    }
+
+    fn skip_newlines(&mut self) {
+        while self.tokens[self.pos].kind == TokenKind::Newline {
+            self.pos += 1;
+        }
+    }
+
+    fn body(&mut self, in_block: bool) -> Result<Vec<Item>, String> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_newlines();
+            let token = self.peek().clone();
+            match token.kind {
+                TokenKind::Eof if !in_block => return Ok(items),
+                TokenKind::Punct if in_block && token.text == "}" => return Ok(items),
+                TokenKind::Ident => {}
+                _ => return Err(self.error("Expected an attribute or block")),
+            }
+            self.next();
+            let line = line_of(self.src, token.start);
+            if self.eat("=") {
+                let expr = self.expression()?;
+                items.push(Item::Attribute { name: token.text, expr, line });
+            } else {
+                let mut labels = Vec::new();
+                while matches!(self.peek().kind, TokenKind::Template | TokenKind::Ident) {
+                    labels.push(self.next().text);
+                }
+                self.expect("{")?;
+                let body = self.body(true)?;
+                self.expect("}")?;
+                items.push(Item::Block { kind: token.text, labels, body, line });
+            }
+            let next = self.peek();
+            let ends_item = match next.kind {
+                TokenKind::Newline | TokenKind::Eof => true,
+                TokenKind::Punct => in_block && next.text == "}",
+                _ => false,
+            };
+            if !ends_item {
+                return Err(self.error("Expected a newline"));
+            }
+        }
+    }
+
+    fn finish(&self, start: usize, kind: ExprKind) -> Expr {
+        Expr { kind, text: self.src[start..self.last_end].to_string() }
+    }
+
+    fn expression(&mut self) -> Result<Expr, String> {
+        let start = self.peek().start;
+        let condition = self.binary()?;
+        if !self.eat("?") {
+            return Ok(condition);
+        }
+        self.expression()?;
+        self.expect(":")?;
+        self.expression()?;
+        Ok(self.finish(start, ExprKind::Other))
+    }
+
+    fn binary(&mut self) -> Result<Expr, String> {
+        let start = self.peek().start;
+        let first = self.unary()?;
+        let mut operation = false;
+        while BINARY_OPERATORS.iter().any(|op| self.is_punct(op)) {
+            self.next();
+            self.unary()?;
+            operation = true;
+        }
+        Ok(if operation { self.finish(start, ExprKind::Other) } else { first })
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        let start = self.peek().start;
+        let negate = self.is_punct("-");
+        if !negate && !self.is_punct("!") {
+            return self.postfix();
+        }
+        self.next();
+        let operand = self.unary()?;
+        let kind = match operand.kind {
+            ExprKind::Literal(Value::Number(n)) if negate => ExprKind::Literal(negative(&n)),
+            _ => ExprKind::Other,
+        };
+        Ok(self.finish(start, kind))
+    }
+
+    fn postfix(&mut self) -> Result<Expr, String> {
+        let start = self.peek().start;
+        let mut expr = self.primary()?;
+        loop {
+            if self.eat(".") {
+                let token = self.next();
+                let valid = matches!(token.kind, TokenKind::Ident | TokenKind::Number)
+                    || (token.kind == TokenKind::Punct && token.text == "*");
+                if !valid {
+                    return Err(error_at(self.src, token.start, "Expected an attribute name after '.'"));
+                }
+            } else if self.is_punct("[") {
+                self.next();
+                self.nesting += 1;
+                if !self.eat("*") {
+                    self.expression()?;
+                }
+                self.expect("]")?;
+                self.nesting -= 1;
+            } else {
+                return Ok(expr);
+            }
+            expr = self.finish(start, ExprKind::Other);
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let start = self.peek().start;
+        let token = self.peek().clone();
+        let kind = match token.kind {
+            TokenKind::Number => {
+                self.next();
+                let value = number(&token.text).ok_or_else(|| {
+                    error_at(self.src, token.start, &format!("Number {} is too large for JSON", token.text))
+                })?;
+                ExprKind::Literal(value)
+            }
+            TokenKind::Template => {
+                self.next();
+                ExprKind::Literal(Value::String(token.text))
+            }
+            TokenKind::Ident => {
+                self.next();
+                match token.text.as_str() {
+                    "true" => ExprKind::Literal(Value::Bool(true)),
+                    "false" => ExprKind::Literal(Value::Bool(false)),
+                    "null" => ExprKind::Literal(Value::Null),
+                    _ => {
+                        let mut name = token.text;
+                        // Provider-defined functions: `provider::aws::arn_parse(...)`.
+                        while self.is_punct("::") {
+                            self.next();
+                            let part = self.next();
+                            if part.kind != TokenKind::Ident {
+                                return Err(error_at(self.src, part.start, "Expected a name after '::'"));
+                            }
+                            name = format!("{}::{}", name, part.text);
+                        }
+                        if self.is_punct("(") {
+                            self.call(name)?
+                        } else {
+                            ExprKind::Variable(name)
+                        }
+                    }
+                }
+            }
+            TokenKind::Punct if token.text == "(" => {
+                self.next();
+                self.nesting += 1;
+                self.expression()?;
+                self.expect(")")?;
+                self.nesting -= 1;
+                ExprKind::Other
+            }
+            TokenKind::Punct if token.text == "[" => {
+                self.next();
+                self.nesting += 1;
+                let kind = if self.is_for() { self.for_expression("]")? } else { self.tuple()? };
+                self.nesting -= 1;
+                kind
+            }
+            TokenKind::Punct if token.text == "{" => {
+                self.next();
+                self.nesting += 1;
+                let kind = if self.is_for() { self.for_expression("}")? } else { self.object()? };
+                self.nesting -= 1;
+                kind
+            }
+            _ => return Err(self.error("Expected an expression")),
+        };
+        Ok(self.finish(start, kind))
+    }
+
+    fn call(&mut self, name: String) -> Result<ExprKind, String> {
+        self.next();
+        self.nesting += 1;
+        let mut args = Vec::new();
+        while !self.is_punct(")") {
+            args.push(self.expression()?);
+            self.eat("...");
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(")")?;
+        self.nesting -= 1;
+        Ok(ExprKind::Call(name, args))
+    }
+
+    fn tuple(&mut self) -> Result<ExprKind, String> {
+        let mut items = Vec::new();
+        while !self.is_punct("]") {
+            items.push(self.expression()?);
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect("]")?;
+        Ok(ExprKind::Tuple(items))
+    }
+
+    fn object(&mut self) -> Result<ExprKind, String> {
+        let mut entries = Vec::new();
+        while !self.is_punct("}") {
+            let key = self.expression()?;
+            let key = match key.kind {
+                ExprKind::Variable(name) => name,
+                ExprKind::Literal(Value::String(text)) => text,
+                ExprKind::Literal(_) => key.text,
+                _ => format!("${{{}}}", key.text),
+            };
+            if !self.eat("=") {
+                self.expect(":")?;
+            }
+            entries.push((key, self.expression()?));
+            self.eat(",");
+        }
+        self.expect("}")?;
+        Ok(ExprKind::Object(entries))
+    }
+
+    fn is_for(&self) -> bool {
+        let token = self.peek();
+        token.kind == TokenKind::Ident && token.text == "for"
+    }
+
+    /// `[for x in xs : f(x) if c]` or `{for k, v in m : k => v... if c}`.
+    fn for_expression(&mut self, close: &str) -> Result<ExprKind, String> {
+        self.next();
+        self.variable_name()?;
+        if self.eat(",") {
+            self.variable_name()?;
+        }
+        self.keyword("in")?;
+        self.expression()?;
+        self.expect(":")?;
+        self.expression()?;
+        if close == "}" {
+            self.expect("=>")?;
+            self.expression()?;
+            self.eat("...");
+        }
+        if self.peek().kind == TokenKind::Ident && self.peek().text == "if" {
+            self.next();
+            self.expression()?;
+        }
+        self.expect(close)?;
+        Ok(ExprKind::Other)
+    }
+
+    fn variable_name(&mut self) -> Result<(), String> {
+        if self.peek().kind != TokenKind::Ident {
+            return Err(self.error("Expected a variable name"));
+        }
+        self.next();
+        Ok(())
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<(), String> {
+        if self.peek().kind != TokenKind::Ident || self.peek().text != keyword {
+            return Err(self.error(&format!("Expected '{}'", keyword)));
+        }
+        self.next();
+        Ok(())
+    }
+}
+
+/// The JSON number of a literal; `None` when it overflows to infinity.
+fn number(text: &str) -> Option<Value> {
+    match text.parse::<i64>() {
+        Ok(i) => Some(Value::Number(i.into())),
+        Err(_) => text.parse::<f64>().ok().and_then(Number::from_f64).map(Value::Number),
+    }
+}
+
+fn negative(n: &Number) -> Value {
+    match (n.as_i64(), n.as_f64()) {
+        (Some(i), _) => Value::Number((-i).into()),
+        (None, Some(f)) => Number::from_f64(-f).map_or(Value::Null, Value::Number),
+        _ => Value::Null,
+    }
+}
+
+/// Parses native HCL into its attributes and blocks.
+pub(crate) fn parse_body(text: &str) -> Result<Vec<Item>, String> {
+    let tokens = Lexer { src: text, pos: 0 }.tokens()?;
+    let mut parser = Parser { src: text, tokens, pos: 0, nesting: 0, last_end: 0 };
+    parser.body(false)
+}
+
+/// Parses native HCL into the JSON value of Terraform's JSON syntax.
+pub(crate) fn parse_hcl(text: &str) -> Result<Value, String> {
+    body_json(&parse_body(text)?, None)
+}
+
+/// Attributes whose expressions Terraform's JSON syntax takes as bare
+/// references or type expressions rather than `${...}` templates.
+fn is_raw_attribute(block: Option<&str>, name: &str) -> bool {
+    match (block, name) {
+        (None, _) => false,
+        (Some("variable"), "type") => true,
+        (Some("lifecycle"), "ignore_changes" | "replace_triggered_by") => true,
+        (Some("moved" | "removed" | "import"), "from" | "to") => true,
+        (Some(_), "depends_on" | "provider" | "providers") => true,
+        _ => false,
+    }
+}
+
+fn body_json(items: &[Item], block: Option<&str>) -> Result<Value, String> {
+    let mut map = Map::new();
+    let mut attributes = HashSet::new();
+    let mut blocks = HashSet::new();
+    for item in items {
+        match item {
+            Item::Attribute { name, expr, line } => {
+                if blocks.contains(name.as_str()) || !attributes.insert(name.as_str()) {
+                    return Err(format!("Duplicate attribute '{}' on line {}", name, line));
+                }
+                map.insert(name.clone(), expr_json(expr, is_raw_attribute(block, name)));
+            }
+            Item::Block { kind, labels, body, line } => {
+                if attributes.contains(kind.as_str()) {
+                    return Err(format!("Block '{}' on line {} has the name of an attribute", kind, line));
+                }
+                blocks.insert(kind.as_str());
+                let content = body_json(body, Some(kind))?;
+                let mut target = &mut map;
+                let mut key = kind;
+                for label in labels {
+                    target = match target.entry(key.clone()).or_insert_with(|| Value::Object(Map::new())) {
+                        Value::Object(inner) => inner,
+                        _ => return Err(format!("Block '{}' on line {} repeats labels of another block", kind, line)),
+                    };
+                    key = label;
+                }
+                match target.get_mut(key) {
+                    None => {
+                        target.insert(key.clone(), content);
+                    }
+                    Some(Value::Array(items)) => items.push(content),
+                    Some(existing) => *existing = Value::Array(vec![existing.take(), content]),
+                }
+            }
+        }
+    }
+    Ok(Value::Object(map))
+}
+
+fn expr_json(expr: &Expr, raw: bool) -> Value {
+    match &expr.kind {
+        ExprKind::Literal(value) => value.clone(),
+        ExprKind::Tuple(items) => Value::Array(items.iter().map(|item| expr_json(item, raw)).collect()),
+        ExprKind::Object(entries) => {
+            Value::Object(entries.iter().map(|(key, value)| (key.clone(), expr_json(value, raw))).collect())
+        }
+        _ if raw => Value::String(expr.text.clone()),
+        _ => Value::String(format!("${{{}}}", expr.text)),
+    }
+}
+
+/// How many labels a block keyword takes inside the given block, or `None`
+/// when the key is written as an attribute.
+fn block_labels(block: Option<&str>, key: &str) -> Option<usize> {
+    match (block, key) {
+        (None, "resource" | "data") => Some(2),
+        (None, "variable" | "output" | "module" | "provider" | "check") => Some(1),
+        (None, "terraform" | "locals" | "moved" | "import" | "removed") => Some(0),
+        (Some("terraform"), "backend") => Some(1),
+        (Some("terraform"), "required_providers" | "cloud") => Some(0),
+        (Some("cloud"), "workspaces") => Some(0),
+        (Some("check"), "data") => Some(2),
+        (Some("check"), "assert") => Some(0),
+        (Some("dynamic"), "content") => Some(0),
+        (Some(_), "provisioner" | "dynamic") => Some(1),
+        (Some(_), "lifecycle" | "connection" | "precondition" | "postcondition" | "validation") => Some(0),
+        _ => None,
+    }
+}
+
+fn has_block_shape(value: &Value, labels: usize) -> bool {
+    match value {
+        Value::Object(map) if labels > 0 => map.values().all(|inner| has_block_shape(inner, labels - 1)),
+        Value::Object(_) => true,
+        Value::Array(items) => labels == 0 && !items.is_empty() && items.iter().all(Value::is_object),
+        _ => false,
+    }
+}
+
+/// Whether a value in the body of `block` is written as nested blocks: a
+/// list of objects, or an object holding something other than strings, in
+/// a block whose arguments aren't free-form expressions.
+fn is_nested_block(block: Option<&str>, value: &Value) -> bool {
+    let expressions_only = matches!(
+        block,
+        None | Some("locals" | "variable" | "output" | "module" | "required_providers" | "moved" | "import" | "removed")
+    );
+    let is_body = |value: &Value| value.as_object().is_some_and(|map| map.values().any(|v| !v.is_string()));
+    !expressions_only
+        && match value {
+            Value::Array(items) => !items.is_empty() && items.iter().all(Value::is_object),
+            value => is_body(value),
+        }
+}
+
+/// Writes the JSON form of Terraform configuration (or a tfvars object) as
+/// native HCL.
+pub(crate) fn to_hcl_string(value: &Value) -> Result<String, String> {
+    let map = value.as_object().ok_or_else(|| {
+        format!("HCL documents hold an object of attributes and blocks, found {} at the top level", kind(value))
+    })?;
+    let mut out = String::new();
+    write_body(&mut out, map, None, "", 0)?;
+    Ok(out)
+}
+
+fn write_body(
+    out: &mut String,
+    map: &Map<String, Value>,
+    block: Option<&str>,
+    pointer: &str,
+    depth: usize,
+) -> Result<(), String> {
+    let mut attributes = Vec::new();
+    let mut blocks = Vec::new();
+    for (key, value) in map {
+        match block_labels(block, key) {
+            Some(labels) if has_block_shape(value, labels) => blocks.push((key, value, labels)),
+            None if is_identifier(key) && is_nested_block(block, value) => blocks.push((key, value, 0)),
+            _ => {
+                if !is_identifier(key) {
+                    return Err(format!(
+                        "'{}' is not a valid HCL attribute name (at {}/{})",
+                        key,
+                        pointer,
+                        escape(key)
+                    ));
+                }
+                attributes.push((key.clone(), expr_text(value, is_raw_attribute(block, key), depth)));
+            }
+        }
+    }
+    out.push_str(&aligned(attributes, depth));
+    for (key, value, labels) in blocks {
+        let pointer = format!("{}/{}", pointer, escape(key));
+        write_blocks(out, key, &mut Vec::new(), value, labels, &pointer, depth)?;
+    }
+    Ok(())
+}
+
+fn write_blocks(
+    out: &mut String,
+    kind: &str,
+    labels: &mut Vec<String>,
+    value: &Value,
+    remaining: usize,
+    pointer: &str,
+    depth: usize,
+) -> Result<(), String> {
+    match value {
+        Value::Object(map) if remaining > 0 => {
+            for (label, inner) in map {
+                labels.push(quote(label));
+                write_blocks(
+                    out,
+                    kind,
+                    labels,
+                    inner,
+                    remaining - 1,
+                    &format!("{}/{}", pointer, escape(label)),
+                    depth,
+                )?;
+                labels.pop();
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                write_blocks(out, kind, labels, item, 0, &format!("{}/{}", pointer, i), depth)?;
+            }
+        }
+        Value::Object(body) => {
+            if !out.is_empty() && !out.ends_with("{\n") {
+                out.push('\n');
+            }
+            let indent = "  ".repeat(depth);
+            let header: Vec<&str> = std::iter::once(kind).chain(labels.iter().map(String::as_str)).collect();
+            if body.is_empty() {
+                out.push_str(&format!("{}{} {{}}\n", indent, header.join(" ")));
+            } else {
+                out.push_str(&format!("{}{} {{\n", indent, header.join(" ")));
+                write_body(out, body, Some(kind), pointer, depth + 1)?;
+                out.push_str(&format!("{}}}\n", indent));
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// `key = value` lines with the `=` signs lined up, as `terraform fmt` does.
+fn aligned(entries: Vec<(String, String)>, depth: usize) -> String {
+    let width = entries.iter().map(|(key, _)| key.chars().count()).max().unwrap_or(0);
+    let indent = "  ".repeat(depth);
+    entries.into_iter().map(|(key, value)| format!("{}{:width$} = {}\n", indent, key, value, width = width)).collect()
+}
+
+fn expr_text(value: &Value, raw: bool, depth: usize) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) if raw => s.clone(),
+        Value::String(s) => match sole_interpolation(s) {
+            Some(expression) => expression.to_string(),
+            None if s.ends_with('\n') && s.trim_end_matches('\n').contains('\n') => heredoc(s),
+            None => quote(s),
+        },
+        Value::Array(items) if items.is_empty() => "[]".to_string(),
+        Value::Array(items) => {
+            let texts: Vec<String> = items.iter().map(|item| expr_text(item, raw, depth + 1)).collect();
+            let single_line = texts.iter().all(|text| !text.contains('\n'));
+            if single_line && texts.iter().map(|text| text.len() + 2).sum::<usize>() <= 80 {
+                format!("[{}]", texts.join(", "))
+            } else {
+                let indent = "  ".repeat(depth + 1);
+                let lines: String = texts.iter().map(|text| format!("{}{},\n", indent, text)).collect();
+                format!("[\n{}{}]", lines, "  ".repeat(depth))
+            }
+        }
+        Value::Object(map) if map.is_empty() => "{}".to_string(),
+        Value::Object(map) => {
+            let entries = map
+                .iter()
+                .map(|(key, item)| {
+                    let key = if is_identifier(key) { key.clone() } else { quote(key) };
+                    (key, expr_text(item, raw, depth + 1))
+                })
+                .collect();
+            format!("{{\n{}{}}}", aligned(entries, depth + 1), "  ".repeat(depth))
+        }
+    }
+}
+
+/// The expression of a string that is exactly one `${...}` sequence.
+fn sole_interpolation(text: &str) -> Option<&str> {
+    if !text.starts_with("${") || interpolation_end(text, 0)? != text.len() {
+        return None;
+    }
+    Some(text[2..text.len() - 1].trim())
+}
+
+/// A quoted template; `${...}` sequences are copied as they are, since the
+/// strings nested in them are already in HCL form.
+fn quote(text: &str) -> String {
+    let mut out = String::from("\"");
+    let mut pos = 0;
+    while pos < text.len() {
+        let rest = &text[pos..];
+        if rest.starts_with("$${") || rest.starts_with("%%{") {
+            out.push_str(&rest[..3]);
+            pos += 3;
+            continue;
+        }
+        if rest.starts_with("${") || rest.starts_with("%{") {
+            if let Some(end) = interpolation_end(text, pos) {
+                out.push_str(&text[pos..end]);
+                pos = end;
+                continue;
+            }
+        }
+        let c = rest.chars().next().unwrap_or_default();
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+        pos += c.len_utf8();
+    }
+    out.push('"');
+    out
+}
+
+fn heredoc(text: &str) -> String {
+    let mut marker = "EOT".to_string();
+    while text.lines().any(|line| line.trim() == marker) {
+        marker.push('_');
+    }
+    format!("<<{}\n{}{}", marker, text, marker)
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// A JSON Schema for the `variable` blocks of a configuration: one property
+/// per variable, typed from its type constraint or else its default, and
+/// required when it has no default.
+pub(crate) fn variables_schema(text: &str) -> Result<Value, String> {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for item in parse_body(text)? {
+        let Item::Block { kind, labels, body, line } = item else { continue };
+        if kind != "variable" {
+            continue;
+        }
+        let [name] = labels.as_slice() else {
+            return Err(format!("The variable block on line {} needs exactly one label", line));
+        };
+        let attribute = |key: &str| {
+            body.iter().find_map(|item| match item {
+                Item::Attribute { name, expr, .. } if name == key => Some(expr),
+                _ => None,
+            })
+        };
+        let default = attribute("default");
+        let mut schema = match (attribute("type"), default) {
+            (Some(constraint), _) => type_schema(constraint).map_err(|e| format!("{} in variable '{}'", e, name))?,
+            (None, Some(default)) => infer_schema(&[&expr_json(default, false)]),
+            (None, None) => json!({}),
+        };
+        if let (Some(Value::String(description)), Some(schema)) =
+            (attribute("description").map(|expr| expr_json(expr, false)), schema.as_object_mut())
+        {
+            schema.insert("description".to_string(), Value::String(description));
+        }
+        if default.is_none() {
+            required.push(Value::String(name.clone()));
+        }
+        properties.insert(name.clone(), schema);
+    }
+    if properties.is_empty() {
+        return Err("No variable blocks found".to_string());
+    }
+    Ok(json!({ "type": "object", "properties": properties, "required": required }))
+}
+
+/// The JSON Schema of a Terraform type constraint such as
+/// `list(object({ name = string, port = optional(number) }))`.
+fn type_schema(expr: &Expr) -> Result<Value, String> {
+    let unsupported = || Err(format!("Unsupported type constraint '{}'", expr.text));
+    match &expr.kind {
+        ExprKind::Variable(name) => match name.as_str() {
+            "string" => Ok(json!({ "type": "string" })),
+            "number" => Ok(json!({ "type": "number" })),
+            "bool" => Ok(json!({ "type": "boolean" })),
+            "any" => Ok(json!({})),
+            _ => unsupported(),
+        },
+        ExprKind::Call(name, args) => match (name.as_str(), args.as_slice()) {
+            ("list", [item]) => Ok(json!({ "type": "array", "items": type_schema(item)? })),
+            ("set", [item]) => Ok(json!({ "type": "array", "items": type_schema(item)?, "uniqueItems": true })),
+            ("map", [item]) => Ok(json!({ "type": "object", "additionalProperties": type_schema(item)? })),
+            ("tuple", [Expr { kind: ExprKind::Tuple(items), .. }]) => {
+                let schemas = items.iter().map(type_schema).collect::<Result<Vec<_>, _>>()?;
+                // Mixed tuples have no single item type in the generated code.
+                let items = match schemas.first() {
+                    Some(first) if schemas.iter().all(|schema| schema == first) => first.clone(),
+                    _ => json!({}),
+                };
+                Ok(json!({ "type": "array", "items": items }))
+            }
+            ("object", [Expr { kind: ExprKind::Object(fields), .. }]) => {
+                let mut properties = Map::new();
+                let mut required = Vec::new();
+                for (key, field) in fields {
+                    let schema = match &field.kind {
+                        ExprKind::Call(name, args) if name == "optional" && !args.is_empty() => type_schema(&args[0])?,
+                        _ => {
+                            required.push(Value::String(key.clone()));
+                            type_schema(field)?
+                        }
+                    };
+                    properties.insert(key.clone(), schema);
+                }
+                Ok(json!({ "type": "object", "properties": properties, "required": required }))
+            }
+            _ => unsupported(),
+        },
+        _ => unsupported(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+resource "aws_security_group" "web" {
+  name = "web"
+  tags = {
+    Name = "web"
+  }
+
+  ingress {
+    from_port   = 80
+    cidr_blocks = ["0.0.0.0/0"]
+  }
+  ingress {
+    from_port   = 443
+    cidr_blocks = [var.cidr]
+  }
+
+  lifecycle {
+    create_before_destroy = true
+  }
+}
+
+resource "aws_instance" "web" {
+  ami = data.aws_ami.ubuntu.id
+  root_block_device {
+    volume_size = 50
+  }
+}
+
+module "vpc" {
+  source  = "./vpc"
+  subnets = [{ cidr = "10.0.0.0/24" }]
+}
+"#;
+
+    #[test]
+    fn reads_terraform_json_syntax() {
+        let value = parse_hcl(CONFIG).unwrap();
+        let group = &value["resource"]["aws_security_group"]["web"];
+        assert_eq!(group["ingress"][1], json!({"from_port": 443, "cidr_blocks": ["${var.cidr}"]}));
+        assert_eq!(group["tags"], json!({"Name": "web"}));
+        assert_eq!(value["resource"]["aws_instance"]["web"]["ami"], "${data.aws_ami.ubuntu.id}");
+        assert_eq!(value["module"]["vpc"]["subnets"], json!([{"cidr": "10.0.0.0/24"}]));
+    }
+
+    #[test]
+    fn nested_blocks_round_trip() {
+        let value = parse_hcl(CONFIG).unwrap();
+        let written = to_hcl_string(&value).unwrap();
+        assert_eq!(written.matches("  ingress {\n").count(), 2, "{}", written);
+        assert!(written.contains("  root_block_device {\n"), "{}", written);
+        assert!(written.contains("  tags = {\n"), "{}", written);
+        assert!(written.contains("  subnets = [\n"), "{}", written);
+        assert_eq!(parse_hcl(&written).unwrap(), value);
+    }
+
+    #[test]
+    fn out_of_range_numbers_are_errors() {
+        let error = parse_hcl("x = 1e999\n").unwrap_err();
+        assert_eq!(error, "Invalid HCL on line 1, column 5: Number 1e999 is too large for JSON");
+        assert!(parse_hcl("x = -1e999\n").is_err());
+        assert_eq!(parse_hcl("x = -1.5e3\n").unwrap(), json!({"x": -1500.0}));
+    }
+
+    #[test]
+    fn duplicate_attributes_are_errors() {
+        assert_eq!(parse_hcl("a = 1\na = 2\n").unwrap_err(), "Duplicate attribute 'a' on line 2");
+    }
+
+    #[test]
+    fn writer_needs_valid_names() {
+        assert!(to_hcl_string(&json!({"not valid": 1})).is_err());
+        assert!(to_hcl_string(&json!([1])).is_err());
+        let written = to_hcl_string(&json!({"region": "eu-west-1", "count": 2})).unwrap();
+        assert_eq!(written, "count  = 2\nregion = \"eu-west-1\"\n");
+    }
+}
@@ -0,0 +1,21 @@
+use wasm_bindgen::prelude::*;
+
+use crate::hcl_format::parse_hcl;
+use crate::visualization::{convert_to_d3_format, json_graph};
+
+/// Processes HCL (Terraform) into the node/link graph format of `process_json`.
+/// Blocks nest by type and labels, so resources show as resource → type → name
+/// and modules as module → name.
+#[wasm_bindgen]
+pub fn process_hcl_graph(hcl_str: &str) -> Result<String, JsValue> {
+    let value = parse_hcl(hcl_str).map_err(|e| JsValue::from_str(&e))?;
+    serde_json::to_string(&json_graph(&value)).map_err(|e| JsValue::from_str(&format!("Error building JSON: {}", e)))
+}
+
+/// Processes HCL (Terraform) into the tree format of `process_json_tree`.
+#[wasm_bindgen]
+pub fn process_hcl_tree(hcl_str: &str) -> Result<String, JsValue> {
+    let value = parse_hcl(hcl_str).map_err(|e| JsValue::from_str(&e))?;
+    serde_json::to_string(&convert_to_d3_format(&value, "root"))
+        .map_err(|e| JsValue::from_str(&format!("Error building JSON: {}", e)))
+}
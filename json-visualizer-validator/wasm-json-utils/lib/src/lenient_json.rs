@@ -0,0 +1,638 @@
+use serde_json::{Map, Number, Value};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+use crate::schema::parse_ndjson;
+use crate::validator::escape;
+
+// Lenient JSON reading.
+//
+// JSONC (JSON with comments, as in tsconfig.json and VS Code settings) adds
+// `//` and `/* */` comments and trailing commas to JSON. JSON5 also allows
+// unquoted keys, single-quoted strings, hexadecimal numbers and the rest of
+// ECMAScript 5's literal syntax. Both read into `serde_json::Value`; the
+// comments are kept against the JSON Pointer of the member they sit beside
+// so YAML output can write them back. NDJSON (JSON Lines) reads as an array
+// with one element per line.
+
+/// The flavour of JSON an input is written in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum JsonSyntax {
+    Json,
+    Jsonc,
+    Json5,
+    Ndjson,
+}
+
+impl JsonSyntax {
+    pub(crate) fn parse(name: Option<&str>) -> Result<JsonSyntax, String> {
+        match name.unwrap_or_default().trim().to_ascii_lowercase().as_str() {
+            "" | "json" => Ok(JsonSyntax::Json),
+            "jsonc" => Ok(JsonSyntax::Jsonc),
+            "json5" => Ok(JsonSyntax::Json5),
+            "ndjson" | "jsonl" | "jsonlines" => Ok(JsonSyntax::Ndjson),
+            other => Err(format!("Unknown JSON syntax '{}' (expected json, jsonc, json5 or ndjson)", other)),
+        }
+    }
+}
+
+/// Comments written around one member of a document.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct NodeComments {
+    /// Lines on their own before the member.
+    pub before: Vec<String>,
+    /// Comments on the line where the member ends.
+    pub after: Vec<String>,
+    /// For objects and arrays, lines after the last member.
+    pub end: Vec<String>,
+}
+
+/// Comments by the JSON Pointer of the member they belong to.
+pub(crate) type Comments = BTreeMap<String, NodeComments>;
+
+/// Parses `text` written in `syntax`. Plain JSON goes through serde_json.
+pub(crate) fn parse_json_text(text: &str, syntax: JsonSyntax) -> Result<Value, String> {
+    match syntax {
+        JsonSyntax::Json => serde_json::from_str(text).map_err(|e| {
+            // What several JSON documents, one per line, fail with.
+            if e.to_string().starts_with("trailing characters") {
+                format!("{} (for one JSON document per line, use the \"ndjson\" syntax)", e)
+            } else {
+                e.to_string()
+            }
+        }),
+        JsonSyntax::Ndjson => parse_ndjson(text).map(Value::Array),
+        _ => parse_with_comments(text, syntax).map(|(value, _)| value),
+    }
+}
+
+/// Writes an array as NDJSON, one compact element per line. Anything else
+/// is written as a single line.
+pub(crate) fn to_ndjson(value: &Value) -> Result<String, String> {
+    let records = match value {
+        Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    };
+    let mut out = String::new();
+    for record in records {
+        out.push_str(&serde_json::to_string(record).map_err(|e| e.to_string())?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Parses JSONC or JSON5 text, also returning its comments.
+pub(crate) fn parse_with_comments(text: &str, syntax: JsonSyntax) -> Result<(Value, Comments), String> {
+    let mut parser = Parser {
+        syntax,
+        chars: text.chars().collect(),
+        pos: 0,
+        line: 1,
+        column: 1,
+        comments: Comments::new(),
+        pending: Vec::new(),
+        last: None,
+    };
+    parser.skip_trivia()?;
+    parser.begin("");
+    let value = parser.value("")?;
+    parser.end("");
+    parser.skip_trivia()?;
+    if parser.peek().is_some() {
+        return Err(parser.error("trailing characters"));
+    }
+    parser.close("");
+    Ok((value, parser.comments))
+}
+
+struct Parser {
+    syntax: JsonSyntax,
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+    comments: Comments,
+    /// Comments read since the last member began.
+    pending: Vec<String>,
+    /// The member that last ended and its line, which takes comments
+    /// written after it on that line.
+    last: Option<(String, usize)>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("{} at line {} column {}", message, self.line, self.column)
+    }
+
+    fn unexpected(&self) -> String {
+        match self.peek() {
+            Some(c) => self.error(&format!("unexpected character '{}'", c)),
+            None => self.error("unexpected end of input"),
+        }
+    }
+
+    fn json5(&self) -> bool {
+        self.syntax == JsonSyntax::Json5
+    }
+
+    /// Skips whitespace and comments, collecting the comments.
+    fn skip_trivia(&mut self) -> Result<(), String> {
+        loop {
+            match (self.peek(), self.peek_at(1)) {
+                (Some(c), _) if self.is_space(c) => {
+                    self.bump();
+                }
+                (Some('/'), Some('/')) => {
+                    let line = self.line;
+                    self.pos += 2;
+                    let mut text = String::new();
+                    while let Some(c) = self.peek().filter(|c| *c != '\n' && *c != '\r') {
+                        text.push(c);
+                        self.bump();
+                    }
+                    self.comment(line, &text);
+                }
+                (Some('/'), Some('*')) => {
+                    let line = self.line;
+                    self.pos += 2;
+                    let mut text = String::new();
+                    loop {
+                        match self.bump() {
+                            None => return Err(self.error("unterminated comment")),
+                            Some('*') if self.peek() == Some('/') => {
+                                self.bump();
+                                break;
+                            }
+                            Some(c) => text.push(c),
+                        }
+                    }
+                    self.comment(line, &text);
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn is_space(&self, c: char) -> bool {
+        match self.syntax {
+            JsonSyntax::Json5 => c.is_whitespace() || c == '\u{feff}',
+            _ => matches!(c, ' ' | '\t' | '\n' | '\r' | '\u{feff}'),
+        }
+    }
+
+    /// Files a comment that started on `line`: beside the member that just
+    /// ended on that line, or before the next one.
+    fn comment(&mut self, line: usize, text: &str) {
+        let lines = text
+            .lines()
+            .map(|line| line.trim())
+            .map(|line| line.strip_prefix('*').map_or(line, str::trim_start))
+            .filter(|line| !line.is_empty())
+            .map(str::to_string);
+        match &self.last {
+            Some((pointer, end)) if *end == line => {
+                self.comments.entry(pointer.clone()).or_default().after.extend(lines)
+            }
+            _ => self.pending.extend(lines),
+        }
+    }
+
+    fn begin(&mut self, pointer: &str) {
+        if !self.pending.is_empty() {
+            let before = std::mem::take(&mut self.pending);
+            self.comments.entry(pointer.to_string()).or_default().before.extend(before);
+        }
+        self.last = None;
+    }
+
+    fn end(&mut self, pointer: &str) {
+        self.last = Some((pointer.to_string(), self.line));
+    }
+
+    fn close(&mut self, pointer: &str) {
+        if !self.pending.is_empty() {
+            let end = std::mem::take(&mut self.pending);
+            self.comments.entry(pointer.to_string()).or_default().end.extend(end);
+        }
+        self.last = None;
+    }
+
+    fn value(&mut self, pointer: &str) -> Result<Value, String> {
+        match self.peek() {
+            Some('{') => self.object(pointer),
+            Some('[') => self.array(pointer),
+            Some('"') => self.string('"').map(Value::String),
+            Some('\'') if self.json5() => self.string('\'').map(Value::String),
+            Some(c) if c.is_ascii_digit() || c == '-' || (self.json5() && (c == '+' || c == '.')) => self.number(),
+            Some(c) if c.is_alphabetic() => {
+                let at = format!("at line {} column {}", self.line, self.column);
+                let word = self.identifier();
+                match word.as_str() {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    "null" => Ok(Value::Null),
+                    "Infinity" | "NaN" if self.json5() => Err(format!("Infinity and NaN cannot be represented in JSON {}", at)),
+                    _ => Err(format!("unexpected word '{}' {}", word, at)),
+                }
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn object(&mut self, pointer: &str) -> Result<Value, String> {
+        self.bump();
+        let mut map = Map::new();
+        loop {
+            self.skip_trivia()?;
+            if self.peek() == Some('}') {
+                break;
+            }
+            let key = self.key()?;
+            let member = format!("{}/{}", pointer, escape(&key));
+            self.begin(&member);
+            self.skip_trivia()?;
+            if self.peek() != Some(':') {
+                return Err(self.error("expected ':' after property name"));
+            }
+            self.bump();
+            self.skip_trivia()?;
+            let value = self.value(&member)?;
+            self.end(&member);
+            map.insert(key, value);
+            self.skip_trivia()?;
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                }
+                Some('}') => break,
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+        self.close(pointer);
+        self.bump();
+        Ok(Value::Object(map))
+    }
+
+    fn array(&mut self, pointer: &str) -> Result<Value, String> {
+        self.bump();
+        let mut items = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            if self.peek() == Some(']') {
+                break;
+            }
+            let member = format!("{}/{}", pointer, items.len());
+            self.begin(&member);
+            items.push(self.value(&member)?);
+            self.end(&member);
+            self.skip_trivia()?;
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                }
+                Some(']') => break,
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+        self.close(pointer);
+        self.bump();
+        Ok(Value::Array(items))
+    }
+
+    fn key(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some('"') => self.string('"'),
+            Some('\'') if self.json5() => self.string('\''),
+            Some(c) if self.json5() && (c.is_alphabetic() || c == '$' || c == '_') => Ok(self.identifier()),
+            _ => Err(self.error("expected a property name")),
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(c) = self.peek().filter(|c| c.is_alphanumeric() || matches!(c, '$' | '_' | '\u{200c}' | '\u{200d}')) {
+            name.push(c);
+            self.bump();
+        }
+        name
+    }
+
+    fn string(&mut self, quote: char) -> Result<String, String> {
+        self.bump();
+        let mut text = String::new();
+        loop {
+            match self.bump() {
+                None => return Err(self.error("unterminated string")),
+                Some(c) if c == quote => return Ok(text),
+                Some('\\') => self.escape_sequence(&mut text)?,
+                Some(c) if c < ' ' => return Err(self.error("control character in string")),
+                Some(c) => text.push(c),
+            }
+        }
+    }
+
+    fn escape_sequence(&mut self, text: &mut String) -> Result<(), String> {
+        let c = self.bump().ok_or_else(|| self.error("unterminated string"))?;
+        let unescaped = match c {
+            '"' | '\\' | '/' => c,
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => return self.unicode_escape(text),
+            _ if !self.json5() => return Err(self.error(&format!("invalid escape '\\{}'", c))),
+            'v' => '\u{b}',
+            '0' if !self.peek().is_some_and(|c| c.is_ascii_digit()) => '\0',
+            'x' => {
+                let code = self.hex_digits(2)?;
+                char::from_u32(code).ok_or_else(|| self.error("invalid escape"))?
+            }
+            // A backslash before a line break continues the string.
+            '\r' => {
+                if self.peek() == Some('\n') {
+                    self.bump();
+                }
+                return Ok(());
+            }
+            '\n' | '\u{2028}' | '\u{2029}' => return Ok(()),
+            c if c.is_ascii_digit() => return Err(self.error(&format!("invalid escape '\\{}'", c))),
+            c => c,
+        };
+        text.push(unescaped);
+        Ok(())
+    }
+
+    fn unicode_escape(&mut self, text: &mut String) -> Result<(), String> {
+        let code = self.hex_digits(4)?;
+        let code = if (0xD800..0xDC00).contains(&code) {
+            if self.peek() != Some('\\') || self.peek_at(1) != Some('u') {
+                return Err(self.error("unpaired surrogate in unicode escape"));
+            }
+            self.pos += 2;
+            self.column += 2;
+            let low = self.hex_digits(4)?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("unpaired surrogate in unicode escape"));
+            }
+            0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            code
+        };
+        let c = char::from_u32(code).ok_or_else(|| self.error("unpaired surrogate in unicode escape"))?;
+        text.push(c);
+        Ok(())
+    }
+
+    fn hex_digits(&mut self, count: usize) -> Result<u32, String> {
+        let mut code = 0;
+        for _ in 0..count {
+            let digit = self.peek().and_then(|c| c.to_digit(16)).ok_or_else(|| self.error("invalid hexadecimal escape"))?;
+            self.bump();
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let (line, column) = (self.line, self.column);
+        let mut text = String::new();
+        while let Some(c) = self.peek().filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '+' | '-')) {
+            text.push(c);
+            self.bump();
+        }
+        let invalid = || format!("invalid number '{}' at line {} column {}", text, line, column);
+
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text.strip_prefix('+').filter(|_| self.json5()).unwrap_or(&text)),
+        };
+        if self.json5() && (digits == "Infinity" || digits == "NaN") {
+            return Err(format!("Infinity and NaN cannot be represented in JSON at line {} column {}", line, column));
+        }
+        let hex = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X"));
+        let number = match hex.filter(|_| self.json5()) {
+            Some(hex) => {
+                let magnitude = u64::from_str_radix(hex, 16).map_err(|_| invalid())?;
+                if negative {
+                    let value = i64::try_from(magnitude).map_err(|_| invalid())?;
+                    Number::from(-value)
+                } else {
+                    Number::from(magnitude)
+                }
+            }
+            None => {
+                let mut normal = if negative { "-".to_string() } else { String::new() };
+                if self.json5() {
+                    // JSON5 allows `.5` and `5.`, which JSON spells `0.5` and `5.0`.
+                    if digits.starts_with('.') {
+                        normal.push('0');
+                    }
+                    normal.push_str(&digits.replace(".e", ".0e").replace(".E", ".0E"));
+                    if normal.ends_with('.') {
+                        normal.push('0');
+                    }
+                } else {
+                    normal.push_str(digits);
+                }
+                normal.parse::<Number>().map_err(|_| invalid())?
+            }
+        };
+        Ok(Value::Number(number))
+    }
+}
+
+/// Writes `value` as YAML with `comments` as `#` comments beside the members
+/// they were attached to.
+pub(crate) fn to_commented_yaml(value: &Value, comments: &Comments) -> Result<String, String> {
+    let mut writer = YamlWriter { out: String::new(), comments };
+    let root = comments.get("");
+    if let Some(root) = root {
+        writer.comment_lines(&root.before, 0);
+        writer.comment_lines(&root.after, 0);
+    }
+    if is_block(value) {
+        writer.children(value, "", 0)?;
+    } else {
+        writer.out.push_str(&scalar(value)?);
+        writer.out.push('\n');
+        if let Some(root) = root {
+            writer.comment_lines(&root.end, 0);
+        }
+    }
+    Ok(writer.out)
+}
+
+struct YamlWriter<'a> {
+    out: String,
+    comments: &'a Comments,
+}
+
+impl YamlWriter<'_> {
+    fn comment_lines(&mut self, lines: &[String], indent: usize) {
+        for line in lines {
+            self.out.push_str(&format!("{}# {}\n", " ".repeat(indent), line));
+        }
+    }
+
+    /// Writes the members of an object or array at `indent`.
+    fn children(&mut self, value: &Value, pointer: &str, indent: usize) -> Result<(), String> {
+        let pad = " ".repeat(indent);
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map {
+                    let head = format!("{}{}:", pad, scalar(&Value::String(key.clone()))?);
+                    let child_pointer = format!("{}/{}", pointer, escape(key));
+                    // Sequences under a key are not indented, as serde_yaml writes them.
+                    let child_indent = if child.is_array() { indent } else { indent + 2 };
+                    self.entry(&head, child, &child_pointer, indent, child_indent)?;
+                }
+            }
+            Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    self.entry(&format!("{}-", pad), item, &format!("{}/{}", pointer, i), indent, indent + 2)?;
+                }
+            }
+            _ => {}
+        }
+        if let Some(notes) = self.comments.get(pointer) {
+            self.comment_lines(&notes.end, indent);
+        }
+        Ok(())
+    }
+
+    fn entry(&mut self, head: &str, value: &Value, pointer: &str, indent: usize, child_indent: usize) -> Result<(), String> {
+        let notes = self.comments.get(pointer);
+        if let Some(notes) = notes {
+            self.comment_lines(&notes.before, indent);
+        }
+        let inline = match notes {
+            Some(notes) if !notes.after.is_empty() => format!("  # {}", notes.after.join(" ")),
+            _ => String::new(),
+        };
+        if !is_block(value) {
+            self.out.push_str(&format!("{} {}{}\n", head, scalar(value)?, inline));
+        } else if head.trim_start() == "-" && inline.is_empty() {
+            // Start the item's first member on the dash's line: `- key: value`.
+            let start = self.out.len();
+            self.children(value, pointer, child_indent)?;
+            self.out.replace_range(start..start + child_indent, &format!("{}- ", " ".repeat(indent)));
+        } else {
+            self.out.push_str(&format!("{}{}\n", head, inline));
+            self.children(value, pointer, child_indent)?;
+        }
+        Ok(())
+    }
+}
+
+/// Objects and arrays with members, which YAML writes as blocks.
+fn is_block(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => !map.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        _ => false,
+    }
+}
+
+/// A value that fits on one YAML line.
+fn scalar(value: &Value) -> Result<String, String> {
+    match value {
+        Value::Object(_) => Ok("{}".to_string()),
+        Value::Array(_) => Ok("[]".to_string()),
+        // JSON's quoted form is also a YAML double-quoted string, and keeps
+        // multi-line text on one line.
+        Value::String(s) if s.contains(['\n', '\r']) => serde_json::to_string(s).map_err(|e| e.to_string()),
+        _ => serde_yaml::to_string(value).map(|yaml| yaml.trim_end().to_string()).map_err(|e| e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SETTINGS: &str = r#"// Settings
+{
+  /* indent */
+  "tabSize": 2, // spaces
+  "files": [
+    "a", // first
+    "b",
+  ],
+  // trailing
+}
+"#;
+
+    #[test]
+    fn jsonc_allows_comments_and_trailing_commas() {
+        let (value, comments) = parse_with_comments(SETTINGS, JsonSyntax::Jsonc).unwrap();
+        assert_eq!(value, json!({"tabSize": 2, "files": ["a", "b"]}));
+        let root = NodeComments { before: vec!["Settings".into()], after: vec![], end: vec!["trailing".into()] };
+        assert_eq!(comments[""], root);
+        let tab_size = NodeComments { before: vec!["indent".into()], after: vec!["spaces".into()], end: vec![] };
+        assert_eq!(comments["/tabSize"], tab_size);
+        assert_eq!(comments["/files/0"].after, ["first"]);
+        // JSON5 syntax stays out of JSONC.
+        let unquoted = parse_json_text("{a: 1}", JsonSyntax::Jsonc).unwrap_err();
+        assert_eq!(unquoted, "expected a property name at line 1 column 2");
+        let unterminated = parse_json_text("{\"a\": 1 /* x", JsonSyntax::Jsonc).unwrap_err();
+        assert_eq!(unterminated, "unterminated comment at line 1 column 11");
+    }
+
+    #[test]
+    fn json5_reads_ecmascript_literals() {
+        let text = "{unquoted: 'single', hex: 0xFF, lead: .5, trail: 5., plus: +1, multi: 'a\\\nb', list: [1,],}";
+        assert_eq!(
+            parse_json_text(text, JsonSyntax::Json5).unwrap(),
+            json!({"unquoted": "single", "hex": 255, "lead": 0.5, "trail": 5.0, "plus": 1, "multi": "ab", "list": [1]})
+        );
+        let error = parse_json_text("{n: Infinity}", JsonSyntax::Json5).unwrap_err();
+        assert!(error.starts_with("Infinity and NaN cannot be represented in JSON"), "{}", error);
+    }
+
+    #[test]
+    fn comments_are_written_back_as_yaml_comments() {
+        let (value, comments) = parse_with_comments(SETTINGS, JsonSyntax::Jsonc).unwrap();
+        assert_eq!(
+            to_commented_yaml(&value, &comments).unwrap(),
+            "# Settings\nfiles:\n- a  # first\n- b\n# indent\ntabSize: 2  # spaces\n# trailing\n"
+        );
+    }
+
+    #[test]
+    fn ndjson_reads_one_record_per_line() {
+        let records = parse_json_text("{\"a\":1}\n\n  \n{\"a\":2}\n", JsonSyntax::Ndjson).unwrap();
+        assert_eq!(records, json!([{"a": 1}, {"a": 2}]));
+        assert_eq!(to_ndjson(&records).unwrap(), "{\"a\":1}\n{\"a\":2}\n");
+        assert_eq!(JsonSyntax::parse(Some("JSONL")).unwrap(), JsonSyntax::Ndjson);
+    }
+
+    #[test]
+    fn ndjson_errors_name_the_line() {
+        // Blank lines still count towards the line number.
+        let error = parse_json_text("{\"a\":1}\n\n{\"a\":2}\n{bad}\n", JsonSyntax::Ndjson).unwrap_err();
+        assert!(error.starts_with("Invalid JSON on line 4: "), "{}", error);
+        let error = parse_json_text("{\"a\":1}\n{\"a\":2}\n", JsonSyntax::Json).unwrap_err();
+        assert!(error.ends_with("(for one JSON document per line, use the \"ndjson\" syntax)"), "{}", error);
+    }
+}
@@ -4,28 +4,75 @@ use serde_yaml;
 use quick_xml::se::to_string as to_xml;
 use csv:: Writer;
 use std::collections::HashMap;
-use std::collections::HashSet;
 mod types;
 mod visualization;
 mod yaml_visualization;
 mod xml_visualization;
 mod csv_visualization;
+mod schema_types;
+mod naming;
+mod formats;
+mod schema;
+mod dialect;
+mod sample;
+mod pattern;
+mod bundle;
+mod validator;
+mod schema_graph;
+mod compat;
+mod openapi;
+mod toml_format;
+mod toml_visualization;
+mod lenient_json;
+mod binary_formats;
+mod extended_json;
+mod bson_format;
+mod protobuf;
+mod avro;
+mod config_formats;
+mod config_visualization;
+mod hcl_format;
+mod hcl_visualization;
+mod tables;
+
+use toml_format::{parse_toml, to_toml_string};
+use hcl_format::{parse_hcl, to_hcl_string};
+use tables::{object_columns, parse_html_table, to_html_table, to_markdown_table};
+use config_formats::{
+    parse_env, parse_ini, parse_properties, to_env_string, to_ini_string, to_properties_string,
+};
+use lenient_json::{parse_json_text, parse_with_comments, to_commented_yaml, to_ndjson, Comments, JsonSyntax};
 
 // export to js
+// `syntax` is "json" (the default), "jsonc", "json5" or "ndjson"; comments
+// in JSONC and JSON5 input are carried over to the YAML.
 #[wasm_bindgen]
-pub fn json_to_yaml(json_str: &str) -> Result<String, JsValue> {
-    let json_value: Value = serde_json::from_str(json_str).map_err(|e| JsValue::from_str(&e.to_string()))?;
+pub fn json_to_yaml(json_str: &str, syntax: Option<String>) -> Result<String, JsValue> {
+    let (json_value, comments) = parse_json_keeping_comments(json_str, syntax.as_deref())?;
+    if !comments.is_empty() {
+        return to_commented_yaml(&json_value, &comments).map_err(|e| JsValue::from_str(&e));
+    }
     let yaml_string = serde_yaml::to_string(&json_value).map_err(|e| JsValue::from_str(&e.to_string()))?;
     Ok(yaml_string)
 }
 
+// Parses JSON in any syntax, with the comments of JSONC and JSON5 input
+fn parse_json_keeping_comments(json_str: &str, syntax: Option<&str>) -> Result<(Value, Comments), JsValue> {
+    let syntax = JsonSyntax::parse(syntax).map_err(|e| JsValue::from_str(&e))?;
+    match syntax {
+        JsonSyntax::Jsonc | JsonSyntax::Json5 => parse_with_comments(json_str, syntax).map_err(|e| JsValue::from_str(&e)),
+        _ => Ok((parse_json_text(json_str, syntax).map_err(|e| JsValue::from_str(&e))?, Comments::new())),
+    }
+}
+
 
 // json to xml
 
 #[wasm_bindgen]
-pub fn json_to_xml(json_str: &str) -> Result<String, JsValue> {
+pub fn json_to_xml(json_str: &str, syntax: Option<String>) -> Result<String, JsValue> {
     // Parse the JSON string
-    let json_value: Value = serde_json::from_str(json_str)
+    let syntax = JsonSyntax::parse(syntax.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    let json_value: Value = parse_json_text(json_str, syntax)
         .map_err(|e| JsValue::from_str(&format!("JSON parsing error: {}", e)))?;
     
     // Start building the XML with proper declaration and root element
@@ -165,9 +212,10 @@ fn escape_xml_tag(tag: &str) -> String {
 // Json to CSV 
 
 #[wasm_bindgen]
-pub fn json_to_csv(json_str: &str) -> Result<String, JsValue> {
+pub fn json_to_csv(json_str: &str, syntax: Option<String>) -> Result<String, JsValue> {
     // Parse the JSON string
-    let json_value: Value = serde_json::from_str(json_str)
+    let syntax = JsonSyntax::parse(syntax.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    let json_value: Value = parse_json_text(json_str, syntax)
         .map_err(|e| JsValue::from_str(&format!("JSON parsing error: {}", e)))?;
     
     // CSV conversion only makes sense for arrays of objects
@@ -210,26 +258,14 @@ pub fn json_to_csv(json_str: &str) -> Result<String, JsValue> {
 }
 
 fn convert_array_of_objects_to_csv(arr: &Vec<Value>) -> Result<String, JsValue> {
-    // Collect all possible field names across all objects
-    let mut all_fields = HashSet::new();
-    
-    for item in arr {
-        if let Value::Object(obj) = item {
-            for key in obj.keys() {
-                all_fields.insert(key.clone());
-            }
-        }
-    }
+    // Collect all possible field names across all objects, sorted for consistent output
+    let field_list = object_columns(arr);
     
     // No fields found - return error
-    if all_fields.is_empty() {
+    if field_list.is_empty() {
         return Err(JsValue::from_str("No object fields found in array"));
     }
     
-    // Sort fields for consistent output
-    let mut field_list: Vec<String> = all_fields.into_iter().collect();
-    field_list.sort();
-    
     // Create CSV content
     let mut csv = String::new();
     
@@ -263,6 +299,51 @@ fn convert_array_of_objects_to_csv(arr: &Vec<Value>) -> Result<String, JsValue>
     Ok(csv)
 }
 
+// JSON to Markdown table conversion, laid out as for CSV
+#[wasm_bindgen]
+pub fn json_to_markdown_table(json_str: &str, syntax: Option<String>) -> Result<String, JsValue> {
+    let syntax = JsonSyntax::parse(syntax.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    let json_value = parse_json_text(json_str, syntax).map_err(|e| JsValue::from_str(&e))?;
+    Ok(to_markdown_table(&json_value))
+}
+
+// JSON to HTML table conversion, laid out as for CSV
+#[wasm_bindgen]
+pub fn json_to_html_table(json_str: &str, syntax: Option<String>) -> Result<String, JsValue> {
+    let syntax = JsonSyntax::parse(syntax.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    let json_value = parse_json_text(json_str, syntax).map_err(|e| JsValue::from_str(&e))?;
+    Ok(to_html_table(&json_value))
+}
+
+// Reads one <table> of an HTML page into an array of objects; `table` picks
+// it by position among the page's tables (default 0)
+fn html_table_value(html_str: &str, table: Option<u32>) -> Result<Value, JsValue> {
+    parse_html_table(html_str, table.unwrap_or(0) as usize).map_err(|e| JsValue::from_str(&e))
+}
+
+// HTML table to JSON conversion
+#[wasm_bindgen]
+pub fn html_table_to_json(html_str: &str, table: Option<u32>) -> Result<String, JsValue> {
+    let value = html_table_value(html_str, table)?;
+    serde_json::to_string_pretty(&value).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+// HTML table to YAML conversion
+#[wasm_bindgen]
+pub fn html_table_to_yaml(html_str: &str, table: Option<u32>) -> Result<String, JsValue> {
+    let value = html_table_value(html_str, table)?;
+    serde_yaml::to_string(&value).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+// HTML table to CSV conversion
+#[wasm_bindgen]
+pub fn html_table_to_csv(html_str: &str, table: Option<u32>) -> Result<String, JsValue> {
+    match html_table_value(html_str, table)? {
+        Value::Array(rows) if !rows.is_empty() => convert_array_of_objects_to_csv(&rows),
+        _ => Ok(String::new()),
+    }
+}
+
 fn convert_simple_array_to_csv(arr: &Vec<Value>) -> Result<String, JsValue> {
     // For simple arrays (not containing objects), create a single column
     let header = "value";
@@ -350,35 +431,308 @@ pub fn yaml_to_csv(yaml_str: &str) -> Result<String, JsValue> {
     let yaml_value: Value = serde_yaml::from_str(yaml_str)
         .map_err(|e| JsValue::from_str(&e.to_string()))?;
     
-    match &yaml_value {
+    any_value_to_csv(&yaml_value).map_err(|e| JsValue::from_str(&e))
+}
+
+// Picks a CSV layout for whatever shape the document has
+fn any_value_to_csv(value: &Value) -> Result<String, String> {
+    match value {
         Value::Array(arr) => {
             // Check if it's an array of objects, which is ideal for CSV
             if arr.iter().all(|item| item.is_object()) && !arr.is_empty() {
                 // Process array of objects (ideal case)
-                value_to_csv(&yaml_value)
-                    .map_err(|e| JsValue::from_str(&e.to_string()))
+                value_to_csv(value)
             } else if arr.iter().all(|item| item.is_array()) && !arr.is_empty() {
                 // Handle array of arrays as rows and columns
                 array_of_arrays_to_csv(arr)
-                    .map_err(|e| JsValue::from_str(&e.to_string()))
             } else {
                 // Simple array - convert to single column
                 simple_array_to_csv(arr)
-                    .map_err(|e| JsValue::from_str(&e.to_string()))
             }
         },
         Value::Object(obj) => {
             // Handle single object - convert to key-value pairs
             single_object_to_csv(obj)
-                .map_err(|e| JsValue::from_str(&e.to_string()))
         },
         _ => {
             // Handle scalar values or other simple types
-            Ok(format!("value\n{}", scalar_to_string(&yaml_value)))
+            Ok(format!("value\n{}", scalar_to_string(value)))
         }
     }
 }
 
+// TOML to JSON conversion
+#[wasm_bindgen]
+pub fn toml_to_json(toml_str: &str) -> Result<String, JsValue> {
+    let toml_value = parse_toml(toml_str).map_err(|e| JsValue::from_str(&e))?;
+    
+    serde_json::to_string_pretty(&toml_value)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+// TOML to YAML conversion
+#[wasm_bindgen]
+pub fn toml_to_yaml(toml_str: &str) -> Result<String, JsValue> {
+    let toml_value = parse_toml(toml_str).map_err(|e| JsValue::from_str(&e))?;
+    
+    serde_yaml::to_string(&toml_value)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+// TOML to XML conversion
+#[wasm_bindgen]
+pub fn toml_to_xml(toml_str: &str) -> Result<String, JsValue> {
+    let toml_value = parse_toml(toml_str).map_err(|e| JsValue::from_str(&e))?;
+    
+    value_to_xml(&toml_value, "root").map_err(|e| JsValue::from_str(&e))
+}
+
+// TOML to CSV conversion
+#[wasm_bindgen]
+pub fn toml_to_csv(toml_str: &str) -> Result<String, JsValue> {
+    let toml_value = parse_toml(toml_str).map_err(|e| JsValue::from_str(&e))?;
+    
+    any_value_to_csv(&toml_value).map_err(|e| JsValue::from_str(&e))
+}
+
+// JSON to NDJSON conversion; each element of an array becomes one line
+#[wasm_bindgen]
+pub fn json_to_ndjson(json_str: &str, syntax: Option<String>) -> Result<String, JsValue> {
+    let syntax = JsonSyntax::parse(syntax.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    let json_value = parse_json_text(json_str, syntax)
+        .map_err(|e| JsValue::from_str(&format!("JSON parsing error: {}", e)))?;
+
+    to_ndjson(&json_value).map_err(|e| JsValue::from_str(&e))
+}
+
+// JSON to TOML conversion; fails on nulls, which TOML cannot represent
+#[wasm_bindgen]
+pub fn json_to_toml(json_str: &str, syntax: Option<String>) -> Result<String, JsValue> {
+    let syntax = JsonSyntax::parse(syntax.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    let json_value: Value = parse_json_text(json_str, syntax).map_err(|e| JsValue::from_str(&e))?;
+    
+    to_toml_string(&json_value).map_err(|e| JsValue::from_str(&e))
+}
+
+// YAML to TOML conversion
+#[wasm_bindgen]
+pub fn yaml_to_toml(yaml_str: &str) -> Result<String, JsValue> {
+    let yaml_value: Value = serde_yaml::from_str(yaml_str)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    
+    to_toml_string(&yaml_value).map_err(|e| JsValue::from_str(&e))
+}
+
+// INI, .properties and .env conversions. The files read into JSON values
+// plus their comments, which YAML output keeps; writing them takes the
+// comments of JSONC and JSON5 input.
+type ConfigReader = fn(&str) -> Result<(Value, Comments), String>;
+type ConfigWriter = fn(&Value, &Comments) -> Result<String, String>;
+
+fn config_value(text: &str, read: ConfigReader) -> Result<Value, JsValue> {
+    read(text).map(|(value, _)| value).map_err(|e| JsValue::from_str(&e))
+}
+
+fn config_to_yaml(text: &str, read: ConfigReader) -> Result<String, JsValue> {
+    let (value, comments) = read(text).map_err(|e| JsValue::from_str(&e))?;
+    if comments.is_empty() {
+        serde_yaml::to_string(&value).map_err(|e| JsValue::from_str(&e.to_string()))
+    } else {
+        to_commented_yaml(&value, &comments).map_err(|e| JsValue::from_str(&e))
+    }
+}
+
+fn json_to_config(json_str: &str, syntax: Option<String>, write: ConfigWriter) -> Result<String, JsValue> {
+    let (value, comments) = parse_json_keeping_comments(json_str, syntax.as_deref())?;
+    write(&value, &comments).map_err(|e| JsValue::from_str(&e))
+}
+
+fn yaml_to_config(yaml_str: &str, write: ConfigWriter) -> Result<String, JsValue> {
+    let yaml_value: Value = serde_yaml::from_str(yaml_str).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    write(&yaml_value, &Comments::new()).map_err(|e| JsValue::from_str(&e))
+}
+
+fn toml_to_config(toml_str: &str, write: ConfigWriter) -> Result<String, JsValue> {
+    let toml_value = parse_toml(toml_str).map_err(|e| JsValue::from_str(&e))?;
+    write(&toml_value, &Comments::new()).map_err(|e| JsValue::from_str(&e))
+}
+
+// INI to JSON conversion
+#[wasm_bindgen]
+pub fn ini_to_json(ini_str: &str) -> Result<String, JsValue> {
+    let value = config_value(ini_str, parse_ini)?;
+    serde_json::to_string_pretty(&value).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+// INI to YAML conversion, keeping comments
+#[wasm_bindgen]
+pub fn ini_to_yaml(ini_str: &str) -> Result<String, JsValue> {
+    config_to_yaml(ini_str, parse_ini)
+}
+
+// INI to XML conversion
+#[wasm_bindgen]
+pub fn ini_to_xml(ini_str: &str) -> Result<String, JsValue> {
+    value_to_xml(&config_value(ini_str, parse_ini)?, "root").map_err(|e| JsValue::from_str(&e))
+}
+
+// INI to CSV conversion
+#[wasm_bindgen]
+pub fn ini_to_csv(ini_str: &str) -> Result<String, JsValue> {
+    any_value_to_csv(&config_value(ini_str, parse_ini)?).map_err(|e| JsValue::from_str(&e))
+}
+
+// INI to TOML conversion
+#[wasm_bindgen]
+pub fn ini_to_toml(ini_str: &str) -> Result<String, JsValue> {
+    to_toml_string(&config_value(ini_str, parse_ini)?).map_err(|e| JsValue::from_str(&e))
+}
+
+// JSON to INI conversion, keeping JSONC and JSON5 comments
+#[wasm_bindgen]
+pub fn json_to_ini(json_str: &str, syntax: Option<String>) -> Result<String, JsValue> {
+    json_to_config(json_str, syntax, to_ini_string)
+}
+
+// YAML to INI conversion
+#[wasm_bindgen]
+pub fn yaml_to_ini(yaml_str: &str) -> Result<String, JsValue> {
+    yaml_to_config(yaml_str, to_ini_string)
+}
+
+// TOML to INI conversion
+#[wasm_bindgen]
+pub fn toml_to_ini(toml_str: &str) -> Result<String, JsValue> {
+    toml_to_config(toml_str, to_ini_string)
+}
+
+// .properties to JSON conversion
+#[wasm_bindgen]
+pub fn properties_to_json(properties_str: &str) -> Result<String, JsValue> {
+    let value = config_value(properties_str, parse_properties)?;
+    serde_json::to_string_pretty(&value).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+// .properties to YAML conversion, keeping comments
+#[wasm_bindgen]
+pub fn properties_to_yaml(properties_str: &str) -> Result<String, JsValue> {
+    config_to_yaml(properties_str, parse_properties)
+}
+
+// .properties to XML conversion
+#[wasm_bindgen]
+pub fn properties_to_xml(properties_str: &str) -> Result<String, JsValue> {
+    value_to_xml(&config_value(properties_str, parse_properties)?, "root").map_err(|e| JsValue::from_str(&e))
+}
+
+// .properties to CSV conversion
+#[wasm_bindgen]
+pub fn properties_to_csv(properties_str: &str) -> Result<String, JsValue> {
+    any_value_to_csv(&config_value(properties_str, parse_properties)?).map_err(|e| JsValue::from_str(&e))
+}
+
+// .properties to TOML conversion
+#[wasm_bindgen]
+pub fn properties_to_toml(properties_str: &str) -> Result<String, JsValue> {
+    to_toml_string(&config_value(properties_str, parse_properties)?).map_err(|e| JsValue::from_str(&e))
+}
+
+// JSON to .properties conversion, keeping JSONC and JSON5 comments
+#[wasm_bindgen]
+pub fn json_to_properties(json_str: &str, syntax: Option<String>) -> Result<String, JsValue> {
+    json_to_config(json_str, syntax, to_properties_string)
+}
+
+// YAML to .properties conversion
+#[wasm_bindgen]
+pub fn yaml_to_properties(yaml_str: &str) -> Result<String, JsValue> {
+    yaml_to_config(yaml_str, to_properties_string)
+}
+
+// TOML to .properties conversion
+#[wasm_bindgen]
+pub fn toml_to_properties(toml_str: &str) -> Result<String, JsValue> {
+    toml_to_config(toml_str, to_properties_string)
+}
+
+// .env to JSON conversion
+#[wasm_bindgen]
+pub fn env_to_json(env_str: &str) -> Result<String, JsValue> {
+    let value = config_value(env_str, parse_env)?;
+    serde_json::to_string_pretty(&value).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+// .env to YAML conversion, keeping comments
+#[wasm_bindgen]
+pub fn env_to_yaml(env_str: &str) -> Result<String, JsValue> {
+    config_to_yaml(env_str, parse_env)
+}
+
+// .env to XML conversion
+#[wasm_bindgen]
+pub fn env_to_xml(env_str: &str) -> Result<String, JsValue> {
+    value_to_xml(&config_value(env_str, parse_env)?, "root").map_err(|e| JsValue::from_str(&e))
+}
+
+// .env to CSV conversion
+#[wasm_bindgen]
+pub fn env_to_csv(env_str: &str) -> Result<String, JsValue> {
+    any_value_to_csv(&config_value(env_str, parse_env)?).map_err(|e| JsValue::from_str(&e))
+}
+
+// .env to TOML conversion
+#[wasm_bindgen]
+pub fn env_to_toml(env_str: &str) -> Result<String, JsValue> {
+    to_toml_string(&config_value(env_str, parse_env)?).map_err(|e| JsValue::from_str(&e))
+}
+
+// JSON to .env conversion, keeping JSONC and JSON5 comments
+#[wasm_bindgen]
+pub fn json_to_env(json_str: &str, syntax: Option<String>) -> Result<String, JsValue> {
+    json_to_config(json_str, syntax, to_env_string)
+}
+
+// YAML to .env conversion
+#[wasm_bindgen]
+pub fn yaml_to_env(yaml_str: &str) -> Result<String, JsValue> {
+    yaml_to_config(yaml_str, to_env_string)
+}
+
+// TOML to .env conversion
+#[wasm_bindgen]
+pub fn toml_to_env(toml_str: &str) -> Result<String, JsValue> {
+    toml_to_config(toml_str, to_env_string)
+}
+
+// HCL (Terraform) to JSON conversion, giving Terraform's JSON syntax
+#[wasm_bindgen]
+pub fn hcl_to_json(hcl_str: &str) -> Result<String, JsValue> {
+    let value = parse_hcl(hcl_str).map_err(|e| JsValue::from_str(&e))?;
+    serde_json::to_string_pretty(&value).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+// HCL (Terraform) to YAML conversion
+#[wasm_bindgen]
+pub fn hcl_to_yaml(hcl_str: &str) -> Result<String, JsValue> {
+    let value = parse_hcl(hcl_str).map_err(|e| JsValue::from_str(&e))?;
+    serde_yaml::to_string(&value).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+// JSON to HCL conversion; takes Terraform's JSON syntax or a plain tfvars object
+#[wasm_bindgen]
+pub fn json_to_hcl(json_str: &str, syntax: Option<String>) -> Result<String, JsValue> {
+    let syntax = JsonSyntax::parse(syntax.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    let json_value = parse_json_text(json_str, syntax).map_err(|e| JsValue::from_str(&e))?;
+    to_hcl_string(&json_value).map_err(|e| JsValue::from_str(&e))
+}
+
+// YAML to HCL conversion
+#[wasm_bindgen]
+pub fn yaml_to_hcl(yaml_str: &str) -> Result<String, JsValue> {
+    let yaml_value: Value = serde_yaml::from_str(yaml_str).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    to_hcl_string(&yaml_value).map_err(|e| JsValue::from_str(&e))
+}
+
 // Helper function to convert Value to XML
 fn value_to_xml(value: &Value, tag_name: &str) -> Result<String, String> {
     match value {
@@ -619,6 +973,20 @@ pub fn xml_to_csv(xml_str: &str) -> Result<String, JsValue> {
     Ok(csv_string)
 }
 
+// XML to TOML conversion
+#[wasm_bindgen]
+pub fn xml_to_toml(xml_str: &str) -> Result<String, JsValue> {
+    let parsed = parse_xml(xml_str)
+        .map_err(|e| JsValue::from_str(&e))?;
+    
+    let json_string = xml_node_to_json(&parsed)
+        .map_err(|e| JsValue::from_str(&e))?;
+    let json_value: Value = serde_json::from_str(&json_string)
+        .map_err(|e| JsValue::from_str(&format!("JSON parsing error: {}", e)))?;
+    
+    to_toml_string(&json_value).map_err(|e| JsValue::from_str(&e))
+}
+
 // Custom XML parser implementation
 fn parse_xml(xml_str: &str) -> Result<XmlNode, String> {
     // This is a simplified XML parser for illustration
@@ -0,0 +1,365 @@
+// Identifier helpers shared by the code generators.
+//
+// JSON keys can be anything, so every generator maps keys to identifiers that
+// are valid (and idiomatically cased) in its target language and emits a
+// serialization mapping back to the original key where the two differ.
+
+const JAVA_KEYWORDS: &[&str] = &[
+    "abstract", "assert", "boolean", "break", "byte", "case", "catch", "char", "class", "const",
+    "continue", "default", "do", "double", "else", "enum", "extends", "false", "final", "finally",
+    "float", "for", "goto", "if", "implements", "import", "instanceof", "int", "interface", "long",
+    "native", "new", "null", "package", "private", "protected", "public", "record", "return",
+    "short", "static", "strictfp", "super", "switch", "synchronized", "this", "throw", "throws",
+    "transient", "true", "try", "var", "void", "volatile", "while", "yield",
+];
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe",
+    "use", "where", "while", "abstract", "become", "box", "do", "final", "macro", "override",
+    "priv", "typeof", "unsized", "virtual", "yield", "try",
+];
+
+/// Type names that would shadow something every generated file relies on.
+const RESERVED_TYPE_NAMES: &[&str] = &[
+    "Object", "String", "Boolean", "Number", "Integer", "Long", "Double", "List", "Map", "Date",
+    "Any", "Optional", "Union", "Dict", "Enum", "Vec", "Option", "Result", "Box", "HashMap",
+    "Record", "Array",
+];
+
+/// Common Go initialisms that golint expects in upper case.
+const GO_INITIALISMS: &[&str] = &[
+    "ACL", "API", "ASCII", "CPU", "CSS", "DNS", "EOF", "GUID", "HTML", "HTTP", "HTTPS", "ID", "IP",
+    "JSON", "LHS", "QPS", "RAM", "RHS", "RPC", "SLA", "SMTP", "SQL", "SSH", "TCP", "TLS", "TTL",
+    "UDP", "UI", "UID", "URI", "URL", "UTF8", "UUID", "VM", "XML", "XMPP", "XSRF", "XSS",
+];
+
+/// Splits a key into words at separators and lower/upper case boundaries,
+/// e.g. `"firstName"`, `"first_name"` and `"First Name"` all give
+/// `["first", "Name"]`-like word lists and `"HTTPServer"` gives `["HTTP", "Server"]`.
+pub(crate) fn split_words(s: &str) -> Vec<String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if let Some(prev) = current.chars().last() {
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            let boundary = (prev.is_ascii_lowercase() && c.is_ascii_uppercase())
+                || (prev.is_ascii_uppercase() && c.is_ascii_uppercase() && next_is_lower)
+                || (prev.is_ascii_digit() && c.is_ascii_alphabetic() && c.is_ascii_uppercase());
+            if boundary {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn capitalize_word(word: &str) -> String {
+    // Shouting words ("NAME", "URL") are normalised; mixed-case ones are kept.
+    let word = if word.chars().all(|c| !c.is_ascii_lowercase()) {
+        word.to_ascii_lowercase()
+    } else {
+        word.to_string()
+    };
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+fn prefix_leading_digit(name: String, prefix: &str) -> String {
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("{}{}", prefix, name)
+    } else {
+        name
+    }
+}
+
+pub(crate) fn pascal_case(s: &str) -> String {
+    let joined: String = split_words(s).iter().map(|w| capitalize_word(w)).collect();
+    prefix_leading_digit(joined, "_")
+}
+
+pub(crate) fn camel_case(s: &str) -> String {
+    let words = split_words(s);
+    let mut result = String::new();
+    for (i, word) in words.iter().enumerate() {
+        if i == 0 {
+            result.push_str(&word.to_ascii_lowercase());
+        } else {
+            result.push_str(&capitalize_word(word));
+        }
+    }
+    prefix_leading_digit(result, "_")
+}
+
+pub(crate) fn snake_case(s: &str) -> String {
+    let joined = split_words(s)
+        .iter()
+        .map(|w| w.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("_");
+    prefix_leading_digit(joined, "_")
+}
+
+pub(crate) fn screaming_snake_case(s: &str) -> String {
+    snake_case(s).to_ascii_uppercase()
+}
+
+/// A PascalCase type name that is safe in every target language.
+pub(crate) fn type_name(s: &str) -> String {
+    let name = pascal_case(s);
+    if name.is_empty() {
+        "Type".to_string()
+    } else if RESERVED_TYPE_NAMES.contains(&name.as_str()) {
+        format!("{}Type", name)
+    } else {
+        name
+    }
+}
+
+/// An enum constant / variant name for a string value.
+pub(crate) fn enum_member_name(value: &str) -> String {
+    let name = pascal_case(value);
+    if name.is_empty() {
+        "Empty".to_string()
+    } else if name == "Self" {
+        // `Self` cannot name a Rust variant.
+        "SelfValue".to_string()
+    } else {
+        name
+    }
+}
+
+pub(crate) fn is_js_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// TypeScript allows any key as a property name once it is quoted.
+pub(crate) fn typescript_property(key: &str) -> String {
+    if is_js_identifier(key) {
+        key.to_string()
+    } else {
+        serde_json::to_string(key).unwrap_or_else(|_| format!("\"{}\"", key))
+    }
+}
+
+pub(crate) fn java_field_name(key: &str) -> String {
+    let name = camel_case(key);
+    if name.is_empty() {
+        "field".to_string()
+    } else if JAVA_KEYWORDS.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+/// The accessor suffix used in `getX()` / `setX()`.
+pub(crate) fn java_accessor_name(key: &str) -> String {
+    let name = pascal_case(key);
+    if name.is_empty() {
+        "Field".to_string()
+    } else if name == "Class" {
+        // `getClass()` is final on java.lang.Object.
+        "Class_".to_string()
+    } else {
+        name
+    }
+}
+
+pub(crate) fn java_enum_constant(value: &str) -> String {
+    let name = screaming_snake_case(value);
+    if name.is_empty() {
+        "EMPTY".to_string()
+    } else if name.starts_with('_') {
+        format!("VALUE{}", name)
+    } else {
+        name
+    }
+}
+
+/// Exported Go field name, with golint's initialisms (`ID`, `URL`, ...).
+pub(crate) fn go_field_name(key: &str) -> String {
+    let name: String = split_words(key)
+        .iter()
+        .map(|word| {
+            let upper = word.to_ascii_uppercase();
+            if GO_INITIALISMS.contains(&upper.as_str()) {
+                upper
+            } else {
+                capitalize_word(word)
+            }
+        })
+        .collect();
+    if name.is_empty() {
+        "Field".to_string()
+    } else {
+        prefix_leading_digit(name, "X")
+    }
+}
+
+pub(crate) fn python_field_name(key: &str) -> String {
+    let name = snake_case(key);
+    if name.is_empty() {
+        "field".to_string()
+    } else if PYTHON_KEYWORDS.contains(&name.as_str()) || name == "self" {
+        // `self` would clash with the generated `__init__` parameter.
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+/// Whether `key` can be used verbatim as a Python attribute name.
+pub(crate) fn is_python_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    let valid = match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => chars.all(|c| c.is_ascii_alphanumeric() || c == '_'),
+        _ => false,
+    };
+    valid && !PYTHON_KEYWORDS.contains(&key)
+}
+
+pub(crate) fn python_enum_member(value: &str) -> String {
+    let name = screaming_snake_case(value);
+    if name.is_empty() {
+        "EMPTY".to_string()
+    } else if PYTHON_KEYWORDS.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+pub(crate) fn rust_field_name(key: &str) -> String {
+    let name = snake_case(key);
+    if name.is_empty() {
+        "field".to_string()
+    } else if matches!(name.as_str(), "self" | "super" | "crate") {
+        // These cannot be raw identifiers.
+        format!("{}_", name)
+    } else if RUST_KEYWORDS.contains(&name.as_str()) {
+        format!("r#{}", name)
+    } else {
+        name
+    }
+}
+
+/// An Avro name (`[A-Za-z_][A-Za-z0-9_]*`). Unlike the generators above,
+/// the key's own spelling is kept and only invalid characters become `_`;
+/// where the name differs from the key, the generated schema records the key
+/// in the field's `jsonKey` attribute so values can be matched back to it.
+pub(crate) fn avro_name(key: &str) -> String {
+    let name: String = key.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' }).collect();
+    if name.is_empty() {
+        "field".to_string()
+    } else {
+        prefix_leading_digit(name, "_")
+    }
+}
+
+/// Makes every generated name in a scope distinct by appending a counter to
+/// later duplicates (`"first-name"` and `"first_name"` both become `firstName`).
+pub(crate) fn dedupe_names(names: Vec<String>) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    names
+        .into_iter()
+        .map(|name| {
+            let mut candidate = name.clone();
+            let mut counter = 2;
+            while !seen.insert(candidate.clone()) {
+                candidate = format!("{}{}", name, counter);
+                counter += 1;
+            }
+            candidate
+        })
+        .collect()
+}
+
+/// A Go struct tag such as `json:"id,omitempty"` as a literal: a raw string,
+/// or an interpreted one when the tag holds a backtick a raw string can't.
+pub(crate) fn go_tag(tag: &str) -> String {
+    if tag.contains('`') {
+        quoted(tag)
+    } else {
+        format!("`{}`", tag)
+    }
+}
+
+/// Escapes a value for use inside a double-quoted string literal in the
+/// generated source (the same rules hold for Java, Go, Python and Rust).
+pub(crate) fn quoted(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| format!("\"{}\"", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_become_idiomatic_names() {
+        assert_eq!(split_words("HTTPServer"), ["HTTP", "Server"]);
+        assert_eq!(camel_case("first-name"), "firstName");
+        assert_eq!(pascal_case("first_name"), "FirstName");
+        assert_eq!(snake_case("firstName"), "first_name");
+        assert_eq!(go_field_name("user_id"), "UserID");
+        assert_eq!(go_field_name("2fa"), "X2fa");
+        assert_eq!(java_enum_constant("2nd"), "VALUE_2ND");
+        assert_eq!(avro_name("first-name"), "first_name");
+    }
+
+    #[test]
+    fn reserved_words_are_escaped_per_language() {
+        assert_eq!(rust_field_name("type"), "r#type");
+        assert_eq!(rust_field_name("self"), "self_");
+        assert_eq!(java_field_name("class"), "class_");
+        assert_eq!(java_accessor_name("class"), "Class_");
+        assert_eq!(python_field_name("from"), "from_");
+        assert_eq!(python_field_name("self"), "self_");
+        assert_eq!(python_enum_member("none"), "NONE");
+        assert_eq!(enum_member_name("self"), "SelfValue");
+        assert_eq!(type_name("string"), "StringType");
+        assert_eq!(type_name("record"), "RecordType");
+        assert!(!is_python_identifier("class"));
+        assert_eq!(typescript_property("first-name"), "\"first-name\"");
+    }
+
+    #[test]
+    fn duplicate_names_get_counters() {
+        let names = ["first-name", "first_name", "id", "First Name"].iter().map(|key| camel_case(key)).collect();
+        assert_eq!(dedupe_names(names), ["firstName", "firstName2", "id", "firstName3"]);
+    }
+
+    #[test]
+    fn go_tags_with_backticks_are_interpreted_strings() {
+        assert_eq!(go_tag("json:\"id\""), "`json:\"id\"`");
+        assert_eq!(go_tag("json:\"a`b\""), "\"json:\\\"a`b\\\"\"");
+    }
+}
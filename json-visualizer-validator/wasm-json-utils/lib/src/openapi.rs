@@ -0,0 +1,513 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::naming::type_name;
+use crate::schema_graph::SchemaGraph;
+use crate::schema_types::{
+    build_components_model, render_golang, render_java, render_python, render_rust, render_typescript,
+    JavaOptions, PythonStyle,
+};
+use crate::validator::{escape, resolve_ref, ValidationReport, Validator};
+
+// OpenAPI 3 documents.
+//
+// Lists what a spec defines, validates payloads against its schemas and draws
+// it as a graph. Schemas are used where they sit in the document, so `$ref`s
+// to `#/components/...` resolve and every path in the output is a pointer
+// into the spec as written.
+
+const METHODS: [&str; 8] = ["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+/// Longer chains of `$ref`s between document objects are treated as loops.
+const MAX_REF_HOPS: usize = 16;
+
+#[derive(Serialize)]
+struct OpenApiSummary {
+    openapi: String,
+    title: Option<String>,
+    version: Option<String>,
+    paths: Vec<PathSummary>,
+    schemas: Vec<ComponentSummary>,
+}
+
+#[derive(Serialize)]
+struct PathSummary {
+    path: String,
+    operations: Vec<OperationSummary>,
+}
+
+#[derive(Serialize)]
+struct OperationSummary {
+    method: String,
+    operation_id: Option<String>,
+    summary: Option<String>,
+    request_body: Vec<ContentSchema>,
+    responses: Vec<ResponseSummary>,
+}
+
+#[derive(Serialize)]
+struct ResponseSummary {
+    status: String,
+    description: Option<String>,
+    content: Vec<ContentSchema>,
+}
+
+#[derive(Serialize)]
+struct ContentSchema {
+    content_type: String,
+    /// Pointer to the schema, or to its target when it is a `$ref`.
+    schema: String,
+    /// Where the schema itself sits.
+    #[serde(skip)]
+    location: String,
+}
+
+#[derive(Serialize)]
+struct ComponentSummary {
+    name: String,
+    pointer: String,
+    schema_type: Option<String>,
+    description: Option<String>,
+}
+
+/// Options for `openapi_to_types`, e.g. `{"flavor": "pydantic"}` or `{"java": {"style": "record"}}`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct TypesOptions {
+    /// Python class style: dataclass, pydantic or typeddict.
+    flavor: Option<String>,
+    java: JavaOptions,
+}
+
+/// Lists the paths, operations and component schemas of an OpenAPI 3
+/// document given as JSON or YAML.
+#[wasm_bindgen]
+pub fn openapi_summary(spec_str: &str) -> Result<String, JsValue> {
+    let spec = parse_document(spec_str).map_err(|e| JsValue::from_str(&e))?;
+    let info = |key: &str| spec.pointer(&format!("/info/{}", key)).and_then(Value::as_str).map(str::to_string);
+
+    let paths = operations_by_path(&spec)
+        .into_iter()
+        .map(|(path, operations)| PathSummary {
+            path: path.clone(),
+            operations: operations
+                .into_iter()
+                .map(|(method, operation, pointer)| summarize_operation(&spec, method, operation, &pointer))
+                .collect(),
+        })
+        .collect();
+    let schemas = component_schemas(&spec)
+        .map(|(name, schema)| ComponentSummary {
+            name: name.clone(),
+            pointer: format!("#/components/schemas/{}", escape(name)),
+            schema_type: schema.get("type").and_then(Value::as_str).map(str::to_string),
+            description: schema.get("description").and_then(Value::as_str).map(str::to_string),
+        })
+        .collect();
+
+    let summary = OpenApiSummary {
+        openapi: spec["openapi"].as_str().unwrap_or_default().to_string(),
+        title: info("title"),
+        version: info("version"),
+        paths,
+        schemas,
+    };
+    serde_json::to_string_pretty(&summary).map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// Validates a JSON payload against a component schema, named either
+/// `Pet` or `#/components/schemas/Pet`.
+#[wasm_bindgen]
+pub fn validate_openapi_schema(spec_str: &str, schema_name: &str, payload_str: &str) -> Result<String, JsValue> {
+    let spec = parse_document(spec_str).map_err(|e| JsValue::from_str(&e))?;
+    let payload: Value = serde_json::from_str(payload_str)
+        .map_err(|e| JsValue::from_str(&format!("JSON parsing error: {}", e)))?;
+
+    let pointer = if schema_name.starts_with('#') {
+        schema_name.to_string()
+    } else {
+        format!("#/components/schemas/{}", escape(schema_name))
+    };
+    let (schema, pointer) = resolve_ref(&spec, &pointer)
+        .ok_or_else(|| JsValue::from_str(&format!("No schema at {}", pointer)))?;
+    validate(&spec, schema, &pointer, &payload)
+}
+
+/// Validates a JSON payload against the response schema of an operation.
+/// `status` defaults to the first 2xx response, then `default`.
+#[wasm_bindgen]
+pub fn validate_openapi_response(
+    spec_str: &str,
+    path: &str,
+    method: &str,
+    payload_str: &str,
+    status: Option<String>,
+) -> Result<String, JsValue> {
+    let spec = parse_document(spec_str).map_err(|e| JsValue::from_str(&e))?;
+    let payload: Value = serde_json::from_str(payload_str)
+        .map_err(|e| JsValue::from_str(&format!("JSON parsing error: {}", e)))?;
+
+    let method = method.to_ascii_lowercase();
+    let (_, operation, operation_pointer) = operations_by_path(&spec)
+        .into_iter()
+        .filter(|(candidate, _)| *candidate == path)
+        .flat_map(|(_, operations)| operations)
+        .find(|(candidate, _, _)| *candidate == method)
+        .ok_or_else(|| JsValue::from_str(&format!("No {} operation for path {}", method.to_uppercase(), path)))?;
+    let responses = operation.get("responses").and_then(Value::as_object);
+    let status = match status {
+        Some(status) => status,
+        None => responses
+            .and_then(|responses| {
+                let mut statuses = responses.keys();
+                statuses.clone().find(|status| status.starts_with('2')).or_else(|| statuses.find(|s| *s == "default"))
+            })
+            .cloned()
+            .ok_or_else(|| JsValue::from_str("The operation has no success or default response"))?,
+    };
+    let response_pointer = format!("{}/responses/{}", operation_pointer, escape(&status));
+    let (schema, pointer) = resolve_ref(&spec, &response_pointer)
+        .map(|(response, pointer)| follow(&spec, response, pointer))
+        .and_then(|(response, pointer)| preferred_schema(&spec, response, &pointer))
+        .ok_or_else(|| JsValue::from_str(&format!("Response {} has no schema", status)))?;
+    validate(&spec, schema, &pointer, &payload)
+}
+
+/// Builds the node/link graph of an OpenAPI document: paths, their
+/// operations, and the request and response schemas they use.
+#[wasm_bindgen]
+pub fn process_openapi_graph(spec_str: &str) -> Result<String, JsError> {
+    let spec = parse_document(spec_str).map_err(|e| JsError::new(&e))?;
+    let mut graph = SchemaGraph::new(&spec);
+
+    let title = spec.pointer("/info/title").and_then(Value::as_str).unwrap_or("API");
+    let version = format!("OpenAPI {}", spec["openapi"].as_str().unwrap_or_default());
+    let root_id = graph.node(title.to_string(), version, None, 0);
+
+    for (path, operations) in operations_by_path(&spec) {
+        let value = format!("{} operations", operations.len());
+        let path_id = graph.node(path.clone(), value, Some((&root_id, None)), 1);
+        for (method, operation, pointer) in operations {
+            let value = operation
+                .get("operationId")
+                .or_else(|| operation.get("summary"))
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let operation_id = graph.node(method.to_uppercase(), value.to_string(), Some((&path_id, None)), 2);
+            let summary = summarize_operation(&spec, method, operation, &pointer);
+            let labelled = summary
+                .request_body
+                .iter()
+                .map(|content| ("request".to_string(), content))
+                .chain(summary.responses.iter().flat_map(|response| {
+                    response.content.iter().map(move |content| (response.status.clone(), content))
+                }));
+            for (label, content) in labelled {
+                let label = match content.content_type.as_str() {
+                    "application/json" => label,
+                    content_type => format!("{} {}", label, content_type),
+                };
+                if let Some((schema, schema_pointer)) = resolve_ref(&spec, &content.location) {
+                    graph.visit(schema, schema_pointer, label, Some((&operation_id, None)), 3);
+                }
+            }
+        }
+    }
+    graph.visit_unreferenced("#/components/schemas", spec.pointer("/components/schemas"), &root_id, 1);
+
+    serde_json::to_string(&graph.finish()).map_err(|e| JsError::new(&format!("Failed to serialize: {}", e)))
+}
+
+/// Generates a type for every entry of `components.schemas`, named after the
+/// component, with `$ref`s between components kept as type references.
+/// `language` is one of typescript, java, golang, python or rust.
+#[wasm_bindgen]
+pub fn openapi_to_types(spec_str: &str, language: &str, options: Option<String>) -> Result<String, JsValue> {
+    let spec = parse_document(spec_str).map_err(|e| JsValue::from_str(&e))?;
+    let options: TypesOptions = match options.as_deref().map(str::trim) {
+        None | Some("") => TypesOptions::default(),
+        Some(options) => serde_json::from_str(options)
+            .map_err(|e| JsValue::from_str(&format!("Invalid options: {}", e)))?,
+    };
+    let title = spec.pointer("/info/title").and_then(Value::as_str).map(type_name).unwrap_or_default();
+    let root = if title.is_empty() { "Api".to_string() } else { title };
+    let model = build_components_model(&spec, &root).map_err(|e| JsValue::from_str(&e))?;
+
+    match language.to_ascii_lowercase().as_str() {
+        "typescript" | "ts" => Ok(render_typescript(&model)),
+        "java" => Ok(render_java(&model, &options.java)),
+        "golang" | "go" => Ok(render_golang(&model)),
+        "python" | "py" => {
+            let style = PythonStyle::parse(options.flavor.as_deref().unwrap_or_default()).map_err(|e| JsValue::from_str(&e))?;
+            Ok(render_python(&model, style))
+        }
+        "rust" | "rs" => Ok(render_rust(&model)),
+        other => Err(JsValue::from_str(&format!("Unsupported language: {}", other))),
+    }
+}
+
+/// Reads an OpenAPI 3 document from JSON or YAML.
+pub(crate) fn parse_document(text: &str) -> Result<Value, String> {
+    let document: Value = if text.trim_start().starts_with('{') {
+        serde_json::from_str(text).map_err(|e| format!("JSON parsing error: {}", e))?
+    } else {
+        serde_yaml::from_str(text).map_err(|e| format!("Invalid YAML: {}", e))?
+    };
+    match document.get("openapi").and_then(Value::as_str) {
+        Some(version) if version.starts_with("3.") => Ok(document),
+        Some(version) => Err(format!("Unsupported OpenAPI version {}", version)),
+        None => Err("Not an OpenAPI 3 document: missing the \"openapi\" field".to_string()),
+    }
+}
+
+fn validate(spec: &Value, schema: &Value, pointer: &str, payload: &Value) -> Result<String, JsValue> {
+    let errors = Validator::new(spec).validate_at(schema, pointer, payload);
+    serde_json::to_string_pretty(&ValidationReport::new(errors))
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+type Operation<'a> = (&'static str, &'a Value, String);
+
+/// Each path with its operations in method order, and the pointer to each.
+fn operations_by_path(spec: &Value) -> Vec<(&String, Vec<Operation<'_>>)> {
+    let paths = match spec.get("paths") {
+        Some(Value::Object(paths)) => paths,
+        _ => return Vec::new(),
+    };
+    paths
+        .iter()
+        .map(|(path, item)| {
+            let (item, item_pointer) = follow(spec, item, format!("#/paths/{}", escape(path)));
+            let operations = METHODS
+                .iter()
+                .filter_map(|method| {
+                    let operation = item.get(*method)?;
+                    Some((*method, operation, format!("{}/{}", item_pointer, method)))
+                })
+                .collect();
+            (path, operations)
+        })
+        .collect()
+}
+
+fn summarize_operation(spec: &Value, method: &str, operation: &Value, pointer: &str) -> OperationSummary {
+    let text = |key: &str| operation.get(key).and_then(Value::as_str).map(str::to_string);
+    let request_body = match operation.get("requestBody") {
+        Some(body) => {
+            let (body, body_pointer) = follow(spec, body, format!("{}/requestBody", pointer));
+            content_schemas(spec, body, &body_pointer)
+        }
+        None => Vec::new(),
+    };
+    let responses = operation
+        .get("responses")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .map(|(status, response)| {
+            let response_pointer = format!("{}/responses/{}", pointer, escape(status));
+            let (response, response_pointer) = follow(spec, response, response_pointer);
+            ResponseSummary {
+                status: status.clone(),
+                description: response.get("description").and_then(Value::as_str).map(str::to_string),
+                content: content_schemas(spec, response, &response_pointer),
+            }
+        })
+        .collect();
+    OperationSummary {
+        method: method.to_uppercase(),
+        operation_id: text("operationId"),
+        summary: text("summary"),
+        request_body,
+        responses,
+    }
+}
+
+/// The schema of each media type in a request body or response.
+fn content_schemas(spec: &Value, holder: &Value, pointer: &str) -> Vec<ContentSchema> {
+    let content = match holder.get("content") {
+        Some(Value::Object(content)) => content,
+        _ => return Vec::new(),
+    };
+    content
+        .iter()
+        .filter_map(|(content_type, media)| {
+            let schema = media.get("schema")?;
+            let schema_pointer = format!("{}/content/{}/schema", pointer, escape(content_type));
+            let (_, target_pointer) = follow(spec, schema, schema_pointer.clone());
+            Some(ContentSchema {
+                content_type: content_type.clone(),
+                schema: target_pointer,
+                location: schema_pointer,
+            })
+        })
+        .collect()
+}
+
+/// The JSON schema of a response, or else its first schema.
+fn preferred_schema<'a>(spec: &'a Value, response: &'a Value, pointer: &str) -> Option<(&'a Value, String)> {
+    let schemas = content_schemas(spec, response, pointer);
+    let chosen = schemas
+        .iter()
+        .find(|content| content.content_type == "application/json" || content.content_type.ends_with("+json"))
+        .or_else(|| schemas.first())?;
+    resolve_ref(spec, &chosen.schema)
+}
+
+/// Follows `$ref`s between objects of the document, keeping track of where
+/// the object finally found lives.
+fn follow<'a>(spec: &'a Value, mut value: &'a Value, mut pointer: String) -> (&'a Value, String) {
+    for _ in 0..MAX_REF_HOPS {
+        match value.get("$ref").and_then(Value::as_str).and_then(|reference| resolve_ref(spec, reference)) {
+            Some((target, target_pointer)) => {
+                value = target;
+                pointer = target_pointer;
+            }
+            None => break,
+        }
+    }
+    (value, pointer)
+}
+
+fn component_schemas(spec: &Value) -> impl Iterator<Item = (&String, &Value)> {
+    spec.pointer("/components/schemas").and_then(Value::as_object).into_iter().flat_map(Map::iter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SPEC: &str = r##"
+openapi: 3.0.3
+info:
+  title: Pet Store
+  version: "1.0"
+paths:
+  /pets/{id}:
+    $ref: "#/components/pathItems/PetById"
+  /pets:
+    post:
+      operationId: createPet
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: "#/components/schemas/Pet"
+      responses:
+        "201":
+          $ref: "#/components/responses/PetCreated"
+    get:
+      operationId: listPets
+      responses:
+        "200":
+          description: All pets
+          content:
+            text/plain:
+              schema:
+                type: string
+            application/json:
+              schema:
+                type: array
+                items:
+                  $ref: "#/components/schemas/Pet"
+components:
+  pathItems:
+    PetById:
+      get:
+        summary: One pet
+        responses:
+          default:
+            description: The pet
+            content:
+              application/json:
+                schema:
+                  $ref: "#/components/schemas/Pet"
+  responses:
+    PetCreated:
+      description: Created
+      content:
+        application/json:
+          schema:
+            $ref: "#/components/schemas/Pet"
+  schemas:
+    Pet:
+      type: object
+      description: A pet
+      required: [name]
+      properties:
+        name:
+          type: string
+        age:
+          type: integer
+          minimum: 0
+"##;
+
+    fn parse(text: &str) -> Value {
+        serde_json::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn summary_lists_operations_through_refs() {
+        let summary = parse(&openapi_summary(SPEC).unwrap());
+        assert_eq!(summary["title"], "Pet Store");
+        let paths: Vec<&Value> = summary["paths"].as_array().unwrap().iter().map(|p| &p["path"]).collect();
+        assert_eq!(paths, ["/pets", "/pets/{id}"]);
+
+        let operations = summary["paths"][0]["operations"].as_array().unwrap();
+        let methods: Vec<&Value> = operations.iter().map(|o| &o["method"]).collect();
+        assert_eq!(methods, ["GET", "POST"]);
+        assert_eq!(operations[1]["operation_id"], "createPet");
+        assert_eq!(operations[1]["request_body"][0]["schema"], "#/components/schemas/Pet");
+        assert_eq!(operations[1]["responses"][0]["description"], "Created");
+
+        let by_id = &summary["paths"][1]["operations"][0];
+        assert_eq!(by_id["method"], "GET");
+        assert_eq!(by_id["summary"], "One pet");
+        assert_eq!(by_id["responses"][0]["content"][0]["schema"], "#/components/schemas/Pet");
+        let pet = json!({
+            "name": "Pet",
+            "pointer": "#/components/schemas/Pet",
+            "schema_type": "object",
+            "description": "A pet"
+        });
+        assert_eq!(summary["schemas"], json!([pet]));
+    }
+
+    #[test]
+    fn responses_validate_against_the_operation_schema() {
+        let valid = validate_openapi_response(SPEC, "/pets", "GET", r#"[{"name": "Rex", "age": 3}]"#, None).unwrap();
+        assert_eq!(parse(&valid)["valid"], true);
+
+        // The JSON schema of the 200 response is chosen over the text one.
+        let report = parse(&validate_openapi_response(SPEC, "/pets", "get", r#"[{"age": -1}]"#, None).unwrap());
+        assert_eq!(report["valid"], false);
+        let errors = report["errors"].as_array().unwrap();
+        let schema_paths: Vec<&str> = errors.iter().filter_map(|e| e["schema_path"].as_str()).collect();
+        assert_eq!(
+            schema_paths,
+            ["#/components/schemas/Pet/required", "#/components/schemas/Pet/properties/age/minimum"]
+        );
+
+        let created = validate_openapi_response(SPEC, "/pets", "post", r#"{"name": 5}"#, Some("201".into())).unwrap();
+        assert_eq!(parse(&created)["errors"][0]["instance_path"], "/name");
+        let by_id = validate_openapi_response(SPEC, "/pets/{id}", "get", r#"{"name": "Rex"}"#, None).unwrap();
+        assert_eq!(parse(&by_id)["valid"], true);
+    }
+
+    #[test]
+    fn component_schemas_validate_by_name_or_pointer() {
+        let report = parse(&validate_openapi_schema(SPEC, "Pet", r#"{"name": "Rex"}"#).unwrap());
+        assert_eq!(report["valid"], true);
+        let report = parse(&validate_openapi_schema(SPEC, "#/components/schemas/Pet", "{}").unwrap());
+        assert_eq!(report["errors"][0]["schema_path"], "#/components/schemas/Pet/required");
+    }
+
+    #[test]
+    fn only_openapi_3_documents_are_read() {
+        assert!(parse_document(SPEC).is_ok());
+        let swagger = parse_document("openapi: \"2.0\"").unwrap_err();
+        assert!(swagger.contains("Unsupported OpenAPI version 2.0"), "{}", swagger);
+        assert!(parse_document("{\"info\": {}}").unwrap_err().contains("missing the \"openapi\" field"));
+    }
+}
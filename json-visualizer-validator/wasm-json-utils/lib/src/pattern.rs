@@ -0,0 +1,684 @@
+use crate::sample::Rng;
+
+// Regular expressions for JSON Schema `pattern`.
+//
+// Generates strings matching a pattern (for sample data) and tests strings
+// against one (for validation). Covers the ECMA 262 syntax that shows up in
+// schemas: literals, escapes, character classes, groups, alternation,
+// quantifiers and anchors. Lookarounds and backreferences are reported as
+// errors.
+
+/// Unbounded quantifiers (`*`, `+`, `{n,}`) generate at most this many extra repeats.
+const MAX_EXTRA_REPEATS: u32 = 3;
+/// Target lengths tried by `generate_within` before giving up.
+const SIZED_ATTEMPTS: usize = 32;
+/// Patterns compiling to more instructions than this (say `a{1000000}`) are rejected.
+const MAX_PROGRAM_SIZE: usize = 100_000;
+
+#[derive(Debug)]
+enum Node {
+    Literal(char),
+    Class { ranges: Vec<(char, char)>, negated: bool },
+    /// `.`: anything but a line break.
+    Any,
+    /// Alternatives, each a sequence.
+    Group(Vec<Vec<Node>>),
+    /// Minimum and maximum count; no maximum for `*`, `+` and `{n,}`.
+    Repeat(Box<Node>, u32, Option<u32>),
+    Start,
+    End,
+    /// `\b` (true) or `\B` (false).
+    WordBoundary(bool),
+}
+
+fn parse(pattern: &str) -> Result<Node, String> {
+    let mut parser = Parser { chars: pattern.chars().collect(), pos: 0 };
+    let alternatives = parser.alternation()?;
+    if parser.pos < parser.chars.len() {
+        return Err(format!("Unbalanced ')' in pattern '{}'", pattern));
+    }
+    Ok(Node::Group(alternatives))
+}
+
+/// A string that `pattern` matches.
+pub(crate) fn generate(pattern: &str, rng: &mut Rng) -> Result<String, String> {
+    let mut out = String::new();
+    parse(pattern)?.generate(rng, &mut out);
+    Ok(out)
+}
+
+/// A string that `pattern` matches with between `min` and `max` characters,
+/// or `None` when none turns up. Repeats grow or shrink to reach the length,
+/// so `^[A-Z]{3}-\d+$` with a minimum of 10 gets a longer number.
+pub(crate) fn generate_within(
+    pattern: &str,
+    rng: &mut Rng,
+    min: usize,
+    max: Option<usize>,
+) -> Result<Option<String>, String> {
+    let node = parse(pattern)?;
+    let program = Program::compile(&node)?;
+    let (shortest, longest) = node.length_range();
+    let lo = shortest.max(min);
+    let hi = match (longest, max) {
+        (Some(longest), Some(max)) => longest.min(max),
+        (Some(longest), None) => longest,
+        (None, Some(max)) => max,
+        (None, None) => lo + MAX_EXTRA_REPEATS as usize,
+    };
+    if lo > hi {
+        return Ok(None);
+    }
+    for _ in 0..SIZED_ATTEMPTS {
+        // Lengths near the minimum read better than ones near a large maximum.
+        let target = lo + rng.below((hi - lo).min(MAX_EXTRA_REPEATS as usize * 2) as u64 + 1) as usize;
+        let mut out = String::new();
+        if node.generate_sized(rng, target, &mut out) && program.matches(&out) {
+            return Ok(Some(out));
+        }
+    }
+    Ok(None)
+}
+
+/// Whether `pattern` matches somewhere in `text`; like JSON Schema, the
+/// pattern is not implicitly anchored.
+pub(crate) fn is_match(pattern: &str, text: &str) -> Result<bool, String> {
+    Ok(Program::compile(&parse(pattern)?)?.matches(text))
+}
+
+/// The shortest and longest (`None` for unbounded) lengths a sequence matches.
+fn sequence_range(nodes: &[Node]) -> (usize, Option<usize>) {
+    nodes.iter().map(Node::length_range).fold((0, Some(0)), |(lo, hi), (node_lo, node_hi)| {
+        (lo.saturating_add(node_lo), hi.zip(node_hi).map(|(a, b)| a.saturating_add(b)))
+    })
+}
+
+/// Lengths for each part adding up to `target`, each within its range.
+fn split_length(rng: &mut Rng, target: usize, ranges: &[(usize, Option<usize>)]) -> Option<Vec<usize>> {
+    let mut parts: Vec<usize> = ranges.iter().map(|(lo, _)| *lo).collect();
+    let mut remaining = target.checked_sub(parts.iter().sum())?;
+    while remaining > 0 {
+        let open: Vec<usize> =
+            (0..parts.len()).filter(|&i| ranges[i].1.is_none_or(|hi| parts[i] < hi)).collect();
+        if open.is_empty() {
+            return None;
+        }
+        parts[open[rng.below(open.len() as u64) as usize]] += 1;
+        remaining -= 1;
+    }
+    Some(parts)
+}
+
+fn generate_sequence_sized(nodes: &[Node], rng: &mut Rng, target: usize, out: &mut String) -> bool {
+    let ranges: Vec<(usize, Option<usize>)> = nodes.iter().map(Node::length_range).collect();
+    match split_length(rng, target, &ranges) {
+        Some(lengths) => nodes.iter().zip(lengths).all(|(node, length)| node.generate_sized(rng, length, out)),
+        None => false,
+    }
+}
+
+const DIGITS: &[(char, char)] = &[('0', '9')];
+const WORD: &[(char, char)] = &[('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')];
+const SPACE: &[(char, char)] = &[(' ', ' '), ('\t', '\t'), ('\n', '\n'), ('\r', '\r')];
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.pos += 1;
+        c
+    }
+
+    fn eat(&mut self, text: &str) -> bool {
+        let matches = text.chars().enumerate().all(|(i, c)| self.chars.get(self.pos + i) == Some(&c));
+        if matches {
+            self.pos += text.chars().count();
+        }
+        matches
+    }
+
+    fn alternation(&mut self) -> Result<Vec<Vec<Node>>, String> {
+        let mut alternatives = vec![self.sequence()?];
+        while self.peek() == Some('|') {
+            self.pos += 1;
+            alternatives.push(self.sequence()?);
+        }
+        Ok(alternatives)
+    }
+
+    fn sequence(&mut self) -> Result<Vec<Node>, String> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            nodes.push(self.quantified(atom)?);
+        }
+        Ok(nodes)
+    }
+
+    fn quantified(&mut self, atom: Node) -> Result<Node, String> {
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => match self.bounds() {
+                Some(bounds) => bounds,
+                // A `{` that doesn't start a quantifier is a literal.
+                None => return Ok(atom),
+            },
+            _ => return Ok(atom),
+        };
+        // Step over the quantifier character or the closing brace.
+        self.pos += 1;
+        // Lazy and possessive suffixes don't change what matches.
+        if matches!(self.peek(), Some('?') | Some('+')) {
+            self.pos += 1;
+        }
+        Ok(Node::Repeat(Box::new(atom), min, max))
+    }
+
+    /// Parses `{n}`, `{n,}` or `{n,m}` up to (not including) the closing brace.
+    fn bounds(&mut self) -> Option<(u32, Option<u32>)> {
+        let close = self.chars[self.pos..].iter().position(|&c| c == '}')? + self.pos;
+        let body: String = self.chars[self.pos + 1..close].iter().collect();
+        let (min, max) = match body.split_once(',') {
+            None => {
+                let n = body.trim().parse().ok()?;
+                (n, Some(n))
+            }
+            Some((min, max)) => {
+                let min: u32 = min.trim().parse().ok()?;
+                let max = match max.trim() {
+                    "" => None,
+                    max => Some(max.parse::<u32>().ok()?.max(min)),
+                };
+                (min, max)
+            }
+        };
+        self.pos = close;
+        Some((min, max))
+    }
+
+    fn atom(&mut self) -> Result<Node, String> {
+        match self.next() {
+            Some('(') => {
+                if self.eat("?=") || self.eat("?!") || self.eat("?<=") || self.eat("?<!") {
+                    return Err("Lookaround assertions are not supported in patterns".to_string());
+                }
+                if !self.eat("?:") && self.eat("?<") {
+                    // Named group: skip the name.
+                    while let Some(c) = self.next() {
+                        if c == '>' {
+                            break;
+                        }
+                    }
+                }
+                let alternatives = self.alternation()?;
+                if self.next() != Some(')') {
+                    return Err("Unclosed '(' in pattern".to_string());
+                }
+                Ok(Node::Group(alternatives))
+            }
+            Some('[') => self.class(),
+            Some('.') => Ok(Node::Any),
+            Some('^') => Ok(Node::Start),
+            Some('$') => Ok(Node::End),
+            Some('\\') => self.escape(),
+            Some(c) => Ok(Node::Literal(c)),
+            None => Err("Unexpected end of pattern".to_string()),
+        }
+    }
+
+    fn escape(&mut self) -> Result<Node, String> {
+        let c = self.next().ok_or("Pattern ends with '\\'")?;
+        Ok(match c {
+            'd' => Node::Class { ranges: DIGITS.to_vec(), negated: false },
+            'w' => Node::Class { ranges: WORD.to_vec(), negated: false },
+            's' => Node::Class { ranges: SPACE.to_vec(), negated: false },
+            'D' => Node::Class { ranges: DIGITS.to_vec(), negated: true },
+            'W' => Node::Class { ranges: WORD.to_vec(), negated: true },
+            'S' => Node::Class { ranges: SPACE.to_vec(), negated: true },
+            'b' => Node::WordBoundary(true),
+            'B' => Node::WordBoundary(false),
+            '1'..='9' => return Err("Backreferences are not supported in patterns".to_string()),
+            _ => Node::Literal(self.escaped_char(c)?),
+        })
+    }
+
+    /// The character an escape like `\n`, `\x41` or `A` stands for.
+    fn escaped_char(&mut self, c: char) -> Result<char, String> {
+        let hex_digits = match c {
+            'n' => return Ok('\n'),
+            't' => return Ok('\t'),
+            'r' => return Ok('\r'),
+            'f' => return Ok('\u{c}'),
+            'v' => return Ok('\u{b}'),
+            '0' => return Ok('\0'),
+            'x' => 2,
+            'u' => 4,
+            other => return Ok(other),
+        };
+        let hex: String = self.chars.iter().skip(self.pos).take(hex_digits).collect();
+        self.pos += hex_digits;
+        u32::from_str_radix(&hex, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| format!("Invalid escape '\\{}{}' in pattern", c, hex))
+    }
+
+    fn class(&mut self) -> Result<Node, String> {
+        let negated = self.eat("^");
+        let mut ranges = Vec::new();
+        let mut first = true;
+        loop {
+            let c = self.next().ok_or("Unclosed '[' in pattern")?;
+            if c == ']' && !first {
+                break;
+            }
+            first = false;
+            let start = if c == '\\' {
+                match self.escape()? {
+                    Node::Literal(c) => c,
+                    Node::Class { ranges: set, negated: false } => {
+                        ranges.extend(set);
+                        continue;
+                    }
+                    // Negated shorthands inside a class are rare; skip them.
+                    _ => continue,
+                }
+            } else {
+                c
+            };
+            if self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|&c| c != ']') {
+                self.pos += 1;
+                let end = match self.next() {
+                    Some('\\') => match self.escape()? {
+                        Node::Literal(c) => c,
+                        _ => return Err("Invalid range in pattern character class".to_string()),
+                    },
+                    Some(c) => c,
+                    None => return Err("Unclosed '[' in pattern".to_string()),
+                };
+                if end < start {
+                    return Err(format!("Invalid range '{}-{}' in pattern", start, end));
+                }
+                ranges.push((start, end));
+            } else {
+                ranges.push((start, start));
+            }
+        }
+        Ok(Node::Class { ranges, negated })
+    }
+}
+
+impl Node {
+    fn generate(&self, rng: &mut Rng, out: &mut String) {
+        match self {
+            Node::Literal(c) => out.push(*c),
+            Node::Class { ranges, negated: false } => {
+                let total: u32 = ranges.iter().map(|(a, b)| *b as u32 - *a as u32 + 1).sum();
+                let mut index = rng.below(total.max(1) as u64) as u32;
+                for (start, end) in ranges {
+                    let size = *end as u32 - *start as u32 + 1;
+                    if index < size {
+                        out.extend(char::from_u32(*start as u32 + index));
+                        return;
+                    }
+                    index -= size;
+                }
+            }
+            Node::Class { ranges, negated: true } => {
+                let allowed: Vec<char> = ('!'..='~')
+                    .filter(|c| !ranges.iter().any(|(start, end)| (start..=end).contains(&c)))
+                    .collect();
+                if !allowed.is_empty() {
+                    out.push(allowed[rng.below(allowed.len() as u64) as usize]);
+                }
+            }
+            Node::Group(alternatives) => {
+                let chosen = &alternatives[rng.below(alternatives.len() as u64) as usize];
+                for node in chosen {
+                    node.generate(rng, out);
+                }
+            }
+            Node::Any => Node::Class { ranges: WORD.to_vec(), negated: false }.generate(rng, out),
+            Node::Repeat(node, min, max) => {
+                let max = max.unwrap_or(*min + MAX_EXTRA_REPEATS);
+                let times = *min + rng.below((max - *min + 1) as u64) as u32;
+                for _ in 0..times {
+                    node.generate(rng, out);
+                }
+            }
+            Node::Start | Node::End | Node::WordBoundary(_) => {}
+        }
+    }
+
+    fn length_range(&self) -> (usize, Option<usize>) {
+        match self {
+            Node::Literal(_) | Node::Class { .. } | Node::Any => (1, Some(1)),
+            Node::Start | Node::End | Node::WordBoundary(_) => (0, Some(0)),
+            Node::Group(alternatives) => {
+                let ranges: Vec<(usize, Option<usize>)> =
+                    alternatives.iter().map(|sequence| sequence_range(sequence)).collect();
+                let lo = ranges.iter().map(|(lo, _)| *lo).min().unwrap_or(0);
+                let hi = ranges.iter().try_fold(0, |longest, (_, hi)| hi.map(|hi| longest.max(hi)));
+                (lo, hi)
+            }
+            Node::Repeat(node, min, max) => {
+                let (lo, hi) = node.length_range();
+                let longest = match (hi, max) {
+                    (Some(0), _) => Some(0),
+                    (Some(hi), Some(max)) => Some(hi.saturating_mul(*max as usize)),
+                    _ => None,
+                };
+                (lo.saturating_mul(*min as usize), longest)
+            }
+        }
+    }
+
+    /// Like `generate`, but produces exactly `target` characters; false
+    /// when this node can't (e.g. an even-length repeat asked for 5).
+    fn generate_sized(&self, rng: &mut Rng, target: usize, out: &mut String) -> bool {
+        match self {
+            Node::Literal(_) | Node::Class { .. } | Node::Any => {
+                let before = out.chars().count();
+                if target == 1 {
+                    self.generate(rng, out);
+                }
+                out.chars().count() == before + target
+            }
+            Node::Start | Node::End | Node::WordBoundary(_) => target == 0,
+            Node::Group(alternatives) => {
+                let fitting: Vec<&Vec<Node>> = alternatives
+                    .iter()
+                    .filter(|sequence| {
+                        let (lo, hi) = sequence_range(sequence);
+                        lo <= target && hi.is_none_or(|hi| target <= hi)
+                    })
+                    .collect();
+                if fitting.is_empty() {
+                    return false;
+                }
+                let chosen = fitting[rng.below(fitting.len() as u64) as usize];
+                generate_sequence_sized(chosen, rng, target, out)
+            }
+            Node::Repeat(node, min, max) => {
+                let (lo, hi) = node.length_range();
+                let most = match max {
+                    Some(max) => *max as usize,
+                    None if lo > 0 => target / lo,
+                    None => (*min as usize).max(target),
+                };
+                let counts: Vec<usize> = (*min as usize..=most.min(*min as usize + target))
+                    .filter(|&count| {
+                        lo.saturating_mul(count) <= target && hi.is_none_or(|hi| hi.saturating_mul(count) >= target)
+                    })
+                    .take(MAX_EXTRA_REPEATS as usize + 1)
+                    .collect();
+                if counts.is_empty() {
+                    return false;
+                }
+                let count = counts[rng.below(counts.len() as u64) as usize];
+                match split_length(rng, target, &vec![(lo, hi); count]) {
+                    Some(lengths) => lengths.into_iter().all(|length| node.generate_sized(rng, length, out)),
+                    None => false,
+                }
+            }
+        }
+    }
+}
+
+/// One step of a compiled pattern; see `Program`.
+#[derive(Debug)]
+enum Inst {
+    Char(char),
+    Class { ranges: Vec<(char, char)>, negated: bool },
+    Any,
+    /// Continue at both targets.
+    Split(usize, usize),
+    Jump(usize),
+    Start,
+    End,
+    WordBoundary(bool),
+    Match,
+}
+
+/// A pattern compiled for a Pike VM: every candidate match advances through
+/// the text one character at a time in lockstep, so matching never backtracks
+/// and takes time proportional to the program size times the text length.
+struct Program(Vec<Inst>);
+
+/// Active instructions waiting on the character at one position.
+struct Threads {
+    pcs: Vec<usize>,
+    /// The position (plus one) each instruction was last added at.
+    seen: Vec<usize>,
+    stack: Vec<usize>,
+    matched: bool,
+}
+
+impl Program {
+    fn compile(node: &Node) -> Result<Program, String> {
+        let mut program = Program(Vec::new());
+        program.emit(node)?;
+        program.push(Inst::Match)?;
+        Ok(program)
+    }
+
+    fn push(&mut self, inst: Inst) -> Result<usize, String> {
+        if self.0.len() >= MAX_PROGRAM_SIZE {
+            return Err("Pattern is too large to match".to_string());
+        }
+        self.0.push(inst);
+        Ok(self.0.len() - 1)
+    }
+
+    fn emit(&mut self, node: &Node) -> Result<(), String> {
+        match node {
+            Node::Literal(c) => {
+                self.push(Inst::Char(*c))?;
+            }
+            Node::Class { ranges, negated } => {
+                self.push(Inst::Class { ranges: ranges.clone(), negated: *negated })?;
+            }
+            Node::Any => {
+                self.push(Inst::Any)?;
+            }
+            Node::Start => {
+                self.push(Inst::Start)?;
+            }
+            Node::End => {
+                self.push(Inst::End)?;
+            }
+            Node::WordBoundary(expected) => {
+                self.push(Inst::WordBoundary(*expected))?;
+            }
+            Node::Group(alternatives) => {
+                let mut jumps = Vec::new();
+                for (i, sequence) in alternatives.iter().enumerate() {
+                    let split = if i + 1 < alternatives.len() { Some(self.push(Inst::Split(0, 0))?) } else { None };
+                    for node in sequence {
+                        self.emit(node)?;
+                    }
+                    if let Some(split) = split {
+                        jumps.push(self.push(Inst::Jump(0))?);
+                        self.0[split] = Inst::Split(split + 1, self.0.len());
+                    }
+                }
+                let end = self.0.len();
+                for jump in jumps {
+                    self.0[jump] = Inst::Jump(end);
+                }
+            }
+            Node::Repeat(node, min, max) => {
+                // An empty group compiles to nothing, and so does any count of it.
+                let start = self.0.len();
+                self.emit(node)?;
+                let empty = self.0.len() == start;
+                self.0.truncate(start);
+                if empty {
+                    return Ok(());
+                }
+                for _ in 0..*min {
+                    self.emit(node)?;
+                }
+                match max {
+                    Some(max) => {
+                        let mut splits = Vec::new();
+                        for _ in *min..*max {
+                            splits.push(self.push(Inst::Split(0, 0))?);
+                            self.emit(node)?;
+                        }
+                        let end = self.0.len();
+                        for split in splits {
+                            self.0[split] = Inst::Split(split + 1, end);
+                        }
+                    }
+                    None => {
+                        let split = self.push(Inst::Split(0, 0))?;
+                        self.emit(node)?;
+                        self.push(Inst::Jump(split))?;
+                        self.0[split] = Inst::Split(split + 1, self.0.len());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the pattern matches anywhere in `text`.
+    fn matches(&self, text: &str) -> bool {
+        let chars: Vec<char> = text.chars().collect();
+        let mut current = Threads::new(self.0.len());
+        let mut next = Threads::new(self.0.len());
+        for pos in 0..=chars.len() {
+            // Unanchored: a match may start at any position.
+            current.add(self, 0, &chars, pos);
+            if current.matched {
+                return true;
+            }
+            let c = match chars.get(pos) {
+                Some(&c) => c,
+                None => break,
+            };
+            next.clear();
+            for &pc in &current.pcs {
+                let consumed = match &self.0[pc] {
+                    Inst::Char(expected) => *expected == c,
+                    Inst::Class { ranges, negated } => {
+                        ranges.iter().any(|(start, end)| (*start..=*end).contains(&c)) != *negated
+                    }
+                    Inst::Any => c != '\n' && c != '\r',
+                    _ => false,
+                };
+                if consumed {
+                    next.add(self, pc + 1, &chars, pos + 1);
+                }
+            }
+            std::mem::swap(&mut current, &mut next);
+        }
+        false
+    }
+}
+
+impl Threads {
+    fn new(size: usize) -> Threads {
+        Threads { pcs: Vec::new(), seen: vec![0; size], stack: Vec::new(), matched: false }
+    }
+
+    fn clear(&mut self) {
+        self.pcs.clear();
+        self.matched = false;
+    }
+
+    /// Adds the thread at `pc`, following jumps, splits and assertions to the
+    /// instructions that consume a character.
+    fn add(&mut self, program: &Program, pc: usize, text: &[char], pos: usize) {
+        let is_word =
+            |i: Option<usize>| i.and_then(|i| text.get(i)).is_some_and(|c| c.is_ascii_alphanumeric() || *c == '_');
+        self.stack.push(pc);
+        while let Some(pc) = self.stack.pop() {
+            if self.seen[pc] == pos + 1 {
+                continue;
+            }
+            self.seen[pc] = pos + 1;
+            match &program.0[pc] {
+                Inst::Split(first, second) => {
+                    self.stack.push(*second);
+                    self.stack.push(*first);
+                }
+                Inst::Jump(target) => self.stack.push(*target),
+                Inst::Start if pos == 0 => self.stack.push(pc + 1),
+                Inst::End if pos == text.len() => self.stack.push(pc + 1),
+                Inst::WordBoundary(expected) if (is_word(pos.checked_sub(1)) != is_word(Some(pos))) == *expected => {
+                    self.stack.push(pc + 1)
+                }
+                Inst::Start | Inst::End | Inst::WordBoundary(_) => {}
+                Inst::Match => self.matched = true,
+                Inst::Char(_) | Inst::Class { .. } | Inst::Any => self.pcs.push(pc),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_strings_match() {
+        let mut rng = Rng::new(3);
+        for pattern in ["^[A-Z]{2,4}-\\d{3}$", "^(cat|dog)s?$", "^[^a-z]+\\.x$", "\\bid_\\w+"] {
+            for _ in 0..20 {
+                let text = generate(pattern, &mut rng).unwrap();
+                assert!(is_match(pattern, &text).unwrap(), "{} from {}", text, pattern);
+            }
+        }
+    }
+
+    #[test]
+    fn sized_generation_hits_the_length() {
+        let mut rng = Rng::new(3);
+        let text = generate_within("^[A-Z]{3}-\\d+$", &mut rng, 12, Some(12)).unwrap().unwrap();
+        assert_eq!(text.chars().count(), 12);
+        assert!(is_match("^[A-Z]{3}-\\d+$", &text).unwrap());
+        assert_eq!(generate_within("^(ab)+$", &mut rng, 5, Some(5)).unwrap(), None);
+        assert_eq!(generate_within("^a{2}$", &mut rng, 3, None).unwrap(), None);
+    }
+
+    #[test]
+    fn matching_is_unanchored_unless_asked() {
+        assert!(is_match("b+", "abbc").unwrap());
+        assert!(!is_match("^b+$", "abbc").unwrap());
+        assert!(parse("(unclosed").is_err());
+    }
+
+    #[test]
+    fn long_inputs_match_without_deep_recursion() {
+        let text = "a".repeat(100_000);
+        assert!(is_match("^[a-z]*$", &text).unwrap());
+        assert!(!is_match("^[a-z]*$", &format!("{}1", text)).unwrap());
+    }
+
+    #[test]
+    fn nested_quantifiers_do_not_backtrack() {
+        let text = format!("{}c", "a".repeat(5_000));
+        for pattern in ["^(a|a)*$", "^(a+)+$", "^(a*)*b$", "^(a?){50}a{50}$", "^(()|a)*$"] {
+            assert!(!is_match(pattern, &text).unwrap(), "{}", pattern);
+        }
+        assert!(is_match("^(a+)+c$", &text).unwrap());
+        assert!(is_match("^(a?){3}a{3}$", "aaa").unwrap());
+        assert!(is_match("^(\\bab\\b ?)+$", "ab ab").unwrap());
+        assert!(is_match("a{1000000}", "a").is_err());
+    }
+}
@@ -0,0 +1,1602 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use js_sys::Uint8Array;
+use serde_json::{Map, Number, Value};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::convert::TryFrom;
+use wasm_bindgen::prelude::*;
+
+use crate::binary_formats::{decode_error, MAX_DEPTH};
+use crate::extended_json::civil_from_days;
+use crate::naming::{dedupe_names, pascal_case, quoted, screaming_snake_case, snake_case};
+use crate::schema_types::{
+    push_line_doc, referenced_names, rust_variant_name, FieldDef, StringFormat, TypeDef, TypeKind, TypeModel, TypeRef,
+};
+
+// Protocol Buffers binary decoding, and `.proto` generation from a type model.
+//
+// Messages are described by `.proto` source text, which is parsed into a
+// table of message and enum definitions. Only the given file is read, so the
+// well-known `google.protobuf` types are built in, and other imported types
+// have to be pasted into the same text. The output follows the canonical
+// proto3 JSON mapping:
+//
+// - fields use their JSON names (lowerCamelCase or `json_name`)
+// - 64-bit integers are strings, bytes are base64 and enums are value names
+// - fields at their default value are left out unless the field tracks
+//   presence (proto2, `optional`, `oneof` members and messages)
+// - well-known types take their special forms, e.g. Timestamps become
+//   RFC 3339 strings and `Any` embeds its message with an `@type` key
+//
+// Unknown fields are skipped. Input starting with a zero byte is read as gRPC
+// length-prefixed frames, since no message can start with field number 0.
+
+/// Decodes protobuf binary into pretty-printed JSON. `proto_source` is the
+/// `.proto` text defining the message; `message_type` names it (`Name` or
+/// `package.Name`) and defaults to the file's first top-level message.
+#[wasm_bindgen]
+pub fn proto_to_json(bytes: &Uint8Array, proto_source: &str, message_type: Option<String>) -> Result<String, JsValue> {
+    let schema = ProtoSchema::parse(proto_source).map_err(|e| JsValue::from_str(&e))?;
+    let message = schema.message_type(message_type.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    let value = decode_protobuf(&schema, &message, &bytes.to_vec()).map_err(|e| JsValue::from_str(&e))?;
+    serde_json::to_string_pretty(&value).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Decodes one message, or a JSON array of them for several gRPC frames.
+pub(crate) fn decode_protobuf(schema: &ProtoSchema, message: &str, bytes: &[u8]) -> Result<Value, String> {
+    let decoder = Decoder { schema, bytes };
+    let result = match bytes.first() {
+        Some(0) | Some(1) => decoder.frames(message),
+        _ => decoder.message(message, 0, bytes.len(), MAX_DEPTH),
+    };
+    result.map_err(|(offset, message)| decode_error("Protobuf", bytes, offset, &message))
+}
+
+// The well-known types, as far as decoding needs them.
+const WELL_KNOWN_TYPES: &str = r#"
+syntax = "proto3";
+package google.protobuf;
+message Timestamp { int64 seconds = 1; int32 nanos = 2; }
+message Duration { int64 seconds = 1; int32 nanos = 2; }
+message DoubleValue { double value = 1; }
+message FloatValue { float value = 1; }
+message Int64Value { int64 value = 1; }
+message UInt64Value { uint64 value = 1; }
+message Int32Value { int32 value = 1; }
+message UInt32Value { uint32 value = 1; }
+message BoolValue { bool value = 1; }
+message StringValue { string value = 1; }
+message BytesValue { bytes value = 1; }
+message Empty {}
+message FieldMask { repeated string paths = 1; }
+message Any { string type_url = 1; bytes value = 2; }
+message Struct { map<string, Value> fields = 1; }
+message Value {
+  oneof kind {
+    NullValue null_value = 1;
+    double number_value = 2;
+    string string_value = 3;
+    bool bool_value = 4;
+    Struct struct_value = 5;
+    ListValue list_value = 6;
+  }
+}
+enum NullValue { NULL_VALUE = 0; }
+message ListValue { repeated Value values = 1; }
+"#;
+
+/// Well-known types whose JSON form isn't an object of their fields.
+const CUSTOM_JSON_TYPES: &[&str] = &[
+    "google.protobuf.Timestamp",
+    "google.protobuf.Duration",
+    "google.protobuf.DoubleValue",
+    "google.protobuf.FloatValue",
+    "google.protobuf.Int64Value",
+    "google.protobuf.UInt64Value",
+    "google.protobuf.Int32Value",
+    "google.protobuf.UInt32Value",
+    "google.protobuf.BoolValue",
+    "google.protobuf.StringValue",
+    "google.protobuf.BytesValue",
+    "google.protobuf.FieldMask",
+    "google.protobuf.Any",
+    "google.protobuf.Struct",
+    "google.protobuf.Value",
+    "google.protobuf.ListValue",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Scalar {
+    Double,
+    Float,
+    Int64,
+    Uint64,
+    Int32,
+    Fixed64,
+    Fixed32,
+    Bool,
+    String,
+    Bytes,
+    Uint32,
+    Sfixed32,
+    Sfixed64,
+    Sint32,
+    Sint64,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Option<Scalar> {
+        Some(match name {
+            "double" => Scalar::Double,
+            "float" => Scalar::Float,
+            "int64" => Scalar::Int64,
+            "uint64" => Scalar::Uint64,
+            "int32" => Scalar::Int32,
+            "fixed64" => Scalar::Fixed64,
+            "fixed32" => Scalar::Fixed32,
+            "bool" => Scalar::Bool,
+            "string" => Scalar::String,
+            "bytes" => Scalar::Bytes,
+            "uint32" => Scalar::Uint32,
+            "sfixed32" => Scalar::Sfixed32,
+            "sfixed64" => Scalar::Sfixed64,
+            "sint32" => Scalar::Sint32,
+            "sint64" => Scalar::Sint64,
+            _ => return None,
+        })
+    }
+
+    fn wire_type(self) -> u64 {
+        match self {
+            Scalar::Double | Scalar::Fixed64 | Scalar::Sfixed64 => 1,
+            Scalar::String | Scalar::Bytes => 2,
+            Scalar::Float | Scalar::Fixed32 | Scalar::Sfixed32 => 5,
+            _ => 0,
+        }
+    }
+
+    fn default_value(self) -> Value {
+        match self {
+            Scalar::Int64 | Scalar::Uint64 | Scalar::Fixed64 | Scalar::Sfixed64 | Scalar::Sint64 => Value::from("0"),
+            Scalar::Bool => Value::Bool(false),
+            Scalar::String | Scalar::Bytes => Value::from(""),
+            _ => Value::from(0),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum FieldType {
+    Scalar(Scalar),
+    /// A type name not yet resolved to a message or enum.
+    Named(String),
+    Message(String),
+    Enum(String),
+}
+
+#[derive(Debug, Clone)]
+struct Field {
+    name: String,
+    json_name: String,
+    number: u64,
+    repeated: bool,
+    /// Whether an unset field differs from one set to its default.
+    presence: bool,
+    ty: FieldType,
+}
+
+#[derive(Debug, Default)]
+struct MessageDef {
+    fields: Vec<Field>,
+    /// The synthesized entry type of a `map<K, V>` field.
+    map_entry: bool,
+}
+
+/// The messages and enums of a `.proto` file plus the well-known types,
+/// keyed by full name (`package.Outer.Inner`).
+#[derive(Debug, Default)]
+pub(crate) struct ProtoSchema {
+    messages: HashMap<String, MessageDef>,
+    enums: HashMap<String, HashMap<i32, String>>,
+    /// The file's own top-level messages, in order.
+    top_level: Vec<String>,
+}
+
+impl ProtoSchema {
+    pub(crate) fn parse(source: &str) -> Result<ProtoSchema, String> {
+        let mut schema = ProtoSchema::default();
+        ProtoParser::new(source)?.file(&mut schema, true)?;
+        ProtoParser::new(WELL_KNOWN_TYPES)?.file(&mut schema, false)?;
+        schema.resolve()?;
+        Ok(schema)
+    }
+
+    /// The full name of the message `name` refers to.
+    pub(crate) fn message_type(&self, name: Option<&str>) -> Result<String, String> {
+        let name = match name.map(|name| name.trim().trim_start_matches('.')).filter(|name| !name.is_empty()) {
+            Some(name) => name,
+            None => {
+                return self
+                    .top_level
+                    .first()
+                    .cloned()
+                    .ok_or_else(|| "The .proto source defines no messages".to_string())
+            }
+        };
+        if self.messages.contains_key(name) {
+            return Ok(name.to_string());
+        }
+        let suffix = format!(".{}", name);
+        let mut matches: Vec<&String> = self.messages.keys().filter(|full| full.ends_with(&suffix)).collect();
+        matches.sort();
+        match matches.as_slice() {
+            [full] => Ok(full.to_string()),
+            [] => {
+                let mut available = self.top_level.clone();
+                available.sort();
+                Err(format!("No message named '{}' (the file defines {})", name, available.join(", ")))
+            }
+            _ => Err(format!(
+                "'{}' is ambiguous, use one of {}",
+                name,
+                matches.iter().map(|m| m.as_str()).collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+
+    fn resolve(&mut self) -> Result<(), String> {
+        let mut resolved = Vec::new();
+        for (message, def) in &self.messages {
+            for (i, field) in def.fields.iter().enumerate() {
+                if let FieldType::Named(name) = &field.ty {
+                    let ty = self.lookup(name, message).ok_or_else(|| {
+                        format!(
+                            "Unknown type '{}' for field '{}' of {} (imported types other than \
+                             google.protobuf ones must be included in the source)",
+                            name, field.name, message
+                        )
+                    })?;
+                    resolved.push((message.clone(), i, ty));
+                }
+            }
+        }
+        for (message, i, ty) in resolved {
+            let field = &mut self.messages.get_mut(&message).expect("message exists").fields[i];
+            if let FieldType::Message(_) = ty {
+                field.presence = true;
+            }
+            field.ty = ty;
+        }
+        Ok(())
+    }
+
+    /// Finds `name` as protobuf scoping does: in `scope`, then each
+    /// enclosing scope out to the top level.
+    fn lookup(&self, name: &str, scope: &str) -> Option<FieldType> {
+        let found = |full: &str| {
+            if self.messages.contains_key(full) {
+                Some(FieldType::Message(full.to_string()))
+            } else if self.enums.contains_key(full) {
+                Some(FieldType::Enum(full.to_string()))
+            } else {
+                None
+            }
+        };
+        if let Some(full) = name.strip_prefix('.') {
+            return found(full);
+        }
+        let mut scope = scope;
+        loop {
+            let candidate = if scope.is_empty() { name.to_string() } else { format!("{}.{}", scope, name) };
+            if let Some(ty) = found(&candidate) {
+                return Some(ty);
+            }
+            if scope.is_empty() {
+                return None;
+            }
+            scope = scope.rfind('.').map_or("", |dot| &scope[..dot]);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(String),
+    Str(String),
+    Symbol(char),
+}
+
+struct ProtoParser {
+    tokens: Vec<(Token, usize, usize)>,
+    pos: usize,
+    package: String,
+    proto3: bool,
+}
+
+impl ProtoParser {
+    fn new(source: &str) -> Result<ProtoParser, String> {
+        Ok(ProtoParser { tokens: tokenize(source)?, pos: 0, package: String::new(), proto3: false })
+    }
+
+    fn error(&self, message: &str) -> String {
+        match self.tokens.get(self.pos).or_else(|| self.tokens.last()) {
+            Some((_, line, column)) => format!("Proto parsing error at line {} column {}: {}", line, column, message),
+            None => format!("Proto parsing error: {}", message),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _, _)| token)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self.peek().cloned().ok_or_else(|| self.error("unexpected end of file"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        let found = self.peek() == Some(&Token::Symbol(symbol));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), String> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", symbol)))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(Token::Ident(name)) => {
+                let name = name.clone();
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => Err(self.error("expected a name")),
+        }
+    }
+
+    fn is_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(name)) if name == word)
+    }
+
+    fn integer(&mut self) -> Result<i64, String> {
+        let negative = self.eat('-');
+        match self.peek() {
+            Some(Token::Number(text)) => {
+                let value = parse_integer(text).ok_or_else(|| self.error(&format!("'{}' is not an integer", text)))?;
+                self.pos += 1;
+                Ok(if negative { -value } else { value })
+            }
+            _ => Err(self.error("expected an integer")),
+        }
+    }
+
+    /// Skips a statement such as `option ...;` or `reserved ...;`, including
+    /// any `{ ... }` aggregate values in it.
+    fn skip_statement(&mut self) -> Result<(), String> {
+        let mut depth = 0;
+        loop {
+            match self.next()? {
+                Token::Symbol('{') => depth += 1,
+                Token::Symbol('}') => depth -= 1,
+                Token::Symbol(';') if depth == 0 => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+
+    /// Skips up to and including the end of the next `{ ... }` block.
+    fn skip_block(&mut self) -> Result<(), String> {
+        while !self.eat('{') {
+            self.next()?;
+        }
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next()? {
+                Token::Symbol('{') => depth += 1,
+                Token::Symbol('}') => depth -= 1,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn file(&mut self, schema: &mut ProtoSchema, own: bool) -> Result<(), String> {
+        while let Some(token) = self.peek().cloned() {
+            match token {
+                Token::Ident(word) if word == "syntax" || word == "edition" => {
+                    self.pos += 1;
+                    self.expect('=')?;
+                    self.proto3 = word == "syntax" && self.next()? == Token::Str("proto3".to_string());
+                    self.expect(';')?;
+                }
+                Token::Ident(word) if word == "package" => {
+                    self.pos += 1;
+                    self.package = self.ident()?;
+                    self.expect(';')?;
+                }
+                Token::Ident(word) if word == "import" || word == "option" => self.skip_statement()?,
+                Token::Ident(word) if word == "message" => {
+                    self.pos += 1;
+                    let prefix = self.package.clone();
+                    let name = self.message(schema, &prefix)?;
+                    if own {
+                        schema.top_level.push(name);
+                    }
+                }
+                Token::Ident(word) if word == "enum" => {
+                    self.pos += 1;
+                    let prefix = self.package.clone();
+                    self.enumeration(schema, &prefix)?;
+                }
+                Token::Ident(word) if word == "service" || word == "extend" => self.skip_block()?,
+                Token::Symbol(';') => self.pos += 1,
+                _ => return Err(self.error("expected a message, enum, service or option")),
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses a message body after the `message` keyword and returns its
+    /// full name.
+    fn message(&mut self, schema: &mut ProtoSchema, prefix: &str) -> Result<String, String> {
+        let full = qualify(prefix, &self.ident()?);
+        self.expect('{')?;
+        let mut def = MessageDef::default();
+        while !self.eat('}') {
+            match self.peek() {
+                Some(Token::Ident(word)) => match word.as_str() {
+                    "message" => {
+                        self.pos += 1;
+                        self.message(schema, &full)?;
+                    }
+                    "enum" => {
+                        self.pos += 1;
+                        self.enumeration(schema, &full)?;
+                    }
+                    "oneof" => {
+                        self.pos += 1;
+                        self.ident()?;
+                        self.expect('{')?;
+                        while !self.eat('}') {
+                            if self.is_word("option") {
+                                self.skip_statement()?;
+                            } else if !self.eat(';') {
+                                let mut field = self.field(false)?;
+                                field.presence = true;
+                                def.fields.push(field);
+                            }
+                        }
+                    }
+                    "option" | "reserved" | "extensions" => self.skip_statement()?,
+                    "extend" => self.skip_block()?,
+                    "map" if self.tokens.get(self.pos + 1).map(|t| &t.0) == Some(&Token::Symbol('<')) => {
+                        def.fields.push(self.map_field(schema, &full)?);
+                    }
+                    _ => def.fields.push(self.field(true)?),
+                },
+                Some(Token::Symbol(';')) => self.pos += 1,
+                _ => return Err(self.error("expected a field, message, enum or option")),
+            }
+        }
+        schema.messages.entry(full.clone()).or_insert(def);
+        Ok(full)
+    }
+
+    /// `[label] type name = number [options];`
+    fn field(&mut self, labelled: bool) -> Result<Field, String> {
+        let label = match self.peek() {
+            Some(Token::Ident(word)) if labelled && matches!(word.as_str(), "repeated" | "optional" | "required") => {
+                let word = word.clone();
+                self.pos += 1;
+                Some(word)
+            }
+            _ => None,
+        };
+        let repeated = label.as_deref() == Some("repeated");
+        let presence = !self.proto3 || matches!(label.as_deref(), Some("optional") | Some("required"));
+        let type_name = self.ident()?;
+        if type_name == "group" {
+            return Err(self.error("proto2 groups aren't supported"));
+        }
+        let name = self.ident()?;
+        self.expect('=')?;
+        let number = self.field_number()?;
+        let json_name = self.field_options()?.unwrap_or_else(|| proto_json_name(&name));
+        self.expect(';')?;
+        let ty = Scalar::parse(&type_name).map_or(FieldType::Named(type_name), FieldType::Scalar);
+        Ok(Field { name, json_name, number, repeated, presence: presence && !repeated, ty })
+    }
+
+    /// `map<K, V> name = number [options];`, which is a repeated field of
+    /// a synthesized `NameEntry` message with `key` and `value` fields.
+    fn map_field(&mut self, schema: &mut ProtoSchema, message: &str) -> Result<Field, String> {
+        self.pos += 1;
+        self.expect('<')?;
+        let key_type = self.ident()?;
+        let key = Scalar::parse(&key_type).ok_or_else(|| self.error(&format!("'{}' can't be a map key", key_type)))?;
+        self.expect(',')?;
+        let value_type = self.ident()?;
+        self.expect('>')?;
+        let name = self.ident()?;
+        self.expect('=')?;
+        let number = self.field_number()?;
+        let json_name = self.field_options()?.unwrap_or_else(|| proto_json_name(&name));
+        self.expect(';')?;
+
+        let entry = format!("{}.{}Entry", message, pascal_case(&name));
+        let entry_field = |name: &str, number: u64, ty: FieldType| Field {
+            name: name.to_string(),
+            json_name: name.to_string(),
+            number,
+            repeated: false,
+            presence: false,
+            ty,
+        };
+        let value = Scalar::parse(&value_type).map_or(FieldType::Named(value_type), FieldType::Scalar);
+        let fields = vec![entry_field("key", 1, FieldType::Scalar(key)), entry_field("value", 2, value)];
+        schema.messages.insert(entry.clone(), MessageDef { fields, map_entry: true });
+        Ok(Field { name, json_name, number, repeated: true, presence: false, ty: FieldType::Message(entry) })
+    }
+
+    fn field_number(&mut self) -> Result<u64, String> {
+        let number = self.integer()?;
+        match u64::try_from(number) {
+            Ok(number) if (1..=0x1fff_ffff).contains(&number) => Ok(number),
+            _ => Err(self.error(&format!("field number {} is out of range", number))),
+        }
+    }
+
+    /// Skips `[...]` field options, returning the `json_name` if one is set.
+    fn field_options(&mut self) -> Result<Option<String>, String> {
+        let mut json_name = None;
+        if !self.eat('[') {
+            return Ok(None);
+        }
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next()? {
+                Token::Symbol('[') | Token::Symbol('{') => depth += 1,
+                Token::Symbol(']') | Token::Symbol('}') => depth -= 1,
+                Token::Ident(word) if word == "json_name" && depth == 1 => {
+                    self.expect('=')?;
+                    match self.next()? {
+                        Token::Str(name) => json_name = Some(name),
+                        _ => return Err(self.error("json_name must be a string")),
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(json_name)
+    }
+
+    fn enumeration(&mut self, schema: &mut ProtoSchema, prefix: &str) -> Result<(), String> {
+        let full = qualify(prefix, &self.ident()?);
+        self.expect('{')?;
+        let mut values = HashMap::new();
+        while !self.eat('}') {
+            if self.is_word("option") || self.is_word("reserved") {
+                self.skip_statement()?;
+            } else if !self.eat(';') {
+                let name = self.ident()?;
+                self.expect('=')?;
+                let number = self.integer()?;
+                let number =
+                    i32::try_from(number).map_err(|_| self.error(&format!("enum value {} is out of range", number)))?;
+                self.field_options()?;
+                self.expect(';')?;
+                // With `allow_alias` the first name is the one JSON uses.
+                values.entry(number).or_insert(name);
+            }
+        }
+        schema.enums.entry(full).or_insert(values);
+        Ok(())
+    }
+}
+
+fn qualify(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", prefix, name)
+    }
+}
+
+fn parse_integer(text: &str) -> Option<i64> {
+    if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        i64::from_str_radix(hex, 16).ok()
+    } else if text.len() > 1 && text.starts_with('0') {
+        i64::from_str_radix(&text[1..], 8).ok()
+    } else {
+        text.parse().ok()
+    }
+}
+
+fn lex_error(line: usize, column: usize, message: &str) -> String {
+    format!("Proto parsing error at line {} column {}: {}", line, column, message)
+}
+
+/// Splits `.proto` source into tokens with their line and column.
+fn tokenize(source: &str) -> Result<Vec<(Token, usize, usize)>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let (mut i, mut line, mut line_start) = (0, 1, 0);
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i - line_start + 1;
+        if c == '\n' {
+            line += 1;
+            line_start = i + 1;
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                if chars[i] == '\n' {
+                    line += 1;
+                    line_start = i + 1;
+                }
+                i += 1;
+            }
+            if i >= chars.len() {
+                return Err(lex_error(line, column, "unterminated comment"));
+            }
+            i += 2;
+        } else if c.is_ascii_alphabetic()
+            || c == '_'
+            || c == '.' && chars.get(i + 1).is_some_and(|n| n.is_ascii_alphabetic() || *n == '_')
+        {
+            let start = i;
+            i += 1;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_' || chars[i] == '.') {
+                i += 1;
+            }
+            tokens.push((Token::Ident(chars[start..i].iter().collect()), line, column));
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                i += 1;
+            }
+            tokens.push((Token::Number(chars[start..i].iter().collect()), line, column));
+        } else if c == '"' || c == '\'' {
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None | Some('\n') => return Err(lex_error(line, column, "unterminated string")),
+                    Some(q) if *q == c => break,
+                    Some('\\') => {
+                        i += 1;
+                        match chars.get(i) {
+                            Some('n') => text.push('\n'),
+                            Some('t') => text.push('\t'),
+                            Some('r') => text.push('\r'),
+                            Some('x') | Some('X') => {
+                                let digits: String =
+                                    chars[i + 1..].iter().take(2).take_while(|c| c.is_ascii_hexdigit()).collect();
+                                text.push(
+                                    u8::from_str_radix(&digits, 16)
+                                        .map_err(|_| lex_error(line, column, "invalid \\x escape"))?
+                                        as char,
+                                );
+                                i += digits.len();
+                            }
+                            Some(d) if d.is_digit(8) => {
+                                let digits: String = chars[i..].iter().take(3).take_while(|c| c.is_digit(8)).collect();
+                                text.push(
+                                    u8::from_str_radix(&digits, 8)
+                                        .map_err(|_| lex_error(line, column, "invalid octal escape"))?
+                                        as char,
+                                );
+                                i += digits.len() - 1;
+                            }
+                            Some(other) => text.push(*other),
+                            None => return Err(lex_error(line, column, "unterminated string")),
+                        }
+                    }
+                    Some(other) => text.push(*other),
+                }
+                i += 1;
+            }
+            i += 1;
+            tokens.push((Token::Str(text), line, column));
+        } else {
+            tokens.push((Token::Symbol(c), line, column));
+            i += 1;
+        }
+    }
+    Ok(tokens)
+}
+
+/// A decoding failure: the byte offset and what went wrong there.
+type DecodeError = (usize, String);
+
+struct Decoder<'a> {
+    schema: &'a ProtoSchema,
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    /// gRPC frames: a compression flag, a big-endian u32 length and the message.
+    fn frames(&self, message: &str) -> Result<Value, DecodeError> {
+        let mut messages = Vec::new();
+        let mut pos = 0;
+        while pos < self.bytes.len() {
+            match self.bytes[pos] {
+                0 => {}
+                1 => return Err((pos, "compressed gRPC messages aren't supported".to_string())),
+                flag => return Err((pos, format!("gRPC frame flag is 0x{:02x}, not 0 or 1", flag))),
+            }
+            let header = self
+                .bytes
+                .get(pos + 1..pos + 5)
+                .ok_or_else(|| (self.bytes.len(), "unexpected end of input in a gRPC frame header".to_string()))?;
+            let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+            let start = pos + 5;
+            if start + len > self.bytes.len() {
+                return Err((pos + 1, format!("gRPC frame length {} runs past the end of input", len)));
+            }
+            messages.push(self.message(message, start, start + len, MAX_DEPTH)?);
+            pos = start + len;
+        }
+        Ok(if messages.len() == 1 { messages.remove(0) } else { Value::Array(messages) })
+    }
+
+    fn message(&self, name: &str, start: usize, end: usize, depth: usize) -> Result<Value, DecodeError> {
+        if depth == 0 {
+            return Err((start, "nesting is too deep".to_string()));
+        }
+        if name == "google.protobuf.Any" {
+            return self.any(start, end, depth);
+        }
+        let def = &self.schema.messages[name];
+        let mut values: Vec<Vec<(Value, bool)>> = vec![Vec::new(); def.fields.len()];
+        let mut pos = start;
+        while pos < end {
+            let tag_start = pos;
+            let (number, wire) = self.tag(&mut pos, end)?;
+            match def.fields.iter().position(|field| field.number == number) {
+                Some(index) => {
+                    self.field(&def.fields[index], (tag_start, wire), &mut pos, end, depth, &mut values[index])?
+                }
+                None => self.skip(number, wire, tag_start, &mut pos, end, depth)?,
+            }
+        }
+
+        let mut object = Map::new();
+        for (field, values) in def.fields.iter().zip(values) {
+            let value = if values.is_empty() {
+                continue;
+            } else if self.is_map(field) {
+                Value::Object(values.into_iter().map(|(entry, _)| self.map_entry(field, entry)).collect())
+            } else if field.repeated {
+                Value::Array(values.into_iter().map(|(value, _)| value).collect())
+            } else if let FieldType::Message(_) = field.ty {
+                values.into_iter().map(|(value, _)| value).reduce(merge).unwrap_or_default()
+            } else {
+                match values.into_iter().last() {
+                    Some((_, true)) if !field.presence => continue,
+                    Some((value, _)) => value,
+                    None => continue,
+                }
+            };
+            object.insert(field.json_name.clone(), value);
+        }
+        self.well_known(name, object).map_err(|message| (start, message))
+    }
+
+    fn tag(&self, pos: &mut usize, end: usize) -> Result<(u64, u64), DecodeError> {
+        let start = *pos;
+        let tag = self.varint(pos, end)?;
+        let number = tag >> 3;
+        if number == 0 || number > 0x1fff_ffff {
+            return Err((start, format!("field number {} is invalid", number)));
+        }
+        Ok((number, tag & 7))
+    }
+
+    fn varint(&self, pos: &mut usize, end: usize) -> Result<u64, DecodeError> {
+        let start = *pos;
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = *self
+                .bytes
+                .get(*pos)
+                .filter(|_| *pos < end)
+                .ok_or((end, "unexpected end of input in a varint".to_string()))?;
+            *pos += 1;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err((start, "varint is longer than 10 bytes".to_string()))
+    }
+
+    fn take(&self, pos: &mut usize, end: usize, len: usize) -> Result<&'a [u8], DecodeError> {
+        let stop = pos.checked_add(len).filter(|stop| *stop <= end);
+        let stop = stop.ok_or_else(|| (end, "unexpected end of input".to_string()))?;
+        let taken = &self.bytes[*pos..stop];
+        *pos = stop;
+        Ok(taken)
+    }
+
+    /// The length prefix of a length-delimited value.
+    fn len(&self, pos: &mut usize, end: usize) -> Result<usize, DecodeError> {
+        let start = *pos;
+        let len = self.varint(pos, end)?;
+        match usize::try_from(len) {
+            Ok(len) if len <= end - *pos => Ok(len),
+            _ => Err((start, format!("length {} runs past the end of the message", len))),
+        }
+    }
+
+    /// Decodes one occurrence of `field` (a packed run may hold several)
+    /// into `out`.
+    fn field(
+        &self,
+        field: &Field,
+        (tag_start, wire): (usize, u64),
+        pos: &mut usize,
+        end: usize,
+        depth: usize,
+        out: &mut Vec<(Value, bool)>,
+    ) -> Result<(), DecodeError> {
+        let mismatch = |expected: u64| {
+            let message = format!(
+                "field {} ({}) has wire type {} but its type needs {}",
+                field.number, field.name, wire, expected
+            );
+            Err((tag_start, message))
+        };
+        match &field.ty {
+            FieldType::Message(name) => {
+                if wire != 2 {
+                    return mismatch(2);
+                }
+                let len = self.len(pos, end)?;
+                out.push((self.message(name, *pos, *pos + len, depth - 1)?, false));
+                *pos += len;
+            }
+            FieldType::Scalar(scalar) => {
+                let expected = scalar.wire_type();
+                if wire == expected {
+                    out.push(self.scalar(*scalar, pos, end)?);
+                } else if wire == 2 && field.repeated {
+                    // A packed run of numbers.
+                    let len = self.len(pos, end)?;
+                    let stop = *pos + len;
+                    while *pos < stop {
+                        out.push(self.scalar(*scalar, pos, stop)?);
+                    }
+                } else {
+                    return mismatch(expected);
+                }
+            }
+            FieldType::Enum(name) => {
+                let stop = match wire {
+                    0 => end,
+                    2 if field.repeated => {
+                        let len = self.len(pos, end)?;
+                        *pos + len
+                    }
+                    _ => return mismatch(0),
+                };
+                loop {
+                    let number = self.varint(pos, stop)? as i32;
+                    out.push((self.enum_value(name, number), number == 0));
+                    if wire == 0 || *pos >= stop {
+                        break;
+                    }
+                }
+            }
+            FieldType::Named(_) => unreachable!("types are resolved after parsing"),
+        }
+        Ok(())
+    }
+
+    /// A scalar's JSON value and whether it is the type's default.
+    fn scalar(&self, scalar: Scalar, pos: &mut usize, end: usize) -> Result<(Value, bool), DecodeError> {
+        let start = *pos;
+        Ok(match scalar {
+            Scalar::String => {
+                let len = self.len(pos, end)?;
+                let text = std::str::from_utf8(self.take(pos, end, len)?)
+                    .map_err(|e| (*pos - len + e.valid_up_to(), "invalid UTF-8 in a string field".to_string()))?;
+                (Value::from(text), text.is_empty())
+            }
+            Scalar::Bytes => {
+                let len = self.len(pos, end)?;
+                (Value::from(STANDARD.encode(self.take(pos, end, len)?)), len == 0)
+            }
+            Scalar::Double | Scalar::Fixed64 | Scalar::Sfixed64 => {
+                let mut raw = [0; 8];
+                raw.copy_from_slice(self.take(pos, end, 8)?);
+                let bits = u64::from_le_bytes(raw);
+                let value = match scalar {
+                    Scalar::Double => float(f64::from_bits(bits)),
+                    Scalar::Fixed64 => Value::from(bits.to_string()),
+                    _ => Value::from((bits as i64).to_string()),
+                };
+                (value, bits == 0)
+            }
+            Scalar::Float | Scalar::Fixed32 | Scalar::Sfixed32 => {
+                let mut raw = [0; 4];
+                raw.copy_from_slice(self.take(pos, end, 4)?);
+                let bits = u32::from_le_bytes(raw);
+                let value = match scalar {
+                    // Through the shortest decimal form, so 0.1f reads as 0.1.
+                    Scalar::Float => float(f32::from_bits(bits).to_string().parse().unwrap_or(f64::NAN)),
+                    Scalar::Fixed32 => Value::from(bits),
+                    _ => Value::from(bits as i32),
+                };
+                (value, bits == 0)
+            }
+            _ => {
+                let raw = self.varint(pos, end).map_err(|(_, message)| (start, message))?;
+                let value = match scalar {
+                    Scalar::Int64 => Value::from((raw as i64).to_string()),
+                    Scalar::Uint64 => Value::from(raw.to_string()),
+                    Scalar::Sint64 => Value::from(zigzag(raw).to_string()),
+                    Scalar::Int32 => Value::from(raw as i32),
+                    Scalar::Uint32 => Value::from(raw as u32),
+                    Scalar::Sint32 => Value::from(zigzag(raw) as i32),
+                    _ => Value::Bool(raw != 0),
+                };
+                (value, raw == 0)
+            }
+        })
+    }
+
+    fn skip(
+        &self,
+        number: u64,
+        wire: u64,
+        tag_start: usize,
+        pos: &mut usize,
+        end: usize,
+        depth: usize,
+    ) -> Result<(), DecodeError> {
+        match wire {
+            0 => {
+                self.varint(pos, end)?;
+            }
+            1 => {
+                self.take(pos, end, 8)?;
+            }
+            2 => {
+                let len = self.len(pos, end)?;
+                self.take(pos, end, len)?;
+            }
+            3 => {
+                if depth == 0 {
+                    return Err((tag_start, "nesting is too deep".to_string()));
+                }
+                loop {
+                    let inner_start = *pos;
+                    match self.tag(pos, end)? {
+                        (inner, 4) if inner == number => break,
+                        (_, 4) => {
+                            return Err((inner_start, format!("end of group {} doesn't match its start", number)))
+                        }
+                        (inner, inner_wire) => self.skip(inner, inner_wire, inner_start, pos, end, depth - 1)?,
+                    }
+                }
+            }
+            4 => return Err((tag_start, format!("end of group {} without a start", number))),
+            _ => return Err((tag_start, format!("invalid wire type {}", wire))),
+        }
+        Ok(())
+    }
+
+    fn is_map(&self, field: &Field) -> bool {
+        matches!(&field.ty, FieldType::Message(name) if self.schema.messages[name].map_entry)
+    }
+
+    /// A decoded map entry as a key and value, filling in the defaults of
+    /// whichever is missing.
+    fn map_entry(&self, field: &Field, entry: Value) -> (String, Value) {
+        let entry_def = match &field.ty {
+            FieldType::Message(name) => &self.schema.messages[name],
+            _ => unreachable!("map fields hold entry messages"),
+        };
+        let mut entry = match entry {
+            Value::Object(entry) => entry,
+            _ => Map::new(),
+        };
+        let key = match entry.remove("key").unwrap_or_else(|| self.default_value(&entry_def.fields[0].ty)) {
+            Value::String(key) => key,
+            other => other.to_string(),
+        };
+        let value = entry.remove("value").unwrap_or_else(|| self.default_value(&entry_def.fields[1].ty));
+        (key, value)
+    }
+
+    fn default_value(&self, ty: &FieldType) -> Value {
+        match ty {
+            FieldType::Scalar(scalar) => scalar.default_value(),
+            FieldType::Enum(name) => self.enum_value(name, 0),
+            FieldType::Message(name) => self.well_known(name, Map::new()).unwrap_or_default(),
+            FieldType::Named(_) => Value::Null,
+        }
+    }
+
+    fn enum_value(&self, name: &str, number: i32) -> Value {
+        if name == "google.protobuf.NullValue" {
+            return Value::Null;
+        }
+        match self.schema.enums[name].get(&number) {
+            Some(value_name) => Value::from(value_name.as_str()),
+            None => Value::from(number),
+        }
+    }
+
+    /// `google.protobuf.Any`: the embedded message's JSON with an `@type`
+    /// key, or under `value` when it has a special JSON form.
+    fn any(&self, start: usize, end: usize, depth: usize) -> Result<Value, DecodeError> {
+        let mut type_url = String::new();
+        let mut embedded = None;
+        let mut pos = start;
+        while pos < end {
+            let tag_start = pos;
+            match self.tag(&mut pos, end)? {
+                (1, 2) => {
+                    type_url = self.scalar(Scalar::String, &mut pos, end)?.0.as_str().unwrap_or_default().to_string()
+                }
+                (2, 2) => {
+                    let len = self.len(&mut pos, end)?;
+                    embedded = Some((pos, len));
+                    pos += len;
+                }
+                (number, wire) => self.skip(number, wire, tag_start, &mut pos, end, depth)?,
+            }
+        }
+        let (value_start, len) = embedded.unwrap_or((pos, 0));
+        if type_url.is_empty() {
+            return Ok(Value::Object(Map::new()));
+        }
+        let type_name = type_url.rsplit('/').next().unwrap_or_default();
+        let mut object = Map::new();
+        object.insert("@type".to_string(), Value::from(type_url.as_str()));
+        if !self.schema.messages.contains_key(type_name) {
+            // Without the type, show the raw bytes.
+            object
+                .insert("value".to_string(), Value::from(STANDARD.encode(&self.bytes[value_start..value_start + len])));
+            return Ok(Value::Object(object));
+        }
+        match self.message(type_name, value_start, value_start + len, depth - 1)? {
+            Value::Object(fields) if !CUSTOM_JSON_TYPES.contains(&type_name) => object.extend(fields),
+            value => {
+                object.insert("value".to_string(), value);
+            }
+        }
+        Ok(Value::Object(object))
+    }
+
+    /// The special JSON forms of the well-known types.
+    fn well_known(&self, name: &str, mut object: Map<String, Value>) -> Result<Value, String> {
+        let mut take = |key: &str, default: Value| object.remove(key).unwrap_or(default);
+        Ok(match name {
+            "google.protobuf.Timestamp" | "google.protobuf.Duration" => {
+                let seconds = take("seconds", Value::Null).as_str().and_then(|s| s.parse::<i64>().ok()).unwrap_or(0);
+                let nanos = take("nanos", Value::Null).as_i64().unwrap_or(0);
+                let text = if name.ends_with("Timestamp") {
+                    timestamp_text(seconds, nanos)
+                } else {
+                    duration_text(seconds, nanos)
+                };
+                Value::from(text.ok_or_else(|| {
+                    format!("{} of {} seconds and {} nanoseconds is out of range", name, seconds, nanos)
+                })?)
+            }
+            "google.protobuf.DoubleValue"
+            | "google.protobuf.FloatValue"
+            | "google.protobuf.Int32Value"
+            | "google.protobuf.UInt32Value" => take("value", Value::from(0)),
+            "google.protobuf.Int64Value" | "google.protobuf.UInt64Value" => take("value", Value::from("0")),
+            "google.protobuf.BoolValue" => take("value", Value::Bool(false)),
+            "google.protobuf.StringValue" | "google.protobuf.BytesValue" => take("value", Value::from("")),
+            "google.protobuf.Struct" => take("fields", Value::Object(Map::new())),
+            "google.protobuf.ListValue" => take("values", Value::Array(Vec::new())),
+            "google.protobuf.Value" => object.into_iter().next().map_or(Value::Null, |(_, value)| value),
+            "google.protobuf.FieldMask" => {
+                let paths = take("paths", Value::Array(Vec::new()));
+                let paths: Vec<String> =
+                    paths.as_array().into_iter().flatten().filter_map(Value::as_str).map(proto_json_name).collect();
+                Value::from(paths.join(","))
+            }
+            _ => Value::Object(object),
+        })
+    }
+}
+
+fn zigzag(raw: u64) -> i64 {
+    (raw >> 1) as i64 ^ -((raw & 1) as i64)
+}
+
+fn float(f: f64) -> Value {
+    match Number::from_f64(f) {
+        Some(n) => Value::Number(n),
+        None if f.is_nan() => Value::from("NaN"),
+        None if f > 0.0 => Value::from("Infinity"),
+        None => Value::from("-Infinity"),
+    }
+}
+
+/// Repeated occurrences of a singular message field merge into one.
+fn merge(earlier: Value, later: Value) -> Value {
+    match (earlier, later) {
+        (Value::Object(mut earlier), Value::Object(later)) => {
+            for (key, value) in later {
+                let merged = match (earlier.remove(&key), value) {
+                    (Some(Value::Array(mut items)), Value::Array(more)) => {
+                        items.extend(more);
+                        Value::Array(items)
+                    }
+                    (Some(old @ Value::Object(_)), new @ Value::Object(_)) => merge(old, new),
+                    (_, value) => value,
+                };
+                earlier.insert(key, merged);
+            }
+            Value::Object(earlier)
+        }
+        (_, later) => later,
+    }
+}
+
+/// Nanoseconds as 0, 3, 6 or 9 fraction digits, as the JSON mapping writes them.
+fn fraction(nanos: i64) -> String {
+    if nanos == 0 {
+        String::new()
+    } else if nanos % 1_000_000 == 0 {
+        format!(".{:03}", nanos / 1_000_000)
+    } else if nanos % 1000 == 0 {
+        format!(".{:06}", nanos / 1000)
+    } else {
+        format!(".{:09}", nanos)
+    }
+}
+
+/// RFC 3339 in UTC, for years 1 to 9999.
+pub(crate) fn timestamp_text(seconds: i64, nanos: i64) -> Option<String> {
+    if !(-62_135_596_800..=253_402_300_799).contains(&seconds) || !(0..1_000_000_000).contains(&nanos) {
+        return None;
+    }
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let in_day = seconds.rem_euclid(86_400);
+    Some(format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}Z",
+        year,
+        month,
+        day,
+        in_day / 3600,
+        in_day / 60 % 60,
+        in_day % 60,
+        fraction(nanos)
+    ))
+}
+
+/// Seconds with a fraction and an `s` suffix, e.g. `-1.5s`.
+fn duration_text(seconds: i64, nanos: i64) -> Option<String> {
+    let in_range = (-315_576_000_000..=315_576_000_000).contains(&seconds) && nanos.abs() < 1_000_000_000;
+    if !in_range || seconds.signum() * nanos.signum() < 0 {
+        return None;
+    }
+    let sign = if seconds < 0 || nanos < 0 { "-" } else { "" };
+    Some(format!("{}{}{}s", sign, seconds.abs(), fraction(nanos.abs())))
+}
+
+// .proto generation
+
+/// Renders a proto3 file. A type only one message refers to is declared
+/// inside that message; the rest are declared at the top level.
+pub(crate) fn render_proto(model: &TypeModel) -> String {
+    let owners = proto_owners(model);
+    let names = proto_names(model, &owners);
+    let mut writer = ProtoWriter { model, owners: &owners, names: &names, imports: BTreeSet::new() };
+    let blocks: Vec<String> = model
+        .defs
+        .iter()
+        .filter(|def| !owners.contains_key(&def.name))
+        .filter(|def| def.name == model.root || !matches!(def.kind, TypeKind::Alias(_)))
+        .map(|def| writer.def(def, ""))
+        .collect();
+    let mut result = "syntax = \"proto3\";\n\n".to_string();
+    for import in &writer.imports {
+        result.push_str(&format!("import \"{}\";\n", import));
+    }
+    if !writer.imports.is_empty() {
+        result.push('\n');
+    }
+    result.push_str(&blocks.join("\n\n"));
+    result.push('\n');
+    result
+}
+
+/// The message each nested definition is declared in.
+fn proto_owners(model: &TypeModel) -> HashMap<String, String> {
+    let mut referrers: HashMap<String, Vec<String>> = HashMap::new();
+    for def in &model.defs {
+        for name in referenced_names(def) {
+            referrers.entry(name).or_default().push(def.name.clone());
+        }
+    }
+    let mut owners = HashMap::new();
+    for def in &model.defs {
+        if def.name == model.root || matches!(def.kind, TypeKind::Alias(_)) {
+            continue;
+        }
+        if let Some([owner]) = referrers.get(&def.name).map(Vec::as_slice) {
+            if model.get(owner).is_some_and(|owner| matches!(owner.kind, TypeKind::Struct(_) | TypeKind::Union { .. })) {
+                owners.insert(def.name.clone(), owner.clone());
+            }
+        }
+    }
+    // Mutually recursive types can't be declared inside each other.
+    let cyclic: Vec<String> = owners
+        .keys()
+        .filter(|name| {
+            let mut current = owners.get(*name);
+            for _ in 0..owners.len() {
+                match current {
+                    Some(owner) if owner == *name => return true,
+                    Some(owner) => current = owners.get(owner),
+                    None => return false,
+                }
+            }
+            false
+        })
+        .cloned()
+        .collect();
+    for name in cyclic {
+        owners.remove(&name);
+    }
+    owners
+}
+
+/// Nested types drop their owner's name (`RootAddress` in `Root` becomes
+/// `Address`) unless the short name could be mistaken for another type.
+fn proto_names(model: &TypeModel, owners: &HashMap<String, String>) -> HashMap<String, String> {
+    let mut names = HashMap::new();
+    let mut taken: HashSet<(String, String)> = HashSet::new();
+    for def in &model.defs {
+        let mut name = def.name.clone();
+        if let Some(owner) = owners.get(&def.name) {
+            let short = def.name.strip_prefix(owner.as_str()).unwrap_or_default();
+            let usable = short.starts_with(|c: char| c.is_ascii_uppercase()) && model.get(short).is_none();
+            if usable && taken.insert((owner.clone(), short.to_string())) {
+                name = short.to_string();
+            }
+        }
+        names.insert(def.name.clone(), name);
+    }
+    names
+}
+
+struct ProtoWriter<'a> {
+    model: &'a TypeModel,
+    owners: &'a HashMap<String, String>,
+    names: &'a HashMap<String, String>,
+    imports: BTreeSet<&'static str>,
+}
+
+/// How a type is written in a field declaration.
+struct ProtoType {
+    name: String,
+    /// Written with the `repeated` label (maps aren't).
+    repeated: bool,
+    /// A scalar or enum, which needs `optional` for presence.
+    scalar: bool,
+}
+
+impl<'a> ProtoWriter<'a> {
+    fn def(&mut self, def: &TypeDef, indent: &str) -> String {
+        let name = self.names[&def.name].clone();
+        let inner = format!("{}  ", indent);
+        let mut out = String::new();
+        push_line_doc(&mut out, indent, "//", &def.description);
+        match &def.kind {
+            TypeKind::Enum(values) => {
+                out.push_str(&format!("{}enum {} {{\n", indent, name));
+                let prefix = screaming_snake_case(&name);
+                out.push_str(&format!("{}{}_UNSPECIFIED = 0;\n", inner, prefix));
+                let members = dedupe_names(values.iter().map(|v| format!("{}_{}", prefix, screaming_snake_case(v))).collect());
+                for (i, member) in members.iter().enumerate() {
+                    out.push_str(&format!("{}{} = {};\n", inner, member, i + 1));
+                }
+                out.push_str(&format!("{}}}", indent));
+                return out;
+            }
+            TypeKind::Alias(target) => {
+                // Only the root is rendered, as a message holding the value.
+                let field = FieldDef {
+                    json_name: if matches!(target, TypeRef::Array(_)) { "values" } else { "value" }.to_string(),
+                    ty: target.clone(),
+                    required: true,
+                    description: None,
+                };
+                let wrapper = TypeDef { kind: TypeKind::Struct(vec![field]), ..def.clone() };
+                return self.def(&wrapper, indent);
+            }
+            _ => {}
+        }
+
+        out.push_str(&format!("{}message {} {{\n", indent, name));
+        let (model, owners) = (self.model, self.owners);
+        for child in model.defs.iter().filter(|d| owners.get(&d.name) == Some(&def.name)) {
+            out.push_str(&self.def(child, &inner));
+            out.push_str("\n\n");
+        }
+        match &def.kind {
+            TypeKind::Struct(fields) => {
+                let names = dedupe_names(fields.iter().map(|f| proto_field_name(&f.json_name)).collect());
+                for (i, (field, name)) in fields.iter().zip(names).enumerate() {
+                    push_line_doc(&mut out, &inner, "//", &field.description);
+                    let ty = self.field_type(&field.ty);
+                    let label = if ty.repeated {
+                        "repeated "
+                    } else if ty.scalar && (!field.required || matches!(field.ty, TypeRef::Nullable(_))) {
+                        "optional "
+                    } else {
+                        ""
+                    };
+                    let json_name = if proto_json_name(&name) == field.json_name {
+                        String::new()
+                    } else {
+                        format!(" [json_name = {}]", quoted(&field.json_name))
+                    };
+                    out.push_str(&format!("{}{}{} {} = {}{};\n", inner, label, ty.name, name, proto_field_number(i), json_name));
+                }
+            }
+            TypeKind::Union { variants, discriminator } => {
+                out.push_str(&format!("{}oneof value {{\n", inner));
+                let variants: Vec<(usize, &TypeRef)> = variants.iter().enumerate().filter(|(_, v)| **v != TypeRef::Null).collect();
+                let names = dedupe_names(
+                    variants
+                        .iter()
+                        .map(|(i, variant)| match (discriminator, variant) {
+                            (Some(disc), _) => proto_field_name(&disc.tags[*i]),
+                            (None, TypeRef::Named(name)) => format!("{}_value", proto_field_name(&self.names[name])),
+                            (None, _) => format!("{}_value", proto_field_name(&rust_variant_name(variant))),
+                        })
+                        .collect(),
+                );
+                for (number, ((_, variant), name)) in variants.iter().zip(names).enumerate() {
+                    // `oneof` members can't be repeated or maps.
+                    let mut ty = self.field_type(variant);
+                    if ty.repeated || ty.name.starts_with("map<") {
+                        ty.name = self.json_type(if ty.repeated { "ListValue" } else { "Struct" });
+                    }
+                    out.push_str(&format!("{}  {} {} = {};\n", inner, ty.name, name, proto_field_number(number)));
+                }
+                out.push_str(&format!("{}}}\n", inner));
+            }
+            _ => {}
+        }
+        if out.ends_with("\n\n") {
+            out.pop();
+        }
+        out.push_str(&format!("{}}}", indent));
+        out
+    }
+
+    fn field_type(&mut self, ty: &TypeRef) -> ProtoType {
+        let scalar = |name: &str| ProtoType { name: name.to_string(), repeated: false, scalar: true };
+        let message = |name: String| ProtoType { name, repeated: false, scalar: false };
+        match ty {
+            TypeRef::Any | TypeRef::Null => message(self.json_type("Value")),
+            TypeRef::Boolean => scalar("bool"),
+            TypeRef::Integer => scalar("int64"),
+            TypeRef::Number => scalar("double"),
+            TypeRef::Formatted(StringFormat::DateTime) => {
+                self.imports.insert("google/protobuf/timestamp.proto");
+                message("google.protobuf.Timestamp".to_string())
+            }
+            TypeRef::String | TypeRef::Formatted(_) => scalar("string"),
+            TypeRef::Array(inner) | TypeRef::Map(inner) => {
+                // Lists of lists and maps of lists fall back to the JSON types.
+                let mut element = self.field_type(inner);
+                if element.repeated || element.name.starts_with("map<") {
+                    element = message(self.json_type(if element.repeated { "ListValue" } else { "Struct" }));
+                }
+                match ty {
+                    TypeRef::Array(_) => ProtoType { name: element.name, repeated: true, scalar: false },
+                    _ => message(format!("map<string, {}>", element.name)),
+                }
+            }
+            TypeRef::Named(name) => match self.model.get(name).map(|def| &def.kind) {
+                Some(TypeKind::Alias(target)) => self.field_type(target),
+                Some(TypeKind::Enum(_)) => scalar(&self.names[name]),
+                _ => message(self.names.get(name).unwrap_or(name).clone()),
+            },
+            TypeRef::Nullable(inner) => self.field_type(inner),
+        }
+    }
+
+    /// One of the types `google/protobuf/struct.proto` defines for JSON.
+    fn json_type(&mut self, name: &str) -> String {
+        self.imports.insert("google/protobuf/struct.proto");
+        format!("google.protobuf.{}", name)
+    }
+}
+
+fn proto_field_name(key: &str) -> String {
+    let name = snake_case(key);
+    if name.is_empty() {
+        "field".to_string()
+    } else {
+        name
+    }
+}
+
+/// The JSON name protoc gives a field: underscores removed and the letter
+/// after each one capitalized.
+pub(crate) fn proto_json_name(name: &str) -> String {
+    let mut result = String::new();
+    let mut capitalize = false;
+    for c in name.chars() {
+        if c == '_' {
+            capitalize = true;
+        } else if capitalize {
+            result.push(c.to_ascii_uppercase());
+            capitalize = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Field numbers from 1, skipping the range protobuf reserves.
+fn proto_field_number(index: usize) -> usize {
+    if index + 1 >= 19000 {
+        index + 1001
+    } else {
+        index + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const PROTO: &str = r#"
+        syntax = "proto3";
+        package shop;
+        import "google/protobuf/timestamp.proto";
+
+        message Order {
+          enum Status { UNKNOWN = 0; PAID = 1; }
+          int32 id = 1;
+          string customer_name = 2;
+          repeated int64 quantities = 3;
+          Status status = 4;
+          Item item = 5;
+          google.protobuf.Timestamp created = 6;
+          bytes payload = 7;
+          sint32 delta = 8;
+          optional bool gift = 9;
+          map<string, int32> counts = 10;
+          int32 unset = 11;
+        }
+
+        message Item {
+          string sku = 1 [json_name = "SKU"];
+          double price = 2;
+        }
+    "#;
+
+    fn varint(out: &mut Vec<u8>, mut value: u64) {
+        while value >= 0x80 {
+            out.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    fn int_field(out: &mut Vec<u8>, number: u64, value: u64) {
+        varint(out, number << 3);
+        varint(out, value);
+    }
+
+    fn bytes_field(out: &mut Vec<u8>, number: u64, data: &[u8]) {
+        varint(out, number << 3 | 2);
+        varint(out, data.len() as u64);
+        out.extend(data);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Value, String> {
+        let schema = ProtoSchema::parse(PROTO).unwrap();
+        let message = schema.message_type(None).unwrap();
+        decode_protobuf(&schema, &message, bytes)
+    }
+
+    #[test]
+    fn decodes_the_canonical_json_mapping() {
+        let mut item = Vec::new();
+        bytes_field(&mut item, 1, b"A-1");
+        varint(&mut item, 2 << 3 | 1);
+        item.extend(2.5f64.to_le_bytes());
+        let mut created = Vec::new();
+        int_field(&mut created, 1, 1_704_164_645);
+        int_field(&mut created, 2, 500_000_000);
+        let mut entry = Vec::new();
+        bytes_field(&mut entry, 1, b"x");
+        int_field(&mut entry, 2, 7);
+        let mut packed = Vec::new();
+        for quantity in [1, 300, -2i64 as u64] {
+            varint(&mut packed, quantity);
+        }
+
+        let mut order = Vec::new();
+        int_field(&mut order, 1, 42);
+        bytes_field(&mut order, 2, b"Ada");
+        bytes_field(&mut order, 3, &packed);
+        int_field(&mut order, 4, 1);
+        bytes_field(&mut order, 5, &item);
+        bytes_field(&mut order, 6, &created);
+        bytes_field(&mut order, 7, &[0, 1, 2]);
+        int_field(&mut order, 8, 3);
+        int_field(&mut order, 9, 0);
+        bytes_field(&mut order, 10, &entry);
+        // Unknown fields are skipped.
+        int_field(&mut order, 99, 5);
+
+        assert_eq!(
+            decode(&order).unwrap(),
+            json!({
+                "id": 42,
+                "customerName": "Ada",
+                "quantities": ["1", "300", "-2"],
+                "status": "PAID",
+                "item": {"SKU": "A-1", "price": 2.5},
+                "created": "2024-01-02T03:04:05.500Z",
+                "payload": "AAEC",
+                "delta": -2,
+                "gift": false,
+                "counts": {"x": 7}
+            })
+        );
+    }
+
+    #[test]
+    fn grpc_frames_become_an_array() {
+        let frames = [0, 0, 0, 0, 2, 8, 1, 0, 0, 0, 0, 2, 8, 2];
+        assert_eq!(decode(&frames).unwrap(), json!([{"id": 1}, {"id": 2}]));
+        let error = decode(&frames[..12]).unwrap_err();
+        assert!(error.contains("gRPC frame length 2 runs past the end of input"), "{}", error);
+    }
+
+    #[test]
+    fn truncated_messages_are_errors() {
+        let mut order = Vec::new();
+        bytes_field(&mut order, 2, b"Ada");
+        let error = decode(&order[..3]).unwrap_err();
+        assert!(error.starts_with("Protobuf decoding error at byte 1"), "{}", error);
+    }
+
+    #[test]
+    fn resolves_message_names() {
+        let schema = ProtoSchema::parse(PROTO).unwrap();
+        assert_eq!(schema.message_type(None).unwrap(), "shop.Order");
+        assert_eq!(schema.message_type(Some("Item")).unwrap(), "shop.Item");
+        assert!(schema.message_type(Some("Missing")).is_err());
+        let error = ProtoSchema::parse("message A { Missing m = 1; }").err().unwrap();
+        assert!(error.starts_with("Unknown type 'Missing' for field 'm' of A"), "{}", error);
+    }
+}
@@ -0,0 +1,730 @@
+use wasm_bindgen::prelude::*;
+use serde::Deserialize;
+use serde_json::{Map, Number, Value};
+
+use crate::dialect::normalize;
+use crate::pattern;
+use crate::validator::Validator;
+
+// Sample data from a JSON Schema.
+//
+// The reverse of schema inference: produces documents that satisfy a schema,
+// for mock servers and test fixtures. Output is deterministic for a given
+// seed. The schema is normalised to draft-07 first, so `$defs`, `prefixItems`
+// and OpenAPI `nullable` work the same as their draft-07 counterparts.
+
+/// Past this depth only required properties and minimum array lengths are
+/// generated, which ends recursion through optional references.
+const SHALLOW_DEPTH: usize = 8;
+/// A schema that still needs more nesting here requires infinite documents.
+const MAX_DEPTH: usize = 32;
+/// How many strings to draw from a pattern looking for one within the length bounds.
+const PATTERN_ATTEMPTS: usize = 32;
+/// Values drawn from each `oneOf`/`anyOf` option looking for one the whole schema accepts.
+const BRANCH_ATTEMPTS: usize = 4;
+/// Documents drawn looking for one that passes validation.
+const DOCUMENT_ATTEMPTS: usize = 8;
+
+const WORDS: &[&str] = &[
+    "alpha", "amber", "atlas", "beacon", "birch", "cedar", "cobalt", "delta", "ember", "falcon",
+    "garnet", "harbor", "indigo", "juniper", "kestrel", "lumen", "maple", "nova", "onyx", "pine",
+    "quartz", "river", "summit", "tundra", "umber", "violet", "willow", "zephyr",
+];
+
+/// Whether optional properties are generated.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum OptionalProperties {
+    All,
+    None,
+    Random,
+}
+
+/// Options for sample generation, e.g. `{"seed": 7, "count": 10, "optional": "all"}`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SampleOptions {
+    seed: u64,
+    /// Generate an array of this many documents instead of a single one.
+    count: Option<usize>,
+    optional: OptionalProperties,
+}
+
+impl Default for SampleOptions {
+    fn default() -> Self {
+        SampleOptions {
+            seed: 1,
+            count: None,
+            optional: OptionalProperties::Random,
+        }
+    }
+}
+
+/// A small deterministic PRNG (SplitMix64); good enough for sample data.
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n` (0 when `n` is 0).
+    pub(crate) fn below(&mut self, n: u64) -> u64 {
+        if n == 0 {
+            0
+        } else {
+            self.next_u64() % n
+        }
+    }
+
+    /// A number in `lo..=hi`.
+    fn between(&mut self, lo: i64, hi: i64) -> i64 {
+        let span = (hi as i128 - lo as i128 + 1) as u128;
+        (lo as i128 + (self.next_u64() as u128 % span) as i128) as i64
+    }
+
+    /// A float in `0.0..1.0`.
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn chance(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+}
+
+/// Generates an example document satisfying `schema_str`.
+#[wasm_bindgen]
+pub fn schema_to_sample(schema_str: &str, options: Option<String>) -> Result<String, JsValue> {
+    let schema: Value = serde_json::from_str(schema_str)
+        .map_err(|e| JsValue::from_str(&format!("JSON Schema parsing error: {}", e)))?;
+    let options: SampleOptions = match options.as_deref().map(str::trim) {
+        None | Some("") => SampleOptions::default(),
+        Some(options) => serde_json::from_str(options)
+            .map_err(|e| JsValue::from_str(&format!("Invalid sample options: {}", e)))?,
+    };
+
+    let result = generate_samples(schema, &options)
+        .map_err(|e| JsValue::from_str(&format!("Sample generation error: {}", e)))?;
+
+    serde_json::to_string_pretty(&result)
+        .map_err(|e| JsValue::from_str(&format!("Sample serialization error: {}", e)))
+}
+
+fn generate_samples(schema: Value, options: &SampleOptions) -> Result<Value, String> {
+    let root = normalize(schema);
+    let mut generator = Generator {
+        root: &root,
+        validator: Validator::new(&root),
+        rng: Rng::new(options.seed),
+        optional: options.optional,
+    };
+    match options.count {
+        None => generator.document(),
+        Some(count) => (0..count).map(|_| generator.document()).collect::<Result<Vec<_>, _>>().map(Value::Array),
+    }
+}
+
+struct Generator<'a> {
+    root: &'a Value,
+    validator: Validator<'a>,
+    rng: Rng,
+    optional: OptionalProperties,
+}
+
+impl<'a> Generator<'a> {
+    /// A document the validator accepts; keywords the generator doesn't
+    /// steer by (`not`, `uniqueItems`, ...) are handled by drawing again.
+    fn document(&mut self) -> Result<Value, String> {
+        let mut problem = String::new();
+        for _ in 0..DOCUMENT_ATTEMPTS {
+            let value = self.value(self.root, 0, "")?;
+            match self.validator.validate(&value).first() {
+                None => return Ok(value),
+                Some(error) => problem = format!("{} at '{}'", error.message, error.instance_path),
+            }
+        }
+        Err(format!("No generated document passed validation ({})", problem))
+    }
+
+    /// `key` is the property name the value is for, used to pick realistic strings.
+    fn value(&mut self, schema: &Value, depth: usize, key: &str) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return Err("The schema requires infinitely nested documents".to_string());
+        }
+        let obj = match schema {
+            Value::Object(obj) => obj,
+            Value::Bool(true) => return Ok(self.any(key)),
+            Value::Bool(false) => return Err("The schema 'false' matches nothing".to_string()),
+            _ => return Err(format!("Invalid schema: {}", schema)),
+        };
+
+        if let Some(Value::String(reference)) = obj.get("$ref") {
+            let target = self.resolve(reference)?;
+            return self.value(target, depth + 1, key);
+        }
+        if let Some(value) = obj.get("const") {
+            return Ok(value.clone());
+        }
+        if let Some(Value::Array(values)) = obj.get("enum") {
+            if values.is_empty() {
+                return Err("An empty enum matches nothing".to_string());
+            }
+            return Ok(self.rng.pick(values).clone());
+        }
+        if let Some(Value::Array(examples)) = obj.get("examples") {
+            if !examples.is_empty() {
+                return Ok(self.rng.pick(examples).clone());
+            }
+        }
+        if let Some(Value::Array(parts)) = obj.get("allOf") {
+            let merged = self.merge_all_of(obj, parts)?;
+            return self.value(&merged, depth, key);
+        }
+        for combinator in ["oneOf", "anyOf"] {
+            if let Some(Value::Array(options)) = obj.get(combinator) {
+                if options.is_empty() {
+                    return Err(format!("An empty {} matches nothing", combinator));
+                }
+                // Try the options in random order, non-null ones first so
+                // samples carry data, and keep the first value the whole
+                // schema accepts; for `oneOf` that means no other option does.
+                let (mut order, nulls): (Vec<&Value>, Vec<&Value>) =
+                    options.iter().partition(|o| o.get("type") != Some(&"null".into()));
+                for i in (1..order.len()).rev() {
+                    order.swap(i, self.rng.below(i as u64 + 1) as usize);
+                }
+                order.extend(nulls);
+                let whole = Value::Object(obj.clone());
+                let mut problem = None;
+                for chosen in order {
+                    let mut rest = obj.clone();
+                    rest.remove(combinator);
+                    let merged = Value::Object(merge_into(rest, self.deref(chosen)?));
+                    for _ in 0..BRANCH_ATTEMPTS {
+                        match self.value(&merged, depth + 1, key) {
+                            Ok(value) if self.validator.validate_at(&whole, "#", &value).is_empty() => return Ok(value),
+                            Ok(_) => {}
+                            Err(e) => {
+                                problem = Some(e);
+                                break;
+                            }
+                        }
+                    }
+                }
+                return Err(problem.unwrap_or_else(|| format!("No value was found that satisfies the {}", combinator)));
+            }
+        }
+
+        match self.pick_type(obj).as_deref() {
+            Some("null") => Ok(Value::Null),
+            Some("boolean") => Ok(Value::Bool(self.rng.chance())),
+            Some("integer") => self.integer(obj),
+            Some("number") => self.number(obj),
+            Some("string") => self.string(obj, key),
+            Some("array") => self.array(obj, depth),
+            Some("object") => self.object(obj, depth),
+            _ => Ok(self.any(key)),
+        }
+    }
+
+    fn resolve(&self, reference: &str) -> Result<&'a Value, String> {
+        let pointer = reference
+            .strip_prefix('#')
+            .ok_or_else(|| format!("Only local references are supported: {}", reference))?;
+        self.root.pointer(pointer).ok_or_else(|| format!("Unresolvable $ref: {}", reference))
+    }
+
+    fn deref(&self, schema: &Value) -> Result<Map<String, Value>, String> {
+        match schema {
+            Value::Object(obj) => match obj.get("$ref").and_then(Value::as_str) {
+                Some(reference) => self.deref(self.resolve(reference)?),
+                None => Ok(obj.clone()),
+            },
+            Value::Bool(true) => Ok(Map::new()),
+            _ => Err(format!("Invalid schema: {}", schema)),
+        }
+    }
+
+    /// Folds `allOf` parts into one schema; properties and `required` are unioned.
+    fn merge_all_of(&self, obj: &Map<String, Value>, parts: &[Value]) -> Result<Value, String> {
+        let mut merged = obj.clone();
+        merged.remove("allOf");
+        for part in parts {
+            merged = merge_into(merged, self.deref(part)?);
+        }
+        Ok(Value::Object(merged))
+    }
+
+    /// The type to generate; `type` lists prefer their non-null members.
+    fn pick_type(&mut self, obj: &Map<String, Value>) -> Option<String> {
+        match obj.get("type") {
+            Some(Value::String(kind)) => Some(kind.clone()),
+            Some(Value::Array(kinds)) => {
+                let non_null: Vec<&str> = kinds.iter().filter_map(Value::as_str).filter(|k| *k != "null").collect();
+                match non_null.as_slice() {
+                    [] => Some("null".to_string()),
+                    kinds => Some(self.rng.pick(kinds).to_string()),
+                }
+            }
+            _ if obj.contains_key("properties") || obj.contains_key("required") => Some("object".to_string()),
+            _ if obj.contains_key("items") => Some("array".to_string()),
+            _ if obj.contains_key("pattern") || obj.contains_key("format") || obj.contains_key("minLength") => {
+                Some("string".to_string())
+            }
+            _ if obj.contains_key("minimum") || obj.contains_key("maximum") => Some("number".to_string()),
+            _ => None,
+        }
+    }
+
+    /// A value for a schema that allows anything.
+    fn any(&mut self, key: &str) -> Value {
+        Value::String(self.word(key))
+    }
+
+    fn word(&mut self, key: &str) -> String {
+        let key = key.to_ascii_lowercase();
+        if key.contains("name") {
+            let name = self.rng.pick(WORDS);
+            return name[..1].to_ascii_uppercase() + &name[1..];
+        }
+        self.rng.pick(WORDS).to_string()
+    }
+
+    fn integer(&mut self, obj: &Map<String, Value>) -> Result<Value, String> {
+        let (lo, hi) = bounds(obj);
+        // Exclusive bounds on integers move to the next whole number inside.
+        // The range is worked out in i128 so bounds at the ends of i64 can't
+        // overflow, then cut down to what a JSON integer here can hold.
+        let lo = lo.map(|(value, exclusive)| if exclusive { value.floor() as i128 + 1 } else { value.ceil() as i128 });
+        let hi = hi.map(|(value, exclusive)| if exclusive { value.ceil() as i128 - 1 } else { value.floor() as i128 });
+        // Just past either end is as good as any bound further out.
+        let past = |bound: i128| bound.clamp(i128::from(i64::MIN) - 1, i128::from(i64::MAX) + 1);
+        let (lo, hi) = default_range(lo.map(past), hi.map(past), 0, 100);
+        let (lo, hi) = (lo.max(i64::MIN.into()), hi.min(i64::MAX.into()));
+        if lo > hi {
+            return Err(format!("No integer satisfies minimum {} and maximum {}", lo, hi));
+        }
+        let (lo, hi) = (lo as i64, hi as i64);
+        let value = match obj.get("multipleOf").and_then(Value::as_f64).filter(|m| *m > 0.0) {
+            Some(step) => {
+                let none = || format!("No multiple of {} lies between {} and {}", step, lo, hi);
+                let first = (lo as f64 / step).ceil() as i64;
+                let last = (hi as f64 / step).floor() as i64;
+                if first > last {
+                    return Err(none());
+                }
+                // Far from zero, f64 steps can land just outside the range.
+                let value = (self.rng.between(first, last) as f64 * step) as i128;
+                if value < lo.into() || value > hi.into() {
+                    return Err(none());
+                }
+                value as i64
+            }
+            None => self.rng.between(lo, hi),
+        };
+        Ok(Value::Number(value.into()))
+    }
+
+    fn number(&mut self, obj: &Map<String, Value>) -> Result<Value, String> {
+        let (lo, hi) = bounds(obj);
+        let (lo_value, hi_value) = default_range(lo.map(|b| b.0), hi.map(|b| b.0), 0.0, 100.0);
+        if lo_value > hi_value {
+            return Err(format!("No number satisfies minimum {} and maximum {}", lo_value, hi_value));
+        }
+        let inside = |v: f64| {
+            lo.is_none_or(|(b, exclusive)| if exclusive { v > b } else { v >= b })
+                && hi.is_none_or(|(b, exclusive)| if exclusive { v < b } else { v <= b })
+        };
+
+        let value = match obj.get("multipleOf").and_then(Value::as_f64).filter(|m| *m > 0.0) {
+            Some(step) => {
+                let first = (lo_value / step).ceil() as i64;
+                let last = (hi_value / step).floor() as i64;
+                let candidates: Vec<f64> = [self.rng.between(first.min(last), last), first, last]
+                    .iter()
+                    .map(|k| *k as f64 * step)
+                    .filter(|v| inside(*v))
+                    .collect();
+                *candidates
+                    .first()
+                    .ok_or_else(|| format!("No multiple of {} lies between {} and {}", step, lo_value, hi_value))?
+            }
+            None => {
+                let raw = lo_value + self.rng.unit() * (hi_value - lo_value);
+                // Two decimals read better, unless rounding lands on an exclusive bound.
+                let rounded = (raw * 100.0).round() / 100.0;
+                match [rounded, raw, (lo_value + hi_value) / 2.0].iter().find(|v| inside(**v)) {
+                    Some(value) => *value,
+                    None => return Err(format!("No number lies between {} and {}", lo_value, hi_value)),
+                }
+            }
+        };
+        Number::from_f64(value)
+            .map(Value::Number)
+            .ok_or_else(|| format!("Cannot represent {} as JSON", value))
+    }
+
+    fn string(&mut self, obj: &Map<String, Value>, key: &str) -> Result<Value, String> {
+        let min = obj.get("minLength").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let max = obj.get("maxLength").and_then(Value::as_u64).map(|m| m as usize);
+        let fits = |value: &str| {
+            let length = value.chars().count();
+            length >= min && max.is_none_or(|max| length <= max)
+        };
+        if max.is_some_and(|max| max < min) {
+            return Err(format!("No string has minLength {} and maxLength {}", min, max.unwrap_or(0)));
+        }
+        let lengths = || match max {
+            Some(max) => format!("minLength {} and maxLength {}", min, max),
+            None => format!("minLength {}", min),
+        };
+        if let Some(Value::String(pattern)) = obj.get("pattern") {
+            // Plain draws read most naturally; keep the first that fits.
+            for _ in 0..PATTERN_ATTEMPTS {
+                let value = pattern::generate(pattern, &mut self.rng)?;
+                if fits(&value) && pattern::is_match(pattern, &value)? {
+                    return Ok(Value::String(value));
+                }
+            }
+            // Otherwise stretch or shrink the quantifiers to a length in range.
+            if let Some(value) = pattern::generate_within(pattern, &mut self.rng, min, max)? {
+                return Ok(Value::String(value));
+            }
+            // A pattern that isn't anchored at the end still matches with
+            // words appended, which covers `^[A-Z]` with a `minLength`.
+            let mut padded = pattern::generate(pattern, &mut self.rng)?;
+            while padded.chars().count() < min {
+                let word = *self.rng.pick(WORDS);
+                padded.push_str(word);
+            }
+            if let Some(max) = max {
+                padded = padded.chars().take(max).collect();
+            }
+            if fits(&padded) && pattern::is_match(pattern, &padded)? {
+                return Ok(Value::String(padded));
+            }
+            return Err(format!("No string matching pattern '{}' was found with {}", pattern, lengths()));
+        }
+        if let Some(format) = obj.get("format").and_then(Value::as_str) {
+            if self.formatted(format).is_some() {
+                for _ in 0..PATTERN_ATTEMPTS {
+                    match self.formatted(format) {
+                        Some(value) if fits(&value) => return Ok(Value::String(value)),
+                        _ => {}
+                    }
+                }
+                return Err(format!("No '{}' string was found with {}", format, lengths()));
+            }
+        }
+
+        let mut value = self.word(key);
+        while value.chars().count() < min {
+            value.push(' ');
+            let word = *self.rng.pick(WORDS);
+            value.push_str(word);
+        }
+        if let Some(max) = max {
+            value = value.chars().take(max).collect::<String>();
+            // Don't leave a trailing space behind that minLength doesn't need.
+            if value.ends_with(' ') && value.chars().count() > min {
+                value.pop();
+            }
+        }
+        Ok(Value::String(value))
+    }
+
+    fn formatted(&mut self, format: &str) -> Option<String> {
+        let date = format!("{:04}-{:02}-{:02}", self.rng.between(2020, 2025), self.rng.between(1, 12), self.rng.between(1, 28));
+        let time = format!("{:02}:{:02}:{:02}Z", self.rng.between(0, 23), self.rng.between(0, 59), self.rng.between(0, 59));
+        let word = self.rng.pick(WORDS).to_string();
+        Some(match format {
+            "date" => date,
+            "date-time" => format!("{}T{}", date, time),
+            "time" => time,
+            "email" => format!("{}@example.com", word),
+            "uri" | "url" | "uri-reference" | "iri" => format!("https://example.com/{}", word),
+            "hostname" | "idn-hostname" => format!("{}.example.com", word),
+            "ipv4" => format!("192.0.2.{}", self.rng.between(1, 254)),
+            "ipv6" => format!("2001:db8::{:x}", self.rng.between(1, 0xffff)),
+            "uuid" => {
+                let hex: String = (0..32).map(|_| char::from_digit(self.rng.below(16) as u32, 16).unwrap_or('0')).collect();
+                // Version 4, RFC 4122 variant.
+                let variant = char::from_digit(8 + self.rng.below(4) as u32, 16).unwrap_or('8');
+                format!("{}-{}-4{}-{}{}-{}", &hex[..8], &hex[8..12], &hex[13..16], variant, &hex[17..20], &hex[20..])
+            }
+            _ => return None,
+        })
+    }
+
+    fn array(&mut self, obj: &Map<String, Value>, depth: usize) -> Result<Value, String> {
+        let min = obj.get("minItems").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let max = obj.get("maxItems").and_then(Value::as_u64).map(|m| m as usize);
+        let tuple = match obj.get("items") {
+            Some(Value::Array(tuple)) => tuple.as_slice(),
+            _ => &[],
+        };
+        let rest = match obj.get("items") {
+            Some(Value::Array(_)) => obj.get("additionalItems"),
+            items => items,
+        };
+        if rest == Some(&Value::Bool(false)) && min > tuple.len() {
+            return Err(format!("minItems {} exceeds the {} tuple items allowed", min, tuple.len()));
+        }
+
+        let preferred = if depth >= SHALLOW_DEPTH { min } else { min.max(1 + self.rng.below(3) as usize) };
+        let mut len = preferred.max(tuple.len().min(max.unwrap_or(usize::MAX)));
+        if let Some(max) = max {
+            len = len.min(max);
+        }
+        if rest == Some(&Value::Bool(false)) {
+            len = len.min(tuple.len());
+        }
+        let unique = obj.get("uniqueItems") == Some(&Value::Bool(true));
+
+        let any = Value::Bool(true);
+        let mut items: Vec<Value> = Vec::with_capacity(len);
+        for i in 0..len {
+            let schema = tuple.get(i).or(rest).unwrap_or(&any);
+            let mut item = self.value(schema, depth + 1, "")?;
+            // A few retries usually find a distinct value.
+            let mut attempts = 0;
+            while unique && items.contains(&item) && attempts < 16 {
+                item = self.value(schema, depth + 1, "")?;
+                attempts += 1;
+            }
+            if unique && items.contains(&item) {
+                if items.len() >= min {
+                    break;
+                }
+                return Err("Could not generate enough distinct items for uniqueItems".to_string());
+            }
+            items.push(item);
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn object(&mut self, obj: &Map<String, Value>, depth: usize) -> Result<Value, String> {
+        let empty = Map::new();
+        let properties = obj.get("properties").and_then(Value::as_object).unwrap_or(&empty);
+        let required: Vec<&str> = obj
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|r| r.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        let min_properties = obj.get("minProperties").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let extra = match obj.get("additionalProperties") {
+            Some(Value::Bool(false)) => None,
+            Some(schema) => Some(schema.clone()),
+            None => Some(Value::Bool(true)),
+        };
+
+        let mut result = Map::new();
+        for key in &required {
+            let schema = match (properties.get(*key), &extra) {
+                (Some(schema), _) => schema,
+                (None, Some(schema)) => schema,
+                (None, None) => return Err(format!("Required property '{}' is not allowed by the schema", key)),
+            };
+            let value = self.value(schema, depth + 1, key)?;
+            result.insert((*key).to_string(), value);
+        }
+        for (key, schema) in properties {
+            if result.contains_key(key) {
+                continue;
+            }
+            let include = result.len() < min_properties
+                || (depth < SHALLOW_DEPTH
+                    && match self.optional {
+                        OptionalProperties::All => true,
+                        OptionalProperties::None => false,
+                        OptionalProperties::Random => self.rng.chance(),
+                    });
+            if include {
+                let value = self.value(schema, depth + 1, key)?;
+                result.insert(key.clone(), value);
+            }
+        }
+        if result.len() < min_properties {
+            let schema = extra.ok_or_else(|| format!("minProperties {} needs more properties than allowed", min_properties))?;
+            while result.len() < min_properties {
+                let key = format!("{}{}", self.rng.pick(WORDS), result.len());
+                let value = self.value(&schema, depth + 1, &key)?;
+                result.insert(key, value);
+            }
+        }
+        Ok(Value::Object(result))
+    }
+}
+
+/// Combines two schemas for generation: properties and `required` are
+/// unioned, other keywords from `extra` win.
+fn merge_into(mut base: Map<String, Value>, extra: Map<String, Value>) -> Map<String, Value> {
+    for (key, value) in extra {
+        match (key.as_str(), base.get_mut(&key), value) {
+            ("properties", Some(Value::Object(existing)), Value::Object(more)) => existing.extend(more),
+            ("required", Some(Value::Array(existing)), Value::Array(more)) => {
+                for name in more {
+                    if !existing.contains(&name) {
+                        existing.push(name);
+                    }
+                }
+            }
+            (_, _, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+    base
+}
+
+/// A numeric bound as `(value, exclusive)`.
+type Bound = Option<(f64, bool)>;
+
+/// Lower and upper bounds from the draft-06+ keywords.
+fn bounds(obj: &Map<String, Value>) -> (Bound, Bound) {
+    let bound = |inclusive: &str, exclusive: &str| {
+        let inclusive = obj.get(inclusive).and_then(Value::as_f64).map(|v| (v, false));
+        let exclusive = obj.get(exclusive).and_then(Value::as_f64).map(|v| (v, true));
+        (inclusive, exclusive)
+    };
+    let lower = match bound("minimum", "exclusiveMinimum") {
+        (Some(a), Some(b)) => Some(if b.0 >= a.0 { b } else { a }),
+        (a, b) => a.or(b),
+    };
+    let upper = match bound("maximum", "exclusiveMaximum") {
+        (Some(a), Some(b)) => Some(if b.0 <= a.0 { b } else { a }),
+        (a, b) => a.or(b),
+    };
+    (lower, upper)
+}
+
+/// Fills in a missing bound, keeping the range around `lo..hi` when neither is given.
+fn default_range<T>(lo: Option<T>, hi: Option<T>, default_lo: T, default_hi: T) -> (T, T)
+where
+    T: Copy + PartialOrd + std::ops::Add<Output = T> + std::ops::Sub<Output = T>,
+{
+    let span = default_hi - default_lo;
+    match (lo, hi) {
+        (Some(lo), Some(hi)) => (lo, hi),
+        (Some(lo), None) => (lo, lo + span),
+        (None, Some(hi)) if hi >= default_lo => (default_lo, hi),
+        (None, Some(hi)) => (hi - span, hi),
+        (None, None) => (default_lo, default_hi),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn samples(schema: Value, count: usize) -> Result<Value, String> {
+        let options = SampleOptions { count: Some(count), ..SampleOptions::default() };
+        generate_samples(schema, &options)
+    }
+
+    fn assert_valid(schema: &Value, samples: &Value) {
+        let validator = Validator::new(schema);
+        for sample in samples.as_array().unwrap() {
+            assert_eq!(validator.validate(sample).len(), 0, "{} against {}", sample, schema);
+        }
+    }
+
+    #[test]
+    fn samples_pass_validation() {
+        let schema = json!({
+            "type": "object",
+            "required": ["id", "email", "code", "tags", "price"],
+            "properties": {
+                "id": {"type": "integer", "minimum": 1, "exclusiveMaximum": 10},
+                "email": {"type": "string", "format": "email"},
+                "code": {"type": "string", "pattern": "^[A-Z]{3}-\\d+$", "minLength": 10},
+                "tags": {"type": "array", "items": {"enum": ["a", "b"]}, "minItems": 1},
+                "price": {"type": "number", "multipleOf": 0.25, "exclusiveMinimum": 0},
+                "child": {"$ref": "#"}
+            }
+        });
+        let samples = samples(schema.clone(), 20).unwrap();
+        assert_valid(&schema, &samples);
+    }
+
+    #[test]
+    fn pattern_quantifiers_stretch_to_length_bounds() {
+        let long = json!({"type": "string", "pattern": "^[A-Z]{3}-\\d+$", "minLength": 10});
+        assert_valid(&long, &samples(long.clone(), 10).unwrap());
+        let even = json!({"type": "string", "pattern": "^(ab)+$", "minLength": 5, "maxLength": 7});
+        assert!(samples(even, 5).unwrap().as_array().unwrap().iter().all(|s| s == "ababab"));
+    }
+
+    #[test]
+    fn impossible_lengths_are_errors() {
+        assert!(samples(json!({"pattern": "^[A-Z]{3}$", "minLength": 4}), 1).is_err());
+        assert!(samples(json!({"type": "string", "format": "email", "maxLength": 5}), 1).is_err());
+    }
+
+    #[test]
+    fn one_of_takes_exactly_one_option() {
+        let schema = json!({"oneOf": [{"type": "integer"}, {"type": "number"}]});
+        let samples = samples(schema.clone(), 10).unwrap();
+        assert!(samples.as_array().unwrap().iter().all(|s| !s.is_i64() && !s.is_u64()));
+        assert_valid(&schema, &samples);
+    }
+
+    #[test]
+    fn unsatisfiable_schemas_are_errors() {
+        assert!(samples(json!({"type": "integer", "minimum": 5, "maximum": 1}), 1).is_err());
+        assert!(samples(json!({"enum": []}), 1).is_err());
+        assert!(samples(json!({"type": "integer", "not": {"type": "integer"}}), 1).is_err());
+    }
+
+    #[test]
+    fn integer_bounds_at_the_ends_of_i64() {
+        let max = i64::MAX;
+        let above = json!({"type": "integer", "minimum": max, "exclusiveMinimum": max});
+        assert!(samples(above, 1).unwrap_err().starts_with("No integer satisfies"));
+        let below = json!({"type": "integer", "exclusiveMaximum": i64::MIN});
+        assert!(samples(below, 1).unwrap_err().starts_with("No integer satisfies"));
+        assert!(samples(json!({"type": "integer", "minimum": 1e300}), 1).is_err());
+        assert!(samples(json!({"type": "integer", "maximum": -1e300}), 1).is_err());
+
+        for schema in [
+            // 2^63 - 1024, the largest f64 below 2^63.
+            json!({"type": "integer", "minimum": 9_223_372_036_854_774_784i64}),
+            json!({"type": "integer", "maximum": i64::MIN}),
+            json!({"type": "integer", "minimum": i64::MIN, "maximum": i64::MAX}),
+            json!({"type": "integer", "minimum": -1e300, "maximum": 1e300}),
+        ] {
+            let samples = samples(schema.clone(), 10).unwrap();
+            assert!(samples.as_array().unwrap().iter().all(Value::is_i64), "{}", samples);
+            assert_valid(&schema, &samples);
+        }
+    }
+
+    #[test]
+    fn integer_multiples_must_fit() {
+        assert!(samples(json!({"type": "integer", "multipleOf": 7, "minimum": 1, "maximum": 6}), 1).is_err());
+        let schema = json!({"type": "integer", "multipleOf": 3, "minimum": -10, "exclusiveMaximum": 0});
+        let samples = samples(schema.clone(), 10).unwrap();
+        assert!(samples.as_array().unwrap().iter().all(|s| s.as_i64().is_some_and(|i| i % 3 == 0)));
+        assert_valid(&schema, &samples);
+    }
+
+    #[test]
+    fn seeds_are_deterministic() {
+        let schema = json!({"type": "array", "items": {"type": "string"}, "minItems": 3});
+        assert_eq!(samples(schema.clone(), 3), samples(schema, 3));
+    }
+}
@@ -0,0 +1,434 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::dialect::Dialect;
+use crate::formats::detect_format;
+use crate::lenient_json::{parse_json_text, JsonSyntax};
+use crate::naming::type_name;
+
+// Schema inference from sample documents.
+//
+// Every sample is folded into a `Shape` that counts what was seen at each
+// position. Keys missing from some objects become optional, `null` values make
+// a type nullable and array items are merged instead of taken from the first
+// element.
+
+/// How the input text holds its samples.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SampleInput {
+    /// The whole text is one sample.
+    Document,
+    /// The text is a JSON array whose elements are the samples.
+    Samples,
+    /// One sample per line (JSON Lines).
+    Ndjson,
+}
+
+/// Options for schema inference, e.g. `{"input": "ndjson", "enumThreshold": 5}`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub(crate) struct InferOptions {
+    pub input: SampleInput,
+    /// Strings with at most this many distinct values become an `enum`;
+    /// 0 turns enum detection off.
+    pub enum_threshold: usize,
+    /// Emit `format` when every string at a position has the same one.
+    pub formats: bool,
+    /// The JSON Schema draft or OpenAPI version to write.
+    pub draft: Dialect,
+    /// Name of the root schema in OpenAPI `components.schemas`.
+    pub name: String,
+    /// Hoist object schemas that occur more than once into definitions.
+    pub extract_definitions: bool,
+}
+
+impl Default for InferOptions {
+    fn default() -> Self {
+        InferOptions {
+            input: SampleInput::Document,
+            enum_threshold: 0,
+            formats: true,
+            draft: Dialect::Draft07,
+            name: "Root".to_string(),
+            extract_definitions: true,
+        }
+    }
+}
+
+impl InferOptions {
+    pub(crate) fn parse(options: Option<&str>) -> Result<InferOptions, String> {
+        match options.map(str::trim) {
+            None | Some("") => Ok(InferOptions::default()),
+            Some(options) => serde_json::from_str(options).map_err(|e| format!("Invalid schema options: {}", e)),
+        }
+    }
+}
+
+/// Splits `text` into samples according to `input`.
+pub(crate) fn read_samples(text: &str, input: SampleInput, syntax: JsonSyntax) -> Result<Vec<Value>, String> {
+    match input {
+        SampleInput::Document => {
+            let value = parse_json_text(text, syntax).map_err(|e| format!("Invalid JSON: {}", e))?;
+            Ok(vec![value])
+        }
+        SampleInput::Samples => match parse_json_text(text, syntax).map_err(|e| format!("Invalid JSON: {}", e))? {
+            Value::Array(samples) => Ok(samples),
+            _ => Err("Expected a JSON array of samples".to_string()),
+        },
+        SampleInput::Ndjson => parse_ndjson(text),
+    }
+}
+
+/// Parses newline-delimited JSON, skipping blank lines.
+pub(crate) fn parse_ndjson(text: &str) -> Result<Vec<Value>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| serde_json::from_str(line).map_err(|e| format!("Invalid JSON on line {}: {}", i + 1, e)))
+        .collect()
+}
+
+/// Infers a JSON Schema describing all of `samples`.
+pub(crate) fn infer_schema(samples: &[&Value]) -> Value {
+    infer_schema_with(samples, &InferOptions::default())
+}
+
+pub(crate) fn infer_schema_with(samples: &[&Value], options: &InferOptions) -> Value {
+    let mut shape = Shape::default();
+    for sample in samples {
+        shape.add(sample);
+    }
+    shape.to_schema(options)
+}
+
+#[derive(Debug, Default)]
+struct Shape {
+    seen: usize,
+    nulls: usize,
+    booleans: usize,
+    integers: usize,
+    numbers: usize,
+    strings: usize,
+    /// Distinct string values, tracked until there are too many to matter.
+    values: Option<BTreeSet<String>>,
+    /// The format shared by every string so far; `None` once they disagree.
+    format: Option<&'static str>,
+    arrays: usize,
+    items: Option<Box<Shape>>,
+    objects: usize,
+    properties: BTreeMap<String, Shape>,
+}
+
+impl Shape {
+    fn add(&mut self, value: &Value) {
+        self.seen += 1;
+        match value {
+            Value::Null => self.nulls += 1,
+            Value::Bool(_) => self.booleans += 1,
+            Value::Number(n) => {
+                if n.is_i64() || n.is_u64() {
+                    self.integers += 1;
+                } else {
+                    self.numbers += 1;
+                }
+            }
+            Value::String(s) => {
+                let format = detect_format(s);
+                if self.strings == 0 {
+                    self.values = Some(BTreeSet::new());
+                    self.format = format;
+                } else if self.format != format {
+                    self.format = None;
+                }
+                self.strings += 1;
+                if let Some(values) = &mut self.values {
+                    values.insert(s.clone());
+                    if values.len() > MAX_ENUM_VALUES {
+                        self.values = None;
+                    }
+                }
+            }
+            Value::Array(arr) => {
+                self.arrays += 1;
+                let items = self.items.get_or_insert_with(Default::default);
+                for item in arr {
+                    items.add(item);
+                }
+            }
+            Value::Object(obj) => {
+                self.objects += 1;
+                for (key, val) in obj {
+                    self.properties.entry(key.clone()).or_default().add(val);
+                }
+            }
+        }
+    }
+
+    fn to_schema(&self, infer: &InferOptions) -> Value {
+        let mut options = Vec::new();
+        if self.booleans > 0 {
+            options.push(typed("boolean"));
+        }
+        if self.numbers > 0 {
+            options.push(typed("number"));
+        } else if self.integers > 0 {
+            options.push(typed("integer"));
+        }
+        if self.strings > 0 {
+            let mut schema = typed("string");
+            if let (Some(format), true) = (self.format, infer.formats) {
+                schema.insert("format".into(), Value::String(format.into()));
+            }
+            if let Some(values) = self.enum_values(infer.enum_threshold) {
+                schema.insert("enum".into(), Value::Array(values));
+            }
+            options.push(schema);
+        }
+        if self.arrays > 0 {
+            let mut schema = typed("array");
+            let items = match &self.items {
+                Some(items) if items.seen > 0 => items.to_schema(infer),
+                _ => Value::Object(Map::new()),
+            };
+            schema.insert("items".into(), items);
+            options.push(schema);
+        }
+        if self.objects > 0 {
+            options.push(self.object_schema(infer));
+        }
+
+        let nullable = self.nulls > 0;
+        match options.len() {
+            // Only nulls (or nothing at all) were seen.
+            0 if nullable => Value::Object(typed("null")),
+            0 => Value::Object(Map::new()),
+            1 => {
+                let mut schema = options.remove(0);
+                if nullable {
+                    let kind = schema.remove("type").unwrap_or(Value::Null);
+                    schema.insert("type".into(), Value::Array(vec![kind, Value::String("null".into())]));
+                    // `enum` is checked on its own, so it has to allow null too.
+                    if let Some(Value::Array(values)) = schema.get_mut("enum") {
+                        values.push(Value::Null);
+                    }
+                }
+                Value::Object(schema)
+            }
+            // Keywords like `properties` and `items` only apply to their own
+            // type, so the options fold into one schema with a `type` list.
+            // `enum` applies to every type and needs a real union.
+            _ if options.iter().all(|option| !option.contains_key("enum")) => {
+                let mut schema = Map::new();
+                let mut kinds = Vec::new();
+                for option in options {
+                    for (key, value) in option {
+                        if key == "type" {
+                            kinds.push(value);
+                        } else {
+                            schema.insert(key, value);
+                        }
+                    }
+                }
+                if nullable {
+                    kinds.push(Value::String("null".into()));
+                }
+                schema.insert("type".into(), Value::Array(kinds));
+                Value::Object(schema)
+            }
+            _ => {
+                let mut variants: Vec<Value> = options.into_iter().map(Value::Object).collect();
+                if nullable {
+                    variants.push(Value::Object(typed("null")));
+                }
+                let mut schema = Map::new();
+                schema.insert("anyOf".into(), Value::Array(variants));
+                Value::Object(schema)
+            }
+        }
+    }
+
+    /// The values of a low-cardinality string position. A value has to repeat
+    /// somewhere, otherwise every string seen once would become an enum.
+    fn enum_values(&self, threshold: usize) -> Option<Vec<Value>> {
+        let values = self.values.as_ref()?;
+        if values.len() > threshold || values.len() >= self.strings {
+            return None;
+        }
+        Some(values.iter().cloned().map(Value::String).collect())
+    }
+
+    fn object_schema(&self, infer: &InferOptions) -> Map<String, Value> {
+        let mut schema = typed("object");
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for (key, shape) in &self.properties {
+            properties.insert(key.clone(), shape.to_schema(infer));
+            // A key is only required when every object carried it.
+            if shape.seen == self.objects {
+                required.push(Value::String(key.clone()));
+            }
+        }
+        schema.insert("properties".into(), Value::Object(properties));
+        if !required.is_empty() {
+            schema.insert("required".into(), Value::Array(required));
+        }
+        schema
+    }
+}
+
+/// Moves object schemas that occur more than once into `definitions` and
+/// points every occurrence at the shared copy with `$ref`. Definitions are
+/// named after the property where the schema first appears.
+pub(crate) fn extract_definitions(schema: &mut Value, root_name: &str) {
+    let mut counts = HashMap::new();
+    for child in subschemas(schema) {
+        count_objects(child, &mut counts);
+    }
+
+    let mut extractor = Extractor {
+        counts,
+        names: HashMap::new(),
+        definitions: Map::new(),
+        root_name,
+    };
+    // The root itself always stays in place.
+    children(schema, root_name, &mut |child, hint| extractor.visit(child, &hint));
+
+    if let (Value::Object(root), false) = (schema, extractor.definitions.is_empty()) {
+        root.insert("definitions".into(), Value::Object(extractor.definitions));
+    }
+}
+
+/// A key identifying structurally identical object schemas, and whether this
+/// occurrence is nullable. Nullable and non-nullable uses share a definition.
+fn object_signature(schema: &Value) -> Option<(String, bool)> {
+    let obj = schema.as_object()?;
+    if obj.get("properties").and_then(Value::as_object).is_none_or(Map::is_empty) {
+        return None;
+    }
+    let nullable = match obj.get("type")? {
+        Value::String(kind) if kind == "object" => false,
+        Value::Array(kinds) if kinds.len() == 2 && kinds.contains(&"object".into()) && kinds.contains(&"null".into()) => true,
+        _ => return None,
+    };
+    let mut shape = obj.clone();
+    shape.insert("type".into(), Value::String("object".into()));
+    Some((Value::Object(shape).to_string(), nullable))
+}
+
+fn count_objects(schema: &Value, counts: &mut HashMap<String, usize>) {
+    if let Some((signature, _)) = object_signature(schema) {
+        *counts.entry(signature).or_default() += 1;
+    }
+    for child in subschemas(schema) {
+        count_objects(child, counts);
+    }
+}
+
+/// The subschemas `children` visits, read-only.
+fn subschemas(schema: &Value) -> Vec<&Value> {
+    let mut result = Vec::new();
+    if let Some(Value::Object(properties)) = schema.get("properties") {
+        result.extend(properties.values());
+    }
+    for key in ["items", "additionalProperties"] {
+        if let Some(child @ Value::Object(_)) = schema.get(key) {
+            result.push(child);
+        }
+    }
+    for combinator in ["anyOf", "oneOf"] {
+        if let Some(Value::Array(options)) = schema.get(combinator) {
+            result.extend(options);
+        }
+    }
+    result
+}
+
+/// Calls `f` with each subschema of an inferred schema and a name hint for it.
+fn children(schema: &mut Value, hint: &str, f: &mut dyn FnMut(&mut Value, String)) {
+    let obj = match schema {
+        Value::Object(obj) => obj,
+        _ => return,
+    };
+    if let Some(Value::Object(properties)) = obj.get_mut("properties") {
+        for (key, child) in properties.iter_mut() {
+            f(child, key.clone());
+        }
+    }
+    if let Some(items @ Value::Object(_)) = obj.get_mut("items") {
+        f(items, format!("{}Item", hint));
+    }
+    if let Some(value @ Value::Object(_)) = obj.get_mut("additionalProperties") {
+        f(value, format!("{}Value", hint));
+    }
+    for combinator in ["anyOf", "oneOf"] {
+        if let Some(Value::Array(options)) = obj.get_mut(combinator) {
+            for option in options {
+                f(option, hint.to_string());
+            }
+        }
+    }
+}
+
+struct Extractor<'a> {
+    counts: HashMap<String, usize>,
+    /// Definition name by signature.
+    names: HashMap<String, String>,
+    definitions: Map<String, Value>,
+    root_name: &'a str,
+}
+
+impl Extractor<'_> {
+    fn visit(&mut self, schema: &mut Value, hint: &str) {
+        // Signatures are taken before the children are replaced, matching
+        // the counts gathered on the original tree.
+        let signature = object_signature(schema);
+        children(schema, hint, &mut |child, child_hint| self.visit(child, &child_hint));
+
+        let (signature, nullable) = match signature {
+            Some((signature, nullable)) if self.counts.get(&signature).is_some_and(|&n| n > 1) => (signature, nullable),
+            _ => return,
+        };
+        let name = match self.names.get(&signature) {
+            Some(name) => name.clone(),
+            None => {
+                let name = self.unused_name(hint);
+                let mut definition = std::mem::take(schema);
+                if let Value::Object(obj) = &mut definition {
+                    obj.insert("type".into(), Value::String("object".into()));
+                }
+                self.definitions.insert(name.clone(), definition);
+                self.names.insert(signature, name.clone());
+                name
+            }
+        };
+        let reference = serde_json::json!({ "$ref": format!("#/definitions/{}", name) });
+        *schema = if nullable {
+            serde_json::json!({ "anyOf": [reference, { "type": "null" }] })
+        } else {
+            reference
+        };
+    }
+
+    fn unused_name(&self, hint: &str) -> String {
+        let base = type_name(hint);
+        let mut name = base.clone();
+        let mut counter = 2;
+        while name == self.root_name || self.definitions.contains_key(&name) {
+            name = format!("{}{}", base, counter);
+            counter += 1;
+        }
+        name
+    }
+}
+
+/// Past this many distinct values a string position is never an enum.
+const MAX_ENUM_VALUES: usize = 256;
+
+fn typed(kind: &str) -> Map<String, Value> {
+    let mut schema = Map::new();
+    schema.insert("type".into(), Value::String(kind.into()));
+    schema
+}
@@ -0,0 +1,227 @@
+use wasm_bindgen::prelude::*;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+
+use crate::validator::{escape, resolve_ref};
+use crate::visualization::{JsonLink, JsonNode, ProcessedData};
+
+// Schema structure as a graph.
+//
+// Every subschema becomes a node: properties, array items, combinator options
+// and definitions. A `$ref` links to the node of its target, which is created
+// the first time it is referenced, so recursive schemas end in a cycle of
+// links rather than an endless tree.
+
+/// Builds the node/link graph of a JSON Schema, in the same shape as `process_json`.
+#[wasm_bindgen]
+pub fn process_schema_graph(schema_str: &str) -> Result<String, JsError> {
+    let schema: Value = serde_json::from_str(schema_str)
+        .map_err(|e| JsError::new(&format!("Failed to parse JSON Schema: {}", e)))?;
+
+    let data = schema_graph(&schema);
+    serde_json::to_string(&data).map_err(|e| JsError::new(&format!("Failed to serialize: {}", e)))
+}
+
+pub(crate) fn schema_graph(schema: &Value) -> ProcessedData {
+    let mut graph = SchemaGraph::new(schema);
+    let label = schema.get("title").and_then(Value::as_str).unwrap_or("Schema");
+    let root_id = graph.visit(schema, "#".to_string(), label.to_string(), None, 0);
+    for keyword in ["definitions", "$defs"] {
+        graph.visit_unreferenced(&format!("#/{}", keyword), schema.get(keyword), &root_id, 1);
+    }
+    graph.finish()
+}
+
+/// Builds a graph of the schemas inside one document, following `$ref`s
+/// against that document.
+pub(crate) struct SchemaGraph<'a> {
+    root: &'a Value,
+    nodes: Vec<JsonNode>,
+    links: Vec<JsonLink>,
+    next_id: usize,
+    /// Node id for each schema path visited, which `$ref`s link back to.
+    seen: HashMap<String, String>,
+}
+
+impl<'a> SchemaGraph<'a> {
+    pub(crate) fn new(root: &'a Value) -> SchemaGraph<'a> {
+        SchemaGraph {
+            root,
+            nodes: Vec::new(),
+            links: Vec::new(),
+            next_id: 1,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Marks nodes without children as leaves and returns the graph.
+    pub(crate) fn finish(self) -> ProcessedData {
+        let parents: HashSet<&str> = self.links.iter().map(|link| link.source.as_str()).collect();
+        let mut nodes = self.nodes;
+        for node in nodes.iter_mut() {
+            node.is_leaf = !parents.contains(node.id.as_str());
+        }
+        ProcessedData { nodes, links: self.links }
+    }
+
+    /// Adds a node that isn't a schema, such as an API path. `parent` is the
+    /// node it hangs off and the kind of link between them.
+    pub(crate) fn node(&mut self, label: String, value: String, parent: Option<(&str, Option<&str>)>, depth: usize) -> String {
+        let id = self.next_id.to_string();
+        self.next_id += 1;
+        self.nodes.push(JsonNode {
+            id: id.clone(),
+            label,
+            value,
+            depth,
+            parent: parent.map(|(parent, _)| parent.to_string()),
+            is_leaf: true,
+            schema_path: None,
+            schema_type: None,
+            description: None,
+            valid: None,
+            error: None,
+            bson_type: None,
+        });
+        if let Some((parent, kind)) = parent {
+            self.link(parent, &id, kind);
+        }
+        id
+    }
+
+    /// Adds the definitions in `definitions` (found at `path`) that nothing
+    /// has referred to, since they still belong to the contract.
+    pub(crate) fn visit_unreferenced(&mut self, path: &str, definitions: Option<&'a Value>, parent: &str, depth: usize) {
+        if let Some(Value::Object(definitions)) = definitions {
+            for (name, definition) in definitions {
+                let definition_path = format!("{}/{}", path, escape(name));
+                if !self.seen.contains_key(&definition_path) {
+                    self.visit(definition, definition_path, name.clone(), Some((parent, None)), depth);
+                }
+            }
+        }
+    }
+
+    /// Adds a node for `schema` and its subschemas.
+    pub(crate) fn visit(
+        &mut self,
+        schema: &'a Value,
+        path: String,
+        label: String,
+        parent: Option<(&str, Option<&str>)>,
+        depth: usize,
+    ) -> String {
+        let id = self.node(label, summary(schema), parent, depth);
+        self.seen.insert(path.clone(), id.clone());
+
+        let obj = schema.as_object();
+        let field = |key: &str| obj.and_then(|obj| obj.get(key));
+        if let Some(node) = self.nodes.last_mut() {
+            node.schema_path = Some(path.clone());
+            node.schema_type = field("type").map(type_name);
+            node.description = field("description").and_then(Value::as_str).map(str::to_string);
+        }
+
+        let obj = match obj {
+            Some(obj) => obj,
+            None => return id,
+        };
+        if let Some(Value::String(reference)) = obj.get("$ref") {
+            if let Some((target, target_path)) = resolve_ref(self.root, reference) {
+                match self.seen.get(&target_path) {
+                    Some(target_id) => {
+                        let target_id = target_id.clone();
+                        self.link(&id, &target_id, Some("ref"));
+                    }
+                    None => {
+                        let name = target_path.rsplit('/').next().unwrap_or(reference).to_string();
+                        self.visit(target, target_path, name, Some((&id, Some("ref"))), depth + 1);
+                    }
+                }
+            }
+        }
+        for (label, child, child_path) in children(obj, &path) {
+            self.visit(child, child_path, label, Some((&id, None)), depth + 1);
+        }
+        id
+    }
+
+    fn link(&mut self, source: &str, target: &str, kind: Option<&str>) {
+        self.links.push(JsonLink {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind: kind.map(str::to_string),
+        });
+    }
+}
+
+/// The subschemas shown beneath a schema, with their labels and paths.
+/// Combinator options are labelled with their keyword, e.g. `oneOf[1]`.
+fn children<'a>(obj: &'a Map<String, Value>, path: &str) -> Vec<(String, &'a Value, String)> {
+    let mut children = Vec::new();
+    for keyword in ["properties", "patternProperties"] {
+        if let Some(Value::Object(properties)) = obj.get(keyword) {
+            for (name, schema) in properties {
+                children.push((name.clone(), schema, format!("{}/{}/{}", path, keyword, escape(name))));
+            }
+        }
+    }
+    for keyword in ["prefixItems", "items", "allOf", "anyOf", "oneOf"] {
+        match obj.get(keyword) {
+            Some(Value::Array(schemas)) => {
+                for (i, schema) in schemas.iter().enumerate() {
+                    children.push((format!("{}[{}]", keyword, i), schema, format!("{}/{}/{}", path, keyword, i)));
+                }
+            }
+            Some(schema @ Value::Object(_)) if keyword == "items" => {
+                children.push((keyword.to_string(), schema, format!("{}/{}", path, keyword)));
+            }
+            _ => {}
+        }
+    }
+    for keyword in ["additionalProperties", "additionalItems", "contains", "propertyNames", "not", "if", "then", "else"] {
+        if let Some(schema @ Value::Object(_)) = obj.get(keyword) {
+            children.push((keyword.to_string(), schema, format!("{}/{}", path, keyword)));
+        }
+    }
+    children
+}
+
+fn type_name(kind: &Value) -> String {
+    match kind {
+        Value::Array(kinds) => kinds.iter().filter_map(Value::as_str).collect::<Vec<_>>().join(" | "),
+        other => other.as_str().unwrap_or_default().to_string(),
+    }
+}
+
+/// A one-line description of a schema for the node's value.
+fn summary(schema: &Value) -> String {
+    let obj = match schema {
+        Value::Bool(true) => return "any".to_string(),
+        Value::Bool(false) => return "nothing".to_string(),
+        Value::Object(obj) => obj,
+        _ => return String::new(),
+    };
+    if let Some(Value::String(reference)) = obj.get("$ref") {
+        return format!("$ref {}", reference);
+    }
+    if let Some(Value::Array(values)) = obj.get("enum") {
+        return format!("enum ({} values)", values.len());
+    }
+    if let Some(value) = obj.get("const") {
+        return format!("const {}", value);
+    }
+    let kind = match obj.get("type") {
+        Some(kind) => type_name(kind),
+        None if obj.contains_key("properties") => "object".to_string(),
+        None if obj.contains_key("items") || obj.contains_key("prefixItems") => "array".to_string(),
+        None => match ["oneOf", "anyOf", "allOf", "not"].iter().find(|keyword| obj.contains_key(**keyword)) {
+            Some(keyword) => keyword.to_string(),
+            None => "any".to_string(),
+        },
+    };
+    match obj.get("format").and_then(Value::as_str) {
+        Some(format) => format!("{} ({})", kind, format),
+        None => kind,
+    }
+}
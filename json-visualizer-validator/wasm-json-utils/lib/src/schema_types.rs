@@ -0,0 +1,2066 @@
+use wasm_bindgen::prelude::*;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::naming::{
+    dedupe_names, enum_member_name, go_field_name, go_tag, is_python_identifier, java_accessor_name,
+    java_enum_constant, java_field_name, pascal_case, python_enum_member, python_field_name, quoted, rust_field_name,
+    type_name, typescript_property,
+};
+
+// Code generation from JSON Schema documents.
+//
+// The schema is first lowered into a small language-neutral type model
+// (named struct/enum/union/alias definitions plus inline type references)
+// and every target language is rendered from that model.
+
+#[wasm_bindgen]
+pub fn json_schema_to_typescript(schema_str: &str) -> Result<String, JsValue> {
+    let model = parse_schema_model(schema_str).map_err(|e| JsValue::from_str(&e))?;
+    Ok(render_typescript(&model))
+}
+
+/// `options` picks the Java flavor, see `JavaOptions`.
+#[wasm_bindgen]
+pub fn json_schema_to_java(schema_str: &str, options: Option<String>) -> Result<String, JsValue> {
+    let options = JavaOptions::parse(options.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    let model = parse_schema_model(schema_str).map_err(|e| JsValue::from_str(&e))?;
+    Ok(render_java(&model, &options))
+}
+
+#[wasm_bindgen]
+pub fn json_schema_to_golang(schema_str: &str) -> Result<String, JsValue> {
+    let model = parse_schema_model(schema_str).map_err(|e| JsValue::from_str(&e))?;
+    Ok(render_golang(&model))
+}
+
+/// `flavor` selects the class style: `dataclass` (default), `pydantic` or `typeddict`.
+#[wasm_bindgen]
+pub fn json_schema_to_python(schema_str: &str, flavor: Option<String>) -> Result<String, JsValue> {
+    let style = PythonStyle::parse(flavor.as_deref().unwrap_or_default()).map_err(|e| JsValue::from_str(&e))?;
+    let model = parse_schema_model(schema_str).map_err(|e| JsValue::from_str(&e))?;
+    Ok(render_python(&model, style))
+}
+
+#[wasm_bindgen]
+pub fn json_schema_to_rust(schema_str: &str) -> Result<String, JsValue> {
+    let model = parse_schema_model(schema_str).map_err(|e| JsValue::from_str(&e))?;
+    Ok(render_rust(&model))
+}
+
+/// Renders Python for a schema that was built in-process (e.g. inferred from a sample).
+pub(crate) fn schema_to_python(schema: &Value, root_name: &str, style: PythonStyle) -> Result<String, String> {
+    let model = build_type_model(schema, root_name)?;
+    Ok(render_python(&model, style))
+}
+
+/// Renders Java for a schema that was built in-process.
+pub(crate) fn schema_to_java(schema: &Value, root_name: &str, options: &JavaOptions) -> Result<String, String> {
+    let model = build_type_model(schema, root_name)?;
+    Ok(render_java(&model, options))
+}
+
+fn parse_schema_model(schema_str: &str) -> Result<TypeModel, String> {
+    let schema: Value = serde_json::from_str(schema_str)
+        .map_err(|e| format!("JSON Schema parsing error: {}", e))?;
+    build_type_model(&schema, "RootType")
+}
+
+// Type model
+
+/// String formats that map onto richer types in the generated code.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum StringFormat {
+    DateTime,
+    Date,
+    Time,
+    Uuid,
+    Uri,
+    Ipv4,
+    Ipv6,
+}
+
+impl StringFormat {
+    /// Formats without a richer type in any language (`email`, `hostname`)
+    /// stay plain strings.
+    fn from_keyword(format: &str) -> Option<StringFormat> {
+        match format {
+            "date-time" => Some(StringFormat::DateTime),
+            "date" => Some(StringFormat::Date),
+            "time" => Some(StringFormat::Time),
+            "uuid" => Some(StringFormat::Uuid),
+            "uri" => Some(StringFormat::Uri),
+            "ipv4" => Some(StringFormat::Ipv4),
+            "ipv6" => Some(StringFormat::Ipv6),
+            _ => None,
+        }
+    }
+}
+
+/// A reference to a type, either a primitive, a container or a named definition.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TypeRef {
+    Any,
+    Null,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    Formatted(StringFormat),
+    Array(Box<TypeRef>),
+    Map(Box<TypeRef>),
+    Named(String),
+    Nullable(Box<TypeRef>),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct FieldDef {
+    pub json_name: String,
+    pub ty: TypeRef,
+    pub required: bool,
+    pub description: Option<String>,
+}
+
+/// Property and tag values that select a variant of a tagged union.
+#[derive(Debug, Clone)]
+pub(crate) struct Discriminator {
+    pub property: String,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum TypeKind {
+    Struct(Vec<FieldDef>),
+    Enum(Vec<String>),
+    Union {
+        variants: Vec<TypeRef>,
+        discriminator: Option<Discriminator>,
+    },
+    Alias(TypeRef),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TypeDef {
+    pub name: String,
+    pub description: Option<String>,
+    pub kind: TypeKind,
+}
+
+/// Named definitions in dependency order (referenced types come first where
+/// the schema is not recursive).
+#[derive(Debug, Clone)]
+pub(crate) struct TypeModel {
+    pub defs: Vec<TypeDef>,
+    /// Name of the definition for the document root.
+    pub root: String,
+}
+
+impl TypeModel {
+    pub(crate) fn get(&self, name: &str) -> Option<&TypeDef> {
+        self.defs.iter().find(|def| def.name == name)
+    }
+
+    /// Returns the discriminator property a struct must not declare itself
+    /// because a tagged union it belongs to already carries it.
+    fn tag_property_for(&self, struct_name: &str) -> Option<&str> {
+        for def in &self.defs {
+            if let TypeKind::Union { variants, discriminator: Some(disc) } = &def.kind {
+                if variants.iter().any(|v| matches!(v, TypeRef::Named(n) if n == struct_name)) {
+                    return Some(&disc.property);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Names of the other definitions that `def` refers to.
+pub(crate) fn referenced_names(def: &TypeDef) -> BTreeSet<String> {
+    fn collect(ty: &TypeRef, names: &mut BTreeSet<String>) {
+        match ty {
+            TypeRef::Named(name) => {
+                names.insert(name.clone());
+            }
+            TypeRef::Array(inner) | TypeRef::Map(inner) | TypeRef::Nullable(inner) => collect(inner, names),
+            _ => {}
+        }
+    }
+    let mut names = BTreeSet::new();
+    match &def.kind {
+        TypeKind::Struct(fields) => fields.iter().for_each(|f| collect(&f.ty, &mut names)),
+        TypeKind::Union { variants, .. } => variants.iter().for_each(|v| collect(v, &mut names)),
+        TypeKind::Alias(target) => collect(target, &mut names),
+        TypeKind::Enum(_) => {}
+    }
+    names.remove(&def.name);
+    names
+}
+
+/// Lowers a JSON Schema document into a `TypeModel`.
+pub(crate) fn build_type_model(schema: &Value, default_root_name: &str) -> Result<TypeModel, String> {
+    if !schema.is_object() && !schema.is_boolean() {
+        return Err("JSON Schema must be an object".to_string());
+    }
+
+    let mut builder = ModelBuilder::new(schema);
+
+    // Reserve names for every reusable definition up front so that `$ref`s
+    // (including recursive ones) resolve to stable names.
+    for container in ["$defs", "definitions"] {
+        builder.reserve_definitions(&format!("#/{}", container), schema.get(container));
+    }
+
+    let root_name = schema
+        .get("title")
+        .and_then(Value::as_str)
+        .map(type_name)
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| default_root_name.to_string());
+    let root_name = builder.unique_name(&root_name);
+    builder.pointer_names.push(("#".to_string(), root_name.clone()));
+    builder.build_pointer("#")?;
+
+    // Unreferenced definitions are still part of the contract.
+    let pointers: Vec<String> = builder.pointer_names.iter().map(|(p, _)| p.clone()).collect();
+    for pointer in pointers {
+        builder.build_pointer(&pointer)?;
+    }
+
+    Ok(TypeModel { defs: builder.defs, root: root_name })
+}
+
+/// Lowers the `components.schemas` of an OpenAPI document into a
+/// `TypeModel` with one definition per component, named after it. `root`
+/// names the model itself (e.g. the Java holder class); no definition has it.
+pub(crate) fn build_components_model(document: &Value, root: &str) -> Result<TypeModel, String> {
+    let mut builder = ModelBuilder::new(document);
+    builder.reserve_definitions("#/components/schemas", document.pointer("/components/schemas"));
+    let root = builder.unique_name(root);
+
+    let pointers: Vec<String> = builder.pointer_names.iter().map(|(p, _)| p.clone()).collect();
+    for pointer in pointers {
+        builder.build_pointer(&pointer)?;
+    }
+    Ok(TypeModel { defs: builder.defs, root })
+}
+
+struct ModelBuilder<'a> {
+    document: &'a Value,
+    defs: Vec<TypeDef>,
+    used_names: HashSet<String>,
+    pointer_names: Vec<(String, String)>,
+    visited: HashSet<String>,
+}
+
+impl<'a> ModelBuilder<'a> {
+    fn new(document: &'a Value) -> Self {
+        ModelBuilder {
+            document,
+            defs: Vec::new(),
+            used_names: HashSet::new(),
+            pointer_names: Vec::new(),
+            visited: HashSet::new(),
+        }
+    }
+
+    fn unique_name(&mut self, base: &str) -> String {
+        let base = if base.is_empty() { "Type".to_string() } else { base.to_string() };
+        let mut candidate = base.clone();
+        let mut counter = 2;
+        while self.used_names.contains(&candidate) {
+            candidate = format!("{}{}", base, counter);
+            counter += 1;
+        }
+        self.used_names.insert(candidate.clone());
+        candidate
+    }
+
+    /// Names every entry of the `definitions` object found at `container`.
+    fn reserve_definitions(&mut self, container: &str, definitions: Option<&Value>) {
+        if let Some(Value::Object(defs)) = definitions {
+            for key in defs.keys() {
+                let pointer = format!("{}/{}", container, escape_pointer_token(key));
+                let name = self.unique_name(&type_name(key));
+                self.pointer_names.push((pointer, name));
+            }
+        }
+    }
+
+    fn name_for_pointer(&self, pointer: &str) -> Option<String> {
+        self.pointer_names
+            .iter()
+            .find(|(p, _)| p == pointer)
+            .map(|(_, name)| name.clone())
+    }
+
+    /// Builds the named definition registered for `pointer` unless it has
+    /// already been built or is currently being built (recursive reference).
+    fn build_pointer(&mut self, pointer: &str) -> Result<String, String> {
+        let name = self
+            .name_for_pointer(pointer)
+            .ok_or_else(|| format!("Unknown schema reference: {}", pointer))?;
+        if self.visited.insert(pointer.to_string()) {
+            let schema = resolve_pointer(self.document, pointer)
+                .ok_or_else(|| format!("Unresolvable $ref: {}", pointer))?;
+            let kind = self.kind_for(schema, &name)?;
+            self.defs.push(TypeDef {
+                name: name.clone(),
+                description: description_of(schema),
+                kind,
+            });
+        }
+        Ok(name)
+    }
+
+    fn resolve_ref(&mut self, reference: &str) -> Result<TypeRef, String> {
+        if !reference.starts_with('#') {
+            // Remote references cannot be fetched from inside the generator.
+            return Ok(TypeRef::Any);
+        }
+        if self.name_for_pointer(reference).is_none() {
+            if resolve_pointer(self.document, reference).is_none() {
+                return Err(format!("Unresolvable $ref: {}", reference));
+            }
+            let last = reference.rsplit('/').next().unwrap_or("Ref");
+            let name = self.unique_name(&type_name(&unescape_pointer_token(last)));
+            self.pointer_names.push((reference.to_string(), name));
+        }
+        Ok(TypeRef::Named(self.build_pointer(reference)?))
+    }
+
+    /// Returns a reference to the type described by `schema`, registering a
+    /// new named definition (called `hint` unless the schema has a title)
+    /// when the schema needs one.
+    fn type_for(&mut self, schema: &Value, hint: &str) -> Result<TypeRef, String> {
+        let obj = match schema {
+            Value::Bool(_) => return Ok(TypeRef::Any),
+            Value::Object(obj) => obj,
+            _ => return Err("Subschema must be an object or a boolean".to_string()),
+        };
+
+        if let Some(Value::String(reference)) = obj.get("$ref") {
+            return self.resolve_ref(reference);
+        }
+
+        // `oneOf: [X, {type: null}]` is just a nullable X.
+        for combinator in ["oneOf", "anyOf"] {
+            if let Some(Value::Array(options)) = obj.get(combinator) {
+                let non_null: Vec<&Value> = options.iter().filter(|o| !is_null_schema(o)).collect();
+                if non_null.len() == 1 {
+                    let inner = self.type_for(non_null[0], hint)?;
+                    return Ok(if non_null.len() < options.len() || type_includes_null(obj) {
+                        make_nullable(inner)
+                    } else {
+                        inner
+                    });
+                }
+            }
+        }
+
+        if needs_named_type(obj) {
+            let base = obj
+                .get("title")
+                .and_then(Value::as_str)
+                .map(type_name)
+                .filter(|title| !title.is_empty())
+                .unwrap_or_else(|| hint.to_string());
+            let name = self.unique_name(&base);
+            let kind = self.kind_for(schema, &name)?;
+            if let TypeKind::Alias(target) = kind {
+                // Unions that collapse into a single (nullable) type stay inline.
+                self.used_names.remove(&name);
+                return Ok(target);
+            }
+            // `type: ["object", "null"]` and `enum: [..., null]` keep the
+            // null outside the definition; unions already carry it as a variant.
+            let enum_has_null = matches!(obj.get("enum"), Some(Value::Array(values)) if values.iter().any(Value::is_null));
+            let nullable = match &kind {
+                TypeKind::Union { variants, .. } => !variants.contains(&TypeRef::Null) && type_includes_null(obj),
+                _ => type_includes_null(obj) || enum_has_null,
+            };
+            self.defs.push(TypeDef {
+                name: name.clone(),
+                description: description_of(schema),
+                kind,
+            });
+            let named = TypeRef::Named(name);
+            return Ok(if nullable { make_nullable(named) } else { named });
+        }
+
+        self.inline_type(obj, hint)
+    }
+
+    /// Describes the kind of a named definition.
+    fn kind_for(&mut self, schema: &Value, name: &str) -> Result<TypeKind, String> {
+        let obj = match schema {
+            Value::Object(obj) => obj,
+            _ => return Ok(TypeKind::Alias(TypeRef::Any)),
+        };
+
+        if let Some(Value::String(reference)) = obj.get("$ref") {
+            return Ok(TypeKind::Alias(self.resolve_ref(reference)?));
+        }
+
+        if let Some(Value::Array(values)) = obj.get("enum") {
+            let strings: Vec<String> = values
+                .iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect();
+            let has_null = values.iter().any(Value::is_null);
+            if !strings.is_empty() && strings.len() + has_null as usize == values.len() {
+                return Ok(TypeKind::Enum(strings));
+            }
+            return Ok(TypeKind::Alias(primitive_for_values(values)));
+        }
+
+        for combinator in ["oneOf", "anyOf"] {
+            if let Some(Value::Array(options)) = obj.get(combinator) {
+                return self.union_kind(obj, options, name);
+            }
+        }
+
+        if let Some(Value::Array(parts)) = obj.get("allOf") {
+            return self.all_of_kind(obj, parts, name);
+        }
+
+        if let Some(Value::Array(types)) = obj.get("type") {
+            let non_null: Vec<&Value> = types.iter().filter(|t| t.as_str() != Some("null")).collect();
+            if non_null.len() > 1 {
+                let mut variants = Vec::new();
+                for t in non_null {
+                    let mut single = obj.clone();
+                    single.insert("type".into(), t.clone());
+                    let hint = format!("{}{}", name, pascal_case(t.as_str().unwrap_or("Value")));
+                    variants.push(self.type_for(&Value::Object(single), &hint)?);
+                }
+                return Ok(TypeKind::Union { variants, discriminator: None });
+            }
+        }
+
+        if is_object_schema(obj) {
+            if let Some(Value::Object(props)) = obj.get("properties") {
+                return Ok(TypeKind::Struct(self.fields_for(props, &required_of(obj), name)?));
+            }
+        }
+
+        Ok(TypeKind::Alias(self.inline_type(obj, name)?))
+    }
+
+    fn fields_for(
+        &mut self,
+        props: &Map<String, Value>,
+        required: &HashSet<String>,
+        owner: &str,
+    ) -> Result<Vec<FieldDef>, String> {
+        let mut fields = Vec::new();
+        for (key, prop_schema) in props {
+            let hint = format!("{}{}", owner, pascal_case(key));
+            let ty = self.type_for(prop_schema, &hint)?;
+            fields.push(FieldDef {
+                json_name: key.clone(),
+                ty,
+                required: required.contains(key),
+                description: description_of(prop_schema),
+            });
+        }
+        Ok(fields)
+    }
+
+    fn union_kind(
+        &mut self,
+        obj: &Map<String, Value>,
+        options: &[Value],
+        name: &str,
+    ) -> Result<TypeKind, String> {
+        let non_null: Vec<&Value> = options.iter().filter(|o| !is_null_schema(o)).collect();
+        let nullable = non_null.len() < options.len() || type_includes_null(obj);
+
+        if non_null.len() == 1 {
+            let inner = self.type_for(non_null[0], name)?;
+            return Ok(TypeKind::Alias(if nullable { make_nullable(inner) } else { inner }));
+        }
+
+        let discriminator = self.find_discriminator(obj, &non_null);
+        let mut variants = Vec::new();
+        for (i, option) in non_null.iter().enumerate() {
+            let hint = match &discriminator {
+                Some(disc) => format!("{}{}", name, pascal_case(&disc.tags[i])),
+                None => format!("{}Variant{}", name, i + 1),
+            };
+            variants.push(self.type_for(option, &hint)?);
+        }
+        if nullable {
+            variants.push(TypeRef::Null);
+        }
+        Ok(TypeKind::Union { variants, discriminator })
+    }
+
+    /// Detects a property that carries a distinct constant in every variant,
+    /// or uses an explicit OpenAPI-style `discriminator.propertyName`.
+    fn find_discriminator(&self, obj: &Map<String, Value>, options: &[&Value]) -> Option<Discriminator> {
+        let variants: Vec<&Map<String, Value>> = options
+            .iter()
+            .map(|o| self.deref_schema(o).and_then(Value::as_object))
+            .collect::<Option<Vec<_>>>()?;
+
+        let explicit = obj
+            .get("discriminator")
+            .and_then(|d| d.get("propertyName"))
+            .and_then(Value::as_str);
+
+        let candidates: Vec<String> = match explicit {
+            Some(property) => vec![property.to_string()],
+            None => variants
+                .first()?
+                .get("properties")
+                .and_then(Value::as_object)?
+                .keys()
+                .cloned()
+                .collect(),
+        };
+
+        for property in candidates {
+            let tags: Option<Vec<String>> = variants
+                .iter()
+                .map(|variant| {
+                    let prop = variant.get("properties")?.get(&property)?;
+                    constant_string(prop)
+                })
+                .collect();
+            if let Some(tags) = tags {
+                let distinct: HashSet<&String> = tags.iter().collect();
+                if distinct.len() == tags.len() {
+                    return Some(Discriminator { property, tags });
+                }
+            }
+        }
+        None
+    }
+
+    fn all_of_kind(
+        &mut self,
+        obj: &Map<String, Value>,
+        parts: &[Value],
+        name: &str,
+    ) -> Result<TypeKind, String> {
+        let mut props = Map::new();
+        let mut required = required_of(obj);
+        if let Some(Value::Object(own)) = obj.get("properties") {
+            props.extend(own.clone());
+        }
+        for part in parts {
+            let part = match self.deref_schema(part).and_then(Value::as_object) {
+                Some(part) => part.clone(),
+                None => continue,
+            };
+            if let Some(Value::Object(part_props)) = part.get("properties") {
+                for (key, value) in part_props {
+                    props.entry(key.clone()).or_insert_with(|| value.clone());
+                }
+            }
+            required.extend(required_of(&part));
+        }
+        if props.is_empty() {
+            return Ok(TypeKind::Alias(TypeRef::Map(Box::new(TypeRef::Any))));
+        }
+        Ok(TypeKind::Struct(self.fields_for(&props, &required, name)?))
+    }
+
+    /// Follows local `$ref` chains to the schema they point at.
+    fn deref_schema(&self, schema: &'a Value) -> Option<&'a Value> {
+        let mut current = schema;
+        for _ in 0..32 {
+            match current.get("$ref").and_then(Value::as_str) {
+                Some(reference) => current = resolve_pointer(self.document, reference)?,
+                None => return Some(current),
+            }
+        }
+        None
+    }
+
+    /// Types that never need a named definition of their own.
+    fn inline_type(&mut self, obj: &Map<String, Value>, hint: &str) -> Result<TypeRef, String> {
+        if let Some(constant) = obj.get("const") {
+            return Ok(primitive_for_values(std::slice::from_ref(constant)));
+        }
+
+        let nullable = type_includes_null(obj);
+        let ty = match primary_type(obj).as_deref() {
+            Some("null") => return Ok(TypeRef::Null),
+            Some("boolean") => TypeRef::Boolean,
+            Some("integer") => TypeRef::Integer,
+            Some("number") => TypeRef::Number,
+            Some("string") => match obj.get("format").and_then(Value::as_str).and_then(StringFormat::from_keyword) {
+                Some(format) => TypeRef::Formatted(format),
+                None => TypeRef::String,
+            },
+            Some("array") => {
+                let item_hint = format!("{}Item", hint);
+                let items = match obj.get("items") {
+                    Some(Value::Array(_)) | None => match obj.get("prefixItems") {
+                        Some(Value::Array(_)) | None => TypeRef::Any,
+                        Some(other) => self.type_for(other, &item_hint)?,
+                    },
+                    Some(items) => self.type_for(items, &item_hint)?,
+                };
+                TypeRef::Array(Box::new(items))
+            }
+            Some("object") => {
+                let values = match obj.get("additionalProperties") {
+                    Some(Value::Object(_)) => {
+                        let value_hint = format!("{}Value", hint);
+                        self.type_for(&obj["additionalProperties"], &value_hint)?
+                    }
+                    _ => TypeRef::Any,
+                };
+                TypeRef::Map(Box::new(values))
+            }
+            _ => TypeRef::Any,
+        };
+        Ok(if nullable { make_nullable(ty) } else { ty })
+    }
+}
+
+// Schema helpers
+
+fn needs_named_type(obj: &Map<String, Value>) -> bool {
+    if let Some(Value::Array(values)) = obj.get("enum") {
+        return values.iter().any(Value::is_string);
+    }
+    if obj.contains_key("oneOf") || obj.contains_key("anyOf") || obj.contains_key("allOf") {
+        return true;
+    }
+    if let Some(Value::Array(types)) = obj.get("type") {
+        if types.iter().filter(|t| t.as_str() != Some("null")).count() > 1 {
+            return true;
+        }
+    }
+    is_object_schema(obj) && obj.get("properties").is_some_and(|p| p.is_object())
+}
+
+fn is_object_schema(obj: &Map<String, Value>) -> bool {
+    primary_type(obj).as_deref() == Some("object")
+}
+
+/// The first non-null `type`, inferred from structural keywords when absent.
+fn primary_type(obj: &Map<String, Value>) -> Option<String> {
+    match obj.get("type") {
+        Some(Value::String(t)) => Some(t.clone()),
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|t| *t != "null")
+            .or_else(|| types.iter().filter_map(Value::as_str).next())
+            .map(String::from),
+        _ => {
+            if obj.contains_key("properties") || obj.contains_key("additionalProperties") {
+                Some("object".to_string())
+            } else if obj.contains_key("items") || obj.contains_key("prefixItems") {
+                Some("array".to_string())
+            } else if obj.contains_key("format") || obj.contains_key("pattern") {
+                Some("string".to_string())
+            } else {
+                None
+            }
+        }
+    }
+}
+
+fn type_includes_null(obj: &Map<String, Value>) -> bool {
+    let by_type = match obj.get("type") {
+        Some(Value::Array(types)) => types.iter().any(|t| t.as_str() == Some("null")),
+        _ => false,
+    };
+    by_type || obj.get("nullable").and_then(Value::as_bool).unwrap_or(false)
+}
+
+fn is_null_schema(schema: &Value) -> bool {
+    schema.get("type").and_then(Value::as_str) == Some("null")
+}
+
+fn make_nullable(ty: TypeRef) -> TypeRef {
+    match ty {
+        TypeRef::Nullable(_) | TypeRef::Null | TypeRef::Any => ty,
+        other => TypeRef::Nullable(Box::new(other)),
+    }
+}
+
+fn required_of(obj: &Map<String, Value>) -> HashSet<String> {
+    match obj.get("required") {
+        Some(Value::Array(keys)) => keys.iter().filter_map(|k| k.as_str().map(String::from)).collect(),
+        _ => HashSet::new(),
+    }
+}
+
+fn description_of(schema: &Value) -> Option<String> {
+    schema
+        .get("description")
+        .and_then(Value::as_str)
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty())
+}
+
+fn constant_string(schema: &Value) -> Option<String> {
+    if let Some(Value::String(s)) = schema.get("const") {
+        return Some(s.clone());
+    }
+    match schema.get("enum") {
+        Some(Value::Array(values)) if values.len() == 1 => values[0].as_str().map(String::from),
+        _ => None,
+    }
+}
+
+fn primitive_for_values(values: &[Value]) -> TypeRef {
+    let non_null: Vec<&Value> = values.iter().filter(|v| !v.is_null()).collect();
+    let ty = if non_null.iter().all(|v| v.is_string()) && !non_null.is_empty() {
+        TypeRef::String
+    } else if non_null.iter().all(|v| v.is_i64() || v.is_u64()) && !non_null.is_empty() {
+        TypeRef::Integer
+    } else if non_null.iter().all(|v| v.is_number()) && !non_null.is_empty() {
+        TypeRef::Number
+    } else if non_null.iter().all(|v| v.is_boolean()) && !non_null.is_empty() {
+        TypeRef::Boolean
+    } else {
+        TypeRef::Any
+    };
+    if non_null.len() < values.len() {
+        make_nullable(ty)
+    } else {
+        ty
+    }
+}
+
+/// Resolves a local JSON Pointer reference such as `#/definitions/Address`.
+pub(crate) fn resolve_pointer<'v>(document: &'v Value, reference: &str) -> Option<&'v Value> {
+    let pointer = reference.strip_prefix('#')?;
+    if pointer.is_empty() {
+        return Some(document);
+    }
+    document.pointer(pointer)
+}
+
+fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape_pointer_token(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+fn doc_lines(description: &str) -> Vec<&str> {
+    description.lines().map(str::trim_end).collect()
+}
+
+fn push_block_doc(out: &mut String, indent: &str, description: &Option<String>) {
+    if let Some(description) = description {
+        out.push_str(&format!("{}/**\n", indent));
+        for line in doc_lines(description) {
+            out.push_str(&format!("{} * {}\n", indent, line.replace("*/", "*\\/")).replace(" * \n", " *\n"));
+        }
+        out.push_str(&format!("{} */\n", indent));
+    }
+}
+
+pub(crate) fn push_line_doc(out: &mut String, indent: &str, marker: &str, description: &Option<String>) {
+    if let Some(description) = description {
+        for line in doc_lines(description) {
+            if line.is_empty() {
+                out.push_str(&format!("{}{}\n", indent, marker));
+            } else {
+                out.push_str(&format!("{}{} {}\n", indent, marker, line));
+            }
+        }
+    }
+}
+
+// TypeScript
+
+pub(crate) fn render_typescript(model: &TypeModel) -> String {
+    let blocks: Vec<String> = model.defs.iter().map(typescript_def).collect();
+    blocks.join("\n\n") + "\n"
+}
+
+pub(crate) fn typescript_def(def: &TypeDef) -> String {
+    let mut out = String::new();
+    push_block_doc(&mut out, "", &def.description);
+    match &def.kind {
+        TypeKind::Struct(fields) => {
+            out.push_str(&format!("export interface {} {{\n", def.name));
+            for field in fields {
+                push_block_doc(&mut out, "  ", &field.description);
+                let key = typescript_property(&field.json_name);
+                let optional = if field.required { "" } else { "?" };
+                out.push_str(&format!("  {}{}: {};\n", key, optional, ts_type(&field.ty)));
+            }
+            out.push('}');
+        }
+        TypeKind::Enum(values) => {
+            out.push_str(&format!("export enum {} {{\n", def.name));
+            let members = dedupe_names(values.iter().map(|v| enum_member_name(v)).collect());
+            for (value, member) in values.iter().zip(members) {
+                out.push_str(&format!("  {} = {},\n", member, quoted(value)));
+            }
+            out.push('}');
+        }
+        TypeKind::Union { variants, .. } => {
+            let members: Vec<String> = variants.iter().map(ts_type).collect();
+            out.push_str(&format!("export type {} = {};", def.name, members.join(" | ")));
+        }
+        TypeKind::Alias(target) => {
+            out.push_str(&format!("export type {} = {};", def.name, ts_type(target)));
+        }
+    }
+    out
+}
+
+fn ts_type(ty: &TypeRef) -> String {
+    match ty {
+        TypeRef::Any => "any".to_string(),
+        TypeRef::Null => "null".to_string(),
+        TypeRef::Boolean => "boolean".to_string(),
+        TypeRef::Integer | TypeRef::Number => "number".to_string(),
+        TypeRef::String | TypeRef::Formatted(_) => "string".to_string(),
+        TypeRef::Array(inner) => match inner.as_ref() {
+            TypeRef::Nullable(_) => format!("({})[]", ts_type(inner)),
+            _ => format!("{}[]", ts_type(inner)),
+        },
+        TypeRef::Map(inner) => format!("Record<string, {}>", ts_type(inner)),
+        TypeRef::Named(name) => name.clone(),
+        TypeRef::Nullable(inner) => format!("{} | null", ts_type(inner)),
+    }
+}
+
+// Java
+
+/// Class style for generated Java types.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum JavaStyle {
+    Record,
+    Pojo,
+    Lombok,
+}
+
+/// Where the types other than the root one are declared.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum JavaNesting {
+    /// Static nested types of the root type.
+    Inner,
+    /// One top-level type per file.
+    Files,
+}
+
+/// Options for Java output, given either as JSON
+/// (`{"style": "record", "jackson": false, "nested": "inner"}`) or as a bare
+/// style name.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct JavaOptions {
+    pub style: JavaStyle,
+    pub jackson: bool,
+    pub nested: JavaNesting,
+}
+
+impl Default for JavaOptions {
+    fn default() -> Self {
+        JavaOptions {
+            style: JavaStyle::Pojo,
+            jackson: true,
+            nested: JavaNesting::Files,
+        }
+    }
+}
+
+impl JavaOptions {
+    pub(crate) fn parse(options: Option<&str>) -> Result<JavaOptions, String> {
+        let options = match options.map(str::trim) {
+            None | Some("") => return Ok(JavaOptions::default()),
+            Some(options) => options,
+        };
+        if options.starts_with('{') {
+            return serde_json::from_str(options).map_err(|e| format!("Invalid Java options: {}", e));
+        }
+        let style = serde_json::from_value(Value::String(options.to_ascii_lowercase()))
+            .map_err(|_| format!("Unknown Java style '{}' (expected record, pojo or lombok)", options))?;
+        Ok(JavaOptions { style, ..JavaOptions::default() })
+    }
+}
+
+/// A top-level Java type together with the imports it needs.
+pub(crate) struct JavaUnit {
+    pub name: String,
+    pub imports: BTreeSet<&'static str>,
+    pub code: String,
+}
+
+pub(crate) fn render_java(model: &TypeModel, options: &JavaOptions) -> String {
+    let parents = java_union_parents(model);
+    let host = match model.get(&model.root).map(|def| &def.kind) {
+        Some(TypeKind::Struct(_)) if options.nested == JavaNesting::Inner => Some(model.root.as_str()),
+        _ => None,
+    };
+    let units: Vec<JavaUnit> = model
+        .defs
+        .iter()
+        .map(|def| {
+            let nested = options.nested == JavaNesting::Inner && host != Some(def.name.as_str());
+            java_unit(def, model, options, &parents, nested)
+        })
+        .collect();
+
+    match options.nested {
+        JavaNesting::Files => units
+            .iter()
+            .map(|unit| format!("// {}.java\n{}", unit.name, java_file(&unit.imports, &unit.code)))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        JavaNesting::Inner => {
+            let mut imports = BTreeSet::new();
+            let mut inner = Vec::new();
+            let mut outer = None;
+            for unit in units {
+                imports.extend(unit.imports);
+                if host == Some(unit.name.as_str()) {
+                    outer = Some(unit.code);
+                } else {
+                    inner.push(unit.code);
+                }
+            }
+            // Without a root class to hang them on, the types share a holder class.
+            let outer = outer.unwrap_or_else(|| {
+                let holder = format!("{}Types", model.root);
+                format!("public final class {} {{\n    private {}() {{\n    }}\n}}", holder, holder)
+            });
+            java_file(&imports, &java_nest(&outer, &inner))
+        }
+    }
+}
+
+pub(crate) fn java_file(imports: &BTreeSet<&'static str>, code: &str) -> String {
+    let mut result = String::new();
+    for import in imports {
+        result.push_str(&format!("import {};\n", import));
+    }
+    if !imports.is_empty() {
+        result.push('\n');
+    }
+    result.push_str(code);
+    result.push('\n');
+    result
+}
+
+/// Moves `inner` declarations inside the body of `outer`.
+fn java_nest(outer: &str, inner: &[String]) -> String {
+    if inner.is_empty() {
+        return outer.to_string();
+    }
+    let body = outer.trim_end();
+    let body = body.strip_suffix('}').unwrap_or(body).trim_end();
+    let indented: Vec<String> = inner
+        .iter()
+        .map(|code| {
+            code.lines()
+                .map(|line| if line.is_empty() { String::new() } else { format!("    {}", line) })
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect();
+    let separator = if body.ends_with('{') { "\n" } else { "\n\n" };
+    format!("{}{}{}\n}}", body, separator, indented.join("\n\n"))
+}
+
+pub(crate) fn java_unit(
+    def: &TypeDef,
+    model: &TypeModel,
+    options: &JavaOptions,
+    parents: &HashMap<String, String>,
+    nested: bool,
+) -> JavaUnit {
+    let mut imports = BTreeSet::new();
+    let mut out = String::new();
+    let class_keyword = if nested { "public static class" } else { "public class" };
+    match &def.kind {
+        TypeKind::Struct(fields) => {
+            let implements = match parents.get(&def.name) {
+                Some(parent) => format!(" implements {}", parent),
+                None => String::new(),
+            };
+            let field_names = dedupe_names(
+                fields
+                    .iter()
+                    .map(|f| {
+                        let name = java_field_name(&f.json_name);
+                        if options.style == JavaStyle::Record && JAVA_OBJECT_METHODS.contains(&name.as_str()) {
+                            // Record accessors would override `Object` methods.
+                            format!("{}_", name)
+                        } else {
+                            name
+                        }
+                    })
+                    .collect(),
+            );
+            let typed: Vec<(&FieldDef, String, String)> = fields
+                .iter()
+                .zip(field_names)
+                .map(|(field, name)| {
+                    let ty = java_field_type(field, &mut imports);
+                    (field, name, ty)
+                })
+                .collect();
+
+            if options.style == JavaStyle::Record {
+                // Component docs become `@param` tags of the record.
+                let mut doc = def.description.clone().unwrap_or_default();
+                for (field, name, _) in &typed {
+                    if let Some(description) = &field.description {
+                        if !doc.is_empty() {
+                            doc.push('\n');
+                        }
+                        doc.push_str(&format!("@param {} {}", name, description.replace('\n', " ")));
+                    }
+                }
+                push_block_doc(&mut out, "", &Some(doc).filter(|d| !d.is_empty()));
+            } else {
+                push_block_doc(&mut out, "", &def.description);
+            }
+            if options.style == JavaStyle::Lombok {
+                imports.insert("lombok.Data");
+                out.push_str("@Data\n");
+            }
+            if options.jackson {
+                imports.insert("com.fasterxml.jackson.annotation.JsonIgnoreProperties");
+                out.push_str("@JsonIgnoreProperties(ignoreUnknown = true)\n");
+                if fields.iter().any(|f| !f.required) {
+                    imports.insert("com.fasterxml.jackson.annotation.JsonInclude");
+                    out.push_str("@JsonInclude(JsonInclude.Include.NON_NULL)\n");
+                }
+            }
+            let property = |imports: &mut BTreeSet<&'static str>, field: &FieldDef| {
+                if options.jackson {
+                    imports.insert("com.fasterxml.jackson.annotation.JsonProperty");
+                    format!("@JsonProperty({}) ", quoted(&field.json_name))
+                } else {
+                    String::new()
+                }
+            };
+
+            if options.style == JavaStyle::Record {
+                if typed.is_empty() {
+                    out.push_str(&format!("public record {}(){} {{\n}}", def.name, implements));
+                } else {
+                    let components: Vec<String> = typed
+                        .iter()
+                        .map(|(field, name, ty)| format!("    {}{} {}", property(&mut imports, field), ty, name))
+                        .collect();
+                    out.push_str(&format!(
+                        "public record {}(\n{}\n){} {{\n}}",
+                        def.name,
+                        components.join(",\n"),
+                        implements
+                    ));
+                }
+            } else {
+                out.push_str(&format!("{} {}{} {{\n", class_keyword, def.name, implements));
+                let mut accessors = Vec::new();
+                for (field, name, ty) in &typed {
+                    push_block_doc(&mut out, "    ", &field.description);
+                    let annotation = property(&mut imports, field);
+                    if !annotation.is_empty() {
+                        out.push_str(&format!("    {}\n", annotation.trim_end()));
+                    }
+                    out.push_str(&format!("    private {} {};\n", ty, name));
+                    if options.style == JavaStyle::Pojo {
+                        let accessor = java_accessor_name(name);
+                        let getter = if ty == "boolean" { "is" } else { "get" };
+                        accessors.push(format!(
+                            "    public {} {}{}() {{\n        return this.{};\n    }}\n\n    public void set{}({} {}) {{\n        this.{} = {};\n    }}",
+                            ty, getter, accessor, name, accessor, ty, name, name, name
+                        ));
+                    }
+                }
+                if !accessors.is_empty() {
+                    out.push('\n');
+                    out.push_str(&accessors.join("\n\n"));
+                    out.push('\n');
+                }
+                out.push('}');
+            }
+        }
+        TypeKind::Enum(values) => {
+            push_block_doc(&mut out, "", &def.description);
+            out.push_str(&format!("public enum {} {{\n", def.name));
+            let names = dedupe_names(values.iter().map(|v| java_enum_constant(v)).collect());
+            let constants: Vec<String> = values
+                .iter()
+                .zip(names)
+                .map(|(v, name)| format!("    {}({})", name, quoted(v)))
+                .collect();
+            out.push_str(&constants.join(",\n"));
+            out.push_str(";\n\n");
+            out.push_str("    private final String value;\n\n");
+            out.push_str(&format!("    {}(String value) {{\n        this.value = value;\n    }}\n\n", def.name));
+            if options.jackson {
+                imports.insert("com.fasterxml.jackson.annotation.JsonValue");
+                out.push_str("    @JsonValue\n");
+            }
+            out.push_str("    public String getValue() {\n        return value;\n    }\n}");
+        }
+        TypeKind::Union { variants, discriminator } if java_union_is_interface(variants, model) => {
+            push_block_doc(&mut out, "", &def.description);
+            let names = named_variants(variants);
+            if options.jackson {
+                imports.insert("com.fasterxml.jackson.annotation.JsonSubTypes");
+                imports.insert("com.fasterxml.jackson.annotation.JsonTypeInfo");
+                let subtypes: Vec<String> = match discriminator {
+                    Some(disc) => {
+                        out.push_str(&format!(
+                            "@JsonTypeInfo(use = JsonTypeInfo.Id.NAME, include = JsonTypeInfo.As.EXISTING_PROPERTY, property = {}, visible = true)\n",
+                            quoted(&disc.property)
+                        ));
+                        names
+                            .iter()
+                            .zip(&disc.tags)
+                            .map(|(name, tag)| format!("    @JsonSubTypes.Type(value = {}.class, name = {})", name, quoted(tag)))
+                            .collect()
+                    }
+                    None => {
+                        out.push_str("@JsonTypeInfo(use = JsonTypeInfo.Id.DEDUCTION)\n");
+                        names.iter().map(|name| format!("    @JsonSubTypes.Type({}.class)", name)).collect()
+                    }
+                };
+                out.push_str(&format!("@JsonSubTypes({{\n{}\n}})\n", subtypes.join(",\n")));
+            } else if let Some(disc) = discriminator {
+                out.push_str(&format!("// Variants are selected by the \"{}\" property:\n", disc.property));
+                for (tag, name) in disc.tags.iter().zip(&names) {
+                    out.push_str(&format!("//   \"{}\" -> {}\n", tag, name));
+                }
+            }
+            out.push_str(&format!("public interface {} {{\n}}", def.name));
+        }
+        TypeKind::Union { variants, .. } => {
+            let members: Vec<String> = variants.iter().map(|v| java_type(v, &mut imports)).collect();
+            push_block_doc(&mut out, "", &def.description);
+            out.push_str(&format!("// {} is one of: {}\n", def.name, members.join(", ")));
+            java_wrapper(&mut out, &def.name, "Object", class_keyword, options, &mut imports);
+        }
+        TypeKind::Alias(target) => {
+            let target = java_type(target, &mut imports);
+            push_block_doc(&mut out, "", &def.description);
+            out.push_str(&format!("// {} is represented as {}\n", def.name, target));
+            java_wrapper(&mut out, &def.name, &target, class_keyword, options, &mut imports);
+        }
+    }
+    JavaUnit {
+        name: def.name.clone(),
+        imports,
+        code: out,
+    }
+}
+
+/// Methods of `java.lang.Object` that a record component must not shadow.
+const JAVA_OBJECT_METHODS: &[&str] = &[
+    "clone", "finalize", "getClass", "hashCode", "notify", "notifyAll", "toString", "wait",
+];
+
+/// A single-value type that (de)serializes as the value itself.
+fn java_wrapper(
+    out: &mut String,
+    name: &str,
+    target: &str,
+    class_keyword: &str,
+    options: &JavaOptions,
+    imports: &mut BTreeSet<&'static str>,
+) {
+    if options.jackson {
+        imports.insert("com.fasterxml.jackson.annotation.JsonCreator");
+        imports.insert("com.fasterxml.jackson.annotation.JsonValue");
+    }
+    let creator = if options.jackson { "    @JsonCreator(mode = JsonCreator.Mode.DELEGATING)\n" } else { "" };
+    let json_value = if options.jackson { "    @JsonValue\n" } else { "" };
+    match options.style {
+        JavaStyle::Record => {
+            let annotation = if options.jackson { "@JsonValue " } else { "" };
+            out.push_str(&format!("public record {}({}{} value) {{\n", name, annotation, target));
+            if options.jackson {
+                out.push_str(&format!("{}    public {} {{\n    }}\n", creator, name));
+            }
+            out.push('}');
+        }
+        JavaStyle::Pojo => {
+            out.push_str(&format!("{} {} {{\n    private {} value;\n\n", class_keyword, name, target));
+            out.push_str(&format!("    public {}() {{\n    }}\n\n", name));
+            out.push_str(&format!(
+                "{}    public {}({} value) {{\n        this.value = value;\n    }}\n\n",
+                creator, name, target
+            ));
+            out.push_str(&format!(
+                "{}    public {} getValue() {{\n        return this.value;\n    }}\n\n",
+                json_value, target
+            ));
+            out.push_str(&format!(
+                "    public void setValue({} value) {{\n        this.value = value;\n    }}\n}}",
+                target
+            ));
+        }
+        JavaStyle::Lombok => {
+            imports.insert("lombok.Data");
+            imports.insert("lombok.NoArgsConstructor");
+            out.push_str("@Data\n@NoArgsConstructor\n");
+            out.push_str(&format!("{} {} {{\n{}    private {} value;\n\n", class_keyword, name, json_value, target));
+            out.push_str(&format!(
+                "{}    public {}({} value) {{\n        this.value = value;\n    }}\n}}",
+                creator, name, target
+            ));
+        }
+    }
+}
+
+fn named_variants(variants: &[TypeRef]) -> Vec<String> {
+    variants
+        .iter()
+        .filter_map(|v| match v {
+            TypeRef::Named(name) => Some(name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// A union can be modelled as an interface when every variant is a struct.
+fn java_union_is_interface(variants: &[TypeRef], model: &TypeModel) -> bool {
+    variants.iter().all(|v| match v {
+        TypeRef::Named(name) => matches!(model.get(name).map(|d| &d.kind), Some(TypeKind::Struct(_))),
+        _ => false,
+    })
+}
+
+pub(crate) fn java_union_parents(model: &TypeModel) -> HashMap<String, String> {
+    let mut parents = HashMap::new();
+    for def in &model.defs {
+        if let TypeKind::Union { variants, .. } = &def.kind {
+            if java_union_is_interface(variants, model) {
+                for name in named_variants(variants) {
+                    parents.entry(name).or_insert_with(|| def.name.clone());
+                }
+            }
+        }
+    }
+    parents
+}
+
+fn java_type(ty: &TypeRef, imports: &mut BTreeSet<&'static str>) -> String {
+    match ty {
+        TypeRef::Any | TypeRef::Null => "Object".to_string(),
+        TypeRef::Boolean => "Boolean".to_string(),
+        TypeRef::Integer => "Long".to_string(),
+        TypeRef::Number => "Double".to_string(),
+        TypeRef::String => "String".to_string(),
+        TypeRef::Formatted(StringFormat::DateTime) => {
+            imports.insert("java.time.OffsetDateTime");
+            "OffsetDateTime".to_string()
+        }
+        TypeRef::Formatted(StringFormat::Date) => {
+            imports.insert("java.time.LocalDate");
+            "LocalDate".to_string()
+        }
+        TypeRef::Formatted(StringFormat::Time) => {
+            imports.insert("java.time.OffsetTime");
+            "OffsetTime".to_string()
+        }
+        TypeRef::Formatted(StringFormat::Uuid) => {
+            imports.insert("java.util.UUID");
+            "UUID".to_string()
+        }
+        TypeRef::Formatted(StringFormat::Uri) => {
+            imports.insert("java.net.URI");
+            "URI".to_string()
+        }
+        TypeRef::Formatted(StringFormat::Ipv4 | StringFormat::Ipv6) => "String".to_string(),
+        TypeRef::Array(inner) => {
+            imports.insert("java.util.List");
+            format!("List<{}>", java_type(inner, imports))
+        }
+        TypeRef::Map(inner) => {
+            imports.insert("java.util.Map");
+            format!("Map<String, {}>", java_type(inner, imports))
+        }
+        TypeRef::Named(name) => name.clone(),
+        // Java reference types are nullable already.
+        TypeRef::Nullable(inner) => java_type(inner, imports),
+    }
+}
+
+/// Required, non-nullable numbers and booleans use primitives; everything
+/// else needs the boxed type so that it can hold `null`.
+fn java_field_type(field: &FieldDef, imports: &mut BTreeSet<&'static str>) -> String {
+    match (&field.ty, field.required) {
+        (TypeRef::Boolean, true) => "boolean".to_string(),
+        (TypeRef::Integer, true) => "long".to_string(),
+        (TypeRef::Number, true) => "double".to_string(),
+        (ty, _) => java_type(ty, imports),
+    }
+}
+
+// Go
+
+pub(crate) fn render_golang(model: &TypeModel) -> String {
+    let mut imports = BTreeSet::new();
+    let blocks: Vec<String> = model.defs.iter().map(|def| golang_def(model, def, &mut imports)).collect();
+    go_file("main", &imports, &blocks.join("\n\n"))
+}
+
+pub(crate) fn go_file(package: &str, imports: &BTreeSet<&'static str>, code: &str) -> String {
+    let mut result = format!("package {}\n\n", package);
+    if !imports.is_empty() {
+        result.push_str("import (\n");
+        for import in imports {
+            result.push_str(&format!("\t\"{}\"\n", import));
+        }
+        result.push_str(")\n\n");
+    }
+    result.push_str(code);
+    result.push('\n');
+    result
+}
+
+pub(crate) fn golang_def(model: &TypeModel, def: &TypeDef, imports: &mut BTreeSet<&'static str>) -> String {
+    let mut out = String::new();
+    let doc = def.description.as_ref().map(|d| format!("{} {}", def.name, d));
+    push_line_doc(&mut out, "", "//", &doc);
+    match &def.kind {
+        TypeKind::Struct(fields) => {
+            out.push_str(&format!("type {} struct {{\n", def.name));
+            let field_names = dedupe_names(fields.iter().map(|f| go_field_name(&f.json_name)).collect());
+            for (field, field_name) in fields.iter().zip(field_names) {
+                push_line_doc(&mut out, "\t", "//", &field.description);
+                let mut go_type = go_type(&field.ty, model, imports);
+                let pointer_needed = !field.required || is_self_reference(&field.ty, &def.name);
+                if pointer_needed && !go_type.starts_with('*') && go_is_value_type(&field.ty, model) {
+                    go_type = format!("*{}", go_type);
+                }
+                let omit = if field.required { "" } else { ",omitempty" };
+                let tag = format!("json:\"{}{}\"", field.json_name.replace('"', "\\\""), omit);
+                out.push_str(&format!("\t{} {} {}\n", field_name, go_type, go_tag(&tag)));
+            }
+            out.push('}');
+        }
+        TypeKind::Enum(values) => {
+            out.push_str(&format!("type {} string\n\nconst (\n", def.name));
+            let members = dedupe_names(values.iter().map(|v| enum_member_name(v)).collect());
+            for (value, member) in values.iter().zip(members) {
+                out.push_str(&format!("\t{}{} {} = {}\n", def.name, member, def.name, quoted(value)));
+            }
+            out.push(')');
+        }
+        TypeKind::Union { variants, discriminator } => {
+            let members: Vec<String> = variants.iter().map(|v| go_type(v, model, imports)).collect();
+            match discriminator {
+                Some(disc) => out.push_str(&format!(
+                    "// {} is one of: {} (selected by the \"{}\" property).\n",
+                    def.name,
+                    members.join(", "),
+                    disc.property
+                )),
+                None => out.push_str(&format!("// {} is one of: {}.\n", def.name, members.join(", "))),
+            }
+            out.push_str(&format!("type {} interface{{}}", def.name));
+        }
+        TypeKind::Alias(target) => {
+            out.push_str(&format!("type {} {}", def.name, go_type(target, model, imports)));
+        }
+    }
+    out
+}
+
+fn is_self_reference(ty: &TypeRef, owner: &str) -> bool {
+    match ty {
+        TypeRef::Named(name) => name == owner,
+        TypeRef::Nullable(inner) => is_self_reference(inner, owner),
+        _ => false,
+    }
+}
+
+/// Whether the Go type has no natural nil value and needs a pointer to be optional.
+fn go_is_value_type(ty: &TypeRef, model: &TypeModel) -> bool {
+    match ty {
+        TypeRef::Any | TypeRef::Null | TypeRef::Array(_) | TypeRef::Map(_) => false,
+        TypeRef::Nullable(inner) => go_is_value_type(inner, model),
+        TypeRef::Named(name) => match model.get(name).map(|def| &def.kind) {
+            Some(TypeKind::Union { .. }) => false,
+            Some(TypeKind::Alias(target)) => go_is_value_type(target, model),
+            _ => true,
+        },
+        _ => true,
+    }
+}
+
+fn go_type(ty: &TypeRef, model: &TypeModel, imports: &mut BTreeSet<&'static str>) -> String {
+    match ty {
+        TypeRef::Any | TypeRef::Null => "interface{}".to_string(),
+        TypeRef::Boolean => "bool".to_string(),
+        TypeRef::Integer => "int64".to_string(),
+        TypeRef::Number => "float64".to_string(),
+        // `time.Time` only unmarshals full RFC 3339 timestamps.
+        TypeRef::Formatted(StringFormat::DateTime) => {
+            imports.insert("time");
+            "time.Time".to_string()
+        }
+        TypeRef::String | TypeRef::Formatted(_) => "string".to_string(),
+        TypeRef::Array(inner) => format!("[]{}", go_type(inner, model, imports)),
+        TypeRef::Map(inner) => format!("map[string]{}", go_type(inner, model, imports)),
+        TypeRef::Named(name) => name.clone(),
+        TypeRef::Nullable(inner) => {
+            let inner_type = go_type(inner, model, imports);
+            if go_is_value_type(inner, model) {
+                format!("*{}", inner_type)
+            } else {
+                inner_type
+            }
+        }
+    }
+}
+
+// Python
+
+/// Class style used for generated Python structs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum PythonStyle {
+    Dataclass,
+    Pydantic,
+    TypedDict,
+}
+
+impl PythonStyle {
+    pub(crate) fn parse(name: &str) -> Result<PythonStyle, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "" | "dataclass" | "dataclasses" => Ok(PythonStyle::Dataclass),
+            "pydantic" => Ok(PythonStyle::Pydantic),
+            "typeddict" | "typed_dict" => Ok(PythonStyle::TypedDict),
+            other => Err(format!(
+                "Unknown Python flavor '{}' (expected dataclass, pydantic or typeddict)",
+                other
+            )),
+        }
+    }
+}
+
+/// `from module import name` lines collected while rendering Python.
+#[derive(Default)]
+pub(crate) struct PythonImports {
+    modules: BTreeMap<&'static str, BTreeSet<&'static str>>,
+}
+
+impl PythonImports {
+    pub(crate) fn add(&mut self, module: &'static str, name: &'static str) {
+        self.modules.entry(module).or_default().insert(name);
+    }
+
+    fn render(&self) -> String {
+        let line = |(module, names): (&&str, &BTreeSet<&str>)| {
+            format!("from {} import {}\n", module, names.iter().copied().collect::<Vec<_>>().join(", "))
+        };
+        // Third-party imports go in their own group after the standard library.
+        let standard: String = self.modules.iter().filter(|(m, _)| **m != "pydantic").map(line).collect();
+        let third_party: String = self.modules.iter().filter(|(m, _)| **m == "pydantic").map(line).collect();
+        match (standard.is_empty(), third_party.is_empty()) {
+            (_, true) => standard,
+            (true, false) => third_party,
+            (false, false) => format!("{}\n{}", standard, third_party),
+        }
+    }
+}
+
+pub(crate) fn python_header(imports: &PythonImports) -> String {
+    format!("from __future__ import annotations\n\n{}", imports.render())
+}
+
+pub(crate) fn render_python(model: &TypeModel, style: PythonStyle) -> String {
+    let mut imports = PythonImports::default();
+    let mut blocks = Vec::new();
+    // Names bound so far; anything else is quoted where Python evaluates the
+    // annotation at import time (aliases and functional TypedDicts).
+    let mut defined = HashSet::new();
+
+    for def in &model.defs {
+        blocks.push(python_def(def, style, &mut imports, &defined));
+        defined.insert(def.name.clone());
+    }
+
+    let mut result = python_header(&imports);
+    result.push_str("\n\n");
+    result.push_str(&blocks.join("\n\n"));
+    result
+}
+
+pub(crate) fn python_def(
+    def: &TypeDef,
+    style: PythonStyle,
+    imports: &mut PythonImports,
+    defined: &HashSet<String>,
+) -> String {
+    let mut out = String::new();
+    match &def.kind {
+        TypeKind::Struct(fields) => match style {
+            PythonStyle::Dataclass => render_python_dataclass(&mut out, def, fields, imports),
+            PythonStyle::Pydantic => render_python_pydantic(&mut out, def, fields, imports),
+            PythonStyle::TypedDict => render_python_typed_dict(&mut out, def, fields, imports, defined),
+        },
+        TypeKind::Enum(values) if style == PythonStyle::TypedDict => {
+            // TypedDicts hold plain JSON values, so enums stay strings.
+            push_line_doc(&mut out, "", "#", &def.description);
+            imports.add("typing", "Literal");
+            let members: Vec<String> = values.iter().map(|v| quoted(v)).collect();
+            out.push_str(&format!("{} = Literal[{}]\n", def.name, members.join(", ")));
+        }
+        TypeKind::Enum(values) => {
+            imports.add("enum", "Enum");
+            out.push_str(&format!("class {}(str, Enum):\n", def.name));
+            push_python_docstring(&mut out, &def.description);
+            let members = dedupe_names(values.iter().map(|v| python_enum_member(v)).collect());
+            for (value, member) in values.iter().zip(members) {
+                out.push_str(&format!("    {} = {}\n", member, quoted(value)));
+            }
+        }
+        TypeKind::Union { variants, .. } => {
+            push_line_doc(&mut out, "", "#", &def.description);
+            imports.add("typing", "Union");
+            let members: Vec<String> = variants
+                .iter()
+                .map(|v| python_type(v, imports, Some(defined)))
+                .collect();
+            out.push_str(&format!("{} = Union[{}]\n", def.name, members.join(", ")));
+        }
+        TypeKind::Alias(target) => {
+            push_line_doc(&mut out, "", "#", &def.description);
+            let target = python_type(target, imports, Some(defined));
+            out.push_str(&format!("{} = {}\n", def.name, target));
+        }
+    }
+    out
+}
+
+fn push_python_docstring(out: &mut String, description: &Option<String>) {
+    if let Some(description) = description {
+        out.push_str(&format!("    \"\"\"{}\"\"\"\n", description.replace("\"\"\"", "\\\"\\\"\\\"")));
+    }
+}
+
+/// Annotation for an optional field: `Optional[T]` unless `T` already allows `None`.
+fn python_optional(py_type: String, imports: &mut PythonImports) -> String {
+    if py_type.starts_with("Optional[") || py_type == "Any" || py_type == "None" {
+        py_type
+    } else {
+        imports.add("typing", "Optional");
+        format!("Optional[{}]", py_type)
+    }
+}
+
+fn render_python_dataclass(out: &mut String, def: &TypeDef, fields: &[FieldDef], imports: &mut PythonImports) {
+    imports.add("dataclasses", "dataclass");
+    out.push_str(&format!("@dataclass\nclass {}:\n", def.name));
+    push_python_docstring(out, &def.description);
+    if fields.is_empty() {
+        out.push_str("    pass\n");
+    }
+    let field_names = dedupe_names(fields.iter().map(|f| python_field_name(&f.json_name)).collect());
+    let named: Vec<(&FieldDef, String)> = fields.iter().zip(field_names).collect();
+    // Fields with defaults must follow the ones without.
+    let mut ordered: Vec<&(&FieldDef, String)> = named.iter().filter(|(f, _)| f.required).collect();
+    ordered.extend(named.iter().filter(|(f, _)| !f.required));
+    for (field, field_name) in ordered {
+        push_line_doc(out, "    ", "#", &field.description);
+        let mut py_type = python_type(&field.ty, imports, None);
+        if !field.required {
+            py_type = python_optional(py_type, imports);
+        }
+        // Renamed fields keep the wire name as an alias.
+        let alias = format!("metadata={{\"alias\": {}}}", quoted(&field.json_name));
+        match (field.required, *field_name == field.json_name) {
+            (true, true) => out.push_str(&format!("    {}: {}\n", field_name, py_type)),
+            (false, true) => out.push_str(&format!("    {}: {} = None\n", field_name, py_type)),
+            (true, false) => {
+                imports.add("dataclasses", "field");
+                out.push_str(&format!("    {}: {} = field({})\n", field_name, py_type, alias));
+            }
+            (false, false) => {
+                imports.add("dataclasses", "field");
+                out.push_str(&format!("    {}: {} = field(default=None, {})\n", field_name, py_type, alias));
+            }
+        }
+    }
+}
+
+fn render_python_pydantic(out: &mut String, def: &TypeDef, fields: &[FieldDef], imports: &mut PythonImports) {
+    imports.add("pydantic", "BaseModel");
+    out.push_str(&format!("class {}(BaseModel):\n", def.name));
+    push_python_docstring(out, &def.description);
+    if fields.is_empty() {
+        out.push_str("    pass\n");
+        return;
+    }
+    let field_names = dedupe_names(fields.iter().map(|f| python_field_name(&f.json_name)).collect());
+    if fields.iter().zip(&field_names).any(|(f, name)| *name != f.json_name) {
+        // Accept both the Python name and the wire name when validating.
+        imports.add("pydantic", "ConfigDict");
+        out.push_str("    model_config = ConfigDict(populate_by_name=True)\n\n");
+    }
+    for (field, field_name) in fields.iter().zip(&field_names) {
+        push_line_doc(out, "    ", "#", &field.description);
+        let mut py_type = python_type(&field.ty, imports, None);
+        if !field.required {
+            py_type = python_optional(py_type, imports);
+        }
+        let alias = if *field_name == field.json_name {
+            None
+        } else {
+            imports.add("pydantic", "Field");
+            Some(format!("alias={}", quoted(&field.json_name)))
+        };
+        match (field.required, alias) {
+            (true, None) => out.push_str(&format!("    {}: {}\n", field_name, py_type)),
+            (false, None) => out.push_str(&format!("    {}: {} = None\n", field_name, py_type)),
+            (true, Some(alias)) => out.push_str(&format!("    {}: {} = Field({})\n", field_name, py_type, alias)),
+            (false, Some(alias)) => {
+                out.push_str(&format!("    {}: {} = Field(default=None, {})\n", field_name, py_type, alias))
+            }
+        }
+    }
+}
+
+fn render_python_typed_dict(
+    out: &mut String,
+    def: &TypeDef,
+    fields: &[FieldDef],
+    imports: &mut PythonImports,
+    defined: &HashSet<String>,
+) {
+    imports.add("typing", "TypedDict");
+    // TypedDict keys are the JSON keys themselves, so keys that are not valid
+    // identifiers need the functional syntax.
+    let functional = fields.iter().any(|f| !is_python_identifier(&f.json_name));
+    let mut entries = Vec::new();
+    for field in fields {
+        let defined = if functional { Some(defined) } else { None };
+        let mut py_type = python_type(&field.ty, imports, defined);
+        if !field.required {
+            imports.add("typing", "NotRequired");
+            py_type = format!("NotRequired[{}]", py_type);
+        }
+        entries.push((field, py_type));
+    }
+
+    if functional {
+        push_line_doc(out, "", "#", &def.description);
+        out.push_str(&format!("{} = TypedDict({}, {{\n", def.name, quoted(&def.name)));
+        for (field, py_type) in entries {
+            push_line_doc(out, "    ", "#", &field.description);
+            out.push_str(&format!("    {}: {},\n", quoted(&field.json_name), py_type));
+        }
+        out.push_str("})\n");
+    } else {
+        out.push_str(&format!("class {}(TypedDict):\n", def.name));
+        push_python_docstring(out, &def.description);
+        if entries.is_empty() {
+            out.push_str("    pass\n");
+        }
+        for (field, py_type) in entries {
+            push_line_doc(out, "    ", "#", &field.description);
+            out.push_str(&format!("    {}: {}\n", field.json_name, py_type));
+        }
+    }
+}
+
+/// Renders a type annotation. With `defined`, references to names that are
+/// not bound yet are quoted as forward references.
+fn python_type(ty: &TypeRef, imports: &mut PythonImports, defined: Option<&HashSet<String>>) -> String {
+    match ty {
+        TypeRef::Any => {
+            imports.add("typing", "Any");
+            "Any".to_string()
+        }
+        TypeRef::Null => "None".to_string(),
+        TypeRef::Boolean => "bool".to_string(),
+        TypeRef::Integer => "int".to_string(),
+        TypeRef::Number => "float".to_string(),
+        TypeRef::String => "str".to_string(),
+        TypeRef::Formatted(StringFormat::DateTime) => {
+            imports.add("datetime", "datetime");
+            "datetime".to_string()
+        }
+        TypeRef::Formatted(StringFormat::Date) => {
+            imports.add("datetime", "date");
+            "date".to_string()
+        }
+        TypeRef::Formatted(StringFormat::Time) => {
+            imports.add("datetime", "time");
+            "time".to_string()
+        }
+        TypeRef::Formatted(StringFormat::Uuid) => {
+            imports.add("uuid", "UUID");
+            "UUID".to_string()
+        }
+        TypeRef::Formatted(StringFormat::Ipv4) => {
+            imports.add("ipaddress", "IPv4Address");
+            "IPv4Address".to_string()
+        }
+        TypeRef::Formatted(StringFormat::Ipv6) => {
+            imports.add("ipaddress", "IPv6Address");
+            "IPv6Address".to_string()
+        }
+        TypeRef::Formatted(StringFormat::Uri) => "str".to_string(),
+        TypeRef::Array(inner) => format!("list[{}]", python_type(inner, imports, defined)),
+        TypeRef::Map(inner) => format!("dict[str, {}]", python_type(inner, imports, defined)),
+        TypeRef::Named(name) => match defined {
+            Some(defined) if !defined.contains(name) => quoted(name),
+            _ => name.clone(),
+        },
+        TypeRef::Nullable(inner) => {
+            imports.add("typing", "Optional");
+            format!("Optional[{}]", python_type(inner, imports, defined))
+        }
+    }
+}
+
+// Rust
+
+pub(crate) fn render_rust(model: &TypeModel) -> String {
+    let mut uses = BTreeSet::new();
+    let blocks: Vec<String> = model.defs.iter().map(|def| rust_def(model, def, &mut uses)).collect();
+    rust_file(&uses, &blocks.join("\n\n"))
+}
+
+pub(crate) fn rust_file(uses: &BTreeSet<&'static str>, code: &str) -> String {
+    let mut uses = uses.clone();
+    // A file holding only a type alias has nothing to derive.
+    if code.contains("Serialize") {
+        uses.insert("use serde::{Deserialize, Serialize};");
+    }
+    let mut result = String::new();
+    for line in &uses {
+        result.push_str(line);
+        result.push('\n');
+    }
+    if !uses.is_empty() {
+        result.push('\n');
+    }
+    result.push_str(code);
+    result.push('\n');
+    result
+}
+
+pub(crate) fn rust_def(model: &TypeModel, def: &TypeDef, uses: &mut BTreeSet<&'static str>) -> String {
+    let derive = "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]";
+    let mut out = String::new();
+    push_line_doc(&mut out, "", "///", &def.description);
+    match &def.kind {
+        TypeKind::Struct(fields) => {
+            out.push_str(derive);
+            let skipped = model.tag_property_for(&def.name);
+            let fields: Vec<&FieldDef> = fields
+                .iter()
+                .filter(|field| skipped != Some(field.json_name.as_str()))
+                .collect();
+            if fields.is_empty() {
+                out.push_str(&format!("\npub struct {} {{}}", def.name));
+                return out;
+            }
+            out.push_str(&format!("\npub struct {} {{\n", def.name));
+            let field_names = dedupe_names(fields.iter().map(|f| rust_field_name(&f.json_name)).collect());
+            for (field, field_name) in fields.into_iter().zip(field_names) {
+                push_line_doc(&mut out, "    ", "///", &field.description);
+                if field_name.trim_start_matches("r#") != field.json_name {
+                    out.push_str(&format!(
+                        "    #[serde(rename = {})]\n",
+                        serde_json::to_string(&field.json_name).unwrap_or_default()
+                    ));
+                }
+                let mut rust_type = rust_type(&field.ty, uses);
+                if is_self_reference(&field.ty, &def.name) {
+                    rust_type = box_self_reference(&rust_type, &def.name);
+                }
+                if !field.required && !rust_type.starts_with("Option<") {
+                    rust_type = format!("Option<{}>", rust_type);
+                }
+                if !field.required {
+                    out.push_str("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n");
+                }
+                out.push_str(&format!("    pub {}: {},\n", field_name, rust_type));
+            }
+            out.push('}');
+        }
+        TypeKind::Enum(values) => {
+            out.push_str(derive);
+            out.push_str(&format!("\npub enum {} {{\n", def.name));
+            let members = dedupe_names(values.iter().map(|v| enum_member_name(v)).collect());
+            for (value, member) in values.iter().zip(members) {
+                out.push_str(&format!("    #[serde(rename = {})]\n    {},\n", quoted(value), member));
+            }
+            out.push('}');
+        }
+        TypeKind::Union { variants, discriminator } => {
+            out.push_str(derive);
+            match discriminator {
+                Some(disc) => out.push_str(&format!(
+                    "\n#[serde(tag = {})]",
+                    serde_json::to_string(&disc.property).unwrap_or_default()
+                )),
+                None => out.push_str("\n#[serde(untagged)]"),
+            }
+            out.push_str(&format!("\npub enum {} {{\n", def.name));
+            let mut used = HashSet::new();
+            for (i, variant) in variants.iter().enumerate() {
+                let mut variant_name = match discriminator {
+                    Some(disc) => enum_member_name(&disc.tags[i]),
+                    None => rust_variant_name(variant),
+                };
+                while !used.insert(variant_name.clone()) {
+                    variant_name.push_str(&(i + 1).to_string());
+                }
+                if let Some(disc) = discriminator {
+                    out.push_str(&format!(
+                        "    #[serde(rename = {})]\n",
+                        serde_json::to_string(&disc.tags[i]).unwrap_or_default()
+                    ));
+                }
+                if *variant == TypeRef::Null {
+                    out.push_str(&format!("    {},\n", variant_name));
+                } else {
+                    let mut inner = rust_type(variant, uses);
+                    if is_self_reference(variant, &def.name) {
+                        inner = box_self_reference(&inner, &def.name);
+                    }
+                    out.push_str(&format!("    {}({}),\n", variant_name, inner));
+                }
+            }
+            out.push('}');
+        }
+        TypeKind::Alias(target) => {
+            out.push_str(&format!("pub type {} = {};", def.name, rust_type(target, uses)));
+        }
+    }
+    out
+}
+
+fn box_self_reference(rust_type: &str, owner: &str) -> String {
+    rust_type.replacen(owner, &format!("Box<{}>", owner), 1)
+}
+
+pub(crate) fn rust_variant_name(ty: &TypeRef) -> String {
+    match ty {
+        TypeRef::Any => "Value".to_string(),
+        TypeRef::Null => "Null".to_string(),
+        TypeRef::Boolean => "Bool".to_string(),
+        TypeRef::Integer => "Integer".to_string(),
+        TypeRef::Number => "Number".to_string(),
+        TypeRef::String => "String".to_string(),
+        TypeRef::Formatted(StringFormat::DateTime) => "DateTime".to_string(),
+        TypeRef::Formatted(StringFormat::Date) => "Date".to_string(),
+        TypeRef::Formatted(StringFormat::Time) => "Time".to_string(),
+        TypeRef::Formatted(StringFormat::Uuid) => "Uuid".to_string(),
+        TypeRef::Formatted(StringFormat::Uri) => "Uri".to_string(),
+        TypeRef::Formatted(StringFormat::Ipv4) => "Ipv4".to_string(),
+        TypeRef::Formatted(StringFormat::Ipv6) => "Ipv6".to_string(),
+        TypeRef::Array(inner) => format!("{}List", rust_variant_name(inner)),
+        TypeRef::Map(inner) => format!("{}Map", rust_variant_name(inner)),
+        TypeRef::Named(name) => name.clone(),
+        TypeRef::Nullable(inner) => rust_variant_name(inner),
+    }
+}
+
+fn rust_type(ty: &TypeRef, uses: &mut BTreeSet<&'static str>) -> String {
+    match ty {
+        TypeRef::Any | TypeRef::Null => "serde_json::Value".to_string(),
+        TypeRef::Boolean => "bool".to_string(),
+        TypeRef::Integer => "i64".to_string(),
+        TypeRef::Number => "f64".to_string(),
+        TypeRef::String => "String".to_string(),
+        TypeRef::Formatted(StringFormat::DateTime) => {
+            uses.insert("use chrono::{DateTime, Utc};");
+            "DateTime<Utc>".to_string()
+        }
+        TypeRef::Formatted(StringFormat::Date) => {
+            uses.insert("use chrono::NaiveDate;");
+            "NaiveDate".to_string()
+        }
+        TypeRef::Formatted(StringFormat::Uuid) => {
+            uses.insert("use uuid::Uuid;");
+            "Uuid".to_string()
+        }
+        TypeRef::Formatted(StringFormat::Ipv4) => {
+            uses.insert("use std::net::Ipv4Addr;");
+            "Ipv4Addr".to_string()
+        }
+        TypeRef::Formatted(StringFormat::Ipv6) => {
+            uses.insert("use std::net::Ipv6Addr;");
+            "Ipv6Addr".to_string()
+        }
+        // chrono has no type for a time of day with an offset.
+        TypeRef::Formatted(StringFormat::Time | StringFormat::Uri) => "String".to_string(),
+        TypeRef::Array(inner) => format!("Vec<{}>", rust_type(inner, uses)),
+        TypeRef::Map(inner) => {
+            uses.insert("use std::collections::HashMap;");
+            format!("HashMap<String, {}>", rust_type(inner, uses))
+        }
+        TypeRef::Named(name) => name.clone(),
+        TypeRef::Nullable(inner) => format!("Option<{}>", rust_type(inner, uses)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn order_model() -> TypeModel {
+        let schema = json!({
+            "title": "Order",
+            "type": "object",
+            "required": ["id", "type"],
+            "properties": {
+                "id": {"type": "integer"},
+                "type": {"type": "string", "enum": ["self", "other"]},
+                "class": {"type": "string"},
+                "created": {"type": "string", "format": "date-time"},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "customer": {"$ref": "#/definitions/Customer"}
+            },
+            "definitions": {"Customer": {"type": "object", "properties": {"from": {"type": ["string", "null"]}}}}
+        });
+        build_type_model(&schema, "Root").unwrap()
+    }
+
+    #[test]
+    fn referenced_types_come_before_their_users() {
+        let model = order_model();
+        assert_eq!(model.root, "Order");
+        let typescript = render_typescript(&model);
+        let customer = typescript.find("export interface Customer {").unwrap();
+        let order = typescript.find("export interface Order {").unwrap();
+        assert!(customer < order, "{}", typescript);
+        assert!(typescript.contains("  from?: string | null;\n"), "{}", typescript);
+        assert!(typescript.contains("  customer?: Customer;\n  id: number;\n"), "{}", typescript);
+        assert!(typescript.contains("  tags?: string[];\n"), "{}", typescript);
+    }
+
+    #[test]
+    fn optional_fields_are_optional_in_rust_and_go() {
+        let model = order_model();
+        let rust = render_rust(&model);
+        assert!(rust.starts_with("use chrono::{DateTime, Utc};\nuse serde::{Deserialize, Serialize};\n"), "{}", rust);
+        assert!(rust.contains("    pub id: i64,\n"), "{}", rust);
+        assert!(rust.contains("    pub created: Option<DateTime<Utc>>,\n"), "{}", rust);
+        assert!(rust.contains("    pub tags: Option<Vec<String>>,\n"), "{}", rust);
+
+        let go = render_golang(&model);
+        assert!(go.contains("\t\"time\"\n"), "{}", go);
+        assert!(go.contains("\tID int64 `json:\"id\"`\n"), "{}", go);
+        assert!(go.contains("\tCreated *time.Time `json:\"created,omitempty\"`\n"), "{}", go);
+        assert!(go.contains("\tTags []string `json:\"tags,omitempty\"`\n"), "{}", go);
+    }
+
+    #[test]
+    fn reserved_words_are_escaped_in_each_language() {
+        let model = order_model();
+        let rust = render_rust(&model);
+        assert!(rust.contains("    pub r#type: OrderType,\n"), "{}", rust);
+        assert!(rust.contains("    #[serde(rename = \"self\")]\n    SelfValue,\n"), "{}", rust);
+        let java = render_java(&model, &JavaOptions::default());
+        assert!(java.contains("    @JsonProperty(\"class\")\n    private String class_;\n"), "{}", java);
+        assert!(java.contains("    public String getClass_() {\n"), "{}", java);
+        let python = render_python(&model, PythonStyle::Dataclass);
+        let from = "    from_: Optional[str] = field(default=None, metadata={\"alias\": \"from\"})\n";
+        assert!(python.contains(from), "{}", python);
+        assert!(render_typescript(&model).contains("  SelfValue = \"self\",\n"));
+    }
+
+    #[test]
+    fn colliding_keys_get_distinct_fields() {
+        let properties = json!({"first-name": {"type": "string"}, "first_name": {"type": "integer"}});
+        let model = build_type_model(&json!({"type": "object", "properties": properties}), "Person").unwrap();
+        let rust = render_rust(&model);
+        assert!(rust.contains("    #[serde(rename = \"first-name\")]\n"), "{}", rust);
+        assert!(rust.contains("    pub first_name: Option<String>,\n"), "{}", rust);
+        assert!(rust.contains("    #[serde(rename = \"first_name\")]\n"), "{}", rust);
+        assert!(rust.contains("    pub first_name2: Option<i64>,\n"), "{}", rust);
+        let go = render_golang(&model);
+        assert!(go.contains("\tFirstName *string `json:\"first-name,omitempty\"`\n"), "{}", go);
+        assert!(go.contains("\tFirstName2 *int64 `json:\"first_name,omitempty\"`\n"), "{}", go);
+    }
+
+    #[test]
+    fn python_dataclasses_put_required_fields_first() {
+        let python = render_python(&order_model(), PythonStyle::parse("dataclass").unwrap());
+        assert!(python.contains("from dataclasses import dataclass, field\n"), "{}", python);
+        assert!(python.contains("class OrderType(str, Enum):\n    SELF = \"self\"\n"), "{}", python);
+        let order = "@dataclass\nclass Order:\n    id: int\n    type: OrderType\n    class_: ";
+        assert!(python.contains(order), "{}", python);
+        assert!(python.contains("    tags: Optional[list[str]] = None\n"), "{}", python);
+    }
+
+    #[test]
+    fn python_pydantic_models_alias_renamed_fields() {
+        let python = render_python(&order_model(), PythonStyle::parse("Pydantic").unwrap());
+        assert!(python.contains("from pydantic import BaseModel, ConfigDict, Field\n"), "{}", python);
+        let order = "class Order(BaseModel):\n    model_config = ConfigDict(populate_by_name=True)\n";
+        assert!(python.contains(order), "{}", python);
+        assert!(python.contains("    class_: Optional[str] = Field(default=None, alias=\"class\")\n"), "{}", python);
+        assert!(python.contains("    id: int\n"), "{}", python);
+    }
+
+    #[test]
+    fn python_typed_dicts_keep_original_keys() {
+        let python = render_python(&order_model(), PythonStyle::parse("typeddict").unwrap());
+        assert!(python.contains("from typing import Literal, NotRequired, Optional, TypedDict\n"), "{}", python);
+        assert!(python.contains("OrderType = Literal[\"self\", \"other\"]\n"), "{}", python);
+        assert!(python.contains("Order = TypedDict(\"Order\", {\n    \"class\": NotRequired[str],\n"), "{}", python);
+        assert!(python.contains("    \"from\": NotRequired[Optional[str]],\n"), "{}", python);
+        assert!(python.contains("    \"id\": int,\n"), "{}", python);
+        assert!(PythonStyle::parse("attrs").is_err());
+    }
+
+    #[test]
+    fn java_records_take_components() {
+        let java = render_java(&order_model(), &JavaOptions::parse(Some("record")).unwrap());
+        assert!(java.contains("// Order.java\n"), "{}", java);
+        assert!(java.contains("public record Order(\n    @JsonProperty(\"class\") String class_,\n"), "{}", java);
+        assert!(java.contains("    @JsonProperty(\"id\") long id,\n"), "{}", java);
+        assert!(java.contains("public enum OrderType {\n    SELF(\"self\"),\n    OTHER(\"other\");\n"), "{}", java);
+    }
+
+    #[test]
+    fn java_lombok_classes_have_no_accessors() {
+        let java = render_java(&order_model(), &JavaOptions::parse(Some("lombok")).unwrap());
+        assert!(java.contains("import lombok.Data;\n"), "{}", java);
+        assert!(java.contains("@Data\n@JsonIgnoreProperties(ignoreUnknown = true)\n"), "{}", java);
+        let order = "public class Order {\n    @JsonProperty(\"class\")\n    private String class_;\n";
+        assert!(java.contains(order), "{}", java);
+        assert!(!java.contains("getId()"), "{}", java);
+    }
+
+    #[test]
+    fn java_pojos_have_accessors() {
+        let java = render_java(&order_model(), &JavaOptions::parse(Some("pojo")).unwrap());
+        assert!(!java.contains("lombok"), "{}", java);
+        assert!(java.contains("    private List<String> tags;\n"), "{}", java);
+        assert!(java.contains("    public long getId() {\n        return this.id;\n    }\n"), "{}", java);
+        assert!(java.contains("    public void setTags(List<String> tags) {\n"), "{}", java);
+    }
+
+    #[test]
+    fn java_options_pick_jackson_and_nesting() {
+        let options = JavaOptions::parse(Some(r#"{"style": "record", "jackson": false, "nested": "inner"}"#)).unwrap();
+        let java = render_java(&order_model(), &options);
+        assert!(!java.contains("JsonProperty"), "{}", java);
+        assert!(!java.contains("// Customer.java"), "{}", java);
+        assert!(java.contains("    public record Customer(\n        String from\n    ) {\n    }\n"), "{}", java);
+        assert!(JavaOptions::parse(Some("kotlin")).is_err());
+        assert!(JavaOptions::parse(Some(r#"{"styl": "record"}"#)).is_err());
+    }
+
+    #[test]
+    fn unresolvable_refs_are_reported() {
+        let schema = json!({"type": "object", "properties": {"a": {"$ref": "#/definitions/Missing"}}});
+        assert!(build_type_model(&schema, "Root").is_err());
+    }
+}
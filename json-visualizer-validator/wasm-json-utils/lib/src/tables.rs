@@ -0,0 +1,637 @@
+use serde_json::{Map, Value};
+
+use crate::naming::dedupe_names;
+
+// Markdown and HTML tables.
+//
+// Documents are laid out the way the CSV conversion does it: an array of
+// objects gives one column per key any object has (sorted) and one row per
+// element, an array of other values gives a single `value` column, and a
+// lone object is a one-row table. Scalars inside cells are written as they
+// are; Markdown shows nested values as inline JSON and HTML as nested tables.
+
+/// Every key that any object in `items` has, sorted.
+pub(crate) fn object_columns(items: &[Value]) -> Vec<String> {
+    let mut columns: Vec<String> =
+        items.iter().filter_map(Value::as_object).flat_map(|obj| obj.keys().cloned()).collect();
+    columns.sort();
+    columns.dedup();
+    columns
+}
+
+/// The column names and rows of cells of a value; cells missing from an
+/// object are `None`.
+fn table_of(value: &Value) -> (Vec<String>, Vec<Vec<Option<&Value>>>) {
+    match value {
+        Value::Array(items) if items.iter().any(Value::is_object) => {
+            let columns = object_columns(items);
+            let rows = items
+                .iter()
+                .map(|item| columns.iter().map(|column| item.as_object().and_then(|obj| obj.get(column))).collect())
+                .collect();
+            (columns, rows)
+        }
+        Value::Array(items) if items.is_empty() => (Vec::new(), Vec::new()),
+        Value::Array(items) => (vec!["value".to_string()], items.iter().map(|item| vec![Some(item)]).collect()),
+        Value::Object(obj) => (obj.keys().cloned().collect(), vec![obj.values().map(Some).collect()]),
+        _ => (vec!["value".to_string()], vec![vec![Some(value)]]),
+    }
+}
+
+/// Columns whose cells are all numbers (ignoring empty ones) are right-aligned.
+fn numeric_columns(columns: &[String], rows: &[Vec<Option<&Value>>]) -> Vec<bool> {
+    (0..columns.len())
+        .map(|i| {
+            let mut cells = rows.iter().filter_map(|row| row[i]).filter(|cell| !cell.is_null()).peekable();
+            cells.peek().is_some() && cells.all(Value::is_number)
+        })
+        .collect()
+}
+
+fn is_scalar(value: &Value) -> bool {
+    !value.is_array() && !value.is_object()
+}
+
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Writes a value as a GitHub-flavored Markdown table with padded columns.
+pub(crate) fn to_markdown_table(value: &Value) -> String {
+    let (columns, rows) = table_of(value);
+    if columns.is_empty() {
+        return String::new();
+    }
+    let numeric = numeric_columns(&columns, &rows);
+    let header: Vec<String> = columns.iter().map(|column| escape_markdown(column)).collect();
+    let body: Vec<Vec<String>> =
+        rows.iter().map(|row| row.iter().map(|cell| cell.map(markdown_cell).unwrap_or_default()).collect()).collect();
+    let widths: Vec<usize> = (0..columns.len())
+        .map(|i| {
+            std::iter::once(&header[i])
+                .chain(body.iter().map(|row| &row[i]))
+                .map(|text| text.chars().count())
+                .fold(3, usize::max)
+        })
+        .collect();
+
+    let line = |cells: &[String]| {
+        let padded: Vec<String> = cells
+            .iter()
+            .enumerate()
+            .map(|(i, text)| {
+                if numeric[i] {
+                    format!("{:>width$}", text, width = widths[i])
+                } else {
+                    format!("{:width$}", text, width = widths[i])
+                }
+            })
+            .collect();
+        format!("| {} |\n", padded.join(" | "))
+    };
+    let rule: Vec<String> = widths
+        .iter()
+        .zip(&numeric)
+        .map(|(&width, &right)| if right { format!("{}:", "-".repeat(width - 1)) } else { "-".repeat(width) })
+        .collect();
+
+    let mut out = line(&header);
+    out.push_str(&format!("| {} |\n", rule.join(" | ")));
+    for row in &body {
+        out.push_str(&line(row));
+    }
+    out
+}
+
+fn markdown_cell(value: &Value) -> String {
+    match value {
+        Value::Array(items) if items.iter().all(is_scalar) => {
+            items.iter().map(|item| escape_markdown(&scalar_text(item))).collect::<Vec<_>>().join(", ")
+        }
+        Value::Array(_) | Value::Object(_) => {
+            let json = value.to_string().replace('|', "\\|");
+            // A code span holding backticks needs a longer fence.
+            if json.contains('`') {
+                format!("`` {} ``", json)
+            } else {
+                format!("`{}`", json)
+            }
+        }
+        _ => escape_markdown(&scalar_text(value)),
+    }
+}
+
+/// Escapes the characters that would end a cell or start inline formatting.
+fn escape_markdown(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' | '|' | '`' | '*' | '[' | ']' | '<' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("<br>"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Writes a value as an HTML `<table>`.
+pub(crate) fn to_html_table(value: &Value) -> String {
+    html_table(value, true)
+}
+
+/// The top-level table is indented; tables nested in cells are kept on one line.
+fn html_table(value: &Value, pretty: bool) -> String {
+    let (columns, rows) = table_of(value);
+    if columns.is_empty() {
+        return String::new();
+    }
+    let numeric = numeric_columns(&columns, &rows);
+    let (newline, indent) = if pretty { ("\n", "  ") } else { ("", "") };
+    let open_cell = |tag: &str, i: usize| {
+        if numeric[i] {
+            format!("<{} style=\"text-align: right\">", tag)
+        } else {
+            format!("<{}>", tag)
+        }
+    };
+
+    let mut out = format!("<table>{}{}<thead>{}{}<tr>{}", newline, indent, newline, indent.repeat(2), newline);
+    for (i, column) in columns.iter().enumerate() {
+        out.push_str(&format!("{}{}{}</th>{}", indent.repeat(3), open_cell("th", i), escape_html(column), newline));
+    }
+    out.push_str(&format!(
+        "{}</tr>{}{}</thead>{}{}<tbody>{}",
+        indent.repeat(2),
+        newline,
+        indent,
+        newline,
+        indent,
+        newline
+    ));
+    for row in &rows {
+        out.push_str(&format!("{}<tr>{}", indent.repeat(2), newline));
+        for (i, cell) in row.iter().enumerate() {
+            let text = cell.map(html_cell).unwrap_or_default();
+            out.push_str(&format!("{}{}{}</td>{}", indent.repeat(3), open_cell("td", i), text, newline));
+        }
+        out.push_str(&format!("{}</tr>{}", indent.repeat(2), newline));
+    }
+    out.push_str(&format!("{}</tbody>{}</table>{}", indent, newline, newline));
+    out
+}
+
+fn html_cell(value: &Value) -> String {
+    match value {
+        Value::Array(items) if items.iter().all(is_scalar) => {
+            items.iter().map(|item| escape_html(&scalar_text(item))).collect::<Vec<_>>().join(", ")
+        }
+        Value::Array(_) | Value::Object(_) => html_table(value, false),
+        _ => escape_html(&scalar_text(value)),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace("\r\n", "\n")
+        .replace('\n', "<br>")
+}
+
+// HTML table reading
+
+struct Cell {
+    text: String,
+    pending_space: bool,
+    header: bool,
+    colspan: usize,
+    rowspan: usize,
+}
+
+impl Cell {
+    /// Adds text with runs of whitespace collapsed to one space.
+    fn push_text(&mut self, text: &str) {
+        for c in text.chars() {
+            if c.is_whitespace() {
+                self.pending_space = true;
+            } else {
+                if self.pending_space && !self.text.is_empty() && !self.text.ends_with('\n') {
+                    self.text.push(' ');
+                }
+                self.text.push(c);
+                self.pending_space = false;
+            }
+        }
+    }
+
+    fn push_break(&mut self) {
+        self.text.push('\n');
+        self.pending_space = false;
+    }
+}
+
+struct Row {
+    cells: Vec<Cell>,
+    in_head: bool,
+}
+
+#[derive(Default)]
+struct TableReader {
+    rows: Vec<Row>,
+    row: Option<Row>,
+    cell: Option<Cell>,
+    in_head: bool,
+}
+
+impl TableReader {
+    fn finish_cell(&mut self) {
+        if let Some(mut cell) = self.cell.take() {
+            cell.text = cell.text.trim().to_string();
+            let in_head = self.in_head;
+            self.row.get_or_insert_with(|| Row { cells: Vec::new(), in_head }).cells.push(cell);
+        }
+    }
+
+    fn finish_row(&mut self) {
+        self.finish_cell();
+        if let Some(row) = self.row.take() {
+            if !row.cells.is_empty() {
+                self.rows.push(row);
+            }
+        }
+    }
+
+    fn start_cell(&mut self, header: bool, attributes: &str) {
+        self.finish_cell();
+        // Spans are capped so a stray attribute cannot blow up the output.
+        let span =
+            |name: &str| attribute(attributes, name).and_then(|v| v.trim().parse().ok()).unwrap_or(1).clamp(1, 1000);
+        self.cell = Some(Cell {
+            text: String::new(),
+            pending_space: false,
+            header,
+            colspan: span("colspan"),
+            rowspan: span("rowspan"),
+        });
+    }
+}
+
+/// Reads the `table`-th `<table>` of an HTML document (counting from 0 in
+/// document order) into an array of objects keyed by the header row. Cell
+/// text is kept as strings, spanned cells are repeated in every column and
+/// row they cover, and a table without `<th>` or `<thead>` takes its first
+/// row as the header.
+pub(crate) fn parse_html_table(html: &str, table: usize) -> Result<Value, String> {
+    let lower = html.to_ascii_lowercase();
+    let mut reader = TableReader::default();
+    let mut tables_seen = 0;
+    let mut depth = 0;
+    let mut pos = 0;
+    while pos < html.len() {
+        let Some(offset) = html[pos..].find('<') else {
+            if depth > 0 {
+                push_text(&mut reader, &html[pos..]);
+            }
+            break;
+        };
+        if depth > 0 {
+            push_text(&mut reader, &html[pos..pos + offset]);
+        }
+        pos += offset;
+        let rest = &html[pos..];
+        if rest.starts_with("<!--") {
+            pos += rest.find("-->").map_or(rest.len(), |end| end + 3);
+            continue;
+        }
+        let closing = rest.starts_with("</");
+        let name_start = if closing { 2 } else { 1 };
+        let name_len = rest[name_start..].find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len() - name_start);
+        if name_len == 0 && !rest[name_start..].starts_with(['!', '?']) {
+            // A lone `<` is text.
+            if depth > 0 {
+                push_text(&mut reader, "<");
+            }
+            pos += 1;
+            continue;
+        }
+        let end = tag_end(rest);
+        let name = lower[pos + name_start..pos + name_start + name_len].to_string();
+        let attributes = rest.get(name_start + name_len..end.saturating_sub(1)).unwrap_or_default();
+        pos += end;
+
+        if !closing && matches!(name.as_str(), "script" | "style" | "textarea" | "title") {
+            let close = format!("</{}", name);
+            pos = lower[pos..].find(&close).map_or(html.len(), |i| pos + i);
+            continue;
+        }
+        if name == "table" {
+            if closing {
+                if depth > 0 {
+                    depth -= 1;
+                    if depth == 0 {
+                        reader.finish_row();
+                        break;
+                    }
+                }
+            } else {
+                if depth == 0 && tables_seen == table {
+                    depth = 1;
+                } else if depth > 0 {
+                    depth += 1;
+                }
+                tables_seen += 1;
+            }
+            continue;
+        }
+        if depth == 0 {
+            continue;
+        }
+        if depth > 1 {
+            // Nested tables are flattened into the text of the outer cell.
+            if matches!(name.as_str(), "td" | "th" | "tr") {
+                push_text(&mut reader, " ");
+            } else if name == "br" {
+                push_break(&mut reader);
+            }
+            continue;
+        }
+        match (name.as_str(), closing) {
+            ("caption", false) => pos = lower[pos..].find("</caption").map_or(html.len(), |i| pos + i),
+            ("thead", false) => {
+                reader.finish_row();
+                reader.in_head = true;
+            }
+            ("thead" | "tbody" | "tfoot", _) => {
+                reader.finish_row();
+                reader.in_head = false;
+            }
+            ("tr", _) => reader.finish_row(),
+            ("td" | "th", false) => reader.start_cell(name == "th", attributes),
+            ("td" | "th", true) => reader.finish_cell(),
+            ("br", _) => push_break(&mut reader),
+            ("p" | "div" | "li", _) => push_text(&mut reader, " "),
+            _ => {}
+        }
+    }
+    if depth == 0 && tables_seen <= table {
+        return Err(match tables_seen {
+            0 => "No <table> found in the HTML".to_string(),
+            n => format!("Table {} not found; the HTML has {} table{}", table, n, if n == 1 { "" } else { "s" }),
+        });
+    }
+    reader.finish_row();
+    Ok(table_value(reader.rows))
+}
+
+fn push_text(reader: &mut TableReader, text: &str) {
+    if let Some(cell) = reader.cell.as_mut() {
+        cell.push_text(&decode_entities(text));
+    }
+}
+
+fn push_break(reader: &mut TableReader) {
+    if let Some(cell) = reader.cell.as_mut() {
+        cell.push_break();
+    }
+}
+
+/// The offset just past the `>` closing the tag at the start of `rest`,
+/// skipping quoted attribute values.
+fn tag_end(rest: &str) -> usize {
+    let mut quote = None;
+    for (i, c) in rest.char_indices().skip(1) {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return i + 1,
+            _ => {}
+        }
+    }
+    rest.len()
+}
+
+/// The value of an attribute in the text of a start tag.
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let lower = attributes.to_ascii_lowercase();
+    let mut search = 0;
+    while let Some(found) = lower[search..].find(name) {
+        let start = search + found;
+        search = start + name.len();
+        let standalone = lower[..start].ends_with(|c: char| c.is_whitespace());
+        let rest = lower[search..].trim_start();
+        if !standalone || !rest.starts_with('=') {
+            continue;
+        }
+        let value = attributes[attributes.len() - rest.len() + 1..].trim_start();
+        return Some(match value.chars().next() {
+            Some(q @ ('"' | '\'')) => value[1..].split(q).next().unwrap_or_default().to_string(),
+            _ => {
+                value.split(|c: char| c.is_whitespace() || c == '>' || c == '/').next().unwrap_or_default().to_string()
+            }
+        });
+    }
+    None
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| Some((entity(&rest[1..end])?, end)));
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "laquo" => '«',
+        "raquo" => '»',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "euro" => '€',
+        "pound" => '£',
+        "yen" => '¥',
+        "deg" => '°',
+        "times" => '×',
+        "middot" => '·',
+        _ => return None,
+    })
+}
+
+/// Lays the rows out on a grid, repeating spanned cells, and keys the data
+/// rows by the header.
+fn table_value(rows: Vec<Row>) -> Value {
+    let mut carried: Vec<Option<(String, usize)>> = Vec::new();
+    let mut grid: Vec<(Vec<String>, bool)> = Vec::new();
+    for row in rows {
+        let is_header = row.in_head || row.cells.iter().all(|cell| cell.header);
+        let mut cells = row.cells.into_iter();
+        let mut texts = Vec::new();
+        loop {
+            let col = texts.len();
+            if let Some(slot) = carried.get_mut(col) {
+                if let Some((text, left)) = slot {
+                    texts.push(text.clone());
+                    *left -= 1;
+                    if *left == 0 {
+                        *slot = None;
+                    }
+                    continue;
+                }
+            }
+            let Some(cell) = cells.next() else {
+                if carried.iter().skip(col).any(Option::is_some) {
+                    texts.push(String::new());
+                    continue;
+                }
+                break;
+            };
+            for _ in 0..cell.colspan {
+                let col = texts.len();
+                if cell.rowspan > 1 {
+                    if carried.len() <= col {
+                        carried.resize(col + 1, None);
+                    }
+                    carried[col] = Some((cell.text.clone(), cell.rowspan - 1));
+                }
+                texts.push(cell.text.clone());
+            }
+        }
+        grid.push((texts, is_header));
+    }
+
+    let header_rows = match grid.iter().take_while(|(_, is_header)| *is_header).count() {
+        0 => grid.len().min(1),
+        n => n,
+    };
+    let width = grid.iter().map(|(texts, _)| texts.len()).max().unwrap_or(0);
+    let names: Vec<String> = (0..width)
+        .map(|i| {
+            // Stacked header rows combine, e.g. "Name" over "First" gives "Name First".
+            let mut parts: Vec<&str> = Vec::new();
+            for (texts, _) in &grid[..header_rows] {
+                let text = texts.get(i).map_or("", |text| text.trim());
+                if !text.is_empty() && parts.last() != Some(&text) {
+                    parts.push(text);
+                }
+            }
+            if parts.is_empty() {
+                format!("column{}", i + 1)
+            } else {
+                parts.join(" ").replace('\n', " ")
+            }
+        })
+        .collect();
+    let names = dedupe_names(names);
+
+    let items = grid[header_rows..]
+        .iter()
+        .map(|(texts, _)| {
+            let obj: Map<String, Value> = names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (name.clone(), Value::String(texts.get(i).cloned().unwrap_or_default())))
+                .collect();
+            Value::Object(obj)
+        })
+        .collect();
+    Value::Array(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn markdown_escapes_pipes_and_right_aligns_numbers() {
+        let value = json!([
+            {"name": "a|b", "n": 5, "tags": ["x", "y"]},
+            {"name": "*bold*\nline", "n": 10.5, "meta": {"k": "|"}}
+        ]);
+        assert_eq!(
+            to_markdown_table(&value),
+            "| meta         |    n | name             | tags |\n\
+             | ------------ | ---: | ---------------- | ---- |\n\
+             |              |    5 | a\\|b             | x, y |\n\
+             | `{\"k\":\"\\|\"}` | 10.5 | \\*bold\\*<br>line |      |\n"
+        );
+        assert_eq!(to_markdown_table(&json!([])), "");
+        assert_eq!(to_markdown_table(&json!(["a"])), "| value |\n| ----- |\n| a     |\n");
+    }
+
+    #[test]
+    fn html_escapes_text_and_nests_tables() {
+        let html = to_html_table(&json!([{"name": "<b>&\"", "n": 1, "nested": {"k": "v"}}]));
+        assert!(html.contains("      <th style=\"text-align: right\">n</th>\n"), "{}", html);
+        assert!(html.contains("      <td>&lt;b&gt;&amp;&quot;</td>\n"), "{}", html);
+        let nested = "<td><table><thead><tr><th>k</th></tr></thead><tbody><tr><td>v</td></tr></tbody></table></td>";
+        assert!(html.contains(nested), "{}", html);
+    }
+
+    #[test]
+    fn html_tables_read_with_spans_and_entities() {
+        let html = r#"<table><tr><td>skipped</td></tr></table>
+<table>
+  <thead><tr><th>Name</th><th colspan="2">Score</th></tr></thead>
+  <tbody>
+    <tr><td rowspan="2">Ada &amp; Bob</td><td>1</td><td>2</td></tr>
+    <tr><td>3&nbsp;x</td><td>&lt;4&gt; &#65;&#x42;</td></tr>
+  </tbody>
+</table>"#;
+        assert_eq!(
+            parse_html_table(html, 1).unwrap(),
+            json!([
+                {"Name": "Ada & Bob", "Score": "1", "Score2": "2"},
+                {"Name": "Ada & Bob", "Score": "3 x", "Score2": "<4> AB"}
+            ])
+        );
+        assert_eq!(parse_html_table(html, 2).unwrap_err(), "Table 2 not found; the HTML has 2 tables");
+        // Without `<th>` the first row is the header.
+        let plain = "<table><tr><td>h</td></tr><tr><td>v<br>w</td></tr></table>";
+        assert_eq!(parse_html_table(plain, 0).unwrap(), json!([{"h": "v\nw"}]));
+    }
+}
@@ -0,0 +1,147 @@
+use serde_json::{Map, Number, Value};
+use toml::value::Datetime;
+
+use crate::formats::detect_format;
+use crate::validator::escape;
+
+// TOML reading and writing.
+//
+// Documents are converted to and from `serde_json::Value` so TOML plugs into
+// every converter, generator and visualizer that already takes JSON. TOML
+// datetimes become RFC 3339 strings, and strings in those formats become
+// datetimes again on the way back, so a round trip keeps them. Inline and
+// standard tables read the same; output uses standard tables.
+
+/// Parses a TOML document into JSON values.
+pub(crate) fn parse_toml(toml_str: &str) -> Result<Value, String> {
+    let table: toml::Table = toml_str.parse().map_err(|e| format!("Invalid TOML: {}", e))?;
+    Ok(from_toml(toml::Value::Table(table)))
+}
+
+/// Writes JSON as a TOML document. TOML has no null and needs a table at the
+/// top level, so those are errors naming where they occur.
+pub(crate) fn to_toml_string(value: &Value) -> Result<String, String> {
+    if !value.is_object() {
+        return Err(format!("TOML documents must be a table at the top level, found {}", kind(value)));
+    }
+    let toml_value = to_toml(value, "")?;
+    toml::to_string(&toml_value).map_err(|e| format!("TOML serialization error: {}", e))
+}
+
+fn from_toml(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::Number(i.into()),
+        // NaN and infinities have no JSON form.
+        toml::Value::Float(f) => Number::from_f64(f).map_or(Value::Null, Value::Number),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(from_toml).collect()),
+        toml::Value::Table(table) => {
+            Value::Object(table.into_iter().map(|(key, value)| (key, from_toml(value))).collect::<Map<_, _>>())
+        }
+    }
+}
+
+fn to_toml(value: &Value, pointer: &str) -> Result<toml::Value, String> {
+    Ok(match value {
+        Value::Null => {
+            let location = if pointer.is_empty() { "the top level".to_string() } else { pointer.to_string() };
+            return Err(format!("TOML cannot represent null (at {})", location));
+        }
+        Value::Bool(b) => toml::Value::Boolean(*b),
+        Value::Number(n) => match (n.as_i64(), n.as_f64()) {
+            (Some(i), _) => toml::Value::Integer(i),
+            (None, Some(f)) if !n.is_u64() => toml::Value::Float(f),
+            _ => return Err(format!("{} at {} is out of TOML's 64-bit integer range", n, pointer)),
+        },
+        Value::String(s) => match detect_format(s) {
+            Some("date-time" | "date" | "time") => match s.parse::<Datetime>() {
+                Ok(datetime) => toml::Value::Datetime(datetime),
+                Err(_) => toml::Value::String(s.clone()),
+            },
+            _ => toml::Value::String(s.clone()),
+        },
+        Value::Array(items) => toml::Value::Array(
+            items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| to_toml(item, &format!("{}/{}", pointer, i)))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Object(map) => toml::Value::Table(
+            map.iter()
+                .map(|(key, item)| Ok((key.clone(), to_toml(item, &format!("{}/{}", pointer, escape(key)))?)))
+                .collect::<Result<_, String>>()?,
+        ),
+    })
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "a table",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const DOCUMENT: &str = r#"
+title = "Example"
+released = 1979-05-27T07:32:00Z
+birthday = 1979-05-27
+ratio = 0.5
+tags = ["a", "b"]
+point = { x = 1, y = 2 }
+
+[owner]
+name = "Tom"
+
+[[servers]]
+host = "alpha"
+port = 8080
+
+[[servers]]
+host = "beta"
+"#;
+
+    #[test]
+    fn documents_survive_a_round_trip() {
+        let value = parse_toml(DOCUMENT).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "title": "Example",
+                "released": "1979-05-27T07:32:00Z",
+                "birthday": "1979-05-27",
+                "ratio": 0.5,
+                "tags": ["a", "b"],
+                "point": {"x": 1, "y": 2},
+                "owner": {"name": "Tom"},
+                "servers": [{"host": "alpha", "port": 8080}, {"host": "beta"}]
+            })
+        );
+        let written = to_toml_string(&value).unwrap();
+        // Dates go back out as TOML datetimes, not strings.
+        assert!(written.contains("released = 1979-05-27T07:32:00Z\n"), "{}", written);
+        assert!(written.contains("birthday = 1979-05-27\n"), "{}", written);
+        assert!(written.contains("[[servers]]\n"), "{}", written);
+        assert_eq!(parse_toml(&written).unwrap(), value);
+    }
+
+    #[test]
+    fn values_toml_cannot_hold_are_reported() {
+        let null = to_toml_string(&json!({"a": {"b": [1, null]}})).unwrap_err();
+        assert_eq!(null, "TOML cannot represent null (at /a/b/1)");
+        assert!(to_toml_string(&json!([1])).unwrap_err().contains("found an array"));
+        assert!(to_toml_string(&json!({"n": u64::MAX})).unwrap_err().contains("out of TOML's 64-bit integer range"));
+        assert!(parse_toml("a = ").unwrap_err().starts_with("Invalid TOML"));
+    }
+}
@@ -0,0 +1,21 @@
+use wasm_bindgen::prelude::*;
+
+use crate::toml_format::parse_toml;
+use crate::visualization::{convert_to_d3_format, json_graph};
+
+/// Processes a TOML document into the node/link graph format of `process_json`.
+/// Inline and standard tables look the same; datetimes show as RFC 3339 text.
+#[wasm_bindgen]
+pub fn process_toml_graph(toml_str: &str) -> Result<String, JsValue> {
+    let value = parse_toml(toml_str).map_err(|e| JsValue::from_str(&e))?;
+    serde_json::to_string(&json_graph(&value))
+        .map_err(|e| JsValue::from_str(&format!("Error building JSON: {}", e)))
+}
+
+/// Processes a TOML document into the tree format of `process_json_tree`.
+#[wasm_bindgen]
+pub fn process_toml_tree(toml_str: &str) -> Result<String, JsValue> {
+    let value = parse_toml(toml_str).map_err(|e| JsValue::from_str(&e))?;
+    serde_json::to_string(&convert_to_d3_format(&value, "root"))
+        .map_err(|e| JsValue::from_str(&format!("Error building JSON: {}", e)))
+}
@@ -4,15 +4,51 @@ use std::collections::HashMap;
 use std::collections::HashSet;
 use serde_yaml;
 
-// Helper function to parse JSON
-fn parse_json(json_str: &str) -> Result<Value, String> {
-    serde_json::from_str::<Value>(json_str)
+use crate::naming::{
+    dedupe_names, go_field_name, go_tag, java_accessor_name, java_field_name, pascal_case, python_field_name,
+    quoted, type_name, typescript_property,
+};
+use crate::dialect::to_dialect;
+use crate::formats::detect_format;
+use crate::schema::{extract_definitions, infer_schema, infer_schema_with, parse_ndjson, read_samples, InferOptions, SampleInput};
+use crate::schema_types::{
+    build_type_model, render_golang, render_java, render_python, render_rust, render_typescript, schema_to_java,
+    schema_to_python, JavaOptions, PythonStyle, TypeModel,
+};
+use crate::protobuf::render_proto;
+use crate::avro::avro_schema;
+use crate::toml_format::parse_toml;
+use crate::hcl_format::variables_schema;
+use crate::lenient_json::{parse_json_text, JsonSyntax};
+use crate::extended_json::to_plain;
+
+// Helper function to parse JSON, JSONC or JSON5 as chosen by `syntax`.
+// Extended JSON values such as `{"$date": ...}` become plain values.
+fn parse_json(json_str: &str, syntax: Option<&str>) -> Result<Value, String> {
+    let syntax = JsonSyntax::parse(syntax)?;
+    parse_json_text(json_str, syntax)
+        .map(|value| to_plain(&value))
         .map_err(|e| format!("JSON parsing error: {}", e))
 }
 
+/// For NDJSON input, the model of one record type merged across every line,
+/// so a key missing from some lines becomes optional.
+fn ndjson_type_model(json_str: &str, syntax: Option<&str>, root_name: &str) -> Result<Option<TypeModel>, String> {
+    if JsonSyntax::parse(syntax)? != JsonSyntax::Ndjson {
+        return Ok(None);
+    }
+    let records = parse_ndjson(json_str).map_err(|e| format!("JSON parsing error: {}", e))?;
+    let records: Vec<Value> = records.iter().map(to_plain).collect();
+    let records: Vec<&Value> = records.iter().collect();
+    build_type_model(&infer_schema(&records), root_name).map(Some)
+}
+
 #[wasm_bindgen]
-pub fn json_to_typescript(json_str: &str) -> Result<String, JsValue> {
-    let parsed = parse_json(json_str)
+pub fn json_to_typescript(json_str: &str, syntax: Option<String>) -> Result<String, JsValue> {
+    if let Some(model) = ndjson_type_model(json_str, syntax.as_deref(), "RootType").map_err(|e| JsValue::from_str(&e))? {
+        return Ok(render_typescript(&model));
+    }
+    let parsed = parse_json(json_str, syntax.as_deref())
         .map_err(|e| JsValue::from_str(&e))?;
     
     let ts_type = generate_typescript_type(&parsed, "RootType")
@@ -21,11 +57,25 @@ pub fn json_to_typescript(json_str: &str) -> Result<String, JsValue> {
     Ok(ts_type)
 }
 
+/// `options` selects a Java flavor (record, POJO or Lombok, Jackson
+/// annotations, inner classes or separate files); without it the original
+/// interface output is kept.
 #[wasm_bindgen]
-pub fn json_to_java(json_str: &str) -> Result<String, JsValue> {
-    let parsed = parse_json(json_str)
+pub fn json_to_java(json_str: &str, options: Option<String>, syntax: Option<String>) -> Result<String, JsValue> {
+    if let Some(model) = ndjson_type_model(json_str, syntax.as_deref(), "RootClass").map_err(|e| JsValue::from_str(&e))? {
+        let options = JavaOptions::parse(options.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+        return Ok(render_java(&model, &options));
+    }
+    let parsed = parse_json(json_str, syntax.as_deref())
         .map_err(|e| JsValue::from_str(&e))?;
     
+    if let Some(options) = options {
+        let options = JavaOptions::parse(Some(&options)).map_err(|e| JsValue::from_str(&e))?;
+        let schema = infer_schema(&[&parsed]);
+        return schema_to_java(&schema, "RootClass", &options)
+            .map_err(|e| JsValue::from_str(&e));
+    }
+    
     let java_interface = generate_java_interface(&parsed, "RootClass")
         .map_err(|e| JsValue::from_str(&e))?;
     
@@ -33,8 +83,11 @@ pub fn json_to_java(json_str: &str) -> Result<String, JsValue> {
 }
 
 #[wasm_bindgen]
-pub fn json_to_golang(json_str: &str) -> Result<String, JsValue> {
-    let parsed = parse_json(json_str)
+pub fn json_to_golang(json_str: &str, syntax: Option<String>) -> Result<String, JsValue> {
+    if let Some(model) = ndjson_type_model(json_str, syntax.as_deref(), "RootType").map_err(|e| JsValue::from_str(&e))? {
+        return Ok(render_golang(&model));
+    }
+    let parsed = parse_json(json_str, syntax.as_deref())
         .map_err(|e| JsValue::from_str(&e))?;
     
     let golang_struct = generate_golang_struct(&parsed, "RootType")
@@ -43,17 +96,60 @@ pub fn json_to_golang(json_str: &str) -> Result<String, JsValue> {
     Ok(golang_struct)
 }
 
+/// `flavor` is one of `dataclass`, `pydantic` or `typeddict`; without it the
+/// original plain class output is kept.
 #[wasm_bindgen]
-pub fn json_to_python(json_str: &str) -> Result<String, JsValue> {
-    let parsed = parse_json(json_str)
+pub fn json_to_python(json_str: &str, flavor: Option<String>, syntax: Option<String>) -> Result<String, JsValue> {
+    if let Some(model) = ndjson_type_model(json_str, syntax.as_deref(), "RootClass").map_err(|e| JsValue::from_str(&e))? {
+        let style = PythonStyle::parse(flavor.as_deref().unwrap_or_default()).map_err(|e| JsValue::from_str(&e))?;
+        return Ok(render_python(&model, style));
+    }
+    let parsed = parse_json(json_str, syntax.as_deref())
         .map_err(|e| JsValue::from_str(&e))?;
     
+    if let Some(flavor) = flavor {
+        let style = PythonStyle::parse(&flavor).map_err(|e| JsValue::from_str(&e))?;
+        let schema = infer_schema(&[&parsed]);
+        return schema_to_python(&schema, "RootClass", style)
+            .map_err(|e| JsValue::from_str(&e));
+    }
+    
     let python_class = generate_python_class(&parsed, "RootClass")
         .map_err(|e| JsValue::from_str(&e))?;
     
     Ok(python_class)
 }
 
+/// Generates a proto3 `.proto` file for the document's inferred shape.
+#[wasm_bindgen]
+pub fn json_to_proto(json_str: &str, syntax: Option<String>) -> Result<String, JsValue> {
+    if let Some(model) = ndjson_type_model(json_str, syntax.as_deref(), "RootMessage").map_err(|e| JsValue::from_str(&e))? {
+        return Ok(render_proto(&model));
+    }
+    let parsed = parse_json(json_str, syntax.as_deref())
+        .map_err(|e| JsValue::from_str(&e))?;
+    let model = build_type_model(&infer_schema(&[&parsed]), "RootMessage")
+        .map_err(|e| JsValue::from_str(&e))?;
+    Ok(render_proto(&model))
+}
+
+/// Generates an Avro record schema (`.avsc`) for the document's inferred
+/// shape. Fields missing from some samples or null in them are nullable
+/// unions defaulting to null.
+#[wasm_bindgen]
+pub fn json_to_avro_schema(json_str: &str, syntax: Option<String>) -> Result<String, JsValue> {
+    let model = match ndjson_type_model(json_str, syntax.as_deref(), "RootRecord").map_err(|e| JsValue::from_str(&e))? {
+        Some(model) => model,
+        None => {
+            let parsed = parse_json(json_str, syntax.as_deref())
+                .map_err(|e| JsValue::from_str(&e))?;
+            build_type_model(&infer_schema(&[&parsed]), "RootRecord")
+                .map_err(|e| JsValue::from_str(&e))?
+        }
+    };
+    serde_json::to_string_pretty(&avro_schema(&model)).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
 // Helper functions for type conversion
 
 fn generate_typescript_type(value: &Value, type_name: &str) -> Result<String, String> {
@@ -63,31 +159,32 @@ fn generate_typescript_type(value: &Value, type_name: &str) -> Result<String, St
             let mut nested_types = Vec::new();
             
             for (key, val) in map {
+                let property = typescript_property(key);
                 match val {
                     Value::Object(_) => {
-                        let nested_type_name = format!("{}_{}", type_name, to_pascal_case(key));
+                        let nested_type_name = format!("{}_{}", type_name, pascal_case(key));
                         let nested_type = generate_typescript_type(val, &nested_type_name)?;
                         nested_types.push(nested_type);
-                        fields.push(format!("  {}: {};", key, nested_type_name));
+                        fields.push(format!("  {}: {};", property, nested_type_name));
                     },
                     Value::Array(arr) => {
                         if let Some(first) = arr.first() {
                             if let Value::Object(_) = first {
-                                let nested_type_name = format!("{}_{}", type_name, to_pascal_case(key));
+                                let nested_type_name = format!("{}_{}", type_name, pascal_case(key));
                                 let nested_type = generate_typescript_type(first, &nested_type_name)?;
                                 nested_types.push(nested_type);
-                                fields.push(format!("  {}: {}[];", key, nested_type_name));
+                                fields.push(format!("  {}: {}[];", property, nested_type_name));
                             } else {
                                 let ts_type = json_value_to_ts_type(first);
-                                fields.push(format!("  {}: {}[];", key, ts_type));
+                                fields.push(format!("  {}: {}[];", property, ts_type));
                             }
                         } else {
-                            fields.push(format!("  {}: any[];", key));
+                            fields.push(format!("  {}: any[];", property));
                         }
                     },
                     _ => {
                         let ts_type = json_value_to_ts_type(val);
-                        fields.push(format!("  {}: {};", key, ts_type));
+                        fields.push(format!("  {}: {};", property, ts_type));
                     }
                 }
             }
@@ -110,81 +207,54 @@ fn generate_java_interface(value: &Value, class_name: &str) -> Result<String, St
             let mut fields = Vec::new();
             let mut getters = Vec::new();
             let mut nested_classes = Vec::new();
+            let field_names = dedupe_names(map.keys().map(|k| java_field_name(k)).collect());
             
-            for (key, val) in map {
-                match val {
+            for ((key, val), field_name) in map.iter().zip(field_names) {
+                let field_type = match val {
                     Value::Object(_) => {
-                        let nested_class_name = format!("{}", to_pascal_case(key));
+                        let nested_class_name = type_name(key);
                         let nested_class = generate_java_interface(val, &nested_class_name)?;
                         nested_classes.push(nested_class);
-                        
-                        let field_type = nested_class_name;
-                        let field_name = to_camel_case(key);
-                        fields.push(format!("    private {} {};", field_type, field_name));
-                        
-                        let getter = format!(
-                            "    public {} get{}() {{\n        return this.{};\n    }}",
-                            field_type, to_pascal_case(key), field_name
-                        );
-                        getters.push(getter);
+                        nested_class_name
                     },
                     Value::Array(arr) => {
                         if let Some(first) = arr.first() {
                             if let Value::Object(_) = first {
-                                let nested_class_name = format!("{}", to_pascal_case(key));
+                                let nested_class_name = type_name(key);
                                 let nested_class = generate_java_interface(first, &nested_class_name)?;
                                 nested_classes.push(nested_class);
-                                
-                                let field_type = format!("List<{}>", nested_class_name);
-                                let field_name = to_camel_case(key);
-                                fields.push(format!("    private {} {};", field_type, field_name));
-                                
-                                let getter = format!(
-                                    "    public {} get{}() {{\n        return this.{};\n    }}",
-                                    field_type, to_pascal_case(key), field_name
-                                );
-                                getters.push(getter);
+                                format!("List<{}>", nested_class_name)
                             } else {
-                                let java_type = json_value_to_java_type(first);
-                                let field_type = format!("List<{}>", java_type);
-                                let field_name = to_camel_case(key);
-                                fields.push(format!("    private {} {};", field_type, field_name));
-                                
-                                let getter = format!(
-                                    "    public {} get{}() {{\n        return this.{};\n    }}",
-                                    field_type, to_pascal_case(key), field_name
-                                );
-                                getters.push(getter);
+                                format!("List<{}>", json_value_to_java_type(first))
                             }
                         } else {
-                            let field_type = "List<Object>";
-                            let field_name = to_camel_case(key);
-                            fields.push(format!("    private {} {};", field_type, field_name));
-                            
-                            let getter = format!(
-                                "    public {} get{}() {{\n        return this.{};\n    }}",
-                                field_type, to_pascal_case(key), field_name
-                            );
-                            getters.push(getter);
+                            "List<Object>".to_string()
                         }
                     },
-                    _ => {
-                        let java_type = json_value_to_java_type(val);
-                        let field_name = to_camel_case(key);
-                        fields.push(format!("    private {} {};", java_type, field_name));
-                        
-                        let getter = format!(
-                            "    public {} get{}() {{\n        return this.{};\n    }}",
-                            java_type, to_pascal_case(key), field_name
-                        );
-                        getters.push(getter);
-                    }
+                    _ => json_value_to_java_type(val).to_string()
+                };
+                
+                // Map the original key back when the Java name had to change
+                if field_name != *key {
+                    fields.push(format!("    @JsonProperty({})", quoted(key)));
                 }
+                fields.push(format!("    private {} {};", field_type, field_name));
+                
+                let getter = format!(
+                    "    public {} get{}() {{\n        return this.{};\n    }}",
+                    field_type, java_accessor_name(&field_name), field_name
+                );
+                getters.push(getter);
             }
             
+            let jackson_import = if fields.iter().any(|f| f.contains("@JsonProperty")) {
+                "import com.fasterxml.jackson.annotation.JsonProperty;\n"
+            } else {
+                ""
+            };
             let class_content = format!(
-                "import java.util.List;\nimport java.util.Map;\n\npublic class {} {{\n{}\n\n{}\n}}",
-                class_name, fields.join("\n"), getters.join("\n\n")
+                "{}import java.util.List;\nimport java.util.Map;\n\npublic class {} {{\n{}\n\n{}\n}}",
+                jackson_import, class_name, fields.join("\n"), getters.join("\n\n")
             );
             
             if nested_classes.is_empty() {
@@ -202,9 +272,10 @@ fn generate_golang_struct(value: &Value, struct_name: &str) -> Result<String, St
         Value::Object(map) => {
             let mut fields = Vec::new();
             let mut nested_structs = Vec::new();
+            let field_names = dedupe_names(map.keys().map(|k| go_field_name(k)).collect());
             
-            for (key, val) in map {
-                let field_name = to_pascal_case(key);
+            for ((key, val), field_name) in map.iter().zip(field_names) {
+                let tag = go_tag(&format!("json:\"{}\"", key.replace('"', "\\\"")));
                 
                 match val {
                     Value::Object(_) => {
@@ -212,7 +283,7 @@ fn generate_golang_struct(value: &Value, struct_name: &str) -> Result<String, St
                         let nested_struct = generate_golang_struct(val, &nested_struct_name)?;
                         nested_structs.push(nested_struct);
                         
-                        fields.push(format!("\t{} {} `json:\"{}\"`", field_name, nested_struct_name, key));
+                        fields.push(format!("\t{} {} {}", field_name, nested_struct_name, tag));
                     },
                     Value::Array(arr) => {
                         if let Some(first) = arr.first() {
@@ -221,18 +292,18 @@ fn generate_golang_struct(value: &Value, struct_name: &str) -> Result<String, St
                                 let nested_struct = generate_golang_struct(first, &nested_struct_name)?;
                                 nested_structs.push(nested_struct);
                                 
-                                fields.push(format!("\t{} []{} `json:\"{}\"`", field_name, nested_struct_name, key));
+                                fields.push(format!("\t{} []{} {}", field_name, nested_struct_name, tag));
                             } else {
                                 let go_type = json_value_to_go_type(first);
-                                fields.push(format!("\t{} []{}  `json:\"{}\"`", field_name, go_type, key));
+                                fields.push(format!("\t{} []{} {}", field_name, go_type, tag));
                             }
                         } else {
-                            fields.push(format!("\t{} []interface{{}}  `json:\"{}\"`", field_name, key));
+                            fields.push(format!("\t{} []interface{{}} {}", field_name, tag));
                         }
                     },
                     _ => {
                         let go_type = json_value_to_go_type(val);
-                        fields.push(format!("\t{} {}  `json:\"{}\"`", field_name, go_type, key));
+                        fields.push(format!("\t{} {} {}", field_name, go_type, tag));
                     }
                 }
             }
@@ -255,52 +326,54 @@ fn generate_python_class(value: &Value, class_name: &str) -> Result<String, Stri
             let mut fields = Vec::new();
             let mut init_fields = Vec::new();
             let mut nested_classes = Vec::new();
-            let mut imports = vec!["from dataclasses import dataclass", "from typing import List, Dict, Optional, Any"];
+            let field_names = dedupe_names(map.keys().map(|k| python_field_name(k)).collect());
             
-            for (key, val) in map {
-                let field_name = key;
-                
-                match val {
+            for ((key, val), field_name) in map.iter().zip(&field_names) {
+                let py_type = match val {
                     Value::Object(_) => {
-                        let nested_class_name = to_pascal_case(key);
+                        let nested_class_name = type_name(key);
                         let nested_class = generate_python_class(val, &nested_class_name)?;
                         nested_classes.push(nested_class);
-                        
-                        fields.push(format!("    {}: '{}'", field_name, nested_class_name));
-                        init_fields.push(format!("        self.{} = {}", field_name, field_name));
+                        format!("'{}'", nested_class_name)
                     },
                     Value::Array(arr) => {
                         if let Some(first) = arr.first() {
                             if let Value::Object(_) = first {
-                                let nested_class_name = to_pascal_case(key);
+                                let nested_class_name = type_name(key);
                                 let nested_class = generate_python_class(first, &nested_class_name)?;
                                 nested_classes.push(nested_class);
-                                
-                                fields.push(format!("    {}: List['{}']", field_name, nested_class_name));
-                                init_fields.push(format!("        self.{} = {}", field_name, field_name));
+                                format!("List['{}']", nested_class_name)
                             } else {
-                                let py_type = json_value_to_python_type(first);
-                                fields.push(format!("    {}: List[{}]", field_name, py_type));
-                                init_fields.push(format!("        self.{} = {}", field_name, field_name));
+                                format!("List[{}]", json_value_to_python_type(first))
                             }
                         } else {
-                            fields.push(format!("    {}: List[Any]", field_name));
-                            init_fields.push(format!("        self.{} = {}", field_name, field_name));
+                            "List[Any]".to_string()
                         }
                     },
-                    _ => {
-                        let py_type = json_value_to_python_type(val);
-                        fields.push(format!("    {}: {}", field_name, py_type));
-                        init_fields.push(format!("        self.{} = {}", field_name, field_name));
-                    }
+                    _ => json_value_to_python_type(val).to_string()
+                };
+                
+                // Keep the original key reachable when the attribute had to be renamed
+                if field_name != key {
+                    fields.push(format!("    {}: {} = field(metadata={{\"alias\": {}}})", field_name, py_type, quoted(key)));
+                } else {
+                    fields.push(format!("    {}: {}", field_name, py_type));
                 }
+                init_fields.push(format!("        self.{} = {}", field_name, field_name));
             }
             
+            let dataclass_import = if fields.iter().any(|f| f.contains("= field(")) {
+                "from dataclasses import dataclass, field"
+            } else {
+                "from dataclasses import dataclass"
+            };
+            let imports = [dataclass_import, "from typing import List, Dict, Optional, Any"];
+            
             let class_def = format!(
                 "@dataclass\nclass {}:\n{}\n\n    def __init__(self, {}):\n{}",
                 class_name,
                 fields.join("\n"),
-                map.keys().map(|k| format!("{}", k)).collect::<Vec<_>>().join(", "),
+                field_names.join(", "),
                 init_fields.join("\n")
             );
             
@@ -389,41 +462,6 @@ fn json_value_to_python_type(value: &Value) -> &str {
     }
 }
 
-// Helper functions for string casing
-
-fn to_pascal_case(s: &str) -> String {
-    let mut result = String::new();
-    let mut capitalize_next = true;
-    
-    for c in s.chars() {
-        if c == '_' || c == '-' || c == ' ' {
-            capitalize_next = true;
-        } else if capitalize_next {
-            result.push(c.to_ascii_uppercase());
-            capitalize_next = false;
-        } else {
-            result.push(c);
-        }
-    }
-    
-    result
-}
-
-fn to_camel_case(s: &str) -> String {
-    let pascal = to_pascal_case(s);
-    if pascal.is_empty() {
-        return pascal;
-    }
-    
-    let mut result = String::new();
-    result.push(pascal.chars().next().unwrap().to_ascii_lowercase());
-    result.push_str(&pascal[1..]);
-    
-    result
-}
-
-
-
 // Main YAML parsing function
 fn parse_yaml(yaml_str: &str) -> Result<Value, String> {
     serde_yaml::from_str(yaml_str)
@@ -462,9 +500,9 @@ fn generate_typescript_type_yaml(value: &Value, type_name: &str) -> Result<Strin
                         if items.is_empty() {
                             "any[]".to_string()
                         } else {
-                            let item_type = generate_typescript_type(&items[0], &format!("{}Item", key))?;
+                            let item_type = generate_typescript_type(&items[0], &format!("{}Item", pascal_case(key)))?;
                             if item_type.contains("interface") || item_type.contains("type") {
-                                let nested_type_name = format!("{}Item", key);
+                                let nested_type_name = format!("{}Item", pascal_case(key));
                                 fields.push(item_type);
                                 format!("{}[]", nested_type_name)
                             } else {
@@ -473,13 +511,13 @@ fn generate_typescript_type_yaml(value: &Value, type_name: &str) -> Result<Strin
                         }
                     },
                     Value::Object(_) => {
-                        let nested_type_name = format!("{}{}", capitalize(key), "Type");
+                        let nested_type_name = format!("{}{}", pascal_case(key), "Type");
                         let nested_type = generate_typescript_type(val, &nested_type_name)?;
                         fields.push(nested_type);
                         nested_type_name
                     },
                 };
-                fields.push(format!("  {}: {};", key, field_type));
+                fields.push(format!("  {}: {};", typescript_property(key), field_type));
             }
             
             // Render the interface
@@ -550,7 +588,7 @@ fn generate_java_interface_yaml(value: &Value, type_name: &str) -> Result<String
                         if items.is_empty() {
                             ("List<Object>".to_string(), None)
                         } else {
-                            let nested_type_name = format!("{}{}", capitalize(key), "Type");
+                            let nested_type_name = format!("{}{}", pascal_case(key), "Type");
                             let (item_type, nested_class) = match &items[0] {
                                 Value::Object(_) => {
                                     let nested = generate_java_interface(&items[0], &nested_type_name)?;
@@ -570,7 +608,7 @@ fn generate_java_interface_yaml(value: &Value, type_name: &str) -> Result<String
                         }
                     },
                     Value::Object(_) => {
-                        let nested_type_name = format!("{}{}", capitalize(key), "Type");
+                        let nested_type_name = format!("{}{}", pascal_case(key), "Type");
                         let nested_class = generate_java_interface(val, &nested_type_name)?;
                         nested_classes.push(nested_class);
                         (nested_type_name.clone(), None)
@@ -583,8 +621,11 @@ fn generate_java_interface_yaml(value: &Value, type_name: &str) -> Result<String
                 
                 // Create getter method name using Java naming conventions
                 let getter_prefix = if field_type == "Boolean" { "is" } else { "get" };
-                let getter_name = format!("{}{}", getter_prefix, capitalize(key));
+                let getter_name = format!("{}{}", getter_prefix, java_accessor_name(key));
                 
+                if java_field_name(key) != *key {
+                    fields.push(format!("    @JsonProperty({})\n", quoted(key)));
+                }
                 fields.push(format!("    {} {}();\n", field_type, getter_name));
             }
             
@@ -598,6 +639,9 @@ fn generate_java_interface_yaml(value: &Value, type_name: &str) -> Result<String
             }
             
             // Add the main interface
+            if fields.iter().any(|f| f.contains("@JsonProperty")) {
+                result.push_str("import com.fasterxml.jackson.annotation.JsonProperty;\n\n");
+            }
             result.push_str(&format!("public interface {} {{\n", type_name));
             for field in fields {
                 result.push_str(&field);
@@ -657,8 +701,9 @@ fn generate_python_class_yaml(value: &Value, class_name: &str) -> Result<String,
             let mut init_params = Vec::new();
             let mut init_body = Vec::new();
             let mut nested_classes = Vec::new();
+            let attr_names = dedupe_names(map.keys().map(|k| python_field_name(k)).collect());
             
-            for (key, val) in map {
+            for ((key, val), attr) in map.iter().zip(&attr_names) {
                 let (field_type, nested) = match val {
                     Value::Null => ("None".to_string(), None),
                     Value::Bool(_) => ("bool".to_string(), None),
@@ -674,7 +719,7 @@ fn generate_python_class_yaml(value: &Value, class_name: &str) -> Result<String,
                         if items.is_empty() {
                             ("list".to_string(), None)
                         } else {
-                            let nested_class_name = format!("{}{}", capitalize(key), "Type");
+                            let nested_class_name = format!("{}{}", pascal_case(key), "Type");
                             match &items[0] {
                                 Value::Object(_) => {
                                     let nested_class = generate_python_class(&items[0], &nested_class_name)?;
@@ -688,7 +733,7 @@ fn generate_python_class_yaml(value: &Value, class_name: &str) -> Result<String,
                         }
                     },
                     Value::Object(_) => {
-                        let nested_class_name = format!("{}{}", capitalize(key), "Type");
+                        let nested_class_name = format!("{}{}", pascal_case(key), "Type");
                         let nested_class = generate_python_class(val, &nested_class_name)?;
                         (nested_class_name.clone(), Some(nested_class))
                     },
@@ -699,13 +744,13 @@ fn generate_python_class_yaml(value: &Value, class_name: &str) -> Result<String,
                 }
                 
                 // Add type annotation
-                fields.push(format!("    {}: {}", key, field_type));
+                fields.push(format!("    {}: {}", attr, field_type));
                 
                 // Add parameter to __init__
-                init_params.push(format!("{} = None", key));
+                init_params.push(format!("{} = None", attr));
                 
                 // Add assignment in __init__
-                init_body.push(format!("        self.{} = {}", key, key));
+                init_body.push(format!("        self.{} = {}", attr, attr));
             }
             
             // Render the class
@@ -746,26 +791,28 @@ fn generate_python_class_yaml(value: &Value, class_name: &str) -> Result<String,
             
             // Generate constructor arguments
             let mut from_dict_args = Vec::new();
-            for (key, val) in map {
+            for ((key, val), attr) in map.iter().zip(&attr_names) {
+                // The dict is keyed by the original name, the attribute may be renamed
+                let data_key = quoted(key);
                 match val {
                     Value::Object(_) => {
-                        let class_name = format!("{}{}", capitalize(key), "Type");
-                        from_dict_args.push(format!("            {} = {}.from_dict(data.get('{}'))", key, class_name, key));
+                        let class_name = format!("{}{}", pascal_case(key), "Type");
+                        from_dict_args.push(format!("            {} = {}.from_dict(data.get({}))", attr, class_name, data_key));
                     },
                     Value::Array(items) if !items.is_empty() => {
                         match &items[0] {
                             Value::Object(_) => {
-                                let class_name = format!("{}{}", capitalize(key), "Type");
+                                let class_name = format!("{}{}", pascal_case(key), "Type");
                                 // Fixed string concatenation issue
-                                from_dict_args.push(format!("            {} = [{}.from_dict(item) for item in data.get('{}', [])]", key, class_name, key));
+                                from_dict_args.push(format!("            {} = [{}.from_dict(item) for item in data.get({}, [])]", attr, class_name, data_key));
                             },
                             _ => {
-                                from_dict_args.push(format!("            {} = data.get('{}')", key, key));
+                                from_dict_args.push(format!("            {} = data.get({})", attr, data_key));
                             }
                         }
                     },
                     _ => {
-                        from_dict_args.push(format!("            {} = data.get('{}')", key, key));
+                        from_dict_args.push(format!("            {} = data.get({})", attr, data_key));
                     }
                 }
             }
@@ -848,40 +895,42 @@ fn generate_golang_struct_yaml(value: &Value, struct_name: &str) -> Result<Strin
             let mut fields = Vec::new();
             let mut nested_structs = Vec::new();
             
-            for (key, val) in map {
-                let field_name = capitalize(key);
-                let (field_type, json_tag, nested) = match val {
-                    Value::Null => ("interface{}".to_string(), format!("`json:\"{}\" yaml:\"{}\"`", key, key), None),
-                    Value::Bool(_) => ("bool".to_string(), format!("`json:\"{}\" yaml:\"{}\"`", key, key), None),
+            let field_names = dedupe_names(map.keys().map(|k| go_field_name(k)).collect());
+
+            for ((key, val), field_name) in map.iter().zip(field_names) {
+                let key = &key.replace('"', "\\\"");
+                let (field_type, nested) = match val {
+                    Value::Null => ("interface{}".to_string(), None),
+                    Value::Bool(_) => ("bool".to_string(), None),
                     Value::Number(n) => {
                         if n.is_i64() || n.is_u64() {
-                            ("int64".to_string(), format!("`json:\"{}\" yaml:\"{}\"`", key, key), None)
+                            ("int64".to_string(), None)
                         } else {
-                            ("float64".to_string(), format!("`json:\"{}\" yaml:\"{}\"`", key, key), None)
+                            ("float64".to_string(), None)
                         }
                     },
-                    Value::String(_) => ("string".to_string(), format!("`json:\"{}\" yaml:\"{}\"`", key, key), None),
+                    Value::String(_) => ("string".to_string(), None),
                     Value::Array(items) => {
                         if items.is_empty() {
-                            ("[]interface{}".to_string(), format!("`json:\"{}\" yaml:\"{}\"`", key, key), None)
+                            ("[]interface{}".to_string(), None)
                         } else {
-                            let nested_struct_name = format!("{}{}", capitalize(key), "Item");
+                            let nested_struct_name = format!("{}Item", field_name);
                             match &items[0] {
                                 Value::Object(_) => {
                                     let nested_struct = generate_golang_struct(&items[0], &nested_struct_name)?;
-                                    (format!("[]{}", nested_struct_name), format!("`json:\"{}\" yaml:\"{}\"`", key, key), Some(nested_struct))
+                                    (format!("[]{}", nested_struct_name), Some(nested_struct))
                                 },
                                 _ => {
                                     let simple_type = golang_type_for_value(&items[0])?;
-                                    (format!("[]{}", simple_type), format!("`json:\"{}\" yaml:\"{}\"`", key, key), None)
+                                    (format!("[]{}", simple_type), None)
                                 }
                             }
                         }
                     },
                     Value::Object(_) => {
-                        let nested_struct_name = format!("{}{}", capitalize(key), "Type");
+                        let nested_struct_name = format!("{}Type", field_name);
                         let nested_struct = generate_golang_struct(val, &nested_struct_name)?;
-                        (nested_struct_name.clone(), format!("`json:\"{}\" yaml:\"{}\"`", key, key), Some(nested_struct))
+                        (nested_struct_name.clone(), Some(nested_struct))
                     },
                 };
                 
@@ -889,7 +938,8 @@ fn generate_golang_struct_yaml(value: &Value, struct_name: &str) -> Result<Strin
                     nested_structs.push(nested);
                 }
                 
-                fields.push(format!("\t{} {} {}", field_name, field_type, json_tag));
+                let tag = go_tag(&format!("json:\"{0}\" yaml:\"{0}\"", key));
+                fields.push(format!("\t{} {} {}", field_name, field_type, tag));
             }
             
             // Render the struct
@@ -959,16 +1009,6 @@ fn golang_type_for_value(value: &Value) -> Result<String, String> {
     }
 }
 
-// Helper function to capitalize first letter of a string
-fn capitalize(s: &str) -> String {
-    let mut c = s.chars();
-    match c.next() {
-        None => String::new(),
-        Some(first) => first.to_uppercase().collect::<String>() + c.as_str(),
-    }
-}
-
-
 // XML to JAVA 
 // Define a struct to represent an XML node
 
@@ -1064,12 +1104,12 @@ fn parse_xml(xml: &str) -> Result<XmlElement, JsValue> {
 }
 // TypeScript generation
 fn generate_typescript(element: &XmlElement) -> String {
-    let mut output = format!("interface {} {{\n", element.name);
+    let mut output = format!("interface {} {{\n", type_name(&element.name));
     for (attr, _) in &element.attributes {
-        output.push_str(&format!("  {}: string;\n", attr));
+        output.push_str(&format!("  {}: string;\n", typescript_property(attr)));
     }
     for child in &element.children {
-        output.push_str(&format!("  {}: {};\n", child.name, child.name));
+        output.push_str(&format!("  {}: {};\n", typescript_property(&child.name), type_name(&child.name)));
     }
     output.push_str("}\n");
     
@@ -1081,12 +1121,12 @@ fn generate_typescript(element: &XmlElement) -> String {
 
 // Java generation
 fn generate_java(element: &XmlElement) -> String {
-    let mut output = format!("public interface {} {{\n", element.name);
+    let mut output = format!("public interface {} {{\n", type_name(&element.name));
     for (attr, _) in &element.attributes {
-        output.push_str(&format!("    String get{}();\n", capitalize(attr)));
+        output.push_str(&format!("    String get{}();\n", java_accessor_name(attr)));
     }
     for child in &element.children {
-        output.push_str(&format!("    {} get{}();\n", child.name, capitalize(&child.name)));
+        output.push_str(&format!("    {} get{}();\n", type_name(&child.name), java_accessor_name(&child.name)));
     }
     output.push_str("}\n");
     
@@ -1098,12 +1138,20 @@ fn generate_java(element: &XmlElement) -> String {
 
 // Go generation
 fn generate_golang(element: &XmlElement) -> String {
-    let mut output = format!("type {} struct {{\n", element.name);
+    let names: Vec<&String> = element.attributes.keys()
+        .chain(element.children.iter().map(|child| &child.name))
+        .collect();
+    let field_names = dedupe_names(names.iter().map(|name| go_field_name(name)).collect());
+
+    let mut output = format!("type {} struct {{\n", type_name(&element.name));
+    let mut field_names = field_names.into_iter();
     for (attr, _) in &element.attributes {
-        output.push_str(&format!("    {} string `xml:\"{},attr\"`\n", attr, attr));
+        let field_name = field_names.next().unwrap_or_default();
+        output.push_str(&format!("    {} string `xml:\"{},attr\"`\n", field_name, attr));
     }
     for child in &element.children {
-        output.push_str(&format!("    {} {} `xml:\"{}\"`\n", capitalize(&child.name), child.name, child.name));
+        let field_name = field_names.next().unwrap_or_default();
+        output.push_str(&format!("    {} {} `xml:\"{}\"`\n", field_name, type_name(&child.name), child.name));
     }
     output.push_str("}\n");
     
@@ -1115,12 +1163,20 @@ fn generate_golang(element: &XmlElement) -> String {
 
 // Python generation
 fn generate_python(element: &XmlElement) -> String {
-    let mut output = format!("@dataclass\nclass {}:\n", element.name);
-    for (attr, _) in &element.attributes {
-        output.push_str(&format!("    {}: str\n", attr));
+    let names: Vec<&String> = element.attributes.keys()
+        .chain(element.children.iter().map(|child| &child.name))
+        .collect();
+    let field_names = dedupe_names(names.iter().map(|name| python_field_name(name)).collect());
+
+    let mut output = format!("@dataclass\nclass {}:\n", type_name(&element.name));
+    let mut field_names = field_names.into_iter();
+    for _ in &element.attributes {
+        let field_name = field_names.next().unwrap_or_default();
+        output.push_str(&format!("    {}: str\n", field_name));
     }
     for child in &element.children {
-        output.push_str(&format!("    {}: {}\n", child.name, child.name));
+        let field_name = field_names.next().unwrap_or_default();
+        output.push_str(&format!("    {}: {}\n", field_name, type_name(&child.name)));
     }
     output.push('\n');
     
@@ -1139,6 +1195,81 @@ fn generate_python(element: &XmlElement) -> String {
 // }
 
 
+// TOML type generation. TOML is newer to the crate than the other inputs,
+// so it goes straight through schema inference and the schema renderers;
+// datetimes come out as the languages' date types.
+fn toml_type_model(toml_str: &str) -> Result<TypeModel, JsValue> {
+    let parsed = parse_toml(toml_str).map_err(|e| JsValue::from_str(&e))?;
+    let schema = infer_schema(&[&parsed]);
+    build_type_model(&schema, "RootType").map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn toml_to_typescript(toml_str: &str) -> Result<String, JsValue> {
+    Ok(render_typescript(&toml_type_model(toml_str)?))
+}
+
+/// `options` works as for `json_schema_to_java`.
+#[wasm_bindgen]
+pub fn toml_to_java(toml_str: &str, options: Option<String>) -> Result<String, JsValue> {
+    let options = JavaOptions::parse(options.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    Ok(render_java(&toml_type_model(toml_str)?, &options))
+}
+
+#[wasm_bindgen]
+pub fn toml_to_golang(toml_str: &str) -> Result<String, JsValue> {
+    Ok(render_golang(&toml_type_model(toml_str)?))
+}
+
+/// `flavor` is one of `dataclass` (default), `pydantic` or `typeddict`.
+#[wasm_bindgen]
+pub fn toml_to_python(toml_str: &str, flavor: Option<String>) -> Result<String, JsValue> {
+    let style = PythonStyle::parse(flavor.as_deref().unwrap_or_default()).map_err(|e| JsValue::from_str(&e))?;
+    Ok(render_python(&toml_type_model(toml_str)?, style))
+}
+
+#[wasm_bindgen]
+pub fn toml_to_rust(toml_str: &str) -> Result<String, JsValue> {
+    Ok(render_rust(&toml_type_model(toml_str)?))
+}
+
+// HCL type generation describes the `variable` blocks of a Terraform
+// configuration: each variable becomes a field of `Variables`, typed from its
+// type constraint (or its default) and optional when it has a default.
+fn hcl_type_model(hcl_str: &str) -> Result<TypeModel, JsValue> {
+    let schema = variables_schema(hcl_str).map_err(|e| JsValue::from_str(&e))?;
+    build_type_model(&schema, "Variables").map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn hcl_to_typescript(hcl_str: &str) -> Result<String, JsValue> {
+    Ok(render_typescript(&hcl_type_model(hcl_str)?))
+}
+
+/// `options` works as for `json_schema_to_java`.
+#[wasm_bindgen]
+pub fn hcl_to_java(hcl_str: &str, options: Option<String>) -> Result<String, JsValue> {
+    let options = JavaOptions::parse(options.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    Ok(render_java(&hcl_type_model(hcl_str)?, &options))
+}
+
+#[wasm_bindgen]
+pub fn hcl_to_golang(hcl_str: &str) -> Result<String, JsValue> {
+    Ok(render_golang(&hcl_type_model(hcl_str)?))
+}
+
+/// `flavor` is one of `dataclass` (default), `pydantic` or `typeddict`.
+#[wasm_bindgen]
+pub fn hcl_to_python(hcl_str: &str, flavor: Option<String>) -> Result<String, JsValue> {
+    let style = PythonStyle::parse(flavor.as_deref().unwrap_or_default()).map_err(|e| JsValue::from_str(&e))?;
+    Ok(render_python(&hcl_type_model(hcl_str)?, style))
+}
+
+#[wasm_bindgen]
+pub fn hcl_to_rust(hcl_str: &str) -> Result<String, JsValue> {
+    Ok(render_rust(&hcl_type_model(hcl_str)?))
+}
+
 // CSV parsing and type generation functions
 #[wasm_bindgen]
 pub fn csv_to_typescript(csv: &str) -> Result<String, JsValue> {
@@ -1176,14 +1307,22 @@ pub fn csv_to_golang(csv: &str) -> Result<String, JsValue> {
         .map_err(|e| JsValue::from_str(&format!("Golang generation error: {}", e)))
 }
 
+/// `flavor` works as for `json_to_python`.
 #[wasm_bindgen]
-pub fn csv_to_python(csv: &str) -> Result<String, JsValue> {
+pub fn csv_to_python(csv: &str, flavor: Option<String>) -> Result<String, JsValue> {
     let (headers, rows) = parse_csv(csv)
         .map_err(|e| JsValue::from_str(&format!("CSV parsing error: {}", e)))?;
     
     let type_info = infer_types(&headers, &rows)
         .map_err(|e| JsValue::from_str(&format!("Type inference error: {}", e)))?;
     
+    if let Some(flavor) = flavor {
+        let style = PythonStyle::parse(&flavor).map_err(|e| JsValue::from_str(&e))?;
+        let schema = csv_schema(&headers, &rows, &type_info);
+        return schema_to_python(&schema, "CsvData", style)
+            .map_err(|e| JsValue::from_str(&format!("Python generation error: {}", e)));
+    }
+    
     generate_python_class_csv(&headers, &type_info)
         .map_err(|e| JsValue::from_str(&format!("Python generation error: {}", e)))
 }
@@ -1196,7 +1335,8 @@ enum FieldType {
     String,
     Number,
     Boolean,
-    Date,
+    // Holds the schema format, "date" or "date-time"
+    Date(&'static str),
     Null,
     Mixed(Vec<FieldType>),
 }
@@ -1325,10 +1465,9 @@ fn infer_value_type(value: &str) -> FieldType {
         return FieldType::Number;
     }
     
-    // Check for date (simple ISO format check)
-    if (trimmed.len() == 10 && trimmed.chars().filter(|&c| c == '-').count() == 2) ||
-       (trimmed.len() >= 19 && trimmed.contains('T') && trimmed.contains(':')) {
-        return FieldType::Date;
+    // Check for an ISO date or date-time
+    if let Some(format @ ("date" | "date-time")) = detect_format(trimmed) {
+        return FieldType::Date(format);
     }
     
     // Default to string
@@ -1340,6 +1479,8 @@ fn merge_types(current: &FieldType, new: &FieldType) -> FieldType {
     match (current, new) {
         (FieldType::Null, _) => new.clone(),
         (_, FieldType::Null) => current.clone(),
+        // Neither format holds for a column mixing dates and date-times
+        (FieldType::Date(a), FieldType::Date(b)) if a != b => FieldType::String,
         (a, b) if std::mem::discriminant(a) == std::mem::discriminant(b) => a.clone(),
         (FieldType::Mixed(types), new_type) => {
             let mut updated_types = types.clone();
@@ -1366,8 +1507,8 @@ fn generate_typescript_type_csv(headers: &[String], type_info: &HashMap<String,
         let field_type = type_info.get(header).unwrap_or(&FieldType::String);
         let ts_type = field_type_to_typescript_csv(field_type);
         
-        // Sanitize field name
-        let field_name = sanitize_field_name(header);
+        // Quote headers that are not valid identifiers
+        let field_name = typescript_property(header);
         
         // Add optional marker if field might be null
         let optional = matches!(field_type, FieldType::Null | FieldType::Mixed(_));
@@ -1386,7 +1527,7 @@ fn field_type_to_typescript_csv(field_type: &FieldType) -> String {
         FieldType::String => "string".to_string(),
         FieldType::Number => "number".to_string(),
         FieldType::Boolean => "boolean".to_string(),
-        FieldType::Date => "Date".to_string(),
+        FieldType::Date(_) => "Date".to_string(),
         FieldType::Null => "null".to_string(),
         FieldType::Mixed(types) => {
             let mut unique_types = Vec::new();
@@ -1404,20 +1545,27 @@ fn field_type_to_typescript_csv(field_type: &FieldType) -> String {
 // Generate Java interface from type info
 fn generate_java_interface_csv(headers: &[String], type_info: &HashMap<String, FieldType>) -> Result<String, String> {
     let mut output = String::from("public interface CsvData {\n");
+    let mut needs_jackson = false;
     
     // Generate getters for each field
     for header in headers {
         let field_type = type_info.get(header).unwrap_or(&FieldType::String);
         let java_type = field_type_to_java(field_type);
         
-        // Sanitize field name
-        let field_name = sanitize_field_name(header);
-        let getter_name = format!("get{}", capitalize_first(field_name.as_str()));
+        let getter_name = format!("get{}", java_accessor_name(header));
         
+        // Map the getter back to the original header when the names differ
+        if java_field_name(header) != *header {
+            output.push_str(&format!("    @JsonProperty({})\n", quoted(header)));
+            needs_jackson = true;
+        }
         output.push_str(&format!("    {} {}();\n", java_type, getter_name));
     }
     
     output.push_str("}\n");
+    if needs_jackson {
+        output.insert_str(0, "import com.fasterxml.jackson.annotation.JsonProperty;\n\n");
+    }
     Ok(output)
 }
 
@@ -1427,7 +1575,7 @@ fn field_type_to_java(field_type: &FieldType) -> String {
         FieldType::String => "String".to_string(),
         FieldType::Number => "Double".to_string(),
         FieldType::Boolean => "Boolean".to_string(),
-        FieldType::Date => "java.util.Date".to_string(),
+        FieldType::Date(_) => "java.util.Date".to_string(),
         FieldType::Null => "Object".to_string(),
         FieldType::Mixed(_) => "Object".to_string(),
     }
@@ -1436,17 +1584,15 @@ fn field_type_to_java(field_type: &FieldType) -> String {
 // Generate Golang struct from type info
 fn generate_golang_struct_csv(headers: &[String], type_info: &HashMap<String, FieldType>) -> Result<String, String> {
     let mut output = String::from("type CsvData struct {\n");
+    let field_names = dedupe_names(headers.iter().map(|h| go_field_name(h)).collect());
     
-    for header in headers {
+    for (header, field_name) in headers.iter().zip(field_names) {
         let field_type = type_info.get(header).unwrap_or(&FieldType::String);
         let go_type = field_type_to_golang(field_type);
         
-        // Sanitize and capitalize field name (Go public fields start with capitals)
-        let field_name = capitalize_first(&sanitize_field_name(header));
-        
         // Add JSON tag
-        let json_tag = header.replace("\"", "\\\"");
-        output.push_str(&format!("\t{} {} `json:\"{}\"` \n", field_name, go_type, json_tag));
+        let json_tag = go_tag(&format!("json:\"{}\"", header.replace("\"", "\\\"")));
+        output.push_str(&format!("\t{} {} {}\n", field_name, go_type, json_tag));
     }
     
     output.push_str("}\n");
@@ -1459,7 +1605,7 @@ fn field_type_to_golang(field_type: &FieldType) -> String {
         FieldType::String => "string".to_string(),
         FieldType::Number => "float64".to_string(),
         FieldType::Boolean => "bool".to_string(),
-        FieldType::Date => "time.Time".to_string(),
+        FieldType::Date(_) => "time.Time".to_string(),
         FieldType::Null => "interface{}".to_string(),
         FieldType::Mixed(_) => "interface{}".to_string(),
     }
@@ -1467,7 +1613,10 @@ fn field_type_to_golang(field_type: &FieldType) -> String {
 
 // Generate Python class from type info
 fn generate_python_class_csv(headers: &[String], type_info: &HashMap<String, FieldType>) -> Result<String, String> {
-    let mut output = String::from("from dataclasses import dataclass\nfrom typing import Optional, Union, Any\nfrom datetime import datetime\n\n");
+    let field_names = dedupe_names(headers.iter().map(|h| python_field_name(h)).collect());
+    let needs_field = headers.iter().zip(&field_names).any(|(header, name)| header != name);
+    let dataclass_import = if needs_field { "dataclass, field" } else { "dataclass" };
+    let mut output = format!("from dataclasses import {}\nfrom typing import Optional, Union, Any\nfrom datetime import datetime\n\n", dataclass_import);
     output.push_str("@dataclass\nclass CsvData:\n");
     
     if headers.is_empty() {
@@ -1475,13 +1624,10 @@ fn generate_python_class_csv(headers: &[String], type_info: &HashMap<String, Fie
         return Ok(output);
     }
     
-    for header in headers {
+    for (header, field_name) in headers.iter().zip(&field_names) {
         let field_type = type_info.get(header).unwrap_or(&FieldType::String);
         let python_type = field_type_to_python(field_type);
         
-        // Sanitize field name
-        let field_name = sanitize_field_name(header);
-        
         // Add type annotation
         let optional = matches!(field_type, FieldType::Null | FieldType::Mixed(_));
         let type_annotation = if optional {
@@ -1490,19 +1636,70 @@ fn generate_python_class_csv(headers: &[String], type_info: &HashMap<String, Fie
             python_type
         };
         
-        output.push_str(&format!("    {}: {} = None\n", field_name, type_annotation));
+        if field_name != header {
+            // Keep the original header reachable for (de)serialization
+            output.push_str(&format!(
+                "    {}: {} = field(default=None, metadata={{\"alias\": {}}})\n",
+                field_name, type_annotation, quoted(header)
+            ));
+        } else {
+            output.push_str(&format!("    {}: {} = None\n", field_name, type_annotation));
+        }
     }
     
     Ok(output)
 }
 
+// Describe the CSV columns as a JSON Schema object; every column is present
+// in every row, empty (or missing) cells make it nullable
+fn csv_schema(headers: &[String], rows: &[Vec<String>], type_info: &HashMap<String, FieldType>) -> Value {
+    let mut properties = Map::new();
+    for (i, header) in headers.iter().enumerate() {
+        let field_type = type_info.get(header).unwrap_or(&FieldType::String);
+        let mut column = field_type_to_schema(field_type);
+        let has_empty = rows.iter().any(|row| row.get(i).is_none_or(|v| v.trim().is_empty()));
+        if has_empty && !matches!(field_type, FieldType::Null) {
+            column = serde_json::json!({ "anyOf": [column, { "type": "null" }] });
+        }
+        properties.insert(header.clone(), column);
+    }
+    let required = headers.iter().map(|h| Value::String(h.clone())).collect();
+    
+    let mut schema = Map::new();
+    schema.insert("type".into(), Value::String("object".into()));
+    schema.insert("properties".into(), Value::Object(properties));
+    schema.insert("required".into(), Value::Array(required));
+    Value::Object(schema)
+}
+
+// Convert FieldType to a JSON Schema fragment
+fn field_type_to_schema(field_type: &FieldType) -> Value {
+    match field_type {
+        FieldType::String => serde_json::json!({ "type": "string" }),
+        FieldType::Number => serde_json::json!({ "type": "number" }),
+        FieldType::Boolean => serde_json::json!({ "type": "boolean" }),
+        FieldType::Date(format) => serde_json::json!({ "type": "string", "format": format }),
+        FieldType::Null => serde_json::json!({ "type": "null" }),
+        FieldType::Mixed(types) => {
+            let mut options: Vec<Value> = Vec::new();
+            for t in types {
+                let option = field_type_to_schema(t);
+                if !options.contains(&option) {
+                    options.push(option);
+                }
+            }
+            serde_json::json!({ "anyOf": options })
+        }
+    }
+}
+
 // Convert FieldType to Python type
 fn field_type_to_python(field_type: &FieldType) -> String {
     match field_type {
         FieldType::String => "str".to_string(),
         FieldType::Number => "float".to_string(),
         FieldType::Boolean => "bool".to_string(),
-        FieldType::Date => "datetime".to_string(),
+        FieldType::Date(_) => "datetime".to_string(),
         FieldType::Null => "None".to_string(),
         FieldType::Mixed(types) => {
             let mut unique_types = Vec::new();
@@ -1523,73 +1720,54 @@ fn field_type_to_python(field_type: &FieldType) -> String {
     }
 }
 
-// Helper function to sanitize field names
-fn sanitize_field_name(name: &str) -> String {
-    let mut result = String::new();
-    let mut chars = name.chars().peekable();
-    let mut is_first = true;
-    
-    while let Some(c) = chars.next() {
-        if is_first {
-            // First character must be a letter or underscore for most languages
-            if c.is_alphabetic() || c == '_' {
-                result.push(c);
-            } else {
-                result.push('_');
-            }
-            is_first = false;
-        } else {
-            // Subsequent characters can be alphanumeric or underscore
-            if c.is_alphanumeric() || c == '_' {
-                result.push(c);
-            } else {
-                result.push('_');
-            }
-        }
-    }
-    
-    // Handle reserved keywords
-    match result.as_str() {
-        "class" | "interface" | "type" | "struct" | "enum" | "import" | "export" |
-        "function" | "if" | "else" | "for" | "while" | "return" | "break" | "continue" |
-        "switch" | "case" | "default" | "try" | "catch" | "finally" | "throw" | "new" |
-        "this" | "super" | "extends" | "implements" | "static" | "public" | "private" |
-        "protected" | "final" | "abstract" | "const" | "let" | "var" | "void" => {
-            format!("{}_", result)
-        },
-        _ => result
-    }
-}
-
-// Helper function to capitalize the first letter of a string
-fn capitalize_first(s: &str) -> String {
-    let mut chars = s.chars();
-    match chars.next() {
-        None => String::new(),
-        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+/// Without options the schema describes the single document as given. With
+/// options (`{"input": "samples" | "ndjson", "enumThreshold": n, "draft": "2020-12"}`)
+/// it is inferred over many samples: keys missing from some samples are
+/// optional, types are unioned and array items merged. NDJSON input (the
+/// `"ndjson"` syntax) is always inferred over its lines.
+#[wasm_bindgen]
+pub fn json_to_json_schema(json_str: &str, options: Option<String>, syntax: Option<String>) -> Result<String, JsValue> {
+    let syntax = JsonSyntax::parse(syntax.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    if options.is_some() || syntax == JsonSyntax::Ndjson {
+        return infer_json_schema(json_str, options.as_deref(), syntax).map_err(|e| JsValue::from_str(&e));
     }
-}
-
-
 
-#[wasm_bindgen]
-pub fn json_to_json_schema(json_str: &str) -> Result<String, JsValue> {
     // First, parse the JSON string
-    let parsed = parse_json_json(json_str)
+    let parsed = parse_json_json(json_str, syntax)
         .map_err(|e| JsValue::from_str(&format!("JSON parsing error: {}", e)))?;
     
     // Convert parsed JSON to JSON Schema
-    let schema = generate_json_schema(&parsed)
+    let mut schema = generate_json_schema(&parsed)
         .map_err(|e| JsValue::from_str(&format!("Schema generation error: {}", e)))?;
+
+    // Repeated object shapes are shared through `definitions`, as with options
+    extract_definitions(&mut schema, &InferOptions::default().name);
     
     // Serialize schema back to string
     serde_json::to_string_pretty(&schema)
         .map_err(|e| JsValue::from_str(&format!("Schema serialization error: {}", e)))
 }
 
+fn infer_json_schema(json_str: &str, options: Option<&str>, syntax: JsonSyntax) -> Result<String, String> {
+    let mut options = InferOptions::parse(options)?;
+    if syntax == JsonSyntax::Ndjson {
+        options.input = SampleInput::Ndjson;
+    }
+    let samples: Vec<Value> = read_samples(json_str, options.input, syntax)?.iter().map(to_plain).collect();
+    let samples: Vec<&Value> = samples.iter().collect();
+
+    let mut schema = infer_schema_with(&samples, &options);
+    if options.extract_definitions {
+        extract_definitions(&mut schema, &options.name);
+    }
+    let schema = to_dialect(schema, options.draft, &options.name);
+    serde_json::to_string_pretty(&schema).map_err(|e| format!("Schema serialization error: {}", e))
+}
+
 // Parse JSON string into Value
-fn parse_json_json(json_str: &str) -> Result<Value, String> {
-    serde_json::from_str::<Value>(json_str)
+fn parse_json_json(json_str: &str, syntax: JsonSyntax) -> Result<Value, String> {
+    parse_json_text(json_str, syntax)
+        .map(|value| to_plain(&value))
         .map_err(|e| format!("Invalid JSON: {}", e))
 }
 
@@ -1622,9 +1800,12 @@ fn detect_type_and_generate_schema(value: &Value, is_root: bool) -> Result<Value
             }
             Ok(Value::Object(schema))
         },
-        Value::String(_) => {
+        Value::String(s) => {
             let mut schema = Map::new();
             schema.insert("type".into(), Value::String("string".into()));
+            if let Some(format) = detect_format(s) {
+                schema.insert("format".into(), Value::String(format.into()));
+            }
             Ok(Value::Object(schema))
         },
         Value::Array(arr) => {
@@ -1641,7 +1822,15 @@ fn detect_type_and_generate_schema(value: &Value, is_root: bool) -> Result<Value
                 
                 if all_same_type(&sample_schemas) {
                     // Use the first item's schema as the general schema
-                    schema.insert("items".into(), sample_schemas[0].clone());
+                    let mut items = sample_schemas[0].clone();
+                    // A format only holds if every item has it
+                    let format = items.get("format").cloned();
+                    if sample_schemas.iter().any(|s| s.get("format").cloned() != format) {
+                        if let Value::Object(obj) = &mut items {
+                            obj.remove("format");
+                        }
+                    }
+                    schema.insert("items".into(), items);
                 } else {
                     // Create oneOf with all possible types
                     let mut unique_schemas = Vec::new();
@@ -1733,4 +1922,26 @@ fn get_schema_signature(schema: &Value) -> String {
         }
     }
     "unknown".to_string()
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn go_fields_from_colliding_keys_get_distinct_names() {
+        let go = yaml_to_golang("user-id: 1\nuser_id: 2\n").unwrap();
+        assert!(go.contains("\tUserID int64 `json:\"user-id\" yaml:\"user-id\"`\n"), "{}", go);
+        assert!(go.contains("\tUserID2 int64 `json:\"user_id\" yaml:\"user_id\"`\n"), "{}", go);
+        let go = json_to_golang(r#"{"user-id": 1, "user_id": 2}"#, None).unwrap();
+        assert!(go.contains("UserID2 int64"), "{}", go);
+    }
+
+    #[test]
+    fn go_tags_holding_backticks_are_interpreted_strings() {
+        let go = json_to_golang(r#"{"a`b": 1, "c": "x"}"#, None).unwrap();
+        assert!(go.contains("\tAB int64 \"json:\\\"a`b\\\"\"\n"), "{}", go);
+        assert!(go.contains("\tC string `json:\"c\"`\n"), "{}", go);
+        assert!(yaml_to_golang("a`b: 1\n").unwrap().contains("\"json:\\\"a`b\\\" yaml:\\\"a`b\\\"\""));
+        assert!(csv_to_golang("a`b,c\n1,2\n").unwrap().contains("\tAB float64 \"json:\\\"a`b\\\"\"\n"));
+    }
+}
@@ -0,0 +1,693 @@
+use wasm_bindgen::prelude::*;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::dialect::Dialect;
+use crate::formats::check_format;
+use crate::pattern;
+
+// JSON Schema validation.
+//
+// Validates documents against draft-04 to 2020-12 schemas (and OpenAPI schema
+// objects) without rewriting them, so every error points into the schema as
+// written. Only local `$ref`s are resolved. Known formats are asserted.
+
+/// Nested `$ref`s that don't move through the document give up past this.
+const MAX_REF_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ValidationError {
+    /// JSON Pointer to the offending value ("" for the root).
+    pub instance_path: String,
+    /// Pointer to the failing keyword, e.g. `#/properties/age/minimum`.
+    pub schema_path: String,
+    pub message: String,
+}
+
+#[derive(Serialize)]
+pub(crate) struct ValidationReport {
+    valid: bool,
+    errors: Vec<ValidationError>,
+}
+
+impl ValidationReport {
+    pub(crate) fn new(errors: Vec<ValidationError>) -> ValidationReport {
+        ValidationReport { valid: errors.is_empty(), errors }
+    }
+}
+
+/// Validates a document against a schema. Returns `{"valid": bool, "errors": [...]}`.
+#[wasm_bindgen]
+pub fn validate_json(json_str: &str, schema_str: &str) -> Result<String, JsValue> {
+    let instance: Value = serde_json::from_str(json_str)
+        .map_err(|e| JsValue::from_str(&format!("JSON parsing error: {}", e)))?;
+    let schema: Value = serde_json::from_str(schema_str)
+        .map_err(|e| JsValue::from_str(&format!("JSON Schema parsing error: {}", e)))?;
+
+    let errors = Validator::new(&schema).validate(&instance);
+    let report = ValidationReport::new(errors);
+    serde_json::to_string_pretty(&report).map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// What a schema says about one position of a document.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NodeSchema {
+    pub schema_path: String,
+    pub declared_type: Option<String>,
+    pub description: Option<String>,
+}
+
+pub(crate) struct Validator<'a> {
+    root: &'a Value,
+    /// Up to draft-07 keywords next to `$ref` are ignored.
+    ref_overrides: bool,
+    /// Draft-04 and OpenAPI 3.0 spell exclusive bounds as booleans.
+    boolean_exclusive: bool,
+}
+
+impl<'a> Validator<'a> {
+    pub(crate) fn new(root: &'a Value) -> Validator<'a> {
+        let dialect = Dialect::declared_by(root).unwrap_or_default();
+        Validator {
+            root,
+            ref_overrides: matches!(
+                dialect,
+                Dialect::Draft04 | Dialect::Draft06 | Dialect::Draft07 | Dialect::OpenApi30
+            ),
+            boolean_exclusive: matches!(dialect, Dialect::Draft04 | Dialect::OpenApi30),
+        }
+    }
+
+    pub(crate) fn validate(&self, instance: &Value) -> Vec<ValidationError> {
+        self.validate_at(self.root, "#", instance)
+    }
+
+    /// Validates `instance` against the subschema at `schema_path`.
+    pub(crate) fn validate_at(&self, schema: &Value, schema_path: &str, instance: &Value) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        self.check(schema, schema_path, instance, "", &mut errors, 0);
+        errors
+    }
+
+    fn is_valid(&self, schema: &Value, instance: &Value, depth: usize) -> bool {
+        let mut errors = Vec::new();
+        self.check(schema, "", instance, "", &mut errors, depth);
+        errors.is_empty()
+    }
+
+    fn resolve(&self, reference: &str) -> Option<(&'a Value, String)> {
+        resolve_ref(self.root, reference)
+    }
+
+    fn check(
+        &self,
+        schema: &Value,
+        schema_path: &str,
+        instance: &Value,
+        instance_path: &str,
+        errors: &mut Vec<ValidationError>,
+        depth: usize,
+    ) {
+        let mut fail = |keyword: &str, message: String| {
+            errors.push(ValidationError {
+                instance_path: instance_path.to_string(),
+                schema_path: join(schema_path, keyword),
+                message,
+            });
+        };
+        let obj = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => return fail("", "No value is allowed here".to_string()),
+            Value::Object(obj) => obj,
+            _ => return,
+        };
+
+        if let Some(Value::String(reference)) = obj.get("$ref") {
+            if depth > MAX_REF_DEPTH {
+                return fail("$ref", format!("Reference loop at {}", reference));
+            }
+            match self.resolve(reference) {
+                Some((target, target_path)) => {
+                    self.check(target, &target_path, instance, instance_path, errors, depth + 1)
+                }
+                None => fail("$ref", format!("Unresolvable reference {}", reference)),
+            }
+            if self.ref_overrides {
+                return;
+            }
+        }
+        let mut fail = |keyword: &str, message: String| {
+            errors.push(ValidationError {
+                instance_path: instance_path.to_string(),
+                schema_path: join(schema_path, keyword),
+                message,
+            });
+        };
+
+        if let Some(expected) = obj.get("type") {
+            let kinds: Vec<&str> = match expected {
+                Value::String(kind) => vec![kind.as_str()],
+                Value::Array(kinds) => kinds.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            let nullable = obj.get("nullable") == Some(&Value::Bool(true)) && instance.is_null();
+            if !kinds.is_empty() && !nullable && !kinds.iter().any(|kind| has_type(instance, kind)) {
+                fail("type", format!("Expected {}, found {}", kinds.join(" or "), type_of(instance)));
+            }
+        }
+        if let Some(Value::Array(values)) = obj.get("enum") {
+            if !values.contains(instance) {
+                fail("enum", format!("{} is not one of {}", short(instance), short(&Value::Array(values.clone()))));
+            }
+        }
+        if let Some(value) = obj.get("const") {
+            if value != instance {
+                fail("const", format!("Expected {}", short(value)));
+            }
+        }
+
+        match instance {
+            Value::Number(n) => self.check_number(obj, n.as_f64().unwrap_or(0.0), &mut fail),
+            Value::String(s) => check_string(obj, s, &mut fail),
+            _ => {}
+        }
+        match instance {
+            Value::Array(items) => self.check_array(obj, schema_path, items, instance_path, errors, depth),
+            Value::Object(map) => self.check_object(obj, schema_path, map, instance_path, errors, depth),
+            _ => {}
+        }
+        self.check_combinators(obj, schema_path, instance, instance_path, errors, depth);
+    }
+
+    fn check_number(&self, obj: &Map<String, Value>, n: f64, fail: &mut dyn FnMut(&str, String)) {
+        let number = |key: &str| obj.get(key).and_then(Value::as_f64);
+        let flag = |key: &str| obj.get(key) == Some(&Value::Bool(true));
+        if let Some(min) = number("minimum") {
+            let exclusive = self.boolean_exclusive && flag("exclusiveMinimum");
+            if n < min || (exclusive && n == min) {
+                let relation = if exclusive { "greater than" } else { "at least" };
+                fail("minimum", format!("{} must be {} {}", n, relation, min));
+            }
+        }
+        if let Some(max) = number("maximum") {
+            let exclusive = self.boolean_exclusive && flag("exclusiveMaximum");
+            if n > max || (exclusive && n == max) {
+                let relation = if exclusive { "less than" } else { "at most" };
+                fail("maximum", format!("{} must be {} {}", n, relation, max));
+            }
+        }
+        // Draft-06 and later: numeric exclusive bounds. OpenAPI 3.0 uses the
+        // boolean form without declaring draft-04, so accept it there too.
+        match obj.get("exclusiveMinimum") {
+            Some(Value::Number(min)) if n <= min.as_f64().unwrap_or(f64::MIN) => {
+                fail("exclusiveMinimum", format!("{} must be greater than {}", n, min))
+            }
+            Some(Value::Bool(true)) if !self.boolean_exclusive && number("minimum") == Some(n) => {
+                fail("exclusiveMinimum", format!("{} must be greater than {}", n, n))
+            }
+            _ => {}
+        }
+        match obj.get("exclusiveMaximum") {
+            Some(Value::Number(max)) if n >= max.as_f64().unwrap_or(f64::MAX) => {
+                fail("exclusiveMaximum", format!("{} must be less than {}", n, max))
+            }
+            Some(Value::Bool(true)) if !self.boolean_exclusive && number("maximum") == Some(n) => {
+                fail("exclusiveMaximum", format!("{} must be less than {}", n, n))
+            }
+            _ => {}
+        }
+        if let Some(step) = number("multipleOf").filter(|step| *step > 0.0) {
+            let quotient = n / step;
+            if (quotient - quotient.round()).abs() > 1e-9 {
+                fail("multipleOf", format!("{} is not a multiple of {}", n, step));
+            }
+        }
+    }
+
+    fn check_array(
+        &self,
+        obj: &Map<String, Value>,
+        schema_path: &str,
+        items: &[Value],
+        instance_path: &str,
+        errors: &mut Vec<ValidationError>,
+        depth: usize,
+    ) {
+        let count = |key: &str| obj.get(key).and_then(Value::as_u64).map(|n| n as usize);
+        let mut fail = |keyword: &str, message: String| {
+            errors.push(ValidationError {
+                instance_path: instance_path.to_string(),
+                schema_path: join(schema_path, keyword),
+                message,
+            });
+        };
+        if let Some(min) = count("minItems").filter(|min| items.len() < *min) {
+            fail("minItems", format!("Expected at least {} items, found {}", min, items.len()));
+        }
+        if let Some(max) = count("maxItems").filter(|max| items.len() > *max) {
+            fail("maxItems", format!("Expected at most {} items, found {}", max, items.len()));
+        }
+        if obj.get("uniqueItems") == Some(&Value::Bool(true)) {
+            if let Some(i) = (1..items.len()).find(|&i| items[..i].contains(&items[i])) {
+                fail("uniqueItems", format!("Item {} duplicates an earlier item", i));
+            }
+        }
+        if let Some(contains) = obj.get("contains") {
+            if !items.iter().any(|item| self.is_valid(contains, item, depth)) {
+                fail("contains", "No item matches the \"contains\" schema".to_string());
+            }
+        }
+
+        for (i, item) in items.iter().enumerate() {
+            if let Some((schema, path)) = item_schema(obj, schema_path, i) {
+                self.check(schema, &path, item, &format!("{}/{}", instance_path, i), errors, depth);
+            }
+        }
+    }
+
+    fn check_object(
+        &self,
+        obj: &Map<String, Value>,
+        schema_path: &str,
+        map: &Map<String, Value>,
+        instance_path: &str,
+        errors: &mut Vec<ValidationError>,
+        depth: usize,
+    ) {
+        let count = |key: &str| obj.get(key).and_then(Value::as_u64).map(|n| n as usize);
+        if let Some(min) = count("minProperties").filter(|min| map.len() < *min) {
+            errors.push(ValidationError {
+                instance_path: instance_path.to_string(),
+                schema_path: join(schema_path, "minProperties"),
+                message: format!("Expected at least {} properties, found {}", min, map.len()),
+            });
+        }
+        if let Some(max) = count("maxProperties").filter(|max| map.len() > *max) {
+            errors.push(ValidationError {
+                instance_path: instance_path.to_string(),
+                schema_path: join(schema_path, "maxProperties"),
+                message: format!("Expected at most {} properties, found {}", max, map.len()),
+            });
+        }
+        if let Some(Value::Array(required)) = obj.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !map.contains_key(name) {
+                    errors.push(ValidationError {
+                        instance_path: instance_path.to_string(),
+                        schema_path: join(schema_path, "required"),
+                        message: format!("Missing required property \"{}\"", name),
+                    });
+                }
+            }
+        }
+        let dependencies = ["dependencies", "dependentRequired", "dependentSchemas"];
+        for keyword in dependencies {
+            let Some(Value::Object(deps)) = obj.get(keyword) else { continue };
+            for (name, dependency) in deps.iter().filter(|(name, _)| map.contains_key(*name)) {
+                match dependency {
+                    Value::Array(needed) => {
+                        for other in needed.iter().filter_map(Value::as_str).filter(|other| !map.contains_key(*other)) {
+                            errors.push(ValidationError {
+                                instance_path: instance_path.to_string(),
+                                schema_path: join(&join(schema_path, keyword), name),
+                                message: format!("Property \"{}\" requires property \"{}\"", name, other),
+                            });
+                        }
+                    }
+                    schema => {
+                        let path = join(&join(schema_path, keyword), name);
+                        self.check(schema, &path, &Value::Object(map.clone()), instance_path, errors, depth);
+                    }
+                }
+            }
+        }
+
+        for (key, value) in map {
+            let child_path = format!("{}/{}", instance_path, escape(key));
+            if let Some(names) = obj.get("propertyNames") {
+                let path = join(schema_path, "propertyNames");
+                for mut error in self.validate_at(names, &path, &Value::String(key.clone())) {
+                    error.instance_path = child_path.clone();
+                    error.message = format!("Property name \"{}\": {}", key, error.message);
+                    errors.push(error);
+                }
+            }
+            let schemas = property_schemas(obj, schema_path, key);
+            if schemas.is_empty() && obj.get("additionalProperties") == Some(&Value::Bool(false)) {
+                errors.push(ValidationError {
+                    instance_path: child_path.clone(),
+                    schema_path: join(schema_path, "additionalProperties"),
+                    message: format!("Property \"{}\" is not allowed", key),
+                });
+            }
+            for (schema, path) in schemas {
+                self.check(schema, &path, value, &child_path, errors, depth);
+            }
+        }
+    }
+
+    fn check_combinators(
+        &self,
+        obj: &Map<String, Value>,
+        schema_path: &str,
+        instance: &Value,
+        instance_path: &str,
+        errors: &mut Vec<ValidationError>,
+        depth: usize,
+    ) {
+        if let Some(Value::Array(parts)) = obj.get("allOf") {
+            for (i, part) in parts.iter().enumerate() {
+                let path = join(&join(schema_path, "allOf"), &i.to_string());
+                self.check(part, &path, instance, instance_path, errors, depth);
+            }
+        }
+        let mut fail = |keyword: &str, message: String| {
+            errors.push(ValidationError {
+                instance_path: instance_path.to_string(),
+                schema_path: join(schema_path, keyword),
+                message,
+            });
+        };
+        if let Some(Value::Array(options)) = obj.get("anyOf") {
+            if !options.iter().any(|option| self.is_valid(option, instance, depth)) {
+                fail("anyOf", "Value does not match any of the allowed schemas".to_string());
+            }
+        }
+        if let Some(Value::Array(options)) = obj.get("oneOf") {
+            match options.iter().filter(|option| self.is_valid(option, instance, depth)).count() {
+                0 => fail("oneOf", "Value does not match any of the allowed schemas".to_string()),
+                1 => {}
+                n => fail("oneOf", format!("Value matches {} schemas in oneOf, expected exactly one", n)),
+            }
+        }
+        if let Some(not) = obj.get("not") {
+            if self.is_valid(not, instance, depth) {
+                fail("not", "Value must not match the \"not\" schema".to_string());
+            }
+        }
+        if let Some(condition) = obj.get("if") {
+            let branch = if self.is_valid(condition, instance, depth) { "then" } else { "else" };
+            if let Some(schema) = obj.get(branch) {
+                self.check(schema, &join(schema_path, branch), instance, instance_path, errors, depth);
+            }
+        }
+    }
+
+    /// Schema information for every position of `instance`, keyed by JSON Pointer.
+    pub(crate) fn annotate(&self, instance: &Value) -> HashMap<String, NodeSchema> {
+        let mut out = HashMap::new();
+        self.annotate_node(self.root, "#".to_string(), instance, String::new(), &mut out, 0);
+        out
+    }
+
+    fn annotate_node(
+        &self,
+        schema: &Value,
+        schema_path: String,
+        instance: &Value,
+        instance_path: String,
+        out: &mut HashMap<String, NodeSchema>,
+        depth: usize,
+    ) {
+        let applicable = self.applicable(schema, schema_path, instance, depth);
+        let first = |key: &str| applicable.iter().find_map(|(schema, _)| schema.get(key));
+
+        let declared_type = match (first("type"), first("enum"), first("const")) {
+            (Some(Value::String(kind)), _, _) => Some(kind.clone()),
+            (Some(Value::Array(kinds)), _, _) => {
+                Some(kinds.iter().filter_map(Value::as_str).collect::<Vec<_>>().join(" | "))
+            }
+            (_, Some(_), _) => Some("enum".to_string()),
+            (_, _, Some(value)) => Some(format!("const {}", short(value))),
+            _ => None,
+        };
+        let description = first("description").and_then(Value::as_str).map(str::to_string);
+        if let Some((_, path)) = applicable.first() {
+            out.insert(
+                instance_path.clone(),
+                NodeSchema { schema_path: path.clone(), declared_type, description },
+            );
+        }
+
+        match instance {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    let child = applicable.iter().find_map(|(schema, path)| {
+                        let obj = schema.as_object()?;
+                        property_schemas(obj, path, key).into_iter().next()
+                    });
+                    if let Some((child_schema, child_path)) = child {
+                        let pointer = format!("{}/{}", instance_path, escape(key));
+                        self.annotate_node(child_schema, child_path, value, pointer, out, depth + 1);
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    let child = applicable
+                        .iter()
+                        .find_map(|(schema, path)| item_schema(schema.as_object()?, path, i));
+                    if let Some((child_schema, child_path)) = child {
+                        let pointer = format!("{}/{}", instance_path, i);
+                        self.annotate_node(child_schema, child_path, item, pointer, out, depth + 1);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The schemas that describe `instance` here: the schema itself (after
+    /// `$ref`), its `allOf` parts, the first matching `anyOf`/`oneOf` option
+    /// and the `if` branch taken.
+    fn applicable(&self, schema: &'a Value, path: String, instance: &Value, depth: usize) -> Vec<(&'a Value, String)> {
+        let mut result = Vec::new();
+        if depth > MAX_REF_DEPTH {
+            return result;
+        }
+        let obj = match schema {
+            Value::Object(obj) => obj,
+            _ => return result,
+        };
+        if let Some(Value::String(reference)) = obj.get("$ref") {
+            if let Some((target, target_path)) = self.resolve(reference) {
+                result.extend(self.applicable(target, target_path, instance, depth + 1));
+            }
+            if self.ref_overrides {
+                return result;
+            }
+        }
+        result.insert(0, (schema, path.clone()));
+        if let Some(Value::Array(parts)) = obj.get("allOf") {
+            for (i, part) in parts.iter().enumerate() {
+                result.extend(self.applicable(part, format!("{}/allOf/{}", path, i), instance, depth + 1));
+            }
+        }
+        for combinator in ["anyOf", "oneOf"] {
+            if let Some(Value::Array(options)) = obj.get(combinator) {
+                let chosen = options.iter().position(|o| self.is_valid(o, instance, depth)).unwrap_or(0);
+                if let Some(option) = options.get(chosen) {
+                    let option_path = format!("{}/{}/{}", path, combinator, chosen);
+                    result.extend(self.applicable(option, option_path, instance, depth + 1));
+                }
+            }
+        }
+        if let Some(condition) = obj.get("if") {
+            let branch = if self.is_valid(condition, instance, depth) { "then" } else { "else" };
+            if let Some(schema) = obj.get(branch) {
+                result.extend(self.applicable(schema, format!("{}/{}", path, branch), instance, depth + 1));
+            }
+        }
+        result
+    }
+}
+
+/// Looks up a local `$ref` (`#/definitions/Name`) in `root`, returning the
+/// target and its normalized path.
+pub(crate) fn resolve_ref<'v>(root: &'v Value, reference: &str) -> Option<(&'v Value, String)> {
+    let pointer = reference.strip_prefix('#')?;
+    let target = root.pointer(&percent_decode(pointer))?;
+    Some((target, format!("#{}", pointer)))
+}
+
+/// The schemas that apply to property `key`: its `properties` entry, matching
+/// `patternProperties`, or else `additionalProperties`.
+fn property_schemas<'s>(obj: &'s Map<String, Value>, path: &str, key: &str) -> Vec<(&'s Value, String)> {
+    let mut schemas = Vec::new();
+    if let Some(schema) = obj.get("properties").and_then(|p| p.get(key)) {
+        schemas.push((schema, format!("{}/properties/{}", path, escape(key))));
+    }
+    if let Some(Value::Object(patterns)) = obj.get("patternProperties") {
+        for (pattern, schema) in patterns {
+            if pattern::is_match(pattern, key).unwrap_or(false) {
+                schemas.push((schema, format!("{}/patternProperties/{}", path, escape(pattern))));
+            }
+        }
+    }
+    if schemas.is_empty() {
+        if let Some(schema @ Value::Object(_)) = obj.get("additionalProperties") {
+            schemas.push((schema, format!("{}/additionalProperties", path)));
+        }
+    }
+    schemas
+}
+
+/// The schema for array item `i`, from tuple (`prefixItems` or an `items`
+/// array) or list (`items`, `additionalItems`) keywords.
+fn item_schema<'s>(obj: &'s Map<String, Value>, path: &str, i: usize) -> Option<(&'s Value, String)> {
+    let (tuple_key, rest_key) = match (obj.get("prefixItems"), obj.get("items")) {
+        (Some(_), _) => ("prefixItems", "items"),
+        (None, Some(Value::Array(_))) => ("items", "additionalItems"),
+        _ => ("", "items"),
+    };
+    let tuple = obj.get(tuple_key).and_then(Value::as_array);
+    match tuple.and_then(|t| t.get(i)) {
+        Some(schema) => Some((schema, format!("{}/{}/{}", path, tuple_key, i))),
+        None => obj.get(rest_key).map(|schema| (schema, format!("{}/{}", path, rest_key))),
+    }
+}
+
+fn check_string(obj: &Map<String, Value>, s: &str, fail: &mut dyn FnMut(&str, String)) {
+    let length = s.chars().count();
+    if let Some(min) = obj.get("minLength").and_then(Value::as_u64).filter(|min| (length as u64) < *min) {
+        fail("minLength", format!("Expected at least {} characters, found {}", min, length));
+    }
+    if let Some(max) = obj.get("maxLength").and_then(Value::as_u64).filter(|max| (length as u64) > *max) {
+        fail("maxLength", format!("Expected at most {} characters, found {}", max, length));
+    }
+    if let Some(Value::String(pattern)) = obj.get("pattern") {
+        match pattern::is_match(pattern, s) {
+            Ok(true) => {}
+            Ok(false) => fail("pattern", format!("\"{}\" does not match {}", s, pattern)),
+            Err(e) => fail("pattern", e),
+        }
+    }
+    if let Some(Value::String(format)) = obj.get("format") {
+        if check_format(format, s) == Some(false) {
+            fail("format", format!("\"{}\" is not a valid {}", s, format));
+        }
+    }
+}
+
+fn has_type(instance: &Value, kind: &str) -> bool {
+    match (kind, instance) {
+        ("null", Value::Null) | ("boolean", Value::Bool(_)) | ("string", Value::String(_)) => true,
+        ("array", Value::Array(_)) | ("object", Value::Object(_)) | ("number", Value::Number(_)) => true,
+        ("integer", Value::Number(n)) => n.is_i64() || n.is_u64() || n.as_f64().is_some_and(|f| f.fract() == 0.0),
+        _ => false,
+    }
+}
+
+pub(crate) fn type_of(instance: &Value) -> &'static str {
+    match instance {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// A value for an error message, cut short when long.
+fn short(value: &Value) -> String {
+    let text = value.to_string();
+    if text.chars().count() > 60 {
+        format!("{}...", text.chars().take(57).collect::<String>())
+    } else {
+        text
+    }
+}
+
+fn join(path: &str, keyword: &str) -> String {
+    if keyword.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}/{}", path, escape(keyword))
+    }
+}
+
+/// Escapes a JSON Pointer reference token.
+pub(crate) fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Decodes `%XX` escapes in a URI fragment pointer.
+fn percent_decode(pointer: &str) -> String {
+    if !pointer.contains('%') {
+        return pointer.to_string();
+    }
+    let bytes = pointer.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn errors(schema: Value, instance: Value) -> Vec<ValidationError> {
+        Validator::new(&schema).validate(&instance)
+    }
+
+    #[test]
+    fn reports_paths_into_document_and_schema() {
+        let schema = json!({"properties": {"age": {"type": "integer", "minimum": 0}}, "required": ["name"]});
+        let found = errors(schema, json!({"age": -1}));
+        assert_eq!(found.len(), 2);
+        assert!(found.iter().any(|e| e.instance_path == "/age" && e.schema_path == "#/properties/age/minimum"));
+        assert!(found.iter().any(|e| e.message == "Missing required property \"name\""));
+    }
+
+    #[test]
+    fn one_of_needs_exactly_one_match() {
+        let schema = json!({"oneOf": [{"type": "integer"}, {"type": "number"}]});
+        assert!(errors(schema.clone(), json!(1.5)).is_empty());
+        assert!(!errors(schema, json!(2)).is_empty());
+    }
+
+    #[test]
+    fn follows_local_refs() {
+        let schema = json!({
+            "$defs": {"node": {"type": "object", "properties": {"next": {"$ref": "#/$defs/node"}}}},
+            "$ref": "#/$defs/node"
+        });
+        assert!(errors(schema.clone(), json!({"next": {"next": {}}})).is_empty());
+        assert_eq!(errors(schema, json!({"next": {"next": 3}}))[0].instance_path, "/next/next");
+    }
+
+    #[test]
+    fn asserts_formats_and_patterns() {
+        let schema = json!({"type": "string", "format": "date", "pattern": "^2024"});
+        assert!(errors(schema.clone(), json!("2024-02-29")).is_empty());
+        assert_eq!(errors(schema.clone(), json!("2023-02-29")).len(), 2);
+    }
+
+    #[test]
+    fn draft_04_exclusive_bounds_are_booleans() {
+        let schema = json!({
+            "$schema": "http://json-schema.org/draft-04/schema#",
+            "maximum": 5,
+            "exclusiveMaximum": true
+        });
+        assert!(!errors(schema.clone(), json!(5)).is_empty());
+        assert!(errors(schema, json!(4.9)).is_empty());
+    }
+}
@@ -9,14 +9,19 @@ use std::collections::HashMap;
 use serde_yaml::Value as YamlValue;
 use wasm_bindgen::JsError;
 
+use crate::extended_json::wrapper_value;
+use crate::lenient_json::{parse_json_text, JsonSyntax};
+use crate::validator::{escape, Validator};
+
 // use serde_json::{json, Value as Value};
 
 // use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
-pub fn process_json_tree(json_str: &str) -> Result<String, JsValue> {
+pub fn process_json_tree(json_str: &str, syntax: Option<String>) -> Result<String, JsValue> {
     // Parse the JSON
-    let parsed = match serde_json::from_str::<Value>(json_str) {
+    let syntax = JsonSyntax::parse(syntax.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    let parsed = match parse_json_text(json_str, syntax) {
         Ok(v) => v,
         Err(e) => return Err(JsValue::from_str(&format!("Failed to parse JSON: {}", e))),
     };
@@ -31,9 +36,17 @@ pub fn process_json_tree(json_str: &str) -> Result<String, JsValue> {
     }
 }
 
-fn convert_to_d3_format(value: &Value, name: &str) -> Value {
+pub(crate) fn convert_to_d3_format(value: &Value, name: &str) -> Value {
     match value {
         Value::Object(obj) => {
+            if let Some(bson) = wrapper_value(obj) {
+                return json!({
+                    "name": name,
+                    "value": bson.display(),
+                    "bson_type": bson.type_name()
+                });
+            }
+
             let mut result = json!({
                 "name": name,
                 "children": []
@@ -84,33 +97,71 @@ pub struct JsonNode {
     pub depth: usize,
     pub parent: Option<String>,
     pub is_leaf: bool,
+    /// Set when `process_json` is given a schema.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The BSON type of an Extended JSON value such as `{"$oid": ...}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bson_type: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct JsonLink {
     pub source: String,
     pub target: String,
+    /// `"ref"` for `$ref` edges in schema graphs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct ProcessedData {
-    nodes: Vec<JsonNode>,
-    links: Vec<JsonLink>,
+    pub nodes: Vec<JsonNode>,
+    pub links: Vec<JsonLink>,
 }
 
+/// Builds the node/link graph for a document. With a JSON Schema, each node
+/// also carries the schema that describes it and whether it validates.
+/// `syntax` reads the document as "jsonc" or "json5" instead of JSON.
 #[wasm_bindgen]
-pub fn process_json(json_str: &str) -> Result<String, JsError> {
+pub fn process_json(json_str: &str, schema: Option<String>, syntax: Option<String>) -> Result<String, JsError> {
     // Parse the JSON string into a serde_json::Value
-    let json_value: Value = serde_json::from_str(json_str)
+    let syntax = JsonSyntax::parse(syntax.as_deref()).map_err(|e| JsError::new(&e))?;
+    let json_value: Value = parse_json_text(json_str, syntax)
         .map_err(|e| JsError::new(&format!("Failed to parse JSON: {}", e)))?;
 
+    let mut processed_data = json_graph(&json_value);
+
+    if let Some(schema_str) = schema {
+        let schema: Value = serde_json::from_str(&schema_str)
+            .map_err(|e| JsError::new(&format!("Failed to parse JSON Schema: {}", e)))?;
+        annotate_nodes(&mut processed_data.nodes, &json_value, &schema);
+    }
+
+    // Serialize processed_data into a JSON string
+    let serialized_str = serde_json::to_string(&processed_data)
+        .map_err(|e| JsError::new(&format!("Failed to serialize: {}", e)))?;
+
+    Ok(serialized_str)
+}
+
+/// The node/link graph of a document, for formats that read into JSON values.
+pub(crate) fn json_graph(value: &Value) -> ProcessedData {
     let mut nodes = Vec::new();
     let mut links = Vec::new();
     let mut next_id = 1;
 
     // Process the root element recursively
     process_value(
-        &json_value,
+        value,
         None,
         0,
         &mut nodes,
@@ -119,14 +170,7 @@ pub fn process_json(json_str: &str) -> Result<String, JsError> {
         None,
     );
 
-    // Create the processed data structure
-    let processed_data = ProcessedData { nodes, links };
-
-    // Serialize processed_data into a JSON string
-    let serialized_str = serde_json::to_string(&processed_data)
-        .map_err(|e| JsError::new(&format!("Failed to serialize: {}", e)))?;
-
-    Ok(serialized_str)
+    ProcessedData { nodes, links }
 }
 
 fn process_value(
@@ -141,8 +185,13 @@ fn process_value(
     let id = next_id.to_string();
     *next_id += 1;
 
+    let typed = match value {
+        Value::Object(map) => wrapper_value(map),
+        _ => None,
+    };
+
     match value {
-        Value::Object(map) => {
+        Value::Object(map) if typed.is_none() => {
             let item_count = map.len();
             let label = key.unwrap_or("Object").to_string();
             let value_text = format!("{} items", item_count);
@@ -155,6 +204,12 @@ fn process_value(
                 depth,
                 parent: parent_id.clone(),
                 is_leaf: false,
+                schema_path: None,
+                schema_type: None,
+                description: None,
+                valid: None,
+                error: None,
+                bson_type: None,
             };
             nodes.push(node);
 
@@ -163,6 +218,7 @@ fn process_value(
                 links.push(JsonLink {
                     source: parent.clone(),
                     target: id.clone(),
+                    kind: None,
                 });
             }
 
@@ -194,6 +250,12 @@ fn process_value(
                 depth,
                 parent: parent_id.clone(),
                 is_leaf: false,
+                schema_path: None,
+                schema_type: None,
+                description: None,
+                valid: None,
+                error: None,
+                bson_type: None,
             };
             nodes.push(node);
 
@@ -202,6 +264,7 @@ fn process_value(
                 links.push(JsonLink {
                     source: parent.clone(),
                     target: id.clone(),
+                    kind: None,
                 });
             }
 
@@ -220,9 +283,13 @@ fn process_value(
 
             id
         }
-        // Handle primitive values (string, number, boolean, null)
+        // Handle primitive values (string, number, boolean, null) and
+        // Extended JSON values, which show as typed leaves
         _ => {
-            let (label, value_text) = format_primitive(key, value);
+            let (label, value_text) = match &typed {
+                Some(bson) => (key.unwrap_or("Value").to_string(), bson.display()),
+                None => format_primitive(key, value),
+            };
 
             // Create node for this primitive value
             let node = JsonNode {
@@ -232,6 +299,12 @@ fn process_value(
                 depth,
                 parent: parent_id.clone(),
                 is_leaf: true,
+                schema_path: None,
+                schema_type: None,
+                description: None,
+                valid: None,
+                error: None,
+                bson_type: typed.map(|bson| bson.type_name().to_string()),
             };
             nodes.push(node);
 
@@ -240,6 +313,7 @@ fn process_value(
                 links.push(JsonLink {
                     source: parent.clone(),
                     target: id.clone(),
+                    kind: None,
                 });
             }
 
@@ -248,6 +322,35 @@ fn process_value(
     }
 }
 
+/// Fills in the schema fields of `nodes`. Errors are reported on the node
+/// they point at; a node with no errors of its own is valid.
+fn annotate_nodes(nodes: &mut [JsonNode], document: &Value, schema: &Value) {
+    let validator = Validator::new(schema);
+    let annotations = validator.annotate(document);
+    let mut errors: HashMap<String, Vec<String>> = HashMap::new();
+    for error in validator.validate(document) {
+        errors.entry(error.instance_path).or_default().push(error.message);
+    }
+
+    // Nodes come parent-first, so each parent's pointer is known in time.
+    let mut pointers: HashMap<String, String> = HashMap::new();
+    for node in nodes.iter_mut() {
+        let pointer = match node.parent.as_ref().and_then(|parent| pointers.get(parent)) {
+            Some(parent) => format!("{}/{}", parent, escape(&node.label)),
+            None => String::new(),
+        };
+        if let Some(info) = annotations.get(&pointer) {
+            node.schema_path = Some(info.schema_path.clone());
+            node.schema_type = info.declared_type.clone();
+            node.description = info.description.clone();
+        }
+        let messages = errors.get(&pointer);
+        node.valid = Some(messages.is_none());
+        node.error = messages.map(|messages| messages.join("; "));
+        pointers.insert(node.id.clone(), pointer);
+    }
+}
+
 fn format_primitive(key: Option<&str>, value: &Value) -> (String, String) {
     let label = key.unwrap_or("Value").to_string();
 